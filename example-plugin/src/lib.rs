@@ -0,0 +1,28 @@
+// A minimal content pack for `plugin::load`. Build it with
+//
+//     cargo build --release
+//
+// and pass the folder holding `target/release/libexample_plugin.so` (or the
+// platform's equivalent) to `text-game --plugins`. Its rusty-ecs-core
+// features have to match the game's, or the loader refuses it.
+
+use rusty_ecs_core::{ExclusiveSystem, SystemExecutor, World};
+
+// Spawns one entity every frame, so a host can see the pack running.
+struct ReinforcementSystem;
+
+impl ExclusiveSystem for ReinforcementSystem {
+    fn run(&mut self, world: &mut World) {
+        world.create_entity();
+    }
+
+    fn name(&self) -> &'static str {
+        "ReinforcementSystem"
+    }
+}
+
+fn register(_world: &mut World, executor: &mut SystemExecutor) {
+    executor.add_exclusive_system(ReinforcementSystem);
+}
+
+rusty_ecs_core::export_plugin!(register);
@@ -0,0 +1,79 @@
+use crate::components::{AttackEvent, Health, Hero, Monster, Stairs};
+use crate::turns::TurnEvent;
+use rusty_ecs_core::{Behaviors, Destination, Entity, GridMap, Position, Status, Visible, World};
+
+// How far the hero sees, and so how far off monsters notice it.
+pub const SIGHT: u32 = 8;
+
+pub fn hero(world: &World) -> Option<Entity> {
+    world.query_entities::<Hero>().into_iter().min()
+}
+
+fn position(world: &World, e: Entity) -> Option<Position> {
+    world.get_component::<Position>(e).copied()
+}
+
+// The first foe right next to `e`: any monster for the hero, the hero for
+// monsters.
+fn adjacent_foe(world: &World, e: Entity) -> Option<Entity> {
+    let at = position(world, e)?;
+    let mut foes = match world.get_component::<Hero>(e) {
+        Some(_) => world.query_entities::<Monster>(),
+        None => hero(world).into_iter().collect(),
+    };
+    foes.sort();
+    foes.into_iter().find(|&foe| position(world, foe).is_some_and(|p| p.distance(at) == 1))
+}
+
+// Sets off towards `goal`; `PathFollowSystem` takes the step.
+fn head_for(world: &mut World, e: Entity, goal: Option<Position>) -> Status {
+    match goal {
+        Some(goal) => {
+            world.add_component(e, Destination::new(goal));
+            Status::Success
+        }
+        None => Status::Failure,
+    }
+}
+
+// Steps to the first neighbouring cell further from the hero, if any.
+fn flee(world: &mut World, e: Entity) -> Status {
+    let threat = hero(world).and_then(|h| position(world, h));
+    let (Some(threat), Some(at), Some(grid)) =
+        (threat, position(world, e), world.resource::<GridMap>())
+    else {
+        return Status::Failure;
+    };
+    let away = grid.neighbors(at).find(|cell| cell.distance(threat) > at.distance(threat));
+    head_for(world, e, away)
+}
+
+// The conditions and actions the creatures' behavior trees refer to.
+pub fn behaviors() -> Behaviors {
+    Behaviors::new()
+        .condition("my_turn", |world, e| world.read_events::<TurnEvent>().any(|t| t.entity == e))
+        .condition("foe_adjacent", |world, e| adjacent_foe(world, e).is_some())
+        // Sight is mutual: a monster sees the hero when the hero sees it
+        .condition("hero_in_sight", |world, e| world.get_component::<Visible>(e).is_some())
+        .condition("wounded", |world, e| {
+            world.get_component::<Health>(e).is_some_and(|h| h.hp * 2 < h.max)
+        })
+        .action("attack", |world, e| match adjacent_foe(world, e) {
+            Some(target) => {
+                world.push_event(AttackEvent { attacker: e, target });
+                Status::Success
+            }
+            None => Status::Failure,
+        })
+        .action("chase", |world, e| {
+            let goal = hero(world).and_then(|h| position(world, h));
+            head_for(world, e, goal)
+        })
+        .action("descend", |world, e| {
+            let stairs = world.query_entities::<Stairs>().into_iter().min();
+            let goal = stairs.and_then(|s| position(world, s));
+            head_for(world, e, goal)
+        })
+        .action("flee", flee)
+        .action("wait", |_, _| Status::Success)
+}
@@ -0,0 +1,39 @@
+use rusty_ecs_core::{Entity, EntityEvent, Event};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Name(pub &'static str);
+
+// The character an entity is drawn with on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph(pub char);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    pub hp: i32,
+    pub max: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Damage(pub i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hero;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monster;
+
+// Marker for the way down to the next level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stairs;
+
+#[derive(Event)]
+pub struct AttackEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+}
+
+impl EntityEvent for AttackEvent {
+    fn entity(&self) -> Entity {
+        self.target
+    }
+}
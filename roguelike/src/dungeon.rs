@@ -0,0 +1,152 @@
+use rusty_ecs_core::{GridMap, Position, RngStream};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Room {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Room {
+    pub fn center(&self) -> Position {
+        Position::new(self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn contains(&self, cell: Position) -> bool {
+        (self.x..self.x + self.width).contains(&cell.x)
+            && (self.y..self.y + self.height).contains(&cell.y)
+    }
+
+    // Whether the rooms overlap or touch, walls included.
+    fn crowds(&self, other: &Room) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    pub fn random_cell(&self, rng: &mut RngStream) -> Position {
+        Position::new(
+            self.x + rng.index(self.width as usize) as i32,
+            self.y + rng.index(self.height as usize) as i32,
+        )
+    }
+}
+
+// A level: rooms carved out of solid rock, each joined to the one carved
+// before it by an L-shaped corridor, so every room can reach every other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dungeon {
+    pub width: i32,
+    pub height: i32,
+    pub rooms: Vec<Room>,
+    floor: Vec<bool>,
+}
+
+const ROOM_SIZES: std::ops::Range<u64> = 3..8;
+
+impl Dungeon {
+    // Tries to place `attempts` rooms at random and keeps the ones that do
+    // not crowd an earlier room; there is always at least one.
+    pub fn generate(rng: &mut RngStream, width: i32, height: i32, attempts: usize) -> Self {
+        let mut dungeon = Self {
+            width,
+            height,
+            rooms: Vec::new(),
+            floor: vec![false; (width * height) as usize],
+        };
+        for attempt in 0..attempts.max(1) {
+            let room_width = rng.range(ROOM_SIZES) as i32 * 2;
+            let room_height = rng.range(ROOM_SIZES) as i32;
+            let room = Room {
+                x: 1 + rng.index((width - room_width - 1).max(1) as usize) as i32,
+                y: 1 + rng.index((height - room_height - 1).max(1) as usize) as i32,
+                width: room_width.min(width - 2),
+                height: room_height.min(height - 2),
+            };
+            // The first room is kept whatever happens, so a level is never empty
+            if attempt > 0 && dungeon.rooms.iter().any(|other| room.crowds(other)) {
+                continue;
+            }
+            dungeon.carve_room(&room);
+            if let Some(previous) = dungeon.rooms.last().copied() {
+                dungeon.carve_corridor(previous.center(), room.center(), rng.chance(0.5));
+            }
+            dungeon.rooms.push(room);
+        }
+        dungeon
+    }
+
+    pub fn is_floor(&self, cell: Position) -> bool {
+        let inside = (0..self.width).contains(&cell.x) && (0..self.height).contains(&cell.y);
+        inside && self.floor[(cell.y * self.width + cell.x) as usize]
+    }
+
+    fn dig(&mut self, x: i32, y: i32) {
+        self.floor[(y * self.width + x) as usize] = true;
+    }
+
+    fn carve_room(&mut self, room: &Room) {
+        for y in room.y..room.y + room.height {
+            for x in room.x..room.x + room.width {
+                self.dig(x, y);
+            }
+        }
+    }
+
+    fn carve_corridor(&mut self, from: Position, to: Position, horizontal_first: bool) {
+        let corner = match horizontal_first {
+            true => Position::new(to.x, from.y),
+            false => Position::new(from.x, to.y),
+        };
+        for (a, b) in [(from, corner), (corner, to)] {
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                for y in a.y.min(b.y)..=a.y.max(b.y) {
+                    self.dig(x, y);
+                }
+            }
+        }
+    }
+
+    pub fn grid(&self) -> GridMap {
+        GridMap::parse(&self.to_string()).expect("generated maps only hold walls and floor")
+    }
+}
+
+impl fmt::Display for Dungeon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = if self.is_floor(Position::new(x, y)) { '.' } else { '#' };
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rooms_are_connected_and_apart() {
+        for seed in 0..20 {
+            let dungeon = Dungeon::generate(&mut RngStream::new(seed), 60, 20, 12);
+            assert_eq!(dungeon, Dungeon::generate(&mut RngStream::new(seed), 60, 20, 12));
+            let grid = dungeon.grid();
+            assert_eq!((grid.width(), grid.height()), (60, 20));
+            let first = dungeon.rooms[0].center();
+            for (i, room) in dungeon.rooms.iter().enumerate() {
+                assert!(grid.find_path(first, room.center()).is_some(), "seed {}", seed);
+                assert!(dungeon.rooms[..i].iter().all(|other| !room.crowds(other)));
+            }
+            // The outer edge stays solid
+            assert!((0..60).all(|x| !dungeon.is_floor(Position::new(x, 0))));
+            assert!((0..20).all(|y| !dungeon.is_floor(Position::new(59, y))));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::behaviors::{behaviors, hero, SIGHT};
+use crate::components::{Glyph, Health, Hero, Monster, Stairs};
+use crate::dungeon::Dungeon;
+use crate::prefabs::Prefab;
+use crate::systems::{CombatSystem, DescendSystem, EndTurnSystem};
+use crate::turns::TurnScheduler;
+use rusty_ecs_core::{
+    BehaviorSystem, Destination, ExclusiveSystem, Explored, PathFollowSystem, Position,
+    RngResource, SystemExecutor, VisibilityMap, Visible, Vision, VisionSystem, World,
+};
+
+pub const WIDTH: i32 = 60;
+pub const HEIGHT: i32 = 20;
+const ROOM_ATTEMPTS: usize = 12;
+// The level whose stairs lead out of the dungeon.
+pub const DEPTHS: u32 = 3;
+
+// Resource: the level the hero is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub depth: u32,
+    pub dungeon: Dungeon,
+}
+
+// Resource inserted once the game is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Escaped,
+    Slain,
+}
+
+// Resource: the monsters levels are stocked from.
+pub struct Bestiary(pub Vec<Prefab>);
+
+// A world with the hero standing on the first level; the seed decides every
+// level and fight.
+pub fn new_game(seed: u64) -> World {
+    let mut world = World::new();
+    world.insert_resource(RngResource::new(seed));
+    world.insert_resource(Bestiary(Prefab::builtin_monsters()));
+    world.insert_resource(behaviors());
+    let hero = Prefab::hero().spawn(&mut world, Position::new(0, 0));
+    world.add_component(hero, Hero);
+    world.add_component(hero, Vision { range: SIGHT });
+    build_level(&mut world);
+    VisionSystem.run(&mut world);
+    world
+}
+
+// Each run is one creature's turn.
+pub fn executor() -> SystemExecutor {
+    let mut executor = SystemExecutor::new();
+    executor.add_exclusive_system(TurnScheduler);
+    executor.add_exclusive_system(BehaviorSystem);
+    executor.add_exclusive_system(PathFollowSystem);
+    executor.add_exclusive_system(CombatSystem);
+    executor.add_exclusive_system(DescendSystem);
+    executor.add_exclusive_system(VisionSystem);
+    executor.add_exclusive_system(EndTurnSystem);
+    executor
+}
+
+// Replaces the current level with a fresh one at the `Level`'s depth, the
+// first one without a `Level` yet: the hero starts in the first room, the
+// stairs are in the last and the rooms in between get up to `depth`
+// monsters each.
+pub fn build_level(world: &mut World) {
+    let mut cleared = world.query_entities::<Monster>();
+    cleared.extend(world.query_entities::<Stairs>());
+    for e in cleared {
+        world.destroy_entity(e);
+    }
+    let depth = world.resource::<Level>().map_or(1, |level| level.depth);
+    let bestiary: Vec<Prefab> = world
+        .resource::<Bestiary>()
+        .map_or(Vec::new(), |b| b.0.iter().filter(|p| p.depth <= depth).cloned().collect());
+    let rng = world.init_resource::<RngResource>().stream("dungeon");
+    let dungeon = Dungeon::generate(rng, WIDTH, HEIGHT, ROOM_ATTEMPTS);
+    let mut monsters = Vec::new();
+    for room in dungeon.rooms.iter().skip(1) {
+        for _ in 0..rng.index(depth as usize + 1) {
+            if let Some(prefab) = rng.pick(&bestiary) {
+                monsters.push((prefab.clone(), room.random_cell(rng)));
+            }
+        }
+    }
+
+    let start = dungeon.rooms[0].center();
+    let exit = dungeon.rooms.last().expect("levels have a room").center();
+    for (prefab, at) in monsters {
+        let monster = prefab.spawn(world, at);
+        world.add_component(monster, Monster);
+    }
+    let stairs = world.create_entity();
+    world.add_component(stairs, Stairs);
+    world.add_component(stairs, Glyph('>'));
+    world.add_component(stairs, exit);
+    if let Some(hero) = hero(world) {
+        world.add_component(hero, start);
+        world.remove_component::<Destination>(hero);
+    }
+    world.insert_resource(dungeon.grid());
+    world.insert_resource(Level { depth, dungeon });
+    // Nothing of the new level has been seen yet
+    world.remove_resource::<VisibilityMap>();
+}
+
+// The level as far as the hero has explored it, with the creatures in view
+// drawn over the stairs and the hero on top, followed by a status line.
+pub fn render(world: &World) -> String {
+    let Some(level) = world.resource::<Level>() else {
+        return String::new();
+    };
+    let map = level.dungeon.to_string();
+    let mut rows: Vec<Vec<char>> = map.lines().map(|line| line.chars().collect()).collect();
+    if let Some(seen) = world.resource::<VisibilityMap>() {
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if !seen.is_explored(Position::new(x as i32, y as i32)) {
+                    *cell = ' ';
+                }
+            }
+        }
+    }
+    // Found stairs stay drawn, creatures only while in view
+    let mut drawn = world.query_entities::<Glyph>();
+    drawn.retain(|&e| match world.get_component::<Stairs>(e) {
+        Some(_) => world.get_component::<Explored>(e).is_some(),
+        None => world.get_component::<Visible>(e).is_some(),
+    });
+    drawn.sort_by_key(|&e| {
+        let layer = if world.get_component::<Hero>(e).is_some() {
+            2
+        } else if world.get_component::<Stairs>(e).is_some() {
+            0
+        } else {
+            1
+        };
+        (layer, e)
+    });
+    for e in drawn {
+        let glyph = world.get_component::<Glyph>(e).unwrap();
+        if let Some(at) = world.get_component::<Position>(e) {
+            rows[at.y as usize][at.x as usize] = glyph.0;
+        }
+    }
+    let mut text = String::new();
+    for row in rows {
+        text.push_str(row.into_iter().collect::<String>().trim_end());
+        text.push('\n');
+    }
+    let health = hero(world).and_then(|h| world.get_component::<Health>(h).copied());
+    let (hp, max) = health.map_or((0, 0), |h| (h.hp.max(0), h.max));
+    text.push_str(&format!("Depth {} of {} | HP {}/{}\n", level.depth, DEPTHS, hp, max));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::Log;
+
+    // Plays whole games, checking the rules hold on every turn.
+    #[test]
+    fn test_games_run_to_an_end() {
+        let mut outcomes = Vec::new();
+        for seed in 0..10 {
+            let mut world = new_game(seed);
+            let mut executor = executor();
+            let hero = hero(&world).unwrap();
+            // The start is in view, the rest of the level still dark
+            let map = render(&world);
+            assert!(map.contains('@') && map.contains(' '), "seed {}", seed);
+            for _ in 0..10_000 {
+                executor.run(&mut world);
+                let grid = world.resource::<rusty_ecs_core::GridMap>().unwrap();
+                for e in world.query_entities::<Position>() {
+                    let at = *world.get_component::<Position>(e).unwrap();
+                    assert!(grid.is_walkable(at), "seed {}: {:?} in a wall", seed, e);
+                }
+                for e in world.query_entities::<Monster>() {
+                    assert!(world.get_component::<Health>(e).unwrap().hp > 0);
+                }
+                if world.resource::<Outcome>().is_some() {
+                    break;
+                }
+            }
+            let outcome = *world.resource::<Outcome>().expect("the game ends");
+            let hp = world.get_component::<Health>(hero).unwrap().hp;
+            assert_eq!(outcome == Outcome::Slain, hp <= 0, "seed {}", seed);
+            let log = &world.resource::<Log>().unwrap().0;
+            if outcome == Outcome::Escaped {
+                assert_eq!(world.resource::<Level>().unwrap().depth, DEPTHS);
+                assert!(log.iter().any(|m| m.contains("descends to depth 3")));
+            }
+            outcomes.push(outcome);
+        }
+        assert!(outcomes.contains(&Outcome::Escaped) && outcomes.contains(&Outcome::Slain));
+        // The same seed plays out the same way
+        let mut replay = new_game(3);
+        let mut executor = executor();
+        while replay.resource::<Outcome>().is_none() {
+            executor.run(&mut replay);
+        }
+        assert_eq!(replay.resource::<Outcome>(), Some(&outcomes[3]));
+    }
+}
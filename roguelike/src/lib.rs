@@ -0,0 +1,7 @@
+pub mod behaviors;
+pub mod components;
+pub mod dungeon;
+pub mod game;
+pub mod prefabs;
+pub mod systems;
+pub mod turns;
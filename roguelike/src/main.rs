@@ -0,0 +1,59 @@
+use roguelike::behaviors::hero;
+use roguelike::game::{executor, new_game, render, Level, Outcome};
+use roguelike::systems::Log;
+use rusty_ecs_core::Position;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Turns after which a hero that has neither escaped nor died gives up.
+const TURN_LIMIT: u32 = 10_000;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    // `--seed <n>` replays a dungeon, `--watch` draws the map whenever the
+    // hero moves instead of once per level
+    let seed = match arg_value(&args, "--seed").map(|s| s.parse::<u64>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            eprintln!("--seed takes a number");
+            std::process::exit(2);
+        }
+        None => time_seed(),
+    };
+    let watch = args.iter().any(|a| a == "--watch");
+
+    let mut world = new_game(seed);
+    let mut executor = executor();
+    println!("Seed {}", seed);
+    let mut depth = 0;
+    let mut hero_at = None;
+    for _ in 0..TURN_LIMIT {
+        let level = world.resource::<Level>().map_or(0, |l| l.depth);
+        let at = hero(&world).and_then(|h| world.get_component::<Position>(h).copied());
+        if level != depth || (watch && at != hero_at) {
+            depth = level;
+            println!("\n{}", render(&world));
+        }
+        hero_at = at;
+        executor.run(&mut world);
+        let log = world.resource_mut::<Log>().map(|log| std::mem::take(&mut log.0));
+        for message in log.unwrap_or_default() {
+            println!("{}", message);
+        }
+        if let Some(outcome) = world.resource::<Outcome>() {
+            match outcome {
+                Outcome::Escaped => println!("\nThe hero escaped with their life."),
+                Outcome::Slain => println!("\nThe dungeon claims another hero."),
+            }
+            return;
+        }
+    }
+    println!("\nThe hero wanders the dungeon forever.");
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+}
+
+fn time_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
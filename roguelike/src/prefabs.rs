@@ -0,0 +1,116 @@
+use crate::components::{Damage, Glyph, Health, Name};
+use crate::turns::Actor;
+use rusty_ecs_core::{BehaviorNode, BehaviorTree, Entity, Position, World};
+
+// Blueprint for a creature; monsters are read from `data/monsters.txt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prefab {
+    pub name: &'static str,
+    pub glyph: char,
+    pub hp: i32,
+    pub damage: i32,
+    pub speed: u32,
+    // The shallowest level the monster turns up on.
+    pub depth: u32,
+    pub behavior: BehaviorNode,
+}
+
+impl Prefab {
+    // The hero bot fights whatever stands next to it and otherwise heads for
+    // the stairs.
+    pub fn hero() -> Self {
+        Self {
+            name: "Hero",
+            glyph: '@',
+            hp: 24,
+            damage: 5,
+            speed: 10,
+            depth: 1,
+            behavior: BehaviorNode::parse("selector(sequence(if foe_adjacent, attack), descend)")
+                .expect("hero behavior is valid"),
+        }
+    }
+
+    pub fn builtin_monsters() -> Vec<Self> {
+        Self::parse_list(include_str!("../data/monsters.txt"))
+            .expect("built-in monsters are valid")
+    }
+
+    // Takes `'static` text because names end up in `Name` components.
+    pub fn parse_list(text: &'static str) -> Result<Vec<Self>, String> {
+        let mut prefabs = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let prefab = Self::parse(line);
+            prefabs.push(prefab.map_err(|reason| format!("line {}: {}", number + 1, reason))?);
+        }
+        Ok(prefabs)
+    }
+
+    // `name | glyph | hp | damage | speed | first depth | behavior`
+    fn parse(line: &'static str) -> Result<Self, String> {
+        let fields: Vec<&'static str> = line.split('|').map(str::trim).collect();
+        let [name, glyph, hp, damage, speed, depth, behavior] = fields[..] else {
+            return Err(format!("expected 7 fields, found {}", fields.len()));
+        };
+        let number = |field: &str, what: &str| {
+            field.parse::<u32>().map_err(|_| format!("invalid {} `{}`", what, field))
+        };
+        let mut glyphs = glyph.chars();
+        let (Some(glyph), None) = (glyphs.next(), glyphs.next()) else {
+            return Err(format!("glyph `{}` is not a single character", glyph));
+        };
+        Ok(Self {
+            name,
+            glyph,
+            hp: number(hp, "hp")? as i32,
+            damage: number(damage, "damage")? as i32,
+            speed: number(speed, "speed")?,
+            depth: number(depth, "depth")?,
+            behavior: BehaviorNode::parse(behavior)?,
+        })
+    }
+
+    // Leaves the `Hero` or `Monster` marker to the caller.
+    pub fn spawn(&self, world: &mut World, at: Position) -> Entity {
+        let e = world.create_entity();
+        world.add_component(e, Name(self.name));
+        world.add_component(e, Glyph(self.glyph));
+        world.add_component(e, Health { hp: self.hp, max: self.hp });
+        world.add_component(e, Damage(self.damage));
+        world.add_component(e, Actor::new(self.speed));
+        world.add_component(e, at);
+        // Trees are ticked every run, so they only act on their own turn
+        let behavior = BehaviorNode::Sequence(vec![
+            BehaviorNode::Condition("my_turn".to_string()),
+            self.behavior.clone(),
+        ]);
+        world.add_component(e, BehaviorTree::new(behavior));
+        e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behaviors::behaviors;
+
+    #[test]
+    fn test_builtin_monsters_only_use_known_behaviors() {
+        let monsters = Prefab::builtin_monsters();
+        assert_eq!(monsters[0].name, "Rat");
+        assert_eq!((monsters[0].glyph, monsters[0].speed, monsters[0].depth), ('r', 12, 1));
+        let behaviors = behaviors();
+        for prefab in monsters.iter().chain([&Prefab::hero()]) {
+            assert!(behaviors.missing(&prefab.behavior).is_empty(), "{}", prefab.name);
+        }
+
+        assert!(Prefab::parse_list("Rat | r | 4 | 1 | 12 | 1").is_err());
+        assert!(Prefab::parse_list("Rat | rr | 4 | 1 | 12 | 1 | wait").is_err());
+        assert!(Prefab::parse_list("Rat | r | -4 | 1 | 12 | 1 | wait").is_err());
+        assert!(Prefab::parse_list("Rat | r | 4 | 1 | 12 | 1 | selector(wait").is_err());
+    }
+}
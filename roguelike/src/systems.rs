@@ -0,0 +1,112 @@
+use crate::behaviors::hero;
+use crate::components::{AttackEvent, Damage, Health, Hero, Name, Stairs};
+use crate::game::{build_level, Level, Outcome, DEPTHS};
+use crate::turns::TurnEvent;
+use rusty_ecs_core::{
+    Destination, Entity, ExclusiveSystem, PathBlocked, PathCompleted, Position, World,
+};
+
+// What happened, for the frontend to print and clear.
+#[derive(Debug, Default)]
+pub struct Log(pub Vec<String>);
+
+fn log(world: &mut World, message: String) {
+    world.init_resource::<Log>().0.push(message);
+}
+
+fn name(world: &World, e: Entity) -> &'static str {
+    world.get_component::<Name>(e).map_or("Something", |n| n.0)
+}
+
+// Resolves attacks in the order they were made. Slain monsters are
+// despawned; a slain hero ends the game.
+pub struct CombatSystem;
+
+impl ExclusiveSystem for CombatSystem {
+    fn run(&mut self, world: &mut World) {
+        for attack in world.take_events::<AttackEvent>() {
+            let (attacker, target) = (attack.attacker, attack.target);
+            if !world.is_alive(attacker) || !world.is_alive(target) {
+                continue;
+            }
+            let damage = world.get_component::<Damage>(attacker).map_or(0, |d| d.0);
+            let Some(health) = world.get_component_mut::<Health>(target) else {
+                continue;
+            };
+            health.hp -= damage;
+            let hp = *health;
+            let message = format!(
+                "{} hits {} for {} damage. ({}/{})",
+                name(world, attacker),
+                name(world, target),
+                damage,
+                hp.hp.max(0),
+                hp.max
+            );
+            log(world, message);
+            if hp.hp > 0 {
+                continue;
+            }
+            if world.get_component::<Hero>(target).is_some() {
+                let depth = world.resource::<Level>().map_or(0, |l| l.depth);
+                log(world, format!("{} is slain on depth {}.", name(world, target), depth));
+                world.insert_resource(Outcome::Slain);
+            } else {
+                log(world, format!("{} dies.", name(world, target)));
+                world.destroy_entity(target);
+            }
+        }
+    }
+}
+
+// The hero arriving on the stairs goes down a level, healing a little, or
+// escapes the dungeon from the deepest one.
+pub struct DescendSystem;
+
+impl DescendSystem {
+    pub const HEAL: i32 = 8;
+}
+
+impl ExclusiveSystem for DescendSystem {
+    fn run(&mut self, world: &mut World) {
+        // Monsters only ever head for cells next to them, so blocked paths
+        // need no handling
+        world.take_events::<PathBlocked>();
+        let arrived = world.take_events::<PathCompleted>();
+        let Some(hero) = hero(world) else {
+            return;
+        };
+        let on_stairs = world.query_entities::<Stairs>().into_iter().any(|stairs| {
+            world.get_component::<Position>(stairs) == world.get_component::<Position>(hero)
+        });
+        if !on_stairs || !arrived.iter().any(|done| done.entity == hero) {
+            return;
+        }
+        let level = world.resource_mut::<Level>().expect("a level is built");
+        if level.depth == DEPTHS {
+            log(world, format!("{} climbs out of the dungeon!", name(world, hero)));
+            world.insert_resource(Outcome::Escaped);
+            return;
+        }
+        level.depth += 1;
+        let depth = level.depth;
+        if let Some(health) = world.get_component_mut::<Health>(hero) {
+            health.hp = (health.hp + Self::HEAL).min(health.max);
+        }
+        log(world, format!("{} descends to depth {}.", name(world, hero), depth));
+        build_level(world);
+    }
+}
+
+// Ends the turn: destinations only last for the turn they were chosen on,
+// so `PathFollowSystem` moves each creature once per turn of its own.
+pub struct EndTurnSystem;
+
+impl ExclusiveSystem for EndTurnSystem {
+    fn run(&mut self, world: &mut World) {
+        world.take_events::<TurnEvent>();
+        for e in world.query_entities::<Destination>() {
+            world.remove_component::<Destination>(e);
+        }
+    }
+}
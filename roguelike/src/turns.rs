@@ -0,0 +1,84 @@
+use rusty_ecs_core::{Entity, Event, ExclusiveSystem, World};
+
+// The energy an actor spends on one turn.
+pub const TURN_COST: u32 = 100;
+
+// Component for anything that takes turns. Every tick adds `speed` to its
+// energy and it acts once that reaches `TURN_COST`, so an actor twice as
+// fast gets twice the turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Actor {
+    pub speed: u32,
+    pub energy: u32,
+}
+
+impl Actor {
+    pub fn new(speed: u32) -> Self {
+        Self { speed, energy: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct TurnEvent {
+    pub entity: Entity,
+}
+
+// Hands out one turn per run: to the actor with the most energy at or above
+// `TURN_COST` (the earliest entity on ties), ticking every actor until one
+// gets there. Pushes a `TurnEvent` and charges the actor for it.
+pub struct TurnScheduler;
+
+impl ExclusiveSystem for TurnScheduler {
+    fn run(&mut self, world: &mut World) {
+        let mut actors = world.query_entities::<Actor>();
+        actors.sort();
+        if actors.iter().all(|&e| world.get_component::<Actor>(e).unwrap().speed == 0) {
+            return;
+        }
+        let next = loop {
+            let ready = actors
+                .iter()
+                .map(|&e| (world.get_component::<Actor>(e).unwrap().energy, e))
+                .filter(|&(energy, _)| energy >= TURN_COST)
+                .min_by_key(|&(energy, e)| (std::cmp::Reverse(energy), e));
+            if let Some((_, e)) = ready {
+                break e;
+            }
+            for &e in &actors {
+                let actor = world.get_component_mut::<Actor>(e).unwrap();
+                actor.energy += actor.speed;
+            }
+        };
+        world.get_component_mut::<Actor>(next).unwrap().energy -= TURN_COST;
+        world.push_event(TurnEvent { entity: next });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_ecs_core::SystemExecutor;
+
+    #[test]
+    fn test_faster_actors_get_more_turns() {
+        let mut world = World::new();
+        let hero = world.create_entity();
+        let bat = world.create_entity();
+        let slug = world.create_entity();
+        let rock = world.create_entity();
+        world.add_component(hero, Actor::new(10));
+        world.add_component(bat, Actor::new(20));
+        world.add_component(slug, Actor::new(5));
+        world.add_component(rock, Actor::new(0));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(TurnScheduler);
+
+        let mut turns = Vec::new();
+        for _ in 0..7 {
+            executor.run(&mut world);
+            turns.extend(world.take_events::<TurnEvent>().into_iter().map(|t| t.entity));
+        }
+        // Ties go to the earliest entity
+        assert_eq!(turns, [bat, hero, bat, bat, hero, bat, slug]);
+    }
+}
@@ -0,0 +1,178 @@
+// Criterion benchmarks for the ECS core.
+//
+// Run with `cargo bench`. Component benchmarks run once per storage backend:
+// `hashmap` (the default for sized components), `pooled` (the sparse set from
+// `World::register_pooled`) and `tag` (zero-sized markers). Criterion's own
+// baselines compare runs:
+//
+//     cargo bench -- --save-baseline before
+//     cargo bench -- --baseline before
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rusty_ecs_core::{Event, World, Without};
+use std::hint::black_box;
+
+const ENTITY_COUNT: u32 = 10_000;
+const BACKENDS: [&str; 3] = ["hashmap", "pooled", "tag"];
+
+struct Position(f32, f32);
+struct Velocity(f32, f32);
+struct Health(u32);
+struct Player;
+// The marker the `tag` backend uses where the others use `Velocity`.
+struct Moving;
+#[derive(Event)]
+struct HitEvent(u32);
+
+fn world_for(backend: &str) -> World {
+    let mut world = World::new();
+    if backend == "pooled" {
+        world.register_pooled::<Position>();
+        world.register_pooled::<Velocity>();
+        world.register_pooled::<Health>();
+    }
+    world
+}
+
+// Every entity gets a `Position` and `Health`, every other one the
+// backend's second component and every hundredth a `Player`.
+fn populated_world(backend: &str) -> World {
+    let mut world = world_for(backend);
+    for i in 0..ENTITY_COUNT {
+        let e = world.create_entity();
+        world.add_component(e, Position(i as f32, 0.0));
+        world.add_component(e, Health(i));
+        if i % 2 == 0 {
+            match backend {
+                "tag" => world.add_component(e, Moving),
+                _ => world.add_component(e, Velocity(1.0, 1.0)),
+            }
+        }
+        if i % 100 == 0 {
+            world.add_component(e, Player);
+        }
+    }
+    world
+}
+
+fn bench_entities(c: &mut Criterion) {
+    c.bench_function("entity_creation", |b| {
+        b.iter_with_large_drop(|| {
+            let mut world = World::new();
+            for _ in 0..ENTITY_COUNT {
+                black_box(world.create_entity());
+            }
+            world
+        })
+    });
+    c.bench_function("entity_recycling", |b| {
+        let mut world = World::new();
+        let mut entities: Vec<_> = (0..ENTITY_COUNT).map(|_| world.create_entity()).collect();
+        b.iter(|| {
+            for e in entities.iter_mut() {
+                world.destroy_entity(*e);
+                *e = world.create_entity();
+            }
+        })
+    });
+}
+
+fn bench_component_insertion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("component_insertion");
+    for backend in BACKENDS {
+        group.bench_function(BenchmarkId::from_parameter(backend), |b| {
+            b.iter_with_large_drop(|| {
+                let mut world = world_for(backend);
+                for i in 0..ENTITY_COUNT {
+                    let e = world.create_entity();
+                    match backend {
+                        "tag" => world.add_component(e, Moving),
+                        _ => world.add_component(e, Velocity(i as f32, 0.0)),
+                    }
+                }
+                world
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_queries(c: &mut Criterion) {
+    let mut single = c.benchmark_group("single_component_query");
+    for backend in BACKENDS {
+        let world = populated_world(backend);
+        single.bench_function(BenchmarkId::from_parameter(backend), |b| {
+            b.iter(|| match backend {
+                "tag" => world.query::<&Moving, ()>().count() as u64,
+                _ => world.query::<&Velocity, ()>().map(|(_, v)| v.0 as u64).sum(),
+            })
+        });
+    }
+    single.finish();
+
+    let mut multi = c.benchmark_group("multi_component_query");
+    for backend in BACKENDS {
+        let world = populated_world(backend);
+        multi.bench_function(BenchmarkId::from_parameter(backend), |b| {
+            b.iter(|| match backend {
+                "tag" => world.query::<(&Position, &Moving), ()>().map(|(_, (p, _))| p.0).sum(),
+                _ => world
+                    .query::<(&Position, &Velocity), ()>()
+                    .map(|(_, (p, v))| p.0 * v.0 + p.1 * v.1)
+                    .sum::<f32>(),
+            })
+        });
+    }
+    multi.finish();
+
+    let mut filtered = c.benchmark_group("filtered_query");
+    for backend in BACKENDS {
+        let world = populated_world(backend);
+        filtered.bench_function(BenchmarkId::from_parameter(backend), |b| {
+            b.iter(|| {
+                world.query::<&Health, Without<Player>>().map(|(_, h)| h.0 as u64).sum::<u64>()
+            })
+        });
+    }
+    filtered.finish();
+}
+
+fn bench_event_throughput(c: &mut Criterion) {
+    c.bench_function("event_throughput", |b| {
+        let mut world = World::new();
+        b.iter(|| {
+            for i in 0..ENTITY_COUNT {
+                world.push_event(HitEvent(i));
+            }
+            world.drain_events::<HitEvent>().map(|e| e.0 as u64).sum::<u64>()
+        })
+    });
+}
+
+// A deliberately heavy per-entity update, to compare `par_iter_mut` with and
+// without `--features rayon`.
+fn bench_par_iter_mut(c: &mut Criterion) {
+    #[cfg(feature = "rayon")]
+    use rayon::iter::ParallelIterator;
+
+    let mut world = populated_world("hashmap");
+    c.bench_function("par_iter_mut", |b| {
+        b.iter(|| {
+            world.par_iter_mut::<Position>().for_each(|(_, p)| {
+                for _ in 0..100 {
+                    p.0 = (p.0 * 1.0001 + p.1).sqrt();
+                }
+            })
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_entities,
+    bench_component_insertion,
+    bench_queries,
+    bench_event_throughput,
+    bench_par_iter_mut
+);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+// With the `plugins` feature, records the compiler version and the enabled
+// features so the plugin handshake can compare them: `World` and
+// `SystemExecutor` only line up between builds that agree on both.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_PLUGINS").is_none() {
+        return;
+    }
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = Command::new(rustc)
+        .arg("-V")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_default();
+
+    let mut features: Vec<String> = env::var("CARGO_CFG_FEATURE")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+        .map(String::from)
+        .collect();
+    features.sort();
+
+    println!("cargo:rustc-env=RUSTY_RUSTC_VERSION={}", version);
+    println!("cargo:rustc-env=RUSTY_FEATURES={}", features.join(","));
+}
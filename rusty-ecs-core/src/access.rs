@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+use crate::component::Component;
+
+// The component types a system declares it reads and writes. Writing implies
+// reading.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    reads: Vec<(TypeId, &'static str)>,
+    writes: Vec<(TypeId, &'static str)>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read<T: Component>(mut self) -> Self {
+        self.reads
+            .push((TypeId::of::<T>(), core::any::type_name::<T>()));
+        self
+    }
+
+    pub fn write<T: Component>(mut self) -> Self {
+        self.writes
+            .push((TypeId::of::<T>(), core::any::type_name::<T>()));
+        self
+    }
+
+    pub fn can_read(&self, type_id: TypeId) -> bool {
+        self.reads.iter().chain(&self.writes).any(|(id, _)| *id == type_id)
+    }
+
+    pub fn can_write(&self, type_id: TypeId) -> bool {
+        self.writes.iter().any(|(id, _)| *id == type_id)
+    }
+
+    pub fn reads(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.reads.iter().map(|(_, name)| *name)
+    }
+
+    pub fn writes(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.writes.iter().map(|(_, name)| *name)
+    }
+
+    // The first type declared more than once, as a read and a write or twice
+    // as either. Write already implies read, so a repeat is a mistake in the
+    // declaration, the counterpart of one function asking for `&T` and
+    // `&mut T` at the same time.
+    pub fn duplicate(&self) -> Option<&'static str> {
+        let all: Vec<_> = self.reads.iter().chain(&self.writes).collect();
+        all.iter()
+            .enumerate()
+            .find(|(i, (id, _))| all[..*i].iter().any(|(earlier, _)| earlier == id))
+            .map(|(_, (_, name))| *name)
+    }
+
+    // Whether `self` reads something `other` writes, so it would see a
+    // half-finished update if `other` failed partway through.
+    pub fn depends_on(&self, other: &Access) -> bool {
+        other.writes.iter().any(|(id, _)| self.can_read(*id))
+    }
+
+    // Two systems conflict when one writes a type the other reads or writes.
+    pub fn conflicts_with(&self, other: &Access) -> bool {
+        self.writes.iter().any(|(id, _)| other.can_read(*id))
+            || other.writes.iter().any(|(id, _)| self.can_read(*id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Access;
+    use core::any::TypeId;
+
+    struct Position;
+    struct Velocity;
+
+    #[test]
+    fn test_write_implies_read() {
+        let access = Access::new().read::<Velocity>().write::<Position>();
+
+        assert!(access.can_read(TypeId::of::<Position>()));
+        assert!(access.can_read(TypeId::of::<Velocity>()));
+        assert!(!access.can_write(TypeId::of::<Velocity>()));
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let movement = Access::new().read::<Velocity>().write::<Position>();
+        let reader = Access::new().read::<Position>();
+        let other = Access::new().read::<Velocity>();
+
+        assert!(movement.conflicts_with(&reader));
+        assert!(reader.conflicts_with(&movement));
+        assert!(!movement.conflicts_with(&other));
+        assert!(!reader.conflicts_with(&other));
+        assert!(reader.depends_on(&movement));
+        assert!(!movement.depends_on(&reader));
+        assert!(!other.depends_on(&movement));
+    }
+
+    #[test]
+    fn test_duplicate_declarations() {
+        assert_eq!(Access::new().read::<Velocity>().write::<Position>().duplicate(), None);
+        let both = Access::new().read::<Position>().write::<Position>();
+        assert_eq!(both.duplicate(), Some(core::any::type_name::<Position>()));
+        assert!(Access::new().write::<Velocity>().write::<Velocity>().duplicate().is_some());
+    }
+}
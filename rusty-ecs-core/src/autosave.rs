@@ -0,0 +1,178 @@
+use crate::snapshot::{EntityMap, Snapshot, SnapshotError};
+use crate::system::ExclusiveSystem;
+use crate::world::World;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Resource with the base path autosaves are written under. Slot `n` goes to
+// `<base>.<n>`, e.g. `saves/game.0`, `saves/game.1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutosavePath(pub PathBuf);
+
+impl AutosavePath {
+    pub fn slot(&self, slot: usize) -> PathBuf {
+        let mut path = self.0.clone().into_os_string();
+        path.push(format!(".{}", slot));
+        PathBuf::from(path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, crate::Event)]
+pub struct AutosaveFailed {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+#[derive(Debug)]
+pub enum AutosaveError {
+    Io(io::Error),
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for AutosaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutosaveError::Io(err) => write!(f, "could not read autosave: {}", err),
+            AutosaveError::Snapshot(err) => write!(f, "could not restore autosave: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AutosaveError {}
+
+// Writes a snapshot every `every` executor runs, rotating through `slots`
+// files so a save interrupted halfway never replaces the only good one. Each
+// save overwrites a missing slot or else the oldest. Failures are pushed as
+// `AutosaveFailed` events; nothing is written without an `AutosavePath`.
+pub struct AutosaveSystem {
+    every: u32,
+    slots: usize,
+    count: u32,
+}
+
+impl AutosaveSystem {
+    pub fn new(every: u32, slots: usize) -> Self {
+        Self {
+            every: every.max(1),
+            slots: slots.max(1),
+            count: 0,
+        }
+    }
+}
+
+impl ExclusiveSystem for AutosaveSystem {
+    fn run(&mut self, world: &mut World) {
+        self.count += 1;
+        if self.count < self.every {
+            return;
+        }
+        self.count = 0;
+        let Some(base) = world.resource::<AutosavePath>() else {
+            return;
+        };
+        let slots = saved_slots(base, self.slots);
+        let slot = (0..self.slots)
+            .find(|slot| !slots.iter().any(|(s, _)| s == slot))
+            .or_else(|| slots.iter().min_by_key(|(_, modified)| *modified).map(|(s, _)| *s))
+            .unwrap_or(0);
+        let path = base.slot(slot);
+
+        if let Err(err) = fs::write(&path, world.snapshot().to_bytes()) {
+            let error = err.to_string();
+            world.push_event(AutosaveFailed { path, error });
+        }
+    }
+}
+
+// Existing slot files and when each was last written.
+fn saved_slots(base: &AutosavePath, slots: usize) -> Vec<(usize, SystemTime)> {
+    (0..slots)
+        .filter_map(|slot| {
+            let modified = fs::metadata(base.slot(slot)).ok()?.modified().ok()?;
+            Some((slot, modified))
+        })
+        .collect()
+}
+
+// The most recently written of the `slots` autosave files under `base`.
+pub fn latest_autosave(base: &Path, slots: usize) -> Option<PathBuf> {
+    let base = AutosavePath(base.to_path_buf());
+    saved_slots(&base, slots)
+        .into_iter()
+        .max_by_key(|(_, modified)| *modified)
+        .map(|(slot, _)| base.slot(slot))
+}
+
+// Restores the newest autosave into `world`, which must not have live
+// entities yet; `Ok(None)` when there is none.
+pub fn load_latest_autosave(
+    world: &mut World,
+    base: &Path,
+    slots: usize,
+) -> Result<Option<EntityMap>, AutosaveError> {
+    let Some(path) = latest_autosave(base, slots) else {
+        return Ok(None);
+    };
+    let bytes = fs::read(path).map_err(AutosaveError::Io)?;
+    let snapshot = Snapshot::from_bytes(&bytes).map_err(AutosaveError::Snapshot)?;
+    world
+        .restore_snapshot(&snapshot)
+        .map(Some)
+        .map_err(AutosaveError::Snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemExecutor;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Score(u32);
+
+    impl crate::Persist for Score {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Score(u32::read(input)?))
+        }
+    }
+
+    #[test]
+    fn test_autosave_rotates_slots_and_loads_latest() {
+        let dir = std::env::temp_dir().join(format!("rusty-autosave-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("game");
+
+        let mut world = World::new();
+        world.register_persistent::<Score>("score");
+        world.insert_resource(AutosavePath(base.clone()));
+        let e = world.create_entity();
+        world.add_component(e, Score(0));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(AutosaveSystem::new(2, 2));
+
+        for round in 1..=6 {
+            world.get_component_mut::<Score>(e).unwrap().0 = round;
+            executor.run(&mut world);
+            // Keeps the modification times of consecutive saves apart
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(world.take_events::<AutosaveFailed>().is_empty());
+        assert!(!AutosavePath(base.clone()).slot(2).exists());
+        // Saves after runs 2, 4 and 6 went to slots 0, 1 and 0 again
+        assert_eq!(latest_autosave(&base, 2), Some(AutosavePath(base.clone()).slot(0)));
+
+        let mut restored = World::new();
+        restored.register_persistent::<Score>("score");
+        let map = load_latest_autosave(&mut restored, &base, 2).unwrap().unwrap();
+        assert_eq!(restored.get_component::<Score>(map[&e]), Some(&Score(6)));
+        assert!(load_latest_autosave(&mut restored, &dir.join("none"), 2).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,429 @@
+use crate::collections::HashMap;
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::event::Event;
+use crate::snapshot::{Persist, SnapshotError};
+use crate::system::ExclusiveSystem;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+    // The action is still underway; it is ticked again on the next run.
+    Running,
+}
+
+// A node of a behavior tree. Conditions and actions are referenced by name
+// and looked up in the `Behaviors` resource on every tick, so trees can be
+// written in data files. The text form, read by `parse` and written by
+// `Display`, looks like
+//
+//     selector(sequence(if wounded, heal), sequence(if has_target, attack), wander)
+//
+// where `if name` is a condition and a bare name an action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BehaviorNode {
+    // Ticks its children in order until one does not succeed.
+    Sequence(Vec<BehaviorNode>),
+    // Ticks its children in order until one does not fail.
+    Selector(Vec<BehaviorNode>),
+    Condition(String),
+    Action(String),
+}
+
+impl BehaviorNode {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { rest: text };
+        let node = parser.node()?;
+        match parser.rest.trim() {
+            "" => Ok(node),
+            rest => Err(format!("unexpected `{}` after the tree", rest)),
+        }
+    }
+
+    // Every condition and action name in the tree, in order, repeats
+    // included.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.collect_names(&mut names);
+        names
+    }
+
+    fn collect_names<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            BehaviorNode::Sequence(children) | BehaviorNode::Selector(children) => {
+                for child in children {
+                    child.collect_names(names);
+                }
+            }
+            BehaviorNode::Condition(name) | BehaviorNode::Action(name) => names.push(name),
+        }
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn word(&mut self) -> &'a str {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        word
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn node(&mut self) -> Result<BehaviorNode, String> {
+        match self.word() {
+            "" => Err(match self.rest.chars().next() {
+                Some(c) => format!("expected a node, found `{}`", c),
+                None => "expected a node, found the end of the text".to_string(),
+            }),
+            kind @ ("sequence" | "selector") => {
+                if !self.eat('(') {
+                    return Err(format!("expected `(` after `{}`", kind));
+                }
+                let mut children = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        children.push(self.node()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(format!("expected `,` or `)` in `{}`", kind));
+                        }
+                    }
+                }
+                Ok(match kind {
+                    "sequence" => BehaviorNode::Sequence(children),
+                    _ => BehaviorNode::Selector(children),
+                })
+            }
+            "if" => match self.word() {
+                "" => Err("expected a condition name after `if`".to_string()),
+                name => Ok(BehaviorNode::Condition(name.to_string())),
+            },
+            name => Ok(BehaviorNode::Action(name.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for BehaviorNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BehaviorNode::Sequence(children) | BehaviorNode::Selector(children) => {
+                let kind = match self {
+                    BehaviorNode::Sequence(_) => "sequence",
+                    _ => "selector",
+                };
+                write!(f, "{}(", kind)?;
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", child)?;
+                }
+                f.write_str(")")
+            }
+            BehaviorNode::Condition(name) => write!(f, "if {}", name),
+            BehaviorNode::Action(name) => f.write_str(name),
+        }
+    }
+}
+
+impl FromStr for BehaviorNode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// Component: the behavior tree its entity runs, ticked by `BehaviorSystem`.
+// Trees keep no memory between ticks: every tick starts from the root, so a
+// `Running` action is reached again only while the conditions before it
+// still hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BehaviorTree {
+    // Shared so that ticking does not clone the whole tree.
+    root: Arc<BehaviorNode>,
+    status: Option<Status>,
+}
+
+impl BehaviorTree {
+    pub fn new(root: BehaviorNode) -> Self {
+        Self { root: Arc::new(root), status: None }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        BehaviorNode::parse(text).map(Self::new)
+    }
+
+    pub fn root(&self) -> &BehaviorNode {
+        &self.root
+    }
+
+    // What the last tick returned, None before the first one.
+    pub fn status(&self) -> Option<Status> {
+        self.status
+    }
+}
+
+impl FromStr for BehaviorTree {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// Saved in its text form; the last status is not kept.
+impl Persist for BehaviorTree {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.root.to_string().write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Self::parse(&String::read(input)?).map_err(SnapshotError::InvalidData)
+    }
+}
+
+pub type BehaviorCondition = Box<dyn Fn(&World, Entity) -> bool + Send + Sync>;
+pub type BehaviorAction = Box<dyn Fn(&mut World, Entity) -> Status + Send + Sync>;
+
+// Resource: the conditions and actions behavior trees refer to by name. A
+// name missing from it makes its node fail; `missing` finds such names in a
+// tree loaded from a file.
+#[derive(Default)]
+pub struct Behaviors {
+    conditions: HashMap<String, BehaviorCondition>,
+    actions: HashMap<String, BehaviorAction>,
+}
+
+impl Behaviors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn condition(
+        mut self,
+        name: &str,
+        condition: impl Fn(&World, Entity) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.conditions.insert(name.to_string(), Box::new(condition));
+        self
+    }
+
+    pub fn action(
+        mut self,
+        name: &str,
+        action: impl Fn(&mut World, Entity) -> Status + Send + Sync + 'static,
+    ) -> Self {
+        self.actions.insert(name.to_string(), Box::new(action));
+        self
+    }
+
+    // A condition that holds while the entity has a T.
+    pub fn has<T: Component>(self, name: &str) -> Self {
+        self.condition(name, |world, entity| world.get_component::<T>(entity).is_some())
+    }
+
+    // An action that pushes the event `make` builds for the entity and
+    // succeeds.
+    pub fn emit<E: Event>(
+        self,
+        name: &str,
+        make: impl Fn(Entity) -> E + Send + Sync + 'static,
+    ) -> Self {
+        self.action(name, move |world, entity| {
+            world.push_event(make(entity));
+            Status::Success
+        })
+    }
+
+    // The names in `node` with no matching condition or action, in order.
+    pub fn missing<'a>(&self, node: &'a BehaviorNode) -> Vec<&'a str> {
+        let mut missing = Vec::new();
+        self.collect_missing(node, &mut missing);
+        missing
+    }
+
+    fn collect_missing<'a>(&self, node: &'a BehaviorNode, missing: &mut Vec<&'a str>) {
+        match node {
+            BehaviorNode::Sequence(children) | BehaviorNode::Selector(children) => {
+                for child in children {
+                    self.collect_missing(child, missing);
+                }
+            }
+            BehaviorNode::Condition(name) if !self.conditions.contains_key(name) => {
+                missing.push(name)
+            }
+            BehaviorNode::Action(name) if !self.actions.contains_key(name) => missing.push(name),
+            _ => {}
+        }
+    }
+
+    pub fn tick(&self, node: &BehaviorNode, world: &mut World, entity: Entity) -> Status {
+        match node {
+            BehaviorNode::Sequence(children) => {
+                for child in children {
+                    match self.tick(child, world, entity) {
+                        Status::Success => {}
+                        status => return status,
+                    }
+                }
+                Status::Success
+            }
+            BehaviorNode::Selector(children) => {
+                for child in children {
+                    match self.tick(child, world, entity) {
+                        Status::Failure => {}
+                        status => return status,
+                    }
+                }
+                Status::Failure
+            }
+            BehaviorNode::Condition(name) => match self.conditions.get(name) {
+                Some(condition) if condition(world, entity) => Status::Success,
+                _ => Status::Failure,
+            },
+            BehaviorNode::Action(name) => {
+                self.actions.get(name).map_or(Status::Failure, |action| action(world, entity))
+            }
+        }
+    }
+}
+
+// Ticks the tree of every enabled entity with a `BehaviorTree`, in entity
+// order, against the `Behaviors` resource.
+#[derive(Default)]
+pub struct BehaviorSystem;
+
+impl ExclusiveSystem for BehaviorSystem {
+    fn run(&mut self, world: &mut World) {
+        // Taken out while ticking so that actions get the world mutably
+        let Some(behaviors) = world.remove_resource::<Behaviors>() else {
+            return;
+        };
+        let mut entities = world.query_entities::<BehaviorTree>();
+        entities.sort();
+        for entity in entities {
+            // An earlier action may have removed or replaced this tree
+            let Some(root) = world.get_component::<BehaviorTree>(entity).map(|t| t.root.clone())
+            else {
+                continue;
+            };
+            let status = behaviors.tick(&root, world, entity);
+            if let Some(tree) = world.get_component_mut::<BehaviorTree>(entity) {
+                tree.status = Some(status);
+            }
+        }
+        world.insert_resource(behaviors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemExecutor;
+
+    struct Wounded;
+
+    struct Charge(u32);
+
+    #[derive(Debug, PartialEq, crate::Event)]
+    struct Healed(Entity);
+
+    fn behaviors() -> Behaviors {
+        Behaviors::new()
+            .has::<Wounded>("wounded")
+            .emit("heal", Healed)
+            .action("charge", |world, entity| {
+                let charge = world.get_component_mut::<Charge>(entity).unwrap();
+                charge.0 += 1;
+                if charge.0 < 2 { Status::Running } else { Status::Success }
+            })
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let text = "selector(sequence(if wounded, heal), sequence(), charge)";
+        let spaced = " selector( sequence(if wounded,heal),\n sequence( ), charge ) ";
+        let tree = BehaviorNode::parse(spaced).unwrap();
+        assert_eq!(tree.to_string(), text);
+        assert_eq!(BehaviorNode::parse(text), Ok(tree.clone()));
+        assert_eq!(tree.names(), ["wounded", "heal", "charge"]);
+        let napping = BehaviorNode::parse("sequence(if tired, heal, nap)").unwrap();
+        assert_eq!(behaviors().missing(&napping), ["tired", "nap"]);
+
+        let mut bytes = Vec::new();
+        BehaviorTree::new(tree.clone()).write(&mut bytes);
+        let read = BehaviorTree::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read.root(), &tree);
+
+        assert!(BehaviorNode::parse("sequence(heal").is_err());
+        assert!(BehaviorNode::parse("selector heal").is_err());
+        assert!(BehaviorNode::parse("if ,").is_err());
+        assert!(BehaviorNode::parse("heal) charge").is_err());
+        assert!(BehaviorNode::parse("").is_err());
+    }
+
+    #[test]
+    fn test_behavior_system_ticks_trees() {
+        let mut world = World::new();
+        world.insert_resource(behaviors());
+        let tree = BehaviorTree::parse("selector(sequence(if wounded, heal), charge)").unwrap();
+        let knight = world.create_entity();
+        let squire = world.create_entity();
+        let page = world.create_entity();
+        for e in [knight, squire, page] {
+            world.add_component(e, tree.clone());
+            world.add_component(e, Charge(0));
+        }
+        world.add_component(knight, Wounded);
+        world.set_enabled(page, false);
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(BehaviorSystem);
+
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<Healed>(), [Healed(knight)]);
+        let status = |world: &World, e| world.get_component::<BehaviorTree>(e).unwrap().status();
+        assert_eq!(status(&world, knight), Some(Status::Success));
+        assert_eq!(status(&world, squire), Some(Status::Running));
+        assert_eq!(status(&world, page), None);
+
+        executor.run(&mut world);
+        assert_eq!(status(&world, squire), Some(Status::Success));
+        assert_eq!(world.get_component::<Charge>(knight).unwrap().0, 0);
+        assert!(world.resource::<Behaviors>().is_some());
+
+        // Unknown names fail
+        world.add_component(squire, BehaviorTree::parse("sequence(if tired, nap)").unwrap());
+        executor.run(&mut world);
+        assert_eq!(status(&world, squire), Some(Status::Failure));
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::vec::Vec;
+
+const BLOCK_BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    blocks: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self { blocks: Vec::new() }
+    }
+
+    pub fn insert(&mut self, index: u32) {
+        let (block, bit) = Self::position(index);
+        if block >= self.blocks.len() {
+            self.blocks.resize(block + 1, 0);
+        }
+        self.blocks[block] |= 1 << bit;
+    }
+
+    pub fn remove(&mut self, index: u32) {
+        let (block, bit) = Self::position(index);
+        if let Some(b) = self.blocks.get_mut(block) {
+            *b &= !(1 << bit);
+        }
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        let (block, bit) = Self::position(index);
+        self.blocks
+            .get(block)
+            .map(|b| b & (1 << bit) != 0)
+            .unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|b| *b == 0)
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.blocks.capacity() * size_of::<u64>()
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    pub fn first(&self) -> Option<u32> {
+        self.iter().next()
+    }
+
+    // Drops trailing empty blocks and releases their memory.
+    pub fn shrink_to_fit(&mut self) {
+        while self.blocks.last() == Some(&0) {
+            self.blocks.pop();
+        }
+        self.blocks.shrink_to_fit();
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.blocks.truncate(other.blocks.len());
+        for (a, b) in self.blocks.iter_mut().zip(&other.blocks) {
+            *a &= b;
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (a, b) in self.blocks.iter_mut().zip(&other.blocks) {
+            *a &= !b;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.blocks.iter().copied().enumerate().flat_map(Self::block_bits)
+    }
+
+    pub fn into_ids(self) -> impl Iterator<Item = u32> {
+        self.blocks.into_iter().enumerate().flat_map(Self::block_bits)
+    }
+
+    fn block_bits((i, mut bits): (usize, u64)) -> impl Iterator<Item = u32> {
+        core::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let bit = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            Some((i * BLOCK_BITS + bit) as u32)
+        })
+    }
+
+    fn position(index: u32) -> (usize, usize) {
+        let index = index as usize;
+        (index / BLOCK_BITS, index % BLOCK_BITS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut set = BitSet::new();
+
+        set.insert(3);
+        set.insert(64);
+        set.insert(200);
+
+        assert!(set.contains(3));
+        assert!(set.contains(64));
+        assert!(set.contains(200));
+        assert!(!set.contains(4));
+        assert!(!set.contains(10_000));
+        assert_eq!(set.len(), 3);
+
+        set.remove(64);
+        set.remove(10_000);
+        assert!(!set.contains(64));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_is_ascending() {
+        let mut set = BitSet::new();
+        for i in [130, 1, 65, 0, 63] {
+            set.insert(i);
+        }
+
+        let items: Vec<_> = set.iter().collect();
+        assert_eq!(items, vec![0, 1, 63, 65, 130]);
+    }
+
+    #[test]
+    fn test_intersect_and_difference() {
+        let mut a = BitSet::new();
+        let mut b = BitSet::new();
+        for i in [1, 2, 3, 100] {
+            a.insert(i);
+        }
+        for i in [2, 3, 4] {
+            b.insert(i);
+        }
+
+        let mut and = a.clone();
+        and.intersect_with(&b);
+        assert_eq!(and.iter().collect::<Vec<_>>(), vec![2, 3]);
+
+        let mut not = a.clone();
+        not.difference_with(&b);
+        assert_eq!(not.iter().collect::<Vec<_>>(), vec![1, 100]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut set = BitSet::new();
+        assert!(set.is_empty());
+
+        set.insert(5);
+        set.remove(5);
+        assert!(set.is_empty());
+    }
+}
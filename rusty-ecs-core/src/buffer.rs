@@ -0,0 +1,124 @@
+use crate::collections::HashMap;
+use crate::component::{Component, ComponentManager};
+use crate::entity::Entity;
+use alloc::boxed::Box;
+use core::any::{Any, TypeId};
+
+trait Buffer: Any + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn capture(&mut self, components: &ComponentManager);
+}
+
+struct TypedBuffer<T> {
+    values: HashMap<Entity, T>,
+}
+
+impl<T: Component + Clone> Buffer for TypedBuffer<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn capture(&mut self, components: &ComponentManager) {
+        self.values.clear();
+        let Some(storage) = components.storage::<T>() else {
+            return;
+        };
+        for entity in storage.entity_list() {
+            if let Some(value) = storage.get(entity) {
+                self.values.insert(entity, value.clone());
+            }
+        }
+    }
+}
+
+// Copies of double-buffered component types as they were when the current
+// frame started, so render code can blend between the last two simulation
+// states. Only registered types are copied, once per frame.
+#[derive(Default)]
+pub struct PreviousState {
+    buffers: HashMap<TypeId, Box<dyn Buffer>>,
+}
+
+impl PreviousState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: Component + Clone>(&mut self) {
+        self.buffers.entry(TypeId::of::<T>()).or_insert_with(|| {
+            Box::new(TypedBuffer::<T> {
+                values: HashMap::default(),
+            })
+        });
+    }
+
+    pub fn is_registered<T: Component>(&self) -> bool {
+        self.buffers.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.buffers
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref::<TypedBuffer<T>>()?
+            .values
+            .get(&entity)
+    }
+
+    pub fn capture(&mut self, components: &ComponentManager) {
+        for buffer in self.buffers.values_mut() {
+            buffer.capture(components);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Access, System, SystemData, SystemExecutor, World};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Position(f32);
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Velocity(f32);
+
+    struct Movement;
+
+    impl System for Movement {
+        fn access(&self) -> Access {
+            Access::new().read::<Velocity>().write::<Position>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<Velocity>() {
+                let v = data.get::<Velocity>(entity).unwrap().0;
+                if let Some(p) = data.get_mut::<Position>(entity) {
+                    p.0 += v;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_previous_lags_one_frame() {
+        let mut world = World::new();
+        world.register_double_buffered::<Position>();
+        let e = world.create_entity();
+        world.add_component(e, Position(0.0));
+        world.add_component(e, Velocity(2.0));
+        assert_eq!(world.previous::<Position>(e), None);
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(Movement);
+        executor.run(&mut world);
+        assert_eq!(world.previous::<Position>(e), Some(&Position(0.0)));
+        assert_eq!(world.get_component::<Position>(e), Some(&Position(2.0)));
+
+        executor.run(&mut world);
+        assert_eq!(world.previous::<Position>(e), Some(&Position(2.0)));
+        // Not registered, so never copied
+        assert_eq!(world.previous::<Velocity>(e), None);
+
+        world.destroy_entity(e);
+        assert_eq!(world.previous::<Position>(e), None);
+    }
+}
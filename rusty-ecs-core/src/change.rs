@@ -0,0 +1,36 @@
+use crate::bitset::BitSet;
+use crate::collections::HashMap;
+use core::any::TypeId;
+
+// Records which entities had a component of each type added or mutably
+// borrowed since the last `clear`. The executor clears it after every run, so
+// systems see changes made earlier in the same frame and anything done to the
+// world between frames.
+#[derive(Default)]
+pub struct ChangeTracker {
+    changed: HashMap<TypeId, BitSet>,
+}
+
+impl ChangeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mark(&mut self, type_id: TypeId, id: u32) {
+        self.changed.entry(type_id).or_default().insert(id);
+    }
+
+    pub fn is_changed(&self, type_id: TypeId, id: u32) -> bool {
+        self.changed
+            .get(&type_id)
+            .is_some_and(|set| set.contains(id))
+    }
+
+    pub fn changed(&self, type_id: TypeId) -> Option<&BitSet> {
+        self.changed.get(&type_id)
+    }
+
+    pub fn clear(&mut self) {
+        self.changed.clear();
+    }
+}
@@ -0,0 +1,70 @@
+// Map type used by the internal storages. With `std` this is the standard
+// `HashMap`, so construct maps with `HashMap::default()`; without it,
+// `hashbrown`'s map, which the standard one is built on, stands in.
+
+#[cfg(all(feature = "std", not(feature = "fast-hash")))]
+pub(crate) use std::collections::HashMap;
+
+// With `fast-hash` the maps use `FxHasher` instead of SipHash.
+#[cfg(all(feature = "std", feature = "fast-hash"))]
+pub(crate) type HashMap<K, V> =
+    std::collections::HashMap<K, V, core::hash::BuildHasherDefault<FxHasher>>;
+
+#[cfg(not(feature = "std"))]
+pub(crate) type HashMap<K, V> = hashbrown::HashMap<K, V>;
+
+pub(crate) use alloc::collections::VecDeque;
+
+pub(crate) fn map_capacity<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity()
+}
+
+// The Fx hash from rustc: one rotate, xor and multiply per word. Much cheaper
+// than SipHash for the small keys used here (entity ids, `Entity`, `TypeId`),
+// but not resistant to chosen collisions, which only matters for keys an
+// attacker controls.
+#[cfg(feature = "fast-hash")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+#[cfg(feature = "fast-hash")]
+impl FxHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+#[cfg(feature = "fast-hash")]
+impl core::hash::Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.add(u64::from(n));
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.add(u64::from(n));
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.add(n);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.add(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
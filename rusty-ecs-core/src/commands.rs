@@ -0,0 +1,143 @@
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+type Command = Box<dyn FnOnce(&mut World) + Send>;
+
+// Structural changes recorded by data systems and applied later, at the
+// executor's flush points, with full `&mut World` access.
+#[derive(Default)]
+pub struct Commands {
+    queue: Vec<Command>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: impl FnOnce(&mut World) + Send + 'static) {
+        self.queue.push(Box::new(command));
+    }
+
+    pub fn spawn(&mut self, build: impl FnOnce(&mut World, Entity) + Send + 'static) {
+        self.push(move |world| {
+            let entity = world.create_entity();
+            build(world, entity);
+        });
+    }
+
+    pub fn destroy_entity(&mut self, entity: Entity) {
+        self.push(move |world| world.destroy_entity(entity));
+    }
+
+    pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
+        self.push(move |world| world.add_component(entity, component));
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) {
+        self.push(move |world| {
+            world.remove_component::<T>(entity);
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    // Drops the commands recorded after the first `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.queue.truncate(len);
+    }
+
+    // Runs commands in the order they were recorded. Commands pushed while
+    // applying (e.g. by hooks) are not queued here, so this always terminates.
+    pub fn apply(&mut self, world: &mut World) {
+        for command in self.queue.drain(..) {
+            command(world);
+        }
+    }
+}
+
+impl World {
+    // Runs `f` with a read-only view of the world and a command recorder,
+    // then applies what it recorded. Replaces collecting entities into a Vec
+    // just to mutate them after the borrow ends:
+    //
+    //     world.run_scoped(|commands, world| {
+    //         for (e, _) in world.query::<&Health, With<Enemy>>() {
+    //             commands.destroy_entity(e);
+    //         }
+    //     });
+    pub fn run_scoped<R>(&mut self, f: impl FnOnce(&mut Commands, &World) -> R) -> R {
+        let mut commands = Commands::new();
+        let result = f(&mut commands, self);
+        commands.apply(self);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Commands, World};
+
+    struct Health(u32);
+    struct Dead;
+
+    #[test]
+    fn test_commands_are_deferred_until_applied() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(0));
+
+        let mut commands = Commands::new();
+        commands.add_component(e, Dead);
+        commands.spawn(|world, spawned| world.add_component(spawned, Health(5)));
+        assert!(world.get_component::<Dead>(e).is_none());
+        assert_eq!(commands.len(), 2);
+
+        commands.apply(&mut world);
+        assert!(commands.is_empty());
+        assert!(world.get_component::<Dead>(e).is_some());
+        assert_eq!(world.query::<&Health, ()>().map(|(_, h)| h.0).sum::<u32>(), 5);
+    }
+
+    #[test]
+    fn test_commands_apply_in_order() {
+        let mut world = World::new();
+        let e = world.create_entity();
+
+        let mut commands = Commands::new();
+        commands.add_component(e, Health(1));
+        commands.remove_component::<Health>(e);
+        commands.destroy_entity(e);
+        commands.apply(&mut world);
+
+        assert!(!world.is_alive(e));
+    }
+
+    #[test]
+    fn test_run_scoped_applies_after_the_view_is_released() {
+        let mut world = World::new();
+        for hp in [0, 3, 0] {
+            let e = world.create_entity();
+            world.add_component(e, Health(hp));
+        }
+        let marked = world.run_scoped(|commands, world| {
+            for (e, _) in world.query::<&Health, ()>().filter(|(_, h)| h.0 == 0) {
+                commands.add_component(e, Dead);
+            }
+            // Nothing is applied while the closure runs
+            assert_eq!(world.count::<Dead>(), 0);
+            commands.len()
+        });
+        assert_eq!(marked, 2);
+        assert_eq!(world.count::<Dead>(), 2);
+    }
+}
@@ -1,41 +1,133 @@
+use crate::bitset::BitSet;
 use crate::entity::Entity;
-use std::any::{Any, TypeId};
-use std::collections::HashMap;
-
-pub trait Component: Any + 'static {}
-impl<T: Any + 'static> Component for T {}
-
-pub trait ComponentStorage: Any {
+use crate::collections::{map_capacity, HashMap};
+use crate::group::{GroupTypes, OwnedGroup};
+use crate::pool::PooledComponentStorage;
+use crate::tag::TagStorage;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+
+// Components must be shareable across threads so read-only systems can run
+// in parallel over a `&World`.
+pub trait Component: Any + Send + Sync + 'static {}
+impl<T: Any + Send + Sync + 'static> Component for T {}
+
+pub trait ComponentStorage: Any + Send + Sync {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn remove(&mut self, entity: Entity);
+    fn mask(&self) -> &BitSet;
+    fn contains(&self, entity: Entity) -> bool;
+    fn len(&self) -> usize;
+    fn entity_list(&self) -> Vec<Entity>;
+    fn clone_component(&mut self, entity: Entity, target: Entity) -> bool;
+    fn type_name(&self) -> &'static str;
+    fn memory(&self) -> StorageReport;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Position in a dense array, for backends that have one; groups reorder
+    // storages through these.
+    fn dense_index(&self, _entity: Entity) -> Option<usize> {
+        None
+    }
+
+    fn swap_dense(&mut self, _a: usize, _b: usize) {}
+}
+
+// Estimated heap use of one storage, see `ComponentManager::memory_report`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StorageReport {
+    pub type_name: &'static str,
+    pub entities: usize,
+    pub bytes: usize,
+    // Share of the allocated slots in use; low values mean wasted capacity.
+    pub load_factor: f32,
+}
+
+impl StorageReport {
+    pub fn new<T>(entities: usize, bytes: usize, slots: usize) -> Self {
+        Self {
+            type_name: core::any::type_name::<T>(),
+            entities,
+            bytes,
+            load_factor: if slots == 0 { 0.0 } else { entities as f32 / slots as f32 },
+        }
+    }
+}
+
+// Typed access shared by every storage backend, so the world can read and
+// write components without knowing which backend a type was registered with.
+pub trait TypedStorage<T: Component>: ComponentStorage {
+    fn insert(&mut self, entity: Entity, component: T);
+    fn take(&mut self, entity: Entity) -> Option<T>;
+    fn get(&self, entity: Entity) -> Option<&T>;
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T>;
+    // Every component with its entity, in storage order.
+    fn iter_mut(&mut self) -> Box<dyn Iterator<Item = (Entity, &mut T)> + '_>;
 }
 
 pub struct HashMapComponentStorage<T: Component> {
     components: HashMap<Entity, T>,
+    mask: BitSet,
+    pub(crate) clone_fn: Option<fn(&T) -> T>,
 }
 
 impl<T: Component> HashMapComponentStorage<T> {
     pub fn new() -> Self {
         Self {
-            components: HashMap::new(),
+            components: HashMap::default(),
+            mask: BitSet::new(),
+            clone_fn: None,
         }
     }
 
-    pub fn insert(&mut self, entity: Entity, component: T) {
+    pub fn cloneable() -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            clone_fn: Some(T::clone),
+            ..Self::new()
+        }
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        self.components.keys()
+    }
+}
+
+impl<T: Component> Default for HashMapComponentStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component> TypedStorage<T> for HashMapComponentStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
         self.components.insert(entity, component);
+        self.mask.insert(entity.id);
     }
 
-    pub fn get(&self, entity: Entity) -> Option<&T> {
+    fn take(&mut self, entity: Entity) -> Option<T> {
+        let component = self.components.remove(&entity)?;
+        self.mask.remove(entity.id);
+        Some(component)
+    }
+
+    fn get(&self, entity: Entity) -> Option<&T> {
         self.components.get(&entity)
     }
 
-    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
         self.components.get_mut(&entity)
     }
 
-    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
-        self.components.keys()
+    fn iter_mut(&mut self) -> Box<dyn Iterator<Item = (Entity, &mut T)> + '_> {
+        Box::new(self.components.iter_mut().map(|(&entity, component)| (entity, component)))
     }
 }
 
@@ -49,29 +141,211 @@ impl<T: Component> ComponentStorage for HashMapComponentStorage<T> {
     }
 
     fn remove(&mut self, entity: Entity) {
-        self.components.remove(&entity);
+        if self.components.remove(&entity).is_some() {
+            self.mask.remove(entity.id);
+        }
+    }
+
+    fn mask(&self) -> &BitSet {
+        &self.mask
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.components.contains_key(&entity)
+    }
+
+    fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    fn entity_list(&self) -> Vec<Entity> {
+        self.components.keys().copied().collect()
+    }
+
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<T>()
+    }
+
+    // Each slot holds the key, the value and about a byte of bookkeeping.
+    fn memory(&self) -> StorageReport {
+        let slots = map_capacity(&self.components);
+        let bytes = slots * (size_of::<(Entity, T)>() + 1) + self.mask.heap_bytes();
+        StorageReport::new::<T>(self.components.len(), bytes, slots)
+    }
+
+    fn clone_component(&mut self, entity: Entity, target: Entity) -> bool {
+        let Some(clone_fn) = self.clone_fn else {
+            return false;
+        };
+        match self.components.get(&entity).map(clone_fn) {
+            Some(component) => {
+                self.insert(target, component);
+                true
+            }
+            None => false,
+        }
     }
 }
 
 pub struct ComponentManager {
     storages: HashMap<TypeId, Box<dyn ComponentStorage>>,
+    groups: Vec<OwnedGroup>,
 }
 
 impl ComponentManager {
     pub fn new() -> Self {
         Self {
-            storages: HashMap::new(),
+            storages: HashMap::default(),
+            groups: Vec::new(),
         }
     }
 
+    // Zero-sized types are markers and go in a `TagStorage`; everything else
+    // defaults to a hash map.
     pub fn register<T: Component>(&mut self) {
-        let type_id = TypeId::of::<T>();
-        if !self.storages.contains_key(&type_id) {
-            self.storages
-                .insert(type_id, Box::new(HashMapComponentStorage::<T>::new()));
+        self.storages.entry(TypeId::of::<T>()).or_insert_with(|| {
+            if size_of::<T>() == 0 {
+                Box::new(TagStorage::<T>::new())
+            } else {
+                Box::new(HashMapComponentStorage::<T>::new())
+            }
+        });
+    }
+
+    pub fn register_cloneable<T: Component + Clone>(&mut self) {
+        self.register::<T>();
+        let storage = self.storages.get_mut(&TypeId::of::<T>()).unwrap().as_any_mut();
+        if let Some(storage) = storage.downcast_mut::<HashMapComponentStorage<T>>() {
+            storage.clone_fn = Some(T::clone);
+        } else if let Some(tags) = storage.downcast_mut::<TagStorage<T>>() {
+            tags.clone_fn = Some(T::clone);
+        } else if let Some(pool) = storage.downcast_mut::<PooledComponentStorage<T>>() {
+            pool.clone_fn = Some(T::clone);
+        }
+    }
+
+    // Replaces any existing storage for T, so register pooled types before
+    // adding components to them.
+    pub fn register_pooled<T: Component>(&mut self) {
+        self.storages
+            .insert(TypeId::of::<T>(), Box::new(PooledComponentStorage::<T>::new()));
+        self.reset_groups(TypeId::of::<T>());
+    }
+
+    // Moves T into a pooled storage, keeping its components and clone support.
+    pub(crate) fn make_pooled<T: Component>(&mut self) {
+        self.register::<T>();
+        if self.pooled::<T>().is_some() {
+            return;
+        }
+        let old = self.storages.remove(&TypeId::of::<T>()).unwrap();
+        let mut old = old as Box<dyn Any>;
+        let mut pool = PooledComponentStorage::<T>::new();
+        if let Some(storage) = old.downcast_mut::<HashMapComponentStorage<T>>() {
+            pool.clone_fn = storage.clone_fn;
+            for (entity, component) in core::mem::take(&mut storage.components) {
+                pool.insert(entity, component);
+            }
+        } else if let Some(tags) = old.downcast_mut::<TagStorage<T>>() {
+            pool.clone_fn = tags.clone_fn;
+            for entity in tags.entity_list() {
+                if let Some(component) = tags.take(entity) {
+                    pool.insert(entity, component);
+                }
+            }
         }
+        self.storages.insert(TypeId::of::<T>(), Box::new(pool));
     }
 
+    // Keeps the pooled storages of `G` co-sorted so `group_len` entities with
+    // all of them sit at the front of each dense array, in the same order.
+    // A type can belong to one group only; grouping it again with other types
+    // panics.
+    pub fn group<G: GroupTypes>(&mut self) {
+        let types = G::type_ids();
+        if self.groups.iter().any(|group| group.types == types) {
+            return;
+        }
+        assert!(
+            !self.groups.iter().any(|group| types.iter().any(|t| group.owns(*t))),
+            "a component type can only be owned by one group"
+        );
+        G::make_pooled(self);
+        let mut group = OwnedGroup { types, len: 0 };
+        for entity in self.storages[&group.types[0]].entity_list() {
+            group.try_enter(&mut self.storages, entity);
+        }
+        self.groups.push(group);
+    }
+
+    // Number of grouped entities of the group owning every type in `types`.
+    pub fn group_len(&self, types: &[TypeId]) -> Option<usize> {
+        self.groups
+            .iter()
+            .find(|group| types.iter().all(|t| group.owns(*t)))
+            .map(|group| group.len)
+    }
+
+    pub fn pooled<T: Component>(&self) -> Option<&PooledComponentStorage<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref::<PooledComponentStorage<T>>()
+    }
+
+    fn reset_groups(&mut self, type_id: TypeId) {
+        for group in self.groups.iter_mut().filter(|group| group.owns(type_id)) {
+            group.len = 0;
+        }
+    }
+
+    // Empties a pooled storage but keeps its allocations; see
+    // `PooledComponentStorage::clear_frame`.
+    pub fn clear_frame<T: Component>(&mut self) {
+        if let Some(pool) = self.get_pooled_mut::<T>() {
+            pool.clear_frame();
+            self.reset_groups(TypeId::of::<T>());
+        }
+    }
+
+    pub fn storage<T: Component>(&self) -> Option<&dyn TypedStorage<T>> {
+        let storage = self.storages.get(&TypeId::of::<T>())?.as_any();
+        if let Some(storage) = storage.downcast_ref::<HashMapComponentStorage<T>>() {
+            return Some(storage);
+        }
+        if let Some(storage) = storage.downcast_ref::<TagStorage<T>>() {
+            return Some(storage);
+        }
+        storage
+            .downcast_ref::<PooledComponentStorage<T>>()
+            .map(|storage| storage as &dyn TypedStorage<T>)
+    }
+
+    pub fn storage_mut<T: Component>(&mut self) -> Option<&mut dyn TypedStorage<T>> {
+        let storage = self.storages.get_mut(&TypeId::of::<T>())?.as_any_mut();
+        if storage.is::<HashMapComponentStorage<T>>() {
+            return storage
+                .downcast_mut::<HashMapComponentStorage<T>>()
+                .map(|storage| storage as &mut dyn TypedStorage<T>);
+        }
+        if storage.is::<TagStorage<T>>() {
+            return storage
+                .downcast_mut::<TagStorage<T>>()
+                .map(|storage| storage as &mut dyn TypedStorage<T>);
+        }
+        storage
+            .downcast_mut::<PooledComponentStorage<T>>()
+            .map(|storage| storage as &mut dyn TypedStorage<T>)
+    }
+
+    pub fn get_pooled_mut<T: Component>(&mut self) -> Option<&mut PooledComponentStorage<T>> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<PooledComponentStorage<T>>()
+    }
+
+    // Only finds hash map backed types; zero-sized ones live in a TagStorage.
     pub fn get_storage<T: Component>(&self) -> Option<&HashMapComponentStorage<T>> {
         self.storages
             .get(&TypeId::of::<T>())?
@@ -86,24 +360,110 @@ impl ComponentManager {
             .downcast_mut::<HashMapComponentStorage<T>>()
     }
 
+    pub fn mask<T: Component>(&self) -> Option<&BitSet> {
+        Some(self.storages.get(&TypeId::of::<T>())?.mask())
+    }
+
     pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
         self.register::<T>();
-        if let Some(storage) = self.get_storage_mut::<T>() {
+        if let Some(storage) = self.storage_mut::<T>() {
             storage.insert(entity, component);
         }
+        let type_id = TypeId::of::<T>();
+        for group in self.groups.iter_mut().filter(|group| group.owns(type_id)) {
+            group.try_enter(&mut self.storages, entity);
+        }
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(entity)
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.storage_mut::<T>()?.get_mut(entity)
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        let type_id = TypeId::of::<T>();
+        for group in self.groups.iter_mut().filter(|group| group.owns(type_id)) {
+            group.leave(&mut self.storages, entity);
+        }
+        self.storage_mut::<T>()?.take(entity)
+    }
+
+    pub fn entity_list<T: Component>(&self) -> Vec<Entity> {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|storage| storage.entity_list())
+            .unwrap_or_default()
+    }
+
+    pub fn storages(&self) -> impl Iterator<Item = (TypeId, &dyn ComponentStorage)> {
+        self.storages
+            .iter()
+            .map(|(type_id, storage)| (*type_id, storage.as_ref()))
+    }
+
+    // One report per registered type, largest first.
+    pub fn memory_report(&self) -> Vec<StorageReport> {
+        let mut report: Vec<StorageReport> =
+            self.storages.values().map(|storage| storage.memory()).collect();
+        report.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.type_name.cmp(b.type_name)));
+        report
+    }
+
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.storages.values().map(|storage| storage.type_name())
+    }
+
+    pub fn storage_len<T: Component>(&self) -> usize {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|s| s.len())
+            .unwrap_or(0)
+    }
+
+    pub fn component_types(&self, entity: Entity) -> Vec<TypeId> {
+        self.storages
+            .iter()
+            .filter(|(_, storage)| storage.contains(entity))
+            .map(|(type_id, _)| *type_id)
+            .collect()
+    }
+
+    pub fn clone_components(&mut self, source: Entity, target: Entity) -> usize {
+        let mut cloned = 0;
+        for storage in self.storages.values_mut() {
+            if storage.clone_component(source, target) {
+                cloned += 1;
+            }
+        }
+        for group in &mut self.groups {
+            group.try_enter(&mut self.storages, target);
+        }
+        cloned
     }
 
     pub fn remove_all_components(&mut self, entity: Entity) {
+        for group in &mut self.groups {
+            group.leave(&mut self.storages, entity);
+        }
         for storage in self.storages.values_mut() {
             storage.remove(entity);
         }
     }
 }
 
+impl Default for ComponentManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{ComponentManager, Entity, HashMapComponentStorage};
-    use crate::component::ComponentStorage;
+    use crate::component::{ComponentStorage, TypedStorage};
 
     #[derive(Debug, PartialEq)]
     struct Position {
@@ -157,6 +517,24 @@ mod tests {
         assert!(storage.get(entity).is_none());
     }
 
+    #[test]
+    fn test_mask_tracks_entity_ids() {
+        let mut storage = HashMapComponentStorage::<Position>::new();
+        let e1 = Entity { id: 4, generation: 0 };
+        let e2 = Entity { id: 70, generation: 2 };
+
+        storage.insert(e1, Position { x: 0.0, y: 0.0 });
+        storage.insert(e2, Position { x: 0.0, y: 0.0 });
+        assert_eq!(storage.mask().iter().collect::<Vec<_>>(), vec![4, 70]);
+
+        // Removing a stale handle must not clear the bit of the live one
+        storage.remove(Entity { id: 70, generation: 1 });
+        assert!(storage.mask().contains(70));
+
+        storage.remove(e2);
+        assert_eq!(storage.mask().iter().collect::<Vec<_>>(), vec![4]);
+    }
+
     #[test]
     fn test_entities_iterator() {
         let mut storage = HashMapComponentStorage::<Position>::new();
@@ -237,9 +615,115 @@ mod tests {
         assert!(vel_storage.get(entity).is_none());
     }
 
+    #[derive(Debug, Clone, PartialEq)]
+    struct Name(&'static str);
+
+    #[test]
+    fn test_type_erased_storage_queries() {
+        let mut manager = ComponentManager::new();
+        let e1 = Entity { id: 1, generation: 0 };
+        let e2 = Entity { id: 2, generation: 0 };
+
+        manager.add_component(e1, Position { x: 0.0, y: 0.0 });
+        manager.add_component(e2, Position { x: 1.0, y: 1.0 });
+        manager.add_component(e2, Velocity { dx: 1.0, dy: 0.0 });
+
+        assert_eq!(manager.storage_len::<Position>(), 2);
+        assert_eq!(manager.storage_len::<Name>(), 0);
+        assert_eq!(manager.component_types(e1).len(), 1);
+        assert_eq!(manager.component_types(e2).len(), 2);
+
+        let storage: &dyn ComponentStorage = manager.get_storage::<Position>().unwrap();
+        assert!(storage.contains(e1));
+        assert!(!storage.is_empty());
+        let mut list = storage.entity_list();
+        list.sort();
+        assert_eq!(list, vec![e1, e2]);
+    }
+
+    #[test]
+    fn test_clone_components_only_copies_cloneable_types() {
+        let mut manager = ComponentManager::new();
+        manager.register_cloneable::<Name>();
+
+        let source = Entity { id: 1, generation: 0 };
+        let target = Entity { id: 2, generation: 0 };
+        manager.add_component(source, Name("Goblin"));
+        manager.add_component(source, Position { x: 1.0, y: 2.0 });
+
+        assert_eq!(manager.clone_components(source, target), 1);
+        assert_eq!(
+            manager.get_storage::<Name>().unwrap().get(target),
+            Some(&Name("Goblin"))
+        );
+        assert!(manager.get_storage::<Position>().unwrap().get(target).is_none());
+    }
+
+    #[test]
+    fn test_register_cloneable_after_plain_registration() {
+        let mut manager = ComponentManager::new();
+        let source = Entity { id: 1, generation: 0 };
+        let target = Entity { id: 2, generation: 0 };
+
+        manager.add_component(source, Name("Orc"));
+        manager.register_cloneable::<Name>();
+
+        assert_eq!(manager.clone_components(source, target), 1);
+    }
+
+    #[test]
+    fn test_storages_iteration() {
+        let mut manager = ComponentManager::new();
+        let e = Entity { id: 1, generation: 0 };
+        manager.add_component(e, Position { x: 0.0, y: 0.0 });
+        manager.register::<Velocity>();
+
+        let mut names: Vec<_> = manager.type_names().collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        assert!(names[0].ends_with("Position"));
+        assert!(names[1].ends_with("Velocity"));
+
+        let total: usize = manager.storages().map(|(_, storage)| storage.len()).sum();
+        assert_eq!(total, 1);
+
+        let (type_id, storage) = manager
+            .storages()
+            .find(|(_, storage)| storage.contains(e))
+            .unwrap();
+        assert_eq!(type_id, std::any::TypeId::of::<Position>());
+        assert!(storage.type_name().ends_with("Position"));
+    }
+
     #[test]
     fn test_get_storage_returns_none_if_not_registered() {
         let manager = ComponentManager::new();
         assert!(manager.get_storage::<Position>().is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_memory_report_per_type() {
+        struct Marker;
+        let mut manager = ComponentManager::new();
+        for id in 0..100 {
+            let e = Entity { id, generation: 0 };
+            manager.add_component(e, Position { x: 0.0, y: 0.0 });
+            manager.add_component(e, Marker);
+        }
+        manager.register::<Velocity>();
+
+        let report = manager.memory_report();
+        assert_eq!(report.len(), 3);
+        assert!(report[0].type_name.ends_with("Position"));
+        assert_eq!(report[0].entities, 100);
+        assert!(report[0].bytes >= 100 * core::mem::size_of::<(Entity, Position)>());
+        assert!(report[0].load_factor > 0.0 && report[0].load_factor <= 1.0);
+
+        let marker = report.iter().find(|r| r.type_name.ends_with("Marker")).unwrap();
+        assert_eq!((marker.entities, marker.load_factor), (100, 1.0));
+        assert!(marker.bytes < report[0].bytes / 2);
+
+        let velocity = report.iter().find(|r| r.type_name.ends_with("Velocity")).unwrap();
+        assert_eq!((velocity.entities, velocity.bytes, velocity.load_factor), (0, 0, 0.0));
+    }
+}
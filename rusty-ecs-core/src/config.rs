@@ -0,0 +1,81 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeadEntityPolicy {
+    Ignore,
+    #[default]
+    Warn,
+    Panic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOverflowPolicy {
+    DropOldest,
+    DropNewest,
+    Panic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventLimit {
+    pub capacity: usize,
+    pub policy: EventOverflowPolicy,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorldConfig {
+    pub dead_entity_policy: DeadEntityPolicy,
+    pub event_limit: Option<EventLimit>,
+    pub deterministic_iteration: bool,
+    pub change_tracking: bool,
+    pub entity_metadata: bool,
+    pub event_log: bool,
+    pub frame_events: bool,
+}
+
+impl WorldConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dead_entity_policy(mut self, policy: DeadEntityPolicy) -> Self {
+        self.dead_entity_policy = policy;
+        self
+    }
+
+    pub fn event_limit(mut self, capacity: usize, policy: EventOverflowPolicy) -> Self {
+        self.event_limit = Some(EventLimit { capacity, policy });
+        self
+    }
+
+    pub fn deterministic_iteration(mut self, enabled: bool) -> Self {
+        self.deterministic_iteration = enabled;
+        self
+    }
+
+    pub fn change_tracking(mut self, enabled: bool) -> Self {
+        self.change_tracking = enabled;
+        self
+    }
+
+    // Record a creation tick and optional debug label per entity, see
+    // `World::entity_meta`.
+    pub fn entity_metadata(mut self, enabled: bool) -> Self {
+        self.entity_metadata = enabled;
+        self
+    }
+
+    // Number every event as it is pushed, so `World::take_event_log` can hand
+    // out all pending events in push order across types.
+    pub fn event_log(mut self, enabled: bool) -> Self {
+        self.event_log = enabled;
+        self
+    }
+
+    // Events only last for the executor run they were pushed in: every queue
+    // is emptied at the end of the run, keeping its buffer for the next one.
+    // Any number of systems can then read the same events with
+    // `World::read_events` instead of one of them taking them. Diagnostic
+    // events are exempt, see `World::end_event_frame`.
+    pub fn frame_events(mut self, enabled: bool) -> Self {
+        self.frame_events = enabled;
+        self
+    }
+}
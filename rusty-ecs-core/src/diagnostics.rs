@@ -0,0 +1,43 @@
+use crate::component::StorageReport;
+use alloc::vec::Vec;
+use core::fmt;
+
+// Point-in-time summary of a world, from `World::diagnostics`. Byte counts
+// are estimates of heap use, not exact allocator figures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldDiagnostics {
+    pub live_entities: usize,
+    pub entity_capacity: usize,
+    pub resources: usize,
+    pub components: Vec<StorageReport>,
+}
+
+impl WorldDiagnostics {
+    pub fn component_bytes(&self) -> usize {
+        self.components.iter().map(|c| c.bytes).sum()
+    }
+}
+
+impl fmt::Display for WorldDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} live entities (capacity {}), {} resources, ~{} bytes of components",
+            self.live_entities,
+            self.entity_capacity,
+            self.resources,
+            self.component_bytes()
+        )?;
+        for c in &self.components {
+            writeln!(
+                f,
+                "  {:>8} B  {:>5} entities  {:>4.0}% full  {}",
+                c.bytes,
+                c.entities,
+                c.load_factor * 100.0,
+                c.type_name
+            )?;
+        }
+        Ok(())
+    }
+}
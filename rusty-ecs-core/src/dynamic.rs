@@ -0,0 +1,554 @@
+use crate::collections::{HashMap, VecDeque};
+use crate::entity::Entity;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+// The kinds of values a dynamic event field holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Int,
+    Float,
+    Bool,
+    Text,
+    Entity,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+    Entity(Entity),
+}
+
+impl Value {
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::Int(_) => ValueKind::Int,
+            Value::Float(_) => ValueKind::Float,
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Text(_) => ValueKind::Text,
+            Value::Entity(_) => ValueKind::Entity,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_entity(&self) -> Option<Entity> {
+        match self {
+            Value::Entity(entity) => Some(*entity),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DynamicEventId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DynamicComponentId(pub u32);
+
+// The name and fields of an event or component type only known at runtime,
+// e.g. defined by a script, a mod or a network peer:
+//
+//     Schema::new("Explosion")
+//         .field("at", ValueKind::Entity)
+//         .field("radius", ValueKind::Float)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub name: String,
+    pub fields: Vec<(String, ValueKind)>,
+}
+
+impl Schema {
+    pub fn new(name: impl Into<String>) -> Self {
+        Schema {
+            name: name.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn field(mut self, name: impl Into<String>, kind: ValueKind) -> Self {
+        self.fields.push((name.into(), kind));
+        self
+    }
+
+    fn index_of(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|(name, _)| name == field)
+    }
+
+    // Checks `values` against the fields, in order and kind.
+    fn check(&self, values: &[Value]) -> Result<(), DynamicError> {
+        if values.len() != self.fields.len() {
+            return Err(DynamicError::FieldCount {
+                name: self.name.clone(),
+                expected: self.fields.len(),
+                found: values.len(),
+            });
+        }
+        for ((field, expected), value) in self.fields.iter().zip(values) {
+            if value.kind() != *expected {
+                return Err(DynamicError::WrongKind {
+                    name: self.name.clone(),
+                    field: field.clone(),
+                    expected: *expected,
+                    found: value.kind(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+// One event of a runtime-defined type: its values, in schema field order,
+// together with the schema, so fields can be read by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicEvent {
+    id: DynamicEventId,
+    schema: Arc<Schema>,
+    values: Box<[Value]>,
+}
+
+impl DynamicEvent {
+    pub fn id(&self) -> DynamicEventId {
+        self.id
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.values.get(self.schema.index_of(field)?)
+    }
+}
+
+// A component of a runtime-defined type, attached to an entity with
+// `World::add_dynamic_component`. Like a `DynamicEvent`, its fields are read
+// by name; writes keep to the kinds in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicComponent {
+    schema: Arc<Schema>,
+    values: Box<[Value]>,
+}
+
+impl DynamicComponent {
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.values.get(self.schema.index_of(field)?)
+    }
+
+    pub fn set(&mut self, field: &str, value: Value) -> Result<(), DynamicError> {
+        let schema = &self.schema;
+        let index = schema.index_of(field).ok_or_else(|| DynamicError::UnknownField {
+            name: schema.name.clone(),
+            field: field.to_string(),
+        })?;
+        let expected = schema.fields[index].1;
+        if value.kind() != expected {
+            return Err(DynamicError::WrongKind {
+                name: schema.name.clone(),
+                field: field.to_string(),
+                expected,
+                found: value.kind(),
+            });
+        }
+        self.values[index] = value;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynamicError {
+    UnknownType(String),
+    // A different schema is already registered under this name.
+    SchemaConflict(String),
+    UnknownField { name: String, field: String },
+    FieldCount { name: String, expected: usize, found: usize },
+    WrongKind { name: String, field: String, expected: ValueKind, found: ValueKind },
+    EntityDead(Entity),
+}
+
+impl fmt::Display for DynamicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynamicError::UnknownType(key) => write!(f, "no dynamic type {}", key),
+            DynamicError::SchemaConflict(name) => {
+                write!(f, "dynamic type {} is already registered with other fields", name)
+            }
+            DynamicError::UnknownField { name, field } => {
+                write!(f, "{} has no field {}", name, field)
+            }
+            DynamicError::FieldCount { name, expected, found } => {
+                write!(f, "{} takes {} values, got {}", name, expected, found)
+            }
+            DynamicError::WrongKind { name, field, expected, found } => {
+                write!(f, "{}.{} is {:?}, got {:?}", name, field, expected, found)
+            }
+            DynamicError::EntityDead(entity) => write!(
+                f,
+                "entity {} (generation {}) is not alive",
+                entity.id, entity.generation
+            ),
+        }
+    }
+}
+
+impl core::error::Error for DynamicError {}
+
+// Registered schemas with their names, numbered in registration order.
+#[derive(Default)]
+pub struct Schemas {
+    schemas: Vec<Arc<Schema>>,
+    names: HashMap<String, u32>,
+}
+
+impl Schemas {
+    // Registering the same schema again returns its existing index.
+    fn register(&mut self, schema: Schema) -> Result<u32, DynamicError> {
+        if let Some(&index) = self.names.get(&schema.name) {
+            return if *self.schemas[index as usize] == schema {
+                Ok(index)
+            } else {
+                Err(DynamicError::SchemaConflict(schema.name))
+            };
+        }
+        let index = self.schemas.len() as u32;
+        self.names.insert(schema.name.clone(), index);
+        self.schemas.push(Arc::new(schema));
+        Ok(index)
+    }
+
+    fn get(&self, index: u32) -> Option<&Arc<Schema>> {
+        self.schemas.get(index as usize)
+    }
+}
+
+// Names a dynamic type by the name it was registered under or by the id
+// registration returned; `Id` is `DynamicEventId` or `DynamicComponentId`.
+pub trait DynamicKey<Id>: fmt::Display {
+    fn resolve(&self, schemas: &Schemas) -> Option<u32>;
+}
+
+impl<Id> DynamicKey<Id> for &str {
+    fn resolve(&self, schemas: &Schemas) -> Option<u32> {
+        schemas.names.get(*self).copied()
+    }
+}
+
+impl DynamicKey<DynamicEventId> for DynamicEventId {
+    fn resolve(&self, schemas: &Schemas) -> Option<u32> {
+        schemas.get(self.0).map(|_| self.0)
+    }
+}
+
+impl DynamicKey<DynamicComponentId> for DynamicComponentId {
+    fn resolve(&self, schemas: &Schemas) -> Option<u32> {
+        schemas.get(self.0).map(|_| self.0)
+    }
+}
+
+impl fmt::Display for DynamicEventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "event #{}", self.0)
+    }
+}
+
+impl fmt::Display for DynamicComponentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "component #{}", self.0)
+    }
+}
+
+// The queues of runtime-typed events, one per registered schema, kept next
+// to the typed queues in `EventManager`. Values are checked against the
+// schema when pushed, so readers can rely on the field kinds.
+#[derive(Default)]
+pub struct DynamicEvents {
+    schemas: Schemas,
+    queues: Vec<VecDeque<DynamicEvent>>,
+}
+
+impl DynamicEvents {
+    pub fn register(&mut self, schema: Schema) -> Result<DynamicEventId, DynamicError> {
+        let index = self.schemas.register(schema)?;
+        if index as usize == self.queues.len() {
+            self.queues.push(VecDeque::new());
+        }
+        Ok(DynamicEventId(index))
+    }
+
+    pub fn schema(&self, key: impl DynamicKey<DynamicEventId>) -> Option<&Schema> {
+        self.schemas.get(key.resolve(&self.schemas)?).map(|schema| &**schema)
+    }
+
+    pub fn push(
+        &mut self,
+        key: impl DynamicKey<DynamicEventId>,
+        values: Vec<Value>,
+    ) -> Result<(), DynamicError> {
+        let index = key
+            .resolve(&self.schemas)
+            .ok_or_else(|| DynamicError::UnknownType(key.to_string()))?;
+        let schema = &self.schemas.schemas[index as usize];
+        schema.check(&values)?;
+        let event = DynamicEvent {
+            id: DynamicEventId(index),
+            schema: schema.clone(),
+            values: values.into_boxed_slice(),
+        };
+        self.queues[index as usize].push_back(event);
+        Ok(())
+    }
+
+    // Pending events of the type, oldest first, removed from the queue.
+    // Empty for an unknown type.
+    pub fn take(&mut self, key: impl DynamicKey<DynamicEventId>) -> Vec<DynamicEvent> {
+        match key.resolve(&self.schemas) {
+            Some(index) => self.queues[index as usize].drain(..).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn read(
+        &self,
+        key: impl DynamicKey<DynamicEventId>,
+    ) -> impl Iterator<Item = &DynamicEvent> {
+        key.resolve(&self.schemas)
+            .into_iter()
+            .flat_map(|index| self.queues[index as usize].iter())
+    }
+
+    pub fn clear(&mut self) {
+        for queue in &mut self.queues {
+            queue.clear();
+        }
+    }
+}
+
+// Storages of runtime-typed components, one map per registered schema. They
+// sit beside the typed storages: hooks, watches, change tracking and
+// snapshots do not see them.
+#[derive(Default)]
+pub struct DynamicComponents {
+    schemas: Schemas,
+    storages: Vec<HashMap<Entity, DynamicComponent>>,
+}
+
+impl DynamicComponents {
+    pub fn register(&mut self, schema: Schema) -> Result<DynamicComponentId, DynamicError> {
+        let index = self.schemas.register(schema)?;
+        if index as usize == self.storages.len() {
+            self.storages.push(HashMap::default());
+        }
+        Ok(DynamicComponentId(index))
+    }
+
+    pub fn id(&self, key: impl DynamicKey<DynamicComponentId>) -> Option<DynamicComponentId> {
+        key.resolve(&self.schemas).map(DynamicComponentId)
+    }
+
+    pub fn schema(&self, key: impl DynamicKey<DynamicComponentId>) -> Option<&Schema> {
+        self.schemas.get(key.resolve(&self.schemas)?).map(|schema| &**schema)
+    }
+
+    // Replaces any component of the same type the entity already has.
+    pub(crate) fn insert(
+        &mut self,
+        entity: Entity,
+        key: impl DynamicKey<DynamicComponentId>,
+        values: Vec<Value>,
+    ) -> Result<(), DynamicError> {
+        let index = key
+            .resolve(&self.schemas)
+            .ok_or_else(|| DynamicError::UnknownType(key.to_string()))?;
+        let schema = &self.schemas.schemas[index as usize];
+        schema.check(&values)?;
+        let component = DynamicComponent {
+            schema: schema.clone(),
+            values: values.into_boxed_slice(),
+        };
+        self.storages[index as usize].insert(entity, component);
+        Ok(())
+    }
+
+    pub fn get(&self, entity: Entity, id: DynamicComponentId) -> Option<&DynamicComponent> {
+        self.storages.get(id.0 as usize)?.get(&entity)
+    }
+
+    pub(crate) fn get_mut(
+        &mut self,
+        entity: Entity,
+        id: DynamicComponentId,
+    ) -> Option<&mut DynamicComponent> {
+        self.storages.get_mut(id.0 as usize)?.get_mut(&entity)
+    }
+
+    pub(crate) fn remove(
+        &mut self,
+        entity: Entity,
+        id: DynamicComponentId,
+    ) -> Option<DynamicComponent> {
+        self.storages.get_mut(id.0 as usize)?.remove(&entity)
+    }
+
+    pub(crate) fn remove_all(&mut self, entity: Entity) {
+        for storage in &mut self.storages {
+            storage.remove(&entity);
+        }
+    }
+
+    pub(crate) fn clone_all(&mut self, source: Entity, target: Entity) {
+        for storage in &mut self.storages {
+            if let Some(component) = storage.get(&source).cloned() {
+                storage.insert(target, component);
+            }
+        }
+    }
+
+    pub(crate) fn entities(&self, id: DynamicComponentId) -> impl Iterator<Item = Entity> + '_ {
+        self.storages.get(id.0 as usize).into_iter().flat_map(|storage| storage.keys().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Schema, Value, ValueKind, World};
+    use super::DynamicError;
+
+    fn explosion() -> Schema {
+        Schema::new("Explosion")
+            .field("at", ValueKind::Entity)
+            .field("radius", ValueKind::Float)
+    }
+
+    #[test]
+    fn test_dynamic_events_by_name_and_id() {
+        let mut world = World::new();
+        let id = world.register_dynamic_event(explosion()).unwrap();
+        assert_eq!(world.register_dynamic_event(explosion()), Ok(id));
+        let barrel = world.create_entity();
+
+        let values = vec![Value::Entity(barrel), Value::Float(2.5)];
+        world.push_dynamic_event("Explosion", values).unwrap();
+        world.push_dynamic_event(id, vec![Value::Entity(barrel), Value::Float(4.0)]).unwrap();
+        assert_eq!(world.events().dynamic().read(id).count(), 2);
+
+        let events = world.take_dynamic_events("Explosion");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id(), id);
+        assert_eq!(events[0].get("at").and_then(Value::as_entity), Some(barrel));
+        assert_eq!(events[1].get("radius").and_then(Value::as_float), Some(4.0));
+        assert_eq!(events[0].get("damage"), None);
+        assert!(world.take_dynamic_events(id).is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_events_are_checked_against_the_schema() {
+        let mut world = World::new();
+        world.register_dynamic_event(explosion()).unwrap();
+        let barrel = world.create_entity();
+
+        assert_eq!(
+            world.push_dynamic_event("Quake", vec![]),
+            Err(DynamicError::UnknownType("Quake".into()))
+        );
+        assert!(matches!(
+            world.push_dynamic_event("Explosion", vec![Value::Entity(barrel)]),
+            Err(DynamicError::FieldCount { expected: 2, found: 1, .. })
+        ));
+        assert!(matches!(
+            world.push_dynamic_event("Explosion", vec![Value::Entity(barrel), Value::Int(2)]),
+            Err(DynamicError::WrongKind { expected: ValueKind::Float, .. })
+        ));
+        let other = Schema::new("Explosion").field("radius", ValueKind::Int);
+        assert_eq!(
+            world.register_dynamic_event(other),
+            Err(DynamicError::SchemaConflict("Explosion".into()))
+        );
+        assert!(world.take_dynamic_events("Explosion").is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_components_by_name() {
+        struct Enemy;
+
+        let mut world = World::new();
+        let poisoned = Schema::new("Poisoned").field("ticks", ValueKind::Int);
+        let id = world.register_dynamic_component(poisoned).unwrap();
+        world.register_dynamic_component(Schema::new("Cursed")).unwrap();
+        let rat = world.create_entity();
+        let bat = world.create_entity();
+        for e in [rat, bat] {
+            world.add_component(e, Enemy);
+            world.add_dynamic_component(e, "Poisoned", vec![Value::Int(3)]).unwrap();
+        }
+        world.add_dynamic_component(bat, id, vec![Value::Int(1)]).unwrap();
+        world.add_dynamic_component(bat, "Cursed", vec![]).unwrap();
+        assert!(world.add_dynamic_component(rat, "Poisoned", vec![Value::Bool(true)]).is_err());
+
+        let poison = world.get_dynamic_component_mut(rat, "Poisoned").unwrap();
+        poison.set("ticks", Value::Int(2)).unwrap();
+        assert!(poison.set("ticks", Value::Text("two".into())).is_err());
+        assert!(poison.set("damage", Value::Int(1)).is_err());
+
+        assert_eq!(world.dynamic_entities(&["Poisoned"]), vec![rat, bat]);
+        assert_eq!(world.dynamic_entities(&["Poisoned", "Cursed"]), vec![bat]);
+        assert!(world.dynamic_entities(&["Blessed"]).is_empty());
+        let ticks: Vec<i64> = world
+            .query_dynamic::<&Enemy, ()>(&["Poisoned"])
+            .iter()
+            .filter_map(|(_, _, dynamic)| dynamic[0].get("ticks").and_then(Value::as_int))
+            .collect();
+        assert_eq!(ticks, vec![2, 1]);
+
+        let clone = world.clone_entity(bat).unwrap();
+        assert!(world.get_dynamic_component(clone, "Cursed").is_some());
+        world.destroy_entity(bat);
+        assert_eq!(world.dynamic_entities(&["Cursed"]), vec![clone]);
+        assert!(world.remove_dynamic_component(clone, "Cursed").is_some());
+        let dead = world.add_dynamic_component(bat, "Cursed", vec![]);
+        assert_eq!(dead, Err(DynamicError::EntityDead(bat)));
+    }
+}
@@ -1,45 +1,250 @@
+use crate::bitset::BitSet;
+use alloc::format;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Entity {
     pub id: u32,
     pub generation: u32,
 }
 
+const GENERATION_CHUNK: usize = 1024;
+
+// Generations live in fixed-size chunks so growth never copies the whole
+// table. Free ids are reused lowest-first, which lets a spawn spike drain back
+// down: once the highest ids are all free they are retired and their chunks
+// released. New ids past the end start at `generation_floor`, which is above
+// every retired generation, so stale handles to retired ids stay dead.
 pub struct EntityManager {
     next_id: u32,
-    free_ids: Vec<u32>,
-    generations: Vec<u32>,
+    free: BitSet,
+    generations: Vec<Box<[u32]>>,
+    generation_floor: u32,
+    alive: BitSet,
+    live: usize,
+    // Entities ever created and destroyed, for per-frame churn counts.
+    created: u64,
+    destroyed: u64,
 }
 
 impl EntityManager {
     pub fn new() -> Self {
         Self {
             next_id: 0,
-            free_ids: Vec::new(),
+            free: BitSet::new(),
             generations: Vec::new(),
+            generation_floor: 0,
+            alive: BitSet::new(),
+            live: 0,
+            created: 0,
+            destroyed: 0,
         }
     }
 
     pub fn create(&mut self) -> Entity {
-        if let Some(id) = self.free_ids.pop() {
-            Entity {
-                id,
-                generation: self.generations[id as usize],
+        let id = match self.free.first() {
+            Some(id) => {
+                self.free.remove(id);
+                id
             }
-        } else {
-            let id = self.next_id;
-            self.next_id += 1;
-            self.generations.push(0);
-            Entity { id, generation: 0 }
+            None => {
+                let id = self.next_id;
+                self.next_id += 1;
+                if id as usize >= self.capacity() {
+                    self.generations
+                        .push(vec![0; GENERATION_CHUNK].into_boxed_slice());
+                }
+                *self.generation_mut(id) = self.generation_floor;
+                id
+            }
+        };
+        self.alive.insert(id);
+        self.live += 1;
+        self.created += 1;
+        Entity {
+            id,
+            generation: self.generation(id),
         }
     }
 
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.alive.contains(entity.id) && self.generation(entity.id) == entity.generation
+    }
+
+    pub fn entity_at(&self, id: u32) -> Option<Entity> {
+        (id < self.next_id).then(|| Entity {
+            id,
+            generation: self.generation(id),
+        })
+    }
+
     pub fn destroy(&mut self, entity: Entity) {
-        if (entity.id as usize) < self.generations.len() {
-            if self.generations[entity.id as usize] == entity.generation {
-                self.generations[entity.id as usize] += 1;
-                self.free_ids.push(entity.id);
+        if self.is_alive(entity) {
+            *self.generation_mut(entity.id) += 1;
+            self.free.insert(entity.id);
+            self.alive.remove(entity.id);
+            self.live -= 1;
+            self.destroyed += 1;
+            self.retire_trailing_ids();
+        }
+    }
+
+    pub fn alive_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive.iter().filter_map(|id| self.entity_at(id))
+    }
+
+    pub fn live_count(&self) -> usize {
+        self.live
+    }
+
+    pub fn created_count(&self) -> u64 {
+        self.created
+    }
+
+    pub fn destroyed_count(&self) -> u64 {
+        self.destroyed
+    }
+
+    // Number of ids the generation table can hold without allocating.
+    pub fn capacity(&self) -> usize {
+        self.generations.len() * GENERATION_CHUNK
+    }
+
+    // Generation of every allocated id, for snapshots; see `restore`.
+    pub fn generations(&self) -> Vec<u32> {
+        (0..self.next_id).map(|id| self.generation(id)).collect()
+    }
+
+    pub fn generation_floor(&self) -> u32 {
+        self.generation_floor
+    }
+
+    // Rebuilds the allocator from saved state: `alive` get back exactly their
+    // ids and generations, every other id up to the highest one is free with
+    // its saved generation, and ids are handed out in the same order as
+    // before the save.
+    pub fn restore(
+        &mut self,
+        alive: &[Entity],
+        generations: &[u32],
+        generation_floor: u32,
+    ) -> Result<(), String> {
+        let next_id = alive
+            .iter()
+            .map(|e| e.id + 1)
+            .max()
+            .unwrap_or(0)
+            .max(generations.len() as u32);
+        let mut restored = EntityManager::new();
+        restored.next_id = next_id;
+        restored.generation_floor = generation_floor;
+        for _ in 0..(next_id as usize).div_ceil(GENERATION_CHUNK) {
+            restored.generations.push(vec![0; GENERATION_CHUNK].into_boxed_slice());
+        }
+        for (id, &generation) in generations.iter().enumerate() {
+            *restored.generation_mut(id as u32) = generation;
+            restored.free.insert(id as u32);
+        }
+        for id in generations.len() as u32..next_id {
+            restored.free.insert(id);
+        }
+
+        for &entity in alive {
+            if restored.alive.contains(entity.id) {
+                return Err(format!("entity {:?} is listed twice", entity));
+            }
+            if generations.get(entity.id as usize).is_some_and(|&g| g != entity.generation) {
+                return Err(format!("entity {:?} does not match its saved generation", entity));
+            }
+            *restored.generation_mut(entity.id) = entity.generation;
+            restored.free.remove(entity.id);
+            restored.alive.insert(entity.id);
+            restored.live += 1;
+        }
+        restored.created = self.created;
+        restored.destroyed = self.destroyed;
+        *self = restored;
+        Ok(())
+    }
+
+    fn retire_trailing_ids(&mut self) {
+        if !self.free.contains(self.next_id.wrapping_sub(1)) {
+            return;
+        }
+        while self.next_id > 0 && self.free.contains(self.next_id - 1) {
+            let id = self.next_id - 1;
+            self.generation_floor = self.generation_floor.max(self.generation(id));
+            self.free.remove(id);
+            self.next_id = id;
+        }
+
+        let chunks = (self.next_id as usize).div_ceil(GENERATION_CHUNK);
+        if chunks < self.generations.len() {
+            self.generations.truncate(chunks);
+            self.generations.shrink_to_fit();
+            self.free.shrink_to_fit();
+            self.alive.shrink_to_fit();
+        }
+    }
+
+    fn generation(&self, id: u32) -> u32 {
+        let id = id as usize;
+        self.generations
+            .get(id / GENERATION_CHUNK)
+            .map_or(0, |chunk| chunk[id % GENERATION_CHUNK])
+    }
+
+    fn generation_mut(&mut self, id: u32) -> &mut u32 {
+        let id = id as usize;
+        &mut self.generations[id / GENERATION_CHUNK][id % GENERATION_CHUNK]
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if (self.next_id as usize) > self.capacity() {
+            errors.push(format!(
+                "next_id {} exceeds generation capacity {}",
+                self.next_id,
+                self.capacity()
+            ));
+        }
+
+        for id in self.free.iter() {
+            if id >= self.next_id {
+                errors.push(format!("free id {} was never allocated", id));
             }
+            if self.alive.contains(id) {
+                errors.push(format!("id {} is both alive and free", id));
+            }
+        }
+
+        if self.alive.len() != self.live {
+            errors.push(format!(
+                "live count {} does not match {} alive ids",
+                self.live,
+                self.alive.len()
+            ));
         }
+
+        let accounted = self.alive.len() + self.free.len();
+        if accounted != self.next_id as usize {
+            errors.push(format!(
+                "{} ids are neither alive nor free",
+                (self.next_id as usize).saturating_sub(accounted)
+            ));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl Default for EntityManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -134,6 +339,90 @@ mod tests {
         assert_eq!(e.id, 0);
     }
 
+    #[test]
+    fn test_entity_at_returns_current_generation() {
+        let mut manager = EntityManager::new();
+
+        let e1 = manager.create();
+        assert_eq!(manager.entity_at(e1.id), Some(e1));
+
+        manager.destroy(e1);
+        let e2 = manager.create();
+        assert_eq!(manager.entity_at(e1.id), Some(e2));
+        assert_eq!(manager.entity_at(42), None);
+    }
+
+    #[test]
+    fn test_destroy_unborn_generation_is_ignored() {
+        let mut manager = EntityManager::new();
+
+        let e1 = manager.create();
+        let _keep = manager.create();
+        manager.destroy(e1);
+
+        // The freed slot's next generation has not been handed out yet
+        let unborn = manager.entity_at(e1.id).unwrap();
+        assert!(!manager.is_alive(unborn));
+        manager.destroy(unborn);
+
+        assert!(manager.validate().is_ok());
+        let e2 = manager.create();
+        let e3 = manager.create();
+        assert_ne!(e2.id, e3.id);
+    }
+
+    #[test]
+    fn test_is_alive() {
+        let mut manager = EntityManager::new();
+
+        let e1 = manager.create();
+        assert!(manager.is_alive(e1));
+
+        manager.destroy(e1);
+        assert!(!manager.is_alive(e1));
+        assert!(!manager.is_alive(Entity { id: 7, generation: 0 }));
+    }
+
+    #[test]
+    fn test_lowest_free_id_is_reused_first() {
+        let mut manager = EntityManager::new();
+        let entities: Vec<_> = (0..5).map(|_| manager.create()).collect();
+
+        manager.destroy(entities[3]);
+        manager.destroy(entities[1]);
+
+        assert_eq!(manager.create().id, 1);
+        assert_eq!(manager.create().id, 3);
+    }
+
+    #[test]
+    fn test_spike_releases_memory() {
+        let mut manager = EntityManager::new();
+        let keep = manager.create();
+        let spike: Vec<_> = (0..10_000).map(|_| manager.create()).collect();
+        assert_eq!(manager.live_count(), 10_001);
+        assert!(manager.capacity() >= 10_001);
+
+        for e in spike.iter().rev().step_by(2).chain(spike.iter().step_by(2)) {
+            manager.destroy(*e);
+        }
+
+        assert_eq!(manager.live_count(), 1);
+        assert_eq!(manager.capacity(), GENERATION_CHUNK);
+        assert!(manager.validate().is_ok());
+        assert!(manager.is_alive(keep));
+
+        // Retired ids come back with a generation no stale handle carries
+        let reborn = manager.create();
+        assert_eq!(reborn.id, 1);
+        assert!(!spike.contains(&reborn));
+        assert!(spike.iter().all(|e| !manager.is_alive(*e)));
+        for e in &spike {
+            manager.destroy(*e);
+        }
+        assert!(manager.is_alive(reborn));
+    }
+
     #[test]
     fn test_sequential_ids_without_reuse() {
         let mut manager = EntityManager::new();
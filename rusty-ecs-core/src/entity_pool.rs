@@ -0,0 +1,159 @@
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+type Build = Box<dyn Fn(&mut World, Entity) + Send + Sync>;
+type Reset = fn(&mut World, Entity);
+
+// Entities spawned up front and recycled, for projectiles or particles that
+// would otherwise be spawned and despawned every few frames. Free entities
+// stay alive but disabled, so queries skip them; `release` puts the
+// components registered with `resetting` back to their defaults instead of
+// despawning. When every entity is out, `acquire` spawns another.
+//
+//     let mut arrows = EntityPool::new(&mut world, 32, |world, e| {
+//         world.add_component(e, Velocity::default());
+//     })
+//     .resetting::<Velocity>();
+//     let arrow = arrows.acquire(&mut world);
+//     arrows.release(&mut world, arrow);
+pub struct EntityPool {
+    build: Build,
+    resets: Vec<Reset>,
+    free: Vec<Entity>,
+    in_use: Vec<Entity>,
+}
+
+impl EntityPool {
+    // Spawns `size` entities with `build`, all free.
+    pub fn new(
+        world: &mut World,
+        size: usize,
+        build: impl Fn(&mut World, Entity) + Send + Sync + 'static,
+    ) -> Self {
+        let mut pool = EntityPool {
+            build: Box::new(build),
+            resets: Vec::new(),
+            free: Vec::with_capacity(size),
+            in_use: Vec::new(),
+        };
+        for _ in 0..size {
+            let entity = pool.spawn(world);
+            world.set_enabled(entity, false);
+            pool.free.push(entity);
+        }
+        pool
+    }
+
+    // Puts T back to `T::default()` on every released entity, adding it
+    // again if it was removed while the entity was out.
+    pub fn resetting<T: Component + Default>(mut self) -> Self {
+        self.resets.push(|world, entity| world.add_component(entity, T::default()));
+        self
+    }
+
+    fn spawn(&self, world: &mut World) -> Entity {
+        let entity = world.create_entity();
+        (self.build)(world, entity);
+        entity
+    }
+
+    // A free entity, enabled again. Free entities despawned from outside the
+    // pool are skipped.
+    pub fn acquire(&mut self, world: &mut World) -> Entity {
+        let entity = loop {
+            match self.free.pop() {
+                Some(entity) if world.is_alive(entity) => break entity,
+                Some(_) => continue,
+                None => break self.spawn(world),
+            }
+        };
+        world.set_enabled(entity, true);
+        self.in_use.push(entity);
+        entity
+    }
+
+    // Resets and disables `entity` for reuse. False, doing nothing, when it
+    // is not out of this pool or has been despawned.
+    pub fn release(&mut self, world: &mut World, entity: Entity) -> bool {
+        let Some(index) = self.in_use.iter().position(|&e| e == entity) else {
+            return false;
+        };
+        self.in_use.swap_remove(index);
+        if !world.is_alive(entity) {
+            return false;
+        }
+        for reset in &self.resets {
+            reset(world, entity);
+        }
+        world.set_enabled(entity, false);
+        self.free.push(entity);
+        true
+    }
+
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn in_use(&self) -> &[Entity] {
+        &self.in_use
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Velocity(i32);
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Trail(u32);
+
+    fn arrows(world: &mut World, size: usize) -> EntityPool {
+        EntityPool::new(world, size, |world, e| {
+            world.add_component(e, Velocity(0));
+            world.add_component(e, Trail(0));
+        })
+        .resetting::<Velocity>()
+    }
+
+    #[test]
+    fn test_acquire_and_release_recycle_entities() {
+        let mut world = World::new();
+        let mut pool = arrows(&mut world, 2);
+        assert_eq!(pool.available(), 2);
+        assert!(world.query_entities::<Velocity>().is_empty());
+
+        let arrow = pool.acquire(&mut world);
+        assert_eq!(world.query_entities::<Velocity>(), vec![arrow]);
+        world.get_component_mut::<Velocity>(arrow).unwrap().0 = 12;
+        world.get_component_mut::<Trail>(arrow).unwrap().0 = 3;
+
+        assert!(pool.release(&mut world, arrow));
+        assert!(!pool.release(&mut world, arrow));
+        assert!(world.is_alive(arrow));
+        assert!(!world.is_enabled(arrow));
+        assert_eq!(world.get_component::<Velocity>(arrow), Some(&Velocity(0)));
+        // Only registered components are reset
+        assert_eq!(world.get_component::<Trail>(arrow), Some(&Trail(3)));
+        assert_eq!(pool.acquire(&mut world), arrow);
+    }
+
+    #[test]
+    fn test_empty_pool_spawns_more() {
+        let mut world = World::new();
+        let mut pool = arrows(&mut world, 1);
+        let first = pool.acquire(&mut world);
+        let second = pool.acquire(&mut world);
+        assert_ne!(first, second);
+        assert_eq!(world.get_component::<Trail>(second), Some(&Trail(0)));
+        assert_eq!(pool.in_use(), &[first, second]);
+
+        world.destroy_entity(first);
+        assert!(!pool.release(&mut world, first));
+        assert_eq!(pool.in_use(), &[second]);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::entity::Entity;
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldError {
+    EntityDead(Entity),
+    ComponentMissing {
+        entity: Entity,
+        component: &'static str,
+    },
+    StorageMissing(&'static str),
+    // A `single` query matched no entity, or more than one.
+    NoMatch { query: &'static str },
+    MultipleMatches { query: &'static str, count: usize },
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorldError::EntityDead(entity) => write!(
+                f,
+                "entity {} (generation {}) is not alive",
+                entity.id, entity.generation
+            ),
+            WorldError::ComponentMissing { entity, component } => write!(
+                f,
+                "entity {} (generation {}) has no {} component",
+                entity.id, entity.generation, component
+            ),
+            WorldError::StorageMissing(component) => {
+                write!(f, "no storage registered for component {}", component)
+            }
+            WorldError::NoMatch { query } => write!(f, "no entity matches {}", query),
+            WorldError::MultipleMatches { query, count } => {
+                write!(f, "expected one entity to match {}, found {}", query, count)
+            }
+        }
+    }
+}
+
+impl core::error::Error for WorldError {}
@@ -1,37 +1,174 @@
-use std::any::{Any, TypeId};
-use std::collections::{HashMap, VecDeque};
-
-pub trait Event: Any + 'static {}
-impl<T: Any + 'static> Event for T {}
+use crate::collections::{HashMap, VecDeque};
+use crate::config::{EventLimit, EventOverflowPolicy};
+use crate::dynamic::DynamicEvents;
+use crate::entity::Entity;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+
+pub trait Event: Any + Send + Sync + 'static {}
+
+// Implemented by `#[derive(Event)]`. With the `explicit-events` feature only
+// types carrying this marker are events, so a component cannot be pushed as
+// an event by mistake; otherwise every `'static` type is an event.
+pub trait EventMarker {}
+
+#[cfg(not(feature = "explicit-events"))]
+impl<T: Any + Send + Sync + 'static> Event for T {}
+
+#[cfg(feature = "explicit-events")]
+impl<T: EventMarker + Any + Send + Sync + 'static> Event for T {}
+
+// An event about one entity, which `World::resolve_events` joins with that
+// entity's components.
+pub trait EntityEvent: Event {
+    fn entity(&self) -> Entity;
+}
 
-pub trait EventQueueTrait: Any {
+pub trait EventQueueTrait: Any + Send + Sync {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn clear(&mut self);
+    fn len(&self) -> usize;
+    fn type_name(&self) -> &'static str;
+    fn metrics(&self) -> EventMetrics;
+    // Drains the queue for the event log, each event with its push sequence
+    // number.
+    fn drain_logged(&mut self) -> Vec<LoggedEvent>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Lifetime counters for one event type, from `EventManager::metrics`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventMetrics {
+    pub type_name: &'static str,
+    pub pushed: u64,
+    pub consumed: u64,
+    // Lost to an overflow policy or a `clear` without being read.
+    pub dropped: u64,
+    pub pending: usize,
+    pub high_water: usize,
+}
+
+impl EventMetrics {
+    // Events keep arriving but nothing reads them, usually a missing system.
+    pub fn is_leaking(&self) -> bool {
+        self.pending > 0 && self.consumed == 0
+    }
+}
+
+// A pending event taken out of its queue by `World::take_event_log`, with
+// its place in the push order across all event types.
+pub struct LoggedEvent {
+    pub sequence: u64,
+    pub type_name: &'static str,
+    event: Box<dyn Any + Send + Sync>,
+}
+
+impl LoggedEvent {
+    pub fn is<E: Event>(&self) -> bool {
+        self.event.is::<E>()
+    }
+
+    pub fn downcast_ref<E: Event>(&self) -> Option<&E> {
+        self.event.downcast_ref::<E>()
+    }
+
+    // The event itself, or the logged event back when it is not an E.
+    pub fn downcast<E: Event>(self) -> Result<E, Self> {
+        let LoggedEvent { sequence, type_name, event } = self;
+        event
+            .downcast::<E>()
+            .map(|event| *event)
+            .map_err(|event| LoggedEvent { sequence, type_name, event })
+    }
+}
+
+impl core::fmt::Debug for LoggedEvent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LoggedEvent")
+            .field("sequence", &self.sequence)
+            .field("type_name", &self.type_name)
+            .finish_non_exhaustive()
+    }
 }
 
 pub struct EventQueue<E: Event> {
     events: VecDeque<E>,
+    // Push sequence numbers parallel to `events`, only kept while the
+    // manager logs events.
+    sequence: VecDeque<u64>,
+    pushed: u64,
+    consumed: u64,
+    dropped: u64,
+    high_water: usize,
 }
 
 impl<E: Event> EventQueue<E> {
     pub fn new() -> Self {
         Self {
             events: VecDeque::new(),
+            sequence: VecDeque::new(),
+            pushed: 0,
+            consumed: 0,
+            dropped: 0,
+            high_water: 0,
         }
     }
 
     pub fn push(&mut self, event: E) {
         self.events.push_back(event);
+        self.pushed += 1;
+        self.high_water = self.high_water.max(self.events.len());
+    }
+
+    fn push_sequenced(&mut self, sequence: u64, event: E) {
+        self.sequence.push_back(sequence);
+        self.push(event);
     }
 
     pub fn pop(&mut self) -> Option<E> {
-        self.events.pop_front()
+        let event = self.events.pop_front()?;
+        self.sequence.pop_front();
+        self.consumed += 1;
+        Some(event)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &E> {
         self.events.iter()
     }
+
+    // Everything drained counts as consumed, even if the iterator is dropped
+    // before reaching the end.
+    pub fn drain(&mut self) -> impl Iterator<Item = E> + '_ {
+        self.consumed += self.events.len() as u64;
+        self.sequence.clear();
+        self.events.drain(..)
+    }
+
+    fn drop_oldest(&mut self) {
+        if self.events.pop_front().is_some() {
+            self.sequence.pop_front();
+            self.dropped += 1;
+        }
+    }
+}
+
+impl<E: Event> Default for EventQueue<E> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<E: Event> EventQueueTrait for EventQueue<E> {
@@ -44,26 +181,85 @@ impl<E: Event> EventQueueTrait for EventQueue<E> {
     }
 
     fn clear(&mut self) {
+        self.dropped += self.events.len() as u64;
         self.events.clear();
+        self.sequence.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<E>()
+    }
+
+    fn metrics(&self) -> EventMetrics {
+        EventMetrics {
+            type_name: self.type_name(),
+            pushed: self.pushed,
+            consumed: self.consumed,
+            dropped: self.dropped,
+            pending: self.events.len(),
+            high_water: self.high_water,
+        }
+    }
+
+    fn drain_logged(&mut self) -> Vec<LoggedEvent> {
+        let type_name = self.type_name();
+        let sequence = core::mem::take(&mut self.sequence);
+        self.drain()
+            .zip(sequence)
+            .map(|(event, sequence)| LoggedEvent {
+                sequence,
+                type_name,
+                event: Box::new(event),
+            })
+            .collect()
     }
 }
 
 pub struct EventManager {
     queues: HashMap<TypeId, Box<dyn EventQueueTrait>>,
+    limit: Option<EventLimit>,
+    // Next push sequence number; None unless the event log is on.
+    next_sequence: Option<u64>,
+    dynamic: DynamicEvents,
 }
 
 impl EventManager {
     pub fn new() -> Self {
         Self {
-            queues: HashMap::new(),
+            queues: HashMap::default(),
+            limit: None,
+            next_sequence: None,
+            dynamic: DynamicEvents::default(),
         }
     }
 
+    // Events of types defined at runtime, see `World::register_dynamic_event`.
+    pub fn dynamic(&self) -> &DynamicEvents {
+        &self.dynamic
+    }
+
+    pub fn dynamic_mut(&mut self) -> &mut DynamicEvents {
+        &mut self.dynamic
+    }
+
+    // Numbers every pushed event so `take_log` can return them in push
+    // order. Only turned on before the first push, by `WorldConfig::event_log`.
+    pub(crate) fn enable_log(&mut self) {
+        self.next_sequence = Some(0);
+    }
+
+    pub fn set_limit(&mut self, limit: Option<EventLimit>) {
+        self.limit = limit;
+    }
+
     pub fn register<E: Event>(&mut self) {
-        let type_id = TypeId::of::<E>();
-        if !self.queues.contains_key(&type_id) {
-            self.queues.insert(type_id, Box::new(EventQueue::<E>::new()));
-        }
+        self.queues
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(EventQueue::<E>::new()));
     }
 
     pub fn get_queue<E: Event>(&self) -> Option<&EventQueue<E>> {
@@ -80,31 +276,190 @@ impl EventManager {
 
     pub fn push<E: Event>(&mut self, event: E) {
         self.register::<E>();
-        if let Some(queue) = self.get_queue_mut::<E>() {
-            queue.push(event);
+        let limit = self.limit;
+        let sequence = self.next_sequence;
+        if let Some(next) = &mut self.next_sequence {
+            *next += 1;
         }
+        let Some(queue) = self.get_queue_mut::<E>() else {
+            return;
+        };
+
+        if let Some(limit) = limit
+            && queue.len() >= limit.capacity
+        {
+            match limit.policy {
+                EventOverflowPolicy::DropOldest => queue.drop_oldest(),
+                EventOverflowPolicy::DropNewest => {
+                    queue.dropped += 1;
+                    return;
+                }
+                EventOverflowPolicy::Panic => panic!(
+                    "event queue for {} exceeded capacity {}",
+                    core::any::type_name::<E>(),
+                    limit.capacity
+                ),
+            }
+        }
+        match sequence {
+            Some(sequence) => queue.push_sequenced(sequence, event),
+            None => queue.push(event),
+        }
+    }
+
+    // Drains every queue into one list in push order. Empty unless the log
+    // is on.
+    pub fn take_log(&mut self) -> Vec<LoggedEvent> {
+        if self.next_sequence.is_none() {
+            return Vec::new();
+        }
+        let mut log: Vec<LoggedEvent> =
+            self.queues.values_mut().flat_map(|queue| queue.drain_logged()).collect();
+        log.sort_by_key(|event| event.sequence);
+        log
+    }
+
+    // Number of pending events per registered event type.
+    pub fn queue_depths(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        self.queues
+            .values()
+            .map(|queue| (queue.type_name(), queue.len()))
+    }
+
+    // Counters for every registered event type, sorted by type name.
+    pub fn metrics(&self) -> Vec<EventMetrics> {
+        let mut metrics: Vec<EventMetrics> =
+            self.queues.values().map(|queue| queue.metrics()).collect();
+        metrics.sort_by_key(|m| m.type_name);
+        metrics
     }
 
     pub fn clear(&mut self) {
-        for queue in self.queues.values_mut() {
-            queue.clear();
+        self.clear_except(&[]);
+    }
+
+    // Like `clear`, but leaves the queues of the types in `keep` alone.
+    pub fn clear_except(&mut self, keep: &[TypeId]) {
+        for (type_id, queue) in self.queues.iter_mut() {
+            if !keep.contains(type_id) {
+                queue.clear();
+            }
         }
+        self.dynamic.clear();
+    }
+}
+
+impl Default for EventManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-#[cfg(test)] mod tests {
-    use crate::{EventManager, EventQueue};
+type Delivery = Box<dyn FnOnce(&mut EventManager) + Send + Sync>;
+
+// Events waiting for a future tick, kept in tick order and, within a tick, in
+// the order they were scheduled.
+#[derive(Default)]
+pub(crate) struct EventSchedule {
+    pending: Vec<(u64, Delivery)>,
+}
 
-    #[derive(Debug, PartialEq)]
+impl EventSchedule {
+    pub(crate) fn schedule<E: Event>(&mut self, tick: u64, event: E) {
+        let at = self.pending.partition_point(|(t, _)| *t <= tick);
+        self.pending.insert(at, (tick, Box::new(move |events| events.push(event))));
+    }
+
+    pub(crate) fn deliver_due(&mut self, tick: u64, events: &mut EventManager) {
+        let due = self.pending.partition_point(|(t, _)| *t <= tick);
+        for (_, deliver) in self.pending.drain(..due) {
+            deliver(events);
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+// Pushed by the executor when events of one type have waited unread through
+// `frames` consecutive runs, which usually means no system consumes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Event)]
+pub struct StaleEventWarning {
+    pub event: &'static str,
+    pub pending: usize,
+    pub frames: u32,
+}
+
+// Per-type consumed counts from the previous run, and for how many runs in a
+// row nothing was read while events were pending.
+pub(crate) struct StaleEventCheck {
+    frames: u32,
+    seen: HashMap<&'static str, (u64, u32)>,
+}
+
+impl StaleEventCheck {
+    pub(crate) fn new(frames: u32) -> Self {
+        Self {
+            frames: frames.max(1),
+            seen: HashMap::default(),
+        }
+    }
+
+    // Warns once per type when its streak reaches the threshold.
+    pub(crate) fn update(&mut self, events: &EventManager) -> Vec<StaleEventWarning> {
+        let mut warnings = Vec::new();
+        for metrics in events.metrics() {
+            if metrics.type_name == core::any::type_name::<StaleEventWarning>() {
+                continue;
+            }
+            let (consumed, streak) = self.seen.entry(metrics.type_name).or_insert((0, 0));
+            if metrics.pending == 0 || metrics.consumed != *consumed {
+                *streak = 0;
+            } else {
+                *streak += 1;
+                if *streak == self.frames {
+                    warnings.push(StaleEventWarning {
+                        event: metrics.type_name,
+                        pending: metrics.pending,
+                        frames: self.frames,
+                    });
+                }
+            }
+            *consumed = metrics.consumed;
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Event, EventManager, EventQueue};
+    use crate::config::{EventLimit, EventOverflowPolicy};
+
+    #[derive(Debug, PartialEq, Event)]
     struct DamageEvent {
         amount: u32,
     }
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, PartialEq, Event)]
     struct SpawnEvent {
         id: u32,
     }
 
+    #[derive(Debug, PartialEq, Event)]
+    struct Wrapped<T: 'static, const N: usize = 1>([T; N]);
+
+    #[test]
+    fn test_derived_generic_event() {
+        let mut manager = EventManager::new();
+
+        manager.push(Wrapped([1u8, 2]));
+
+        let queue = manager.get_queue::<Wrapped<u8, 2>>().unwrap();
+        assert_eq!(queue.iter().next(), Some(&Wrapped([1, 2])));
+    }
+
     #[test]
     fn test_event_queue_push_and_pop() {
         let mut queue = EventQueue::<DamageEvent>::new();
@@ -144,6 +499,19 @@ impl EventManager {
         assert_eq!(events[1], &DamageEvent { amount: 2 });
     }
 
+    #[test]
+    fn test_event_queue_drain() {
+        let mut queue = EventQueue::<DamageEvent>::new();
+
+        queue.push(DamageEvent { amount: 1 });
+        queue.push(DamageEvent { amount: 2 });
+
+        let drained: Vec<_> = queue.drain().collect();
+
+        assert_eq!(drained, vec![DamageEvent { amount: 1 }, DamageEvent { amount: 2 }]);
+        assert_eq!(queue.pop(), None);
+    }
+
     #[test]
     fn test_event_manager_auto_register_on_push() {
         let mut manager = EventManager::new();
@@ -178,6 +546,44 @@ impl EventManager {
         );
     }
 
+    #[test]
+    fn test_event_limit_drop_oldest() {
+        let mut manager = EventManager::new();
+        manager.set_limit(Some(EventLimit { capacity: 2, policy: EventOverflowPolicy::DropOldest }));
+
+        for amount in 1..=3 {
+            manager.push(DamageEvent { amount });
+        }
+
+        let queue = manager.get_queue::<DamageEvent>().unwrap();
+        let amounts: Vec<_> = queue.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_event_limit_drop_newest() {
+        let mut manager = EventManager::new();
+        manager.set_limit(Some(EventLimit { capacity: 2, policy: EventOverflowPolicy::DropNewest }));
+
+        for amount in 1..=3 {
+            manager.push(DamageEvent { amount });
+        }
+
+        let queue = manager.get_queue::<DamageEvent>().unwrap();
+        let amounts: Vec<_> = queue.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, vec![1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeded capacity")]
+    fn test_event_limit_panic() {
+        let mut manager = EventManager::new();
+        manager.set_limit(Some(EventLimit { capacity: 1, policy: EventOverflowPolicy::Panic }));
+
+        manager.push(SpawnEvent { id: 1 });
+        manager.push(SpawnEvent { id: 2 });
+    }
+
     #[test]
     fn test_get_queue_unregistered() {
         let manager = EventManager::new();
@@ -200,4 +606,34 @@ impl EventManager {
         assert_eq!(damage_queue.iter().count(), 0);
         assert_eq!(spawn_queue.iter().count(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_metrics_count_pushes_reads_and_drops() {
+        let mut manager = EventManager::new();
+        manager.set_limit(Some(EventLimit {
+            capacity: 2,
+            policy: EventOverflowPolicy::DropOldest,
+        }));
+        for amount in 0..3 {
+            manager.push(DamageEvent { amount });
+        }
+        manager.push(SpawnEvent { id: 1 });
+        manager.get_queue_mut::<DamageEvent>().unwrap().pop();
+
+        let metrics = manager.metrics();
+        assert_eq!(metrics.len(), 2);
+        let damage = metrics.iter().find(|m| m.type_name.ends_with("DamageEvent")).unwrap();
+        assert_eq!(
+            (damage.pushed, damage.consumed, damage.dropped, damage.pending, damage.high_water),
+            (3, 1, 1, 1, 2)
+        );
+        assert!(!damage.is_leaking());
+        let spawn = metrics.iter().find(|m| m.type_name.ends_with("SpawnEvent")).unwrap();
+        assert!(spawn.is_leaking());
+
+        manager.clear();
+        let metrics = manager.metrics();
+        let spawn = metrics.iter().find(|m| m.type_name.ends_with("SpawnEvent")).unwrap();
+        assert_eq!((spawn.pending, spawn.dropped, spawn.high_water), (0, 1, 1));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::world::World;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+// Pushed at the end of every executor run once
+// `SystemExecutor::set_frame_reports(true)` is on, so a performance HUD can be
+// built from events alone. Without `std`, or on wasm32-unknown-unknown where
+// `Instant::now` panics, there is no clock and every duration is zero.
+#[derive(Debug, Clone, PartialEq, crate::Event)]
+pub struct FrameCompleted {
+    // The world tick of the run.
+    pub frame: u64,
+    // From the start of the run to its end, including the time between steps
+    // when the run was stepped through.
+    pub elapsed: Duration,
+    // Each system that ran, in order, with how long it took.
+    pub systems: Vec<(&'static str, Duration)>,
+    pub spawned: u64,
+    pub despawned: u64,
+    // Entities marked changed per component type name; None when the world
+    // does not track changes.
+    pub changes: Option<Vec<(&'static str, usize)>>,
+}
+
+impl FrameCompleted {
+    // The slowest system of the run, if any ran.
+    pub fn slowest(&self) -> Option<(&'static str, Duration)> {
+        self.systems.iter().copied().max_by_key(|(_, took)| *took)
+    }
+}
+
+// `Instant::now` panics on wasm32-unknown-unknown, so there the clock is off
+// as it is without `std`.
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+#[derive(Clone, Copy)]
+pub(crate) struct Stopwatch(std::time::Instant);
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self(std::time::Instant::now())
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+#[cfg(not(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown")))))]
+#[derive(Clone, Copy)]
+pub(crate) struct Stopwatch;
+
+#[cfg(not(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown")))))]
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+// What the executor gathers during one run for its `FrameCompleted`.
+pub(crate) struct FrameRecorder {
+    clock: Stopwatch,
+    systems: Vec<(&'static str, Duration)>,
+    created: u64,
+    destroyed: u64,
+}
+
+impl FrameRecorder {
+    pub(crate) fn start(world: &World) -> Self {
+        Self {
+            clock: Stopwatch::start(),
+            systems: Vec::new(),
+            created: world.entities().created_count(),
+            destroyed: world.entities().destroyed_count(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, system: &'static str, took: Duration) {
+        self.systems.push((system, took));
+    }
+
+    // Called before the run's changes are cleared.
+    pub(crate) fn finish(self, world: &World) -> FrameCompleted {
+        FrameCompleted {
+            frame: world.tick(),
+            elapsed: self.clock.elapsed(),
+            systems: self.systems,
+            spawned: world.entities().created_count().saturating_sub(self.created),
+            despawned: world.entities().destroyed_count().saturating_sub(self.destroyed),
+            changes: world.config().change_tracking.then(|| world.change_counts()),
+        }
+    }
+}
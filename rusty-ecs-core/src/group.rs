@@ -0,0 +1,141 @@
+use crate::collections::HashMap;
+use crate::component::{Component, ComponentManager, ComponentStorage};
+use crate::entity::Entity;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+type Storages = HashMap<TypeId, Box<dyn ComponentStorage>>;
+
+// Component types that can be grouped with `World::group`, as a tuple.
+pub trait GroupTypes {
+    fn type_ids() -> Vec<TypeId>;
+    fn make_pooled(components: &mut ComponentManager);
+}
+
+macro_rules! impl_group_types {
+    ($($t:ident),+) => {
+        impl<$($t: Component),+> GroupTypes for ($($t,)+) {
+            fn type_ids() -> Vec<TypeId> {
+                alloc::vec![$(TypeId::of::<$t>()),+]
+            }
+
+            fn make_pooled(components: &mut ComponentManager) {
+                $(components.make_pooled::<$t>();)+
+            }
+        }
+    };
+}
+
+impl_group_types!(A, B);
+impl_group_types!(A, B, C);
+impl_group_types!(A, B, C, D);
+
+// Pooled storages kept co-sorted, like EnTT's owned groups: the first `len`
+// slots of each dense array hold the entities that have every owned type, in
+// the same order, so iterating them is a zip over those prefixes. Entities
+// move in and out by swapping within the dense arrays.
+pub(crate) struct OwnedGroup {
+    pub(crate) types: Vec<TypeId>,
+    pub(crate) len: usize,
+}
+
+impl OwnedGroup {
+    pub(crate) fn owns(&self, type_id: TypeId) -> bool {
+        self.types.contains(&type_id)
+    }
+
+    // Moves `entity` into the group once it has every owned type.
+    pub(crate) fn try_enter(&mut self, storages: &mut Storages, entity: Entity) {
+        let mut indices = Vec::with_capacity(self.types.len());
+        for type_id in &self.types {
+            match storages.get(type_id).and_then(|s| s.dense_index(entity)) {
+                Some(index) => indices.push(index),
+                None => return,
+            }
+        }
+        if indices[0] < self.len {
+            return;
+        }
+        for (type_id, index) in self.types.iter().zip(indices) {
+            if let Some(storage) = storages.get_mut(type_id) {
+                storage.swap_dense(index, self.len);
+            }
+        }
+        self.len += 1;
+    }
+
+    // Moves `entity` just past the group, ahead of a removal that would
+    // otherwise leave a hole in it.
+    pub(crate) fn leave(&mut self, storages: &mut Storages, entity: Entity) {
+        let first = storages
+            .get(&self.types[0])
+            .and_then(|s| s.dense_index(entity));
+        if first.is_none_or(|index| index >= self.len) {
+            return;
+        }
+        self.len -= 1;
+        for type_id in &self.types {
+            if let Some(storage) = storages.get_mut(type_id)
+                && let Some(index) = storage.dense_index(entity)
+            {
+                storage.swap_dense(index, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::World;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Position(i32);
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Velocity(i32);
+
+    fn grouped(world: &World) -> Vec<(i32, i32)> {
+        let mut pairs: Vec<_> = world
+            .group_iter::<Position, Velocity>()
+            .unwrap()
+            .map(|(e, p, v)| {
+                assert_eq!(world.get_component::<Position>(e), Some(p));
+                (p.0, v.0)
+            })
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn test_group_tracks_members() {
+        let mut world = World::new();
+        let mut entities = Vec::new();
+        for i in 0..6 {
+            let e = world.create_entity();
+            world.add_component(e, Position(i));
+            if i % 2 == 0 {
+                world.add_component(e, Velocity(i * 10));
+            }
+            entities.push(e);
+        }
+        assert!(world.group_iter::<Position, Velocity>().is_none());
+
+        // Existing components move into pooled storages when grouping
+        world.group::<(Position, Velocity)>();
+        assert_eq!(grouped(&world), vec![(0, 0), (2, 20), (4, 40)]);
+
+        world.add_component(entities[1], Velocity(10));
+        world.remove_component::<Position>(entities[2]);
+        world.destroy_entity(entities[0]);
+        assert_eq!(grouped(&world), vec![(1, 10), (4, 40)]);
+
+        let clone = world.clone_entity(entities[4]).unwrap();
+        assert!(world.get_component::<Velocity>(clone).is_none());
+        world.register_cloneable::<Position>();
+        world.register_cloneable::<Velocity>();
+        world.clone_entity(entities[1]).unwrap();
+        assert_eq!(grouped(&world), vec![(1, 10), (1, 10), (4, 40)]);
+        assert_eq!(world.count_matching::<(Position, Velocity)>(), 3);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::collections::HashMap;
+use crate::entity::Entity;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+pub type ComponentHook = Box<dyn FnMut(&mut World, Entity) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    OnAdd,
+    OnRemove,
+}
+
+#[derive(Default)]
+pub struct ComponentHooks {
+    on_add: HashMap<TypeId, Vec<ComponentHook>>,
+    on_remove: HashMap<TypeId, Vec<ComponentHook>>,
+}
+
+impl ComponentHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, kind: HookKind, type_id: TypeId, hook: ComponentHook) {
+        self.hooks_mut(kind).entry(type_id).or_default().push(hook);
+    }
+
+    pub fn has_hooks(&self, kind: HookKind, type_id: TypeId) -> bool {
+        match kind {
+            HookKind::OnAdd => self.on_add.contains_key(&type_id),
+            HookKind::OnRemove => self.on_remove.contains_key(&type_id),
+        }
+    }
+
+    // Hooks are moved out while they run so they can receive `&mut World`;
+    // `restore` puts them back ahead of any hooks registered in the meantime.
+    pub(crate) fn take(&mut self, kind: HookKind, type_id: TypeId) -> Option<Vec<ComponentHook>> {
+        self.hooks_mut(kind).remove(&type_id)
+    }
+
+    pub(crate) fn restore(&mut self, kind: HookKind, type_id: TypeId, mut hooks: Vec<ComponentHook>) {
+        let map = self.hooks_mut(kind);
+        if let Some(added) = map.remove(&type_id) {
+            hooks.extend(added);
+        }
+        map.insert(type_id, hooks);
+    }
+
+    fn hooks_mut(&mut self, kind: HookKind) -> &mut HashMap<TypeId, Vec<ComponentHook>> {
+        match kind {
+            HookKind::OnAdd => &mut self.on_add,
+            HookKind::OnRemove => &mut self.on_remove,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use std::sync::{Arc, Mutex};
+
+    struct Health(u32);
+    struct Dead;
+    struct Target(crate::Entity);
+
+    #[test]
+    fn test_on_add_fires_once_per_insert() {
+        let mut world = World::new();
+        let added = Arc::new(Mutex::new(Vec::new()));
+
+        let log = added.clone();
+        world.on_add::<Health>(move |_, entity| log.lock().unwrap().push(entity));
+
+        let e = world.create_entity();
+        world.add_component(e, Health(10));
+        // Replacing an existing component is not an add
+        world.add_component(e, Health(5));
+
+        assert_eq!(*added.lock().unwrap(), vec![e]);
+    }
+
+    #[test]
+    fn test_on_add_can_insert_derived_components() {
+        let mut world = World::new();
+        world.on_add::<Health>(|world, entity| {
+            if world.get_component::<Health>(entity).unwrap().0 == 0 {
+                world.add_component(entity, Dead);
+            }
+        });
+
+        let alive = world.create_entity();
+        let dead = world.create_entity();
+        world.add_component(alive, Health(3));
+        world.add_component(dead, Health(0));
+
+        assert!(world.get_component::<Dead>(alive).is_none());
+        assert!(world.get_component::<Dead>(dead).is_some());
+    }
+
+    #[test]
+    fn test_on_remove_sees_component_before_removal() {
+        let mut world = World::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let log = seen.clone();
+        world.on_remove::<Health>(move |world, entity| {
+            log.lock().unwrap().push(world.get_component::<Health>(entity).unwrap().0);
+        });
+
+        let e1 = world.create_entity();
+        let e2 = world.create_entity();
+        world.add_component(e1, Health(1));
+        world.add_component(e2, Health(2));
+
+        world.remove_component::<Health>(e1);
+        world.destroy_entity(e2);
+        // Nothing left to remove, so no hook
+        world.remove_component::<Health>(e1);
+
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_on_remove_relationship_cleanup() {
+        let mut world = World::new();
+        world.on_remove::<Health>(|world, entity| {
+            let targeting: Vec<_> = world
+                .query::<&Target, ()>()
+                .filter(|(_, t)| t.0 == entity)
+                .map(|(e, _)| e)
+                .collect();
+            for e in targeting {
+                world.remove_component::<Target>(e);
+            }
+        });
+
+        let enemy = world.create_entity();
+        world.add_component(enemy, Health(5));
+        let hunter = world.create_entity();
+        world.add_component(hunter, Target(enemy));
+
+        world.destroy_entity(enemy);
+        assert!(world.get_component::<Target>(hunter).is_none());
+    }
+}
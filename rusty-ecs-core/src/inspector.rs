@@ -0,0 +1,279 @@
+// Data model for a world inspector panel. This module produces what a panel
+// needs (live entities, their components rendered through `Debug`, event
+// queue depths) and applies text edits back to the world; a front-end only
+// has to lay the snapshot out and forward edits. `render_text` is a
+// plain-text panel for terminals and logs, and the `inspector-egui` feature
+// adds `InspectorPanel` for egui.
+
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::meta::EntityMeta;
+use crate::world::World;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::fmt::{Debug, Write};
+use core::str::FromStr;
+
+#[cfg(feature = "inspector-egui")]
+mod panel;
+#[cfg(feature = "inspector-egui")]
+pub use panel::InspectorPanel;
+
+type ShowFn = fn(&World, Entity) -> Option<String>;
+type EditFn = fn(&mut World, Entity, &str) -> Result<(), String>;
+
+struct ComponentView {
+    type_id: TypeId,
+    name: &'static str,
+    show: ShowFn,
+    edit: Option<EditFn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentRow {
+    pub name: &'static str,
+    // None for component types that were not registered with the inspector
+    pub value: Option<String>,
+    pub editable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityRow {
+    pub entity: Entity,
+    // From `World::entity_meta`, when the world records metadata
+    pub meta: Option<EntityMeta>,
+    pub components: Vec<ComponentRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InspectorSnapshot {
+    pub entities: Vec<EntityRow>,
+    pub event_queues: Vec<(&'static str, usize)>,
+}
+
+#[derive(Default)]
+pub struct Inspector {
+    views: Vec<ComponentView>,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: Component + Debug>(&mut self) {
+        self.add_view::<T>(None);
+    }
+
+    // Editable components are parsed from the text typed into the panel.
+    pub fn register_editable<T: Component + Debug + FromStr>(&mut self)
+    where
+        T::Err: Debug,
+    {
+        self.add_view::<T>(Some(|world, entity, text| {
+            let value = text.parse::<T>().map_err(|err| format!("{:?}", err))?;
+            world
+                .try_add_component(entity, value)
+                .map_err(|err| err.to_string())
+        }));
+    }
+
+    fn add_view<T: Component + Debug>(&mut self, edit: Option<EditFn>) {
+        let type_id = TypeId::of::<T>();
+        self.views.retain(|view| view.type_id != type_id);
+        self.views.push(ComponentView {
+            type_id,
+            name: core::any::type_name::<T>(),
+            show: |world, entity| {
+                world
+                    .get_component::<T>(entity)
+                    .map(|component| format!("{:?}", component))
+            },
+            edit,
+        });
+    }
+
+    pub fn snapshot(&self, world: &World) -> InspectorSnapshot {
+        let names: Vec<(TypeId, &'static str)> = world
+            .components()
+            .storages()
+            .map(|(type_id, storage)| (type_id, storage.type_name()))
+            .collect();
+
+        let mut entities: Vec<EntityRow> = world
+            .entities()
+            .alive_entities()
+            .map(|entity| {
+                let mut components: Vec<ComponentRow> = world
+                    .components()
+                    .component_types(entity)
+                    .into_iter()
+                    .map(|type_id| self.row(world, entity, type_id, &names))
+                    .collect();
+                components.sort_by_key(|row| row.name);
+                let meta = world.entity_meta(entity).cloned();
+                EntityRow { entity, meta, components }
+            })
+            .collect();
+        entities.sort_by_key(|row| row.entity);
+
+        let mut event_queues: Vec<_> = world.events().queue_depths().collect();
+        event_queues.sort();
+
+        InspectorSnapshot {
+            entities,
+            event_queues,
+        }
+    }
+
+    fn row(
+        &self,
+        world: &World,
+        entity: Entity,
+        type_id: TypeId,
+        names: &[(TypeId, &'static str)],
+    ) -> ComponentRow {
+        match self.views.iter().find(|view| view.type_id == type_id) {
+            Some(view) => ComponentRow {
+                name: view.name,
+                value: (view.show)(world, entity),
+                editable: view.edit.is_some(),
+            },
+            None => ComponentRow {
+                name: names
+                    .iter()
+                    .find(|(id, _)| *id == type_id)
+                    .map_or("<unknown>", |(_, name)| name),
+                value: None,
+                editable: false,
+            },
+        }
+    }
+
+    pub fn edit(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        component: &str,
+        text: &str,
+    ) -> Result<(), String> {
+        let view = self
+            .views
+            .iter()
+            .find(|view| view.name == component)
+            .ok_or_else(|| format!("{} is not registered with the inspector", component))?;
+        let edit = view
+            .edit
+            .ok_or_else(|| format!("{} is not editable", component))?;
+        edit(world, entity, text)
+    }
+
+    pub fn render_text(&self, world: &World) -> String {
+        let snapshot = self.snapshot(world);
+        let mut out = String::new();
+        for row in &snapshot.entities {
+            let _ = write!(out, "Entity {}v{}", row.entity.id, row.entity.generation);
+            let _ = match &row.meta {
+                Some(EntityMeta { created_tick, label: Some(label) }) => {
+                    writeln!(out, " \"{}\" (tick {})", label, created_tick)
+                }
+                Some(meta) => writeln!(out, " (tick {})", meta.created_tick),
+                None => writeln!(out),
+            };
+            for component in &row.components {
+                let value = component.value.as_deref().unwrap_or("..");
+                let _ = writeln!(out, "  {}: {}", component.name, value);
+            }
+        }
+        for (name, depth) in &snapshot.event_queues {
+            let _ = writeln!(out, "Events {}: {}", name, depth);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Inspector;
+    use crate::World;
+    use core::str::FromStr;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+    struct Opaque;
+    #[derive(Debug, crate::Event)]
+    struct Hit;
+
+    impl FromStr for Health {
+        type Err = core::num::ParseIntError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Health(s.trim().parse()?))
+        }
+    }
+
+    #[test]
+    fn test_snapshot_lists_entities_components_and_events() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(7));
+        world.add_component(e, Opaque);
+        let dead = world.create_entity();
+        world.destroy_entity(dead);
+        world.push_event(Hit);
+        world.push_event(Hit);
+
+        let mut inspector = Inspector::new();
+        inspector.register::<Health>();
+        let snapshot = inspector.snapshot(&world);
+
+        assert_eq!(snapshot.entities.len(), 1);
+        let row = &snapshot.entities[0];
+        assert_eq!(row.entity, e);
+        assert_eq!(row.components.len(), 2);
+        let health = row.components.iter().find(|c| c.name.ends_with("Health")).unwrap();
+        assert_eq!(health.value.as_deref(), Some("Health(7)"));
+        assert!(!health.editable);
+        let opaque = row.components.iter().find(|c| c.name.ends_with("Opaque")).unwrap();
+        assert!(opaque.value.is_none());
+
+        assert_eq!(snapshot.event_queues.len(), 1);
+        assert!(snapshot.event_queues[0].0.ends_with("Hit"));
+        assert_eq!(snapshot.event_queues[0].1, 2);
+    }
+
+    #[test]
+    fn test_edit_writes_back() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(7));
+
+        let mut inspector = Inspector::new();
+        inspector.register_editable::<Health>();
+        let name = core::any::type_name::<Health>();
+
+        assert!(inspector.edit(&mut world, e, name, "12").is_ok());
+        assert_eq!(world.get_component::<Health>(e), Some(&Health(12)));
+        assert!(inspector.edit(&mut world, e, name, "many").is_err());
+        assert!(inspector.edit(&mut world, e, "Missing", "1").is_err());
+
+        world.destroy_entity(e);
+        assert!(inspector.edit(&mut world, e, name, "1").is_err());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(3));
+
+        let mut inspector = Inspector::new();
+        inspector.register::<Health>();
+        let text = inspector.render_text(&world);
+
+        assert!(text.starts_with("Entity 0v0\n"));
+        assert!(text.contains("Health: Health(3)"));
+    }
+}
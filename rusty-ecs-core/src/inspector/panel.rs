@@ -0,0 +1,146 @@
+// An egui front-end for `Inspector`, enabled by the `inspector-egui` feature.
+// Each frame lays out a fresh snapshot: one collapsible row per entity and
+// the event queue depths below. Editable components get a text field that
+// applies its text through `Inspector::edit` when Enter is pressed.
+
+use super::Inspector;
+use crate::collections::HashMap;
+use crate::entity::Entity;
+use crate::meta::EntityMeta;
+use crate::world::World;
+use alloc::format;
+use alloc::string::String;
+
+pub struct InspectorPanel {
+    inspector: Inspector,
+    // Text typed into a focused field, until it is applied or abandoned
+    drafts: HashMap<(Entity, &'static str), String>,
+    // The last rejected edit, shown until the next one is applied
+    error: Option<String>,
+}
+
+impl InspectorPanel {
+    pub fn new(inspector: Inspector) -> Self {
+        InspectorPanel {
+            inspector,
+            drafts: HashMap::default(),
+            error: None,
+        }
+    }
+
+    pub fn inspector_mut(&mut self) -> &mut Inspector {
+        &mut self.inspector
+    }
+
+    // Shows the panel in its own window.
+    pub fn show(&mut self, ctx: &egui::Context, world: &mut World) {
+        egui::Window::new("Inspector").show(ctx, |ui| self.ui(ui, world));
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let snapshot = self.inspector.snapshot(world);
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for row in &snapshot.entities {
+                let title = match &row.meta {
+                    Some(EntityMeta { label: Some(label), .. }) => {
+                        format!("Entity {}v{} \"{}\"", row.entity.id, row.entity.generation, label)
+                    }
+                    _ => format!("Entity {}v{}", row.entity.id, row.entity.generation),
+                };
+                egui::CollapsingHeader::new(title).id_salt(row.entity).show(ui, |ui| {
+                    for component in &row.components {
+                        let value = component.value.as_deref().unwrap_or("..");
+                        if !component.editable {
+                            ui.label(format!("{}: {}", component.name, value));
+                            continue;
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(component.name);
+                            self.field(ui, world, row.entity, component.name, value);
+                        });
+                    }
+                });
+            }
+            if !snapshot.event_queues.is_empty() {
+                ui.separator();
+                for (name, depth) in &snapshot.event_queues {
+                    ui.label(format!("Events {}: {}", name, depth));
+                }
+            }
+        });
+    }
+
+    fn field(
+        &mut self,
+        ui: &mut egui::Ui,
+        world: &mut World,
+        entity: Entity,
+        name: &'static str,
+        value: &str,
+    ) {
+        let key = (entity, name);
+        let mut text = self.drafts.get(&key).cloned().unwrap_or_else(|| value.into());
+        let response = ui.text_edit_singleline(&mut text);
+        if response.lost_focus() {
+            self.drafts.remove(&key);
+            if ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                self.apply(world, entity, name, &text);
+            }
+        } else if response.has_focus() {
+            self.drafts.insert(key, text);
+        }
+    }
+
+    fn apply(&mut self, world: &mut World, entity: Entity, name: &str, text: &str) {
+        self.error = self
+            .inspector
+            .edit(world, entity, name, text)
+            .err()
+            .map(|err| format!("{}: {}", name, err));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InspectorPanel;
+    use crate::World;
+    use crate::inspector::Inspector;
+    use core::str::FromStr;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    impl FromStr for Health {
+        type Err = core::num::ParseIntError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Health(s.trim().parse()?))
+        }
+    }
+
+    #[test]
+    fn test_panel_renders_and_reports_rejected_edits() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(7));
+
+        let mut inspector = Inspector::new();
+        inspector.register_editable::<Health>();
+        let mut panel = InspectorPanel::new(inspector);
+
+        let ctx = egui::Context::default();
+        let output = ctx.run(egui::RawInput::default(), |ctx| panel.show(ctx, &mut world));
+        assert!(!output.shapes.is_empty());
+        assert_eq!(world.get_component::<Health>(e), Some(&Health(7)));
+
+        let name = core::any::type_name::<Health>();
+        panel.apply(&mut world, e, name, "many");
+        assert!(panel.error.as_deref().is_some_and(|err| err.starts_with(name)));
+        panel.apply(&mut world, e, name, "12");
+        assert!(panel.error.is_none());
+        assert_eq!(world.get_component::<Health>(e), Some(&Health(12)));
+    }
+}
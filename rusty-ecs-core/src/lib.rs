@@ -1,11 +1,128 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+extern crate self as rusty_ecs_core;
+
+mod collections;
+
+#[cfg(feature = "std")]
+pub mod autosave;
+pub mod behavior;
+pub mod bitset;
+pub mod buffer;
 pub mod entity;
+pub mod entity_pool;
 pub mod component;
 pub mod event;
+pub mod frame;
+pub mod group;
 pub mod world;
 pub mod system;
+pub mod query;
+pub mod recovery;
+pub mod hooks;
+pub mod lifetime;
+pub mod meta;
+pub mod pathfinding;
+pub mod observer;
+pub mod validation;
+pub mod error;
+pub mod config;
+pub mod diagnostics;
+pub mod dynamic;
+pub mod change;
+pub mod access;
+pub mod commands;
+pub mod pool;
+pub mod prediction;
+pub mod resource;
+pub mod rng;
+pub mod registry;
+pub mod simulation;
+pub mod snapshot;
+pub mod state;
+pub mod tag;
+pub mod testing;
+pub mod time;
+pub mod utility;
+pub mod vision;
+pub mod watch;
+pub mod weak;
+mod trace;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+#[cfg(feature = "plugins")]
+pub mod plugin;
 
+pub use behavior::{
+    BehaviorAction, BehaviorCondition, BehaviorNode, BehaviorSystem, BehaviorTree, Behaviors,
+    Status,
+};
+pub use bitset::BitSet;
+pub use buffer::PreviousState;
+pub use diagnostics::WorldDiagnostics;
+pub use dynamic::{
+    DynamicComponent, DynamicComponentId, DynamicComponents, DynamicError, DynamicEvent,
+    DynamicEventId, DynamicEvents, DynamicKey, Schema, Schemas, Value, ValueKind,
+};
 pub use entity::{Entity, EntityManager};
-pub use component::{Component, ComponentManager, HashMapComponentStorage};
-pub use event::{Event, EventManager, EventQueue};
-pub use world::World;
-pub use system::{System, SystemExecutor};
+pub use entity_pool::EntityPool;
+pub use component::{
+    Component, ComponentManager, ComponentStorage, HashMapComponentStorage, StorageReport,
+    TypedStorage,
+};
+pub use group::GroupTypes;
+pub use pool::PooledComponentStorage;
+pub use prediction::PredictionWorld;
+pub use tag::TagStorage;
+pub use state::{GameStateStack, State, StateChanged, StateMachine, StateSet, TransitionSystem};
+pub use frame::FrameCompleted;
+pub use event::{
+    EntityEvent, Event, EventManager, EventMarker, EventMetrics, EventQueue, LoggedEvent,
+    StaleEventWarning,
+};
+pub use recovery::{SystemPanicked, Tainted};
+pub use rusty_ecs_derive::Event;
+pub use world::{DeadEntityWarning, World};
+pub use access::Access;
+pub use commands::Commands;
+pub use system::{
+    ExclusiveSystem, ReadOnlySystem, StepReport, System, SystemData, SystemExecutor,
+    STALE_EVENT_FRAMES,
+};
+pub use query::{
+    ComponentSet, Disabled, IncludeDisabled, QueryData, QueryFilter, SortedQuery, With, Without,
+};
+pub use hooks::{ComponentHook, HookKind};
+pub use observer::Observer;
+pub use lifetime::{EntityExpired, Lifetime, ReaperSystem};
+pub use meta::EntityMeta;
+pub use pathfinding::{
+    Destination, GridMap, PathBlocked, PathCompleted, PathFollowSystem, Position,
+};
+pub use validation::InvariantViolation;
+pub use error::WorldError;
+pub use config::{DeadEntityPolicy, EventLimit, EventOverflowPolicy, WorldConfig};
+pub use resource::{Resource, Resources};
+pub use rng::{RngResource, RngStream};
+pub use registry::{MigrateFn, TypeRegistry};
+pub use utility::{AIDecisionSystem, Consideration, Considerations, Intent, SimulationFailed};
+pub use vision::{Explored, Visible, VisibilityMap, Vision, VisionSystem};
+pub use simulation::SimulationResult;
+pub use snapshot::{
+    ComponentData, ComponentSnapshot, EntityMap, MapEntities, Persist, Snapshot, SnapshotError,
+    MAX_DECOMPRESSED_SIZE,
+};
+pub use testing::WorldTestExt;
+pub use time::{Stopwatch, Time, Timer, TimerMode};
+pub use watch::WatchReaction;
+pub use weak::WeakEntity;
+#[cfg(feature = "inspector")]
+pub use inspector::Inspector;
+#[cfg(feature = "inspector-egui")]
+pub use inspector::InspectorPanel;
+#[cfg(feature = "std")]
+pub use autosave::{
+    latest_autosave, load_latest_autosave, AutosaveError, AutosaveFailed, AutosavePath,
+    AutosaveSystem,
+};
@@ -0,0 +1,66 @@
+use crate::entity::Entity;
+use crate::system::ExclusiveSystem;
+use crate::world::World;
+
+// Executor runs an entity has left before `ReaperSystem` despawns it, for
+// projectiles, temporary buffs or floating combat text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lifetime(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Event)]
+pub struct EntityExpired {
+    pub entity: Entity,
+}
+
+// Counts every `Lifetime` down by one per run and despawns the entities that
+// reach zero, pushing an `EntityExpired` for each in entity order. Disabled
+// entities are skipped, so their lifetime is paused.
+pub struct ReaperSystem;
+
+impl ExclusiveSystem for ReaperSystem {
+    fn run(&mut self, world: &mut World) {
+        let mut entities = world.query_entities::<Lifetime>();
+        entities.sort();
+        for entity in entities {
+            let Some(lifetime) = world.get_component_mut::<Lifetime>(entity) else {
+                continue;
+            };
+            lifetime.0 = lifetime.0.saturating_sub(1);
+            if lifetime.0 == 0 {
+                world.destroy_entity(entity);
+                world.push_event(EntityExpired { entity });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemExecutor;
+
+    #[test]
+    fn test_reaper_despawns_expired_entities() {
+        let mut world = World::new();
+        let spark = world.create_entity();
+        let buff = world.create_entity();
+        let paused = world.create_entity();
+        world.add_component(spark, Lifetime(1));
+        world.add_component(buff, Lifetime(3));
+        world.add_component(paused, Lifetime(1));
+        world.set_enabled(paused, false);
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(ReaperSystem);
+
+        executor.run(&mut world);
+        assert!(!world.is_alive(spark));
+        assert_eq!(world.take_events::<EntityExpired>(), vec![EntityExpired { entity: spark }]);
+        assert_eq!(world.get_component::<Lifetime>(buff), Some(&Lifetime(2)));
+
+        executor.run(&mut world);
+        executor.run(&mut world);
+        assert!(!world.is_alive(buff));
+        assert_eq!(world.take_events::<EntityExpired>(), vec![EntityExpired { entity: buff }]);
+        assert!(world.is_alive(paused));
+    }
+}
@@ -0,0 +1,16 @@
+use alloc::string::String;
+
+// Where an entity came from, recorded when `WorldConfig::entity_metadata` is
+// on: the tick it was created on and an optional label set with
+// `World::set_debug_label`. Read it back with `World::entity_meta`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityMeta {
+    pub created_tick: u64,
+    pub label: Option<String>,
+}
+
+impl EntityMeta {
+    pub(crate) fn new(created_tick: u64) -> Self {
+        Self { created_tick, label: None }
+    }
+}
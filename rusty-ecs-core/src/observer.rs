@@ -0,0 +1,187 @@
+use crate::collections::HashMap;
+use crate::commands::Commands;
+use crate::entity::Entity;
+use crate::event::Event;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+
+pub type Observer = Box<dyn FnMut(&mut World, Entity, &dyn Any) + Send + Sync>;
+
+// Observers registered on single entities, keyed by the event type they
+// react to. Unlike the global event queues nothing is buffered: `trigger`
+// runs the target's observers immediately and the event is dropped after.
+#[derive(Default)]
+pub struct EntityObservers {
+    by_entity: HashMap<Entity, HashMap<TypeId, Vec<Observer>>>,
+}
+
+impl EntityObservers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, entity: Entity, type_id: TypeId, observer: Observer) {
+        self.by_entity
+            .entry(entity)
+            .or_default()
+            .entry(type_id)
+            .or_default()
+            .push(observer);
+    }
+
+    pub fn has_observers(&self, entity: Entity, type_id: TypeId) -> bool {
+        self.by_entity
+            .get(&entity)
+            .is_some_and(|observers| observers.contains_key(&type_id))
+    }
+
+    pub fn remove(&mut self, entity: Entity, type_id: TypeId) -> bool {
+        let Some(observers) = self.by_entity.get_mut(&entity) else {
+            return false;
+        };
+        let removed = observers.remove(&type_id).is_some();
+        if observers.is_empty() {
+            self.by_entity.remove(&entity);
+        }
+        removed
+    }
+
+    pub(crate) fn remove_all(&mut self, entity: Entity) {
+        self.by_entity.remove(&entity);
+    }
+
+    // Like component hooks, observers are moved out while they run so they
+    // can receive `&mut World`; `restore` puts them back ahead of any
+    // observers registered in the meantime.
+    pub(crate) fn take(&mut self, entity: Entity, type_id: TypeId) -> Option<Vec<Observer>> {
+        let observers = self.by_entity.get_mut(&entity)?;
+        observers.remove(&type_id)
+    }
+
+    pub(crate) fn restore(&mut self, entity: Entity, type_id: TypeId, mut taken: Vec<Observer>) {
+        let observers = self.by_entity.entry(entity).or_default();
+        if let Some(added) = observers.remove(&type_id) {
+            taken.extend(added);
+        }
+        observers.insert(type_id, taken);
+    }
+}
+
+impl World {
+    // Runs `observer` whenever an `E` is triggered on `entity`, e.g. one trap
+    // reacting to being stepped on. Does nothing if `entity` is dead; the
+    // observers go away with the entity.
+    pub fn observe<E: Event>(
+        &mut self,
+        entity: Entity,
+        mut observer: impl FnMut(&mut World, Entity, &E) + Send + Sync + 'static,
+    ) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        let observer: Observer = Box::new(move |world, entity, event| {
+            if let Some(event) = event.downcast_ref::<E>() {
+                observer(world, entity, event);
+            }
+        });
+        self.observers.register(entity, TypeId::of::<E>(), observer);
+    }
+
+    // Removes every `E` observer on `entity`. Returns whether there were any.
+    pub fn unobserve<E: Event>(&mut self, entity: Entity) -> bool {
+        self.observers.remove(entity, TypeId::of::<E>())
+    }
+
+    // Runs the `E` observers of `entity` right away, in registration order.
+    // Returns false, dropping the event, when nothing observes it there.
+    pub fn trigger<E: Event>(&mut self, entity: Entity, event: E) -> bool {
+        let type_id = TypeId::of::<E>();
+        let Some(mut observers) = self.observers.take(entity, type_id) else {
+            return false;
+        };
+        for observer in observers.iter_mut() {
+            observer(self, entity, &event);
+        }
+        // An observer may have destroyed its own entity
+        if self.is_alive(entity) {
+            self.observers.restore(entity, type_id, observers);
+        }
+        true
+    }
+}
+
+impl Commands {
+    // Triggers `event` on `entity` when the commands are applied, for data
+    // systems that cannot run observers themselves.
+    pub fn trigger<E: Event>(&mut self, entity: Entity, event: E) {
+        self.push(move |world| {
+            world.trigger(entity, event);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::Commands;
+    use crate::world::World;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(crate::Event)]
+    struct SteppedOn {
+        weight: u32,
+    }
+    struct Armed(bool);
+
+    #[test]
+    fn test_trigger_reaches_only_the_target() {
+        let mut world = World::new();
+        let trap = world.create_entity();
+        let other = world.create_entity();
+        world.add_component(trap, Armed(true));
+        world.add_component(other, Armed(true));
+        world.observe::<SteppedOn>(trap, |world, entity, event| {
+            if event.weight > 10 {
+                world.get_component_mut::<Armed>(entity).unwrap().0 = false;
+            }
+        });
+
+        assert!(world.trigger(trap, SteppedOn { weight: 5 }));
+        assert!(world.get_component::<Armed>(trap).unwrap().0);
+        assert!(world.trigger(trap, SteppedOn { weight: 20 }));
+        assert!(!world.get_component::<Armed>(trap).unwrap().0);
+        // No observer on `other`, so the event goes nowhere
+        assert!(!world.trigger(other, SteppedOn { weight: 20 }));
+        assert!(world.get_component::<Armed>(other).unwrap().0);
+    }
+
+    #[test]
+    fn test_observers_run_in_order_and_die_with_the_entity() {
+        let mut world = World::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let trap = world.create_entity();
+        for id in 0..2 {
+            let log = seen.clone();
+            world.observe::<SteppedOn>(trap, move |_, _, event| {
+                log.lock().unwrap().push((id, event.weight));
+            });
+        }
+        let log = seen.clone();
+        world.observe::<SteppedOn>(trap, move |world, entity, _| {
+            log.lock().unwrap().push((2, 0));
+            world.destroy_entity(entity);
+        });
+
+        let mut commands = Commands::new();
+        commands.trigger(trap, SteppedOn { weight: 3 });
+        commands.apply(&mut world);
+        assert_eq!(*seen.lock().unwrap(), vec![(0, 3), (1, 3), (2, 0)]);
+
+        // A recycled id does not inherit the dead trap's observers
+        let next = world.create_entity();
+        assert!(!world.trigger(trap, SteppedOn { weight: 3 }));
+        assert!(!world.trigger(next, SteppedOn { weight: 3 }));
+        assert!(!world.unobserve::<SteppedOn>(trap));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::entity::Entity;
+use crate::snapshot::{Persist, SnapshotError};
+use crate::system::ExclusiveSystem;
+use crate::world::World;
+use alloc::collections::BinaryHeap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+// A cell of the `GridMap`; y grows downwards, like the rows of a map file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn distance(self, other: Position) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl Persist for Position {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.x.write(out);
+        self.y.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Position::new(i32::read(input)?, i32::read(input)?))
+    }
+}
+
+// Resource: a rectangular map of cells with a movement cost each, 0 meaning
+// a wall. Movement goes between the four orthogonal neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridMap {
+    width: u32,
+    height: u32,
+    costs: Vec<u8>,
+}
+
+impl GridMap {
+    // An open map where every cell costs 1.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, costs: vec![1; width as usize * height as usize] }
+    }
+
+    // Reads a map with one row per line: `#` is a wall, `.` a cell costing 1
+    // and a digit from 1 to 9 a cell costing that much. Rows must all be as
+    // wide.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut width = None;
+        let mut costs = Vec::new();
+        let mut height = 0;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let row_width = line.chars().count() as u32;
+            if *width.get_or_insert(row_width) != row_width {
+                let reason = format!("rows must all be {} cells wide", width.unwrap());
+                return Err(format!("line {}: {}", number + 1, reason));
+            }
+            for cell in line.chars() {
+                costs.push(match cell {
+                    '#' => 0,
+                    '.' => 1,
+                    '1'..='9' => cell as u8 - b'0',
+                    _ => return Err(format!("line {}: unknown cell `{}`", number + 1, cell)),
+                });
+            }
+            height += 1;
+        }
+        Ok(Self { width: width.unwrap_or(0), height, costs })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub(crate) fn index(&self, cell: Position) -> Option<usize> {
+        let inside = (0..self.width as i32).contains(&cell.x)
+            && (0..self.height as i32).contains(&cell.y);
+        inside.then(|| cell.y as usize * self.width as usize + cell.x as usize)
+    }
+
+    pub(crate) fn position(&self, index: usize) -> Position {
+        let width = self.width as usize;
+        Position::new((index % width) as i32, (index / width) as i32)
+    }
+
+    // The cost of entering `cell`, None for walls and cells off the map.
+    pub fn cost(&self, cell: Position) -> Option<u32> {
+        let cost = self.costs[self.index(cell)?];
+        (cost > 0).then_some(cost as u32)
+    }
+
+    pub fn is_walkable(&self, cell: Position) -> bool {
+        self.cost(cell).is_some()
+    }
+
+    // Sets the cost of entering `cell`, 0 for a wall. Cells off the map are
+    // ignored.
+    pub fn set_cost(&mut self, cell: Position, cost: u8) {
+        if let Some(index) = self.index(cell) {
+            self.costs[index] = cost;
+        }
+    }
+
+    // The walkable cells next to `cell`, north, east, south and west.
+    pub fn neighbors(&self, cell: Position) -> impl Iterator<Item = Position> + '_ {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .map(move |(dx, dy)| Position::new(cell.x + dx, cell.y + dy))
+            .filter(|&next| self.is_walkable(next))
+    }
+
+    // The cheapest path from `start` to `goal` found by A*, as the cells to
+    // step through after `start`, ending with `goal`; empty when they are the
+    // same cell. `start` itself may be a wall. None when `goal` cannot be
+    // reached.
+    pub fn find_path(&self, start: Position, goal: Position) -> Option<Vec<Position>> {
+        let (start_index, goal_index) = (self.index(start)?, self.index(goal)?);
+        if start == goal {
+            return Some(Vec::new());
+        }
+        self.cost(goal)?;
+        // Every cell costs at least 1, so the Manhattan distance never
+        // overestimates and the first time a cell is popped its cost is final
+        let mut spent = vec![u32::MAX; self.costs.len()];
+        let mut came_from = vec![usize::MAX; self.costs.len()];
+        let mut closed = vec![false; self.costs.len()];
+        let mut open = BinaryHeap::new();
+        spent[start_index] = 0;
+        open.push(Reverse((start.distance(goal), start_index)));
+        while let Some(Reverse((_, index))) = open.pop() {
+            if index == goal_index {
+                let mut path = vec![goal];
+                let mut at = came_from[goal_index];
+                while at != start_index {
+                    path.push(self.position(at));
+                    at = came_from[at];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if core::mem::replace(&mut closed[index], true) {
+                continue;
+            }
+            let cell = self.position(index);
+            for next in self.neighbors(cell) {
+                let next_index = self.index(next).unwrap();
+                let cost = spent[index] + self.costs[next_index] as u32;
+                if cost < spent[next_index] {
+                    spent[next_index] = cost;
+                    came_from[next_index] = index;
+                    open.push(Reverse((cost + next.distance(goal), next_index)));
+                }
+            }
+        }
+        None
+    }
+}
+
+// Component: where `PathFollowSystem` walks its entity to, along with the
+// rest of the path it computed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Destination {
+    goal: Position,
+    // Reversed, so the next step is at the end
+    path: Vec<Position>,
+}
+
+impl Destination {
+    pub fn new(goal: Position) -> Self {
+        Self { goal, path: Vec::new() }
+    }
+
+    pub fn goal(&self) -> Position {
+        self.goal
+    }
+
+    // The steps still ahead, in order; empty until the first step is taken.
+    pub fn path(&self) -> impl Iterator<Item = Position> + '_ {
+        self.path.iter().rev().copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Event)]
+pub struct PathCompleted {
+    pub entity: Entity,
+}
+
+// The entity's destination could not be reached from where it stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, crate::Event)]
+pub struct PathBlocked {
+    pub entity: Entity,
+    pub goal: Position,
+}
+
+// Moves every entity with a `Position` and a `Destination` one cell along
+// its path on the `GridMap` per run, in entity order. The path is found with
+// A* on the first step and again whenever its next cell has been walled off.
+// Entities that arrive, or whose goal cannot be reached, lose their
+// `Destination` and get a `PathCompleted` or `PathBlocked`. Only the map
+// blocks movement; entities may share a cell.
+pub struct PathFollowSystem;
+
+impl ExclusiveSystem for PathFollowSystem {
+    fn run(&mut self, world: &mut World) {
+        // Taken out so that components can be changed while it is borrowed
+        let Some(grid) = world.remove_resource::<GridMap>() else {
+            return;
+        };
+        let mut entities = world.query_entities::<Destination>();
+        entities.sort();
+        for entity in entities {
+            let Some(&position) = world.get_component::<Position>(entity) else {
+                continue;
+            };
+            let destination = world.get_component_mut::<Destination>(entity).unwrap();
+            let goal = destination.goal;
+            if position != goal {
+                let next = destination.path.last().copied();
+                let open = |next: Position| grid.is_walkable(next) && next.distance(position) == 1;
+                if !next.is_some_and(open) {
+                    match grid.find_path(position, goal) {
+                        Some(mut path) => {
+                            path.reverse();
+                            destination.path = path;
+                        }
+                        None => {
+                            world.remove_component::<Destination>(entity);
+                            world.push_event(PathBlocked { entity, goal });
+                            continue;
+                        }
+                    }
+                }
+                let next = destination.path.pop().unwrap();
+                *world.get_component_mut::<Position>(entity).unwrap() = next;
+                if next != goal {
+                    continue;
+                }
+            }
+            world.remove_component::<Destination>(entity);
+            world.push_event(PathCompleted { entity });
+        }
+        world.insert_resource(grid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemExecutor;
+
+    const MAP: &str = "
+        .....
+        .#.#.
+        .....
+        .9.#.
+    ";
+
+    #[test]
+    fn test_find_path_around_walls_and_costs() {
+        let grid = GridMap::parse(MAP).unwrap();
+        assert_eq!((grid.width(), grid.height()), (5, 4));
+        assert_eq!(grid.cost(Position::new(1, 3)), Some(9));
+        assert!(!grid.is_walkable(Position::new(1, 1)));
+        assert!(!grid.is_walkable(Position::new(5, 0)));
+        assert_eq!(grid.neighbors(Position::new(2, 1)).collect::<Vec<_>>(), [
+            Position::new(2, 0),
+            Position::new(2, 2)
+        ]);
+
+        // Three steps around the costly cell rather than through it
+        let path = grid.find_path(Position::new(0, 3), Position::new(2, 3)).unwrap();
+        let around = [(0, 2), (1, 2), (2, 2), (2, 3)].map(|(x, y)| Position::new(x, y));
+        assert_eq!(path, around);
+        assert_eq!(grid.find_path(Position::new(0, 0), Position::new(0, 0)), Some(Vec::new()));
+        assert_eq!(grid.find_path(Position::new(0, 0), Position::new(3, 1)), None);
+
+        assert!(GridMap::parse("..\n...").is_err());
+        assert!(GridMap::parse(".x").is_err());
+    }
+
+    #[test]
+    fn test_path_follow_system() {
+        let mut world = World::new();
+        world.insert_resource(GridMap::parse(MAP).unwrap());
+        let rogue = world.create_entity();
+        let rat = world.create_entity();
+        world.add_component(rogue, Position::new(0, 0));
+        world.add_component(rogue, Destination::new(Position::new(2, 0)));
+        world.add_component(rat, Position::new(4, 3));
+        world.add_component(rat, Destination::new(Position::new(3, 1)));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(PathFollowSystem);
+
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Position>(rogue), Some(&Position::new(1, 0)));
+        let destination = world.get_component::<Destination>(rogue).unwrap();
+        assert_eq!(destination.path().collect::<Vec<_>>(), [Position::new(2, 0)]);
+        // The rat is headed into a wall
+        assert_eq!(
+            world.take_events::<PathBlocked>(),
+            [PathBlocked { entity: rat, goal: Position::new(3, 1) }]
+        );
+        assert!(world.get_component::<Destination>(rat).is_none());
+
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Position>(rogue), Some(&Position::new(2, 0)));
+        assert_eq!(world.take_events::<PathCompleted>(), [PathCompleted { entity: rogue }]);
+        assert!(world.get_component::<Destination>(rogue).is_none());
+
+        // A wall dropped on the path sends it back around the long way
+        world.add_component(rogue, Destination::new(Position::new(2, 3)));
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Position>(rogue), Some(&Position::new(2, 1)));
+        world.resource_mut::<GridMap>().unwrap().set_cost(Position::new(2, 2), 0);
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Position>(rogue), Some(&Position::new(2, 0)));
+        let destination = world.get_component::<Destination>(rogue).unwrap();
+        assert!(destination.path().any(|cell| cell == Position::new(1, 3)));
+        assert!(world.take_events::<PathBlocked>().is_empty());
+    }
+}
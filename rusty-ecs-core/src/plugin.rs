@@ -0,0 +1,305 @@
+// Loading third-party content packs from dynamic libraries (`.so`, `.dylib`
+// or `.dll`) with the `plugins` feature.
+//
+// A plugin is a `cdylib` crate depending on this one that exports its entry
+// point with `export_plugin!`:
+//
+//     fn register(world: &mut World, executor: &mut SystemExecutor) {
+//         executor.add_system(PoisonSystem);
+//     }
+//     rusty_ecs_core::export_plugin!(register);
+//
+// Rust has no stable ABI, so `World` and `SystemExecutor` only line up when
+// the plugin was built against the same version of this crate, with the same
+// features and the same compiler. Before calling `register`, the loader asks
+// the plugin through a C-ABI function which `PLUGIN_ABI_VERSION`, crate
+// version, features and rustc it was built with and refuses it on any
+// difference. That catches mismatched builds, not malicious ones: a plugin
+// runs with the full rights of the process, so `load` is `unsafe`.
+
+use crate::system::SystemExecutor;
+use crate::world::World;
+use core::ffi::{c_char, CStr};
+use core::fmt;
+use std::path::{Path, PathBuf};
+
+// Bumped whenever the plugin entry points change shape.
+pub const PLUGIN_ABI_VERSION: u32 = 2;
+
+// The version of this crate, as plugins report it back.
+pub const CORE_VERSION: &CStr = build_string(concat!(env!("CARGO_PKG_VERSION"), "\0"));
+
+// `rustc -V` of the compiler that built this crate, from build.rs.
+pub const RUSTC_VERSION: &CStr = build_string(concat!(env!("RUSTY_RUSTC_VERSION"), "\0"));
+
+// This crate's enabled features, sorted and comma-separated, from build.rs.
+pub const FEATURES: &CStr = build_string(concat!(env!("RUSTY_FEATURES"), "\0"));
+
+const fn build_string(s: &'static str) -> &'static CStr {
+    match CStr::from_bytes_with_nul(s.as_bytes()) {
+        Ok(s) => s,
+        Err(_) => panic!("build string contains a nul byte"),
+    }
+}
+
+// Returned by a plugin's `rusty_plugin_abi`, the first symbol the loader
+// reads. Laid out as C so it can be read before anything else is trusted.
+#[repr(C)]
+pub struct PluginAbi {
+    pub abi_version: u32,
+    pub core_version: *const c_char,
+    pub rustc_version: *const c_char,
+    pub features: *const c_char,
+}
+
+impl PluginAbi {
+    pub fn current() -> Self {
+        PluginAbi {
+            abi_version: PLUGIN_ABI_VERSION,
+            core_version: CORE_VERSION.as_ptr(),
+            rustc_version: RUSTC_VERSION.as_ptr(),
+            features: FEATURES.as_ptr(),
+        }
+    }
+}
+
+type AbiFn = extern "C" fn() -> PluginAbi;
+type RegisterFn = fn(&mut World, &mut SystemExecutor);
+
+// Exports `register`, a `fn(&mut World, &mut SystemExecutor)`, as the
+// plugin's entry point, together with the handshake the loader checks first.
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:path) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn rusty_plugin_abi() -> $crate::plugin::PluginAbi {
+            $crate::plugin::PluginAbi::current()
+        }
+
+        #[unsafe(export_name = "register")]
+        pub fn rusty_plugin_register(
+            world: &mut $crate::World,
+            executor: &mut $crate::SystemExecutor,
+        ) {
+            $register(world, executor)
+        }
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+    Io { path: PathBuf, reason: String },
+    Load { path: PathBuf, reason: String },
+    MissingSymbol { path: PathBuf, symbol: &'static str },
+    AbiMismatch { path: PathBuf, expected: u32, found: u32 },
+    CoreVersionMismatch { path: PathBuf, expected: String, found: String },
+    RustcMismatch { path: PathBuf, expected: String, found: String },
+    FeatureMismatch { path: PathBuf, expected: String, found: String },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Io { path, reason } => {
+                write!(f, "cannot read {}: {}", path.display(), reason)
+            }
+            PluginError::Load { path, reason } => {
+                write!(f, "cannot load plugin {}: {}", path.display(), reason)
+            }
+            PluginError::MissingSymbol { path, symbol } => {
+                write!(f, "plugin {} does not export {}", path.display(), symbol)
+            }
+            PluginError::AbiMismatch { path, expected, found } => write!(
+                f,
+                "plugin {} uses plugin ABI {}, expected {}",
+                path.display(),
+                found,
+                expected
+            ),
+            PluginError::CoreVersionMismatch { path, expected, found } => write!(
+                f,
+                "plugin {} was built against rusty-ecs-core {}, this is {}",
+                path.display(),
+                found,
+                expected
+            ),
+            PluginError::RustcMismatch { path, expected, found } => write!(
+                f,
+                "plugin {} was built with {}, this was built with {}",
+                path.display(),
+                found,
+                expected
+            ),
+            PluginError::FeatureMismatch { path, expected, found } => write!(
+                f,
+                "plugin {} was built with rusty-ecs-core features [{}], this has [{}]",
+                path.display(),
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+// The dynamic libraries directly inside `dir`, sorted by path so plugins
+// always load in the same order.
+pub fn discover(dir: &Path) -> Result<Vec<PathBuf>, PluginError> {
+    let io = |err: std::io::Error| PluginError::Io {
+        path: dir.to_path_buf(),
+        reason: err.to_string(),
+    };
+    let mut plugins = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(io)? {
+        let path = entry.map_err(io)?.path();
+        let is_library = path
+            .extension()
+            .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION);
+        if is_library && path.is_file() {
+            plugins.push(path);
+        }
+    }
+    plugins.sort();
+    Ok(plugins)
+}
+
+/// Loads the plugin at `path`, checks the handshake and lets it register its
+/// systems and resources. The library stays loaded for the rest of the
+/// process, since the systems it registered run its code.
+///
+/// # Safety
+/// Loading runs the library's initialisers, and a plugin that passes the
+/// handshake is called through the Rust ABI with full access to `world`.
+/// Only the build it reports is checked, so `path` must be a plugin built
+/// with `export_plugin!` from code the caller trusts.
+pub unsafe fn load(
+    path: &Path,
+    world: &mut World,
+    executor: &mut SystemExecutor,
+) -> Result<(), PluginError> {
+    let error = |reason: libloading::Error| PluginError::Load {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    };
+    let missing = |symbol| PluginError::MissingSymbol { path: path.to_path_buf(), symbol };
+
+    // SAFETY: the caller vouches for the library, see the function contract.
+    let library = unsafe { libloading::Library::new(path) }.map_err(error)?;
+    // SAFETY: `rusty_plugin_abi` is declared as a C function returning the
+    // `repr(C)` PluginAbi, which does not depend on the Rust ABI.
+    let abi = unsafe { library.get::<AbiFn>(b"rusty_plugin_abi\0") }
+        .map_err(|_| missing("rusty_plugin_abi"))?();
+    if abi.abi_version != PLUGIN_ABI_VERSION {
+        return Err(PluginError::AbiMismatch {
+            path: path.to_path_buf(),
+            expected: PLUGIN_ABI_VERSION,
+            found: abi.abi_version,
+        });
+    }
+    // SAFETY: with a matching ABI version each pointer is one of the plugin's
+    // own nul-terminated build strings, alive while it is loaded.
+    let read = |ptr| unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+    let own = |s: &CStr| s.to_string_lossy().into_owned();
+    let found = read(abi.core_version);
+    if found != own(CORE_VERSION) {
+        return Err(PluginError::CoreVersionMismatch {
+            path: path.to_path_buf(),
+            expected: own(CORE_VERSION),
+            found,
+        });
+    }
+    let found = read(abi.rustc_version);
+    if found != own(RUSTC_VERSION) {
+        return Err(PluginError::RustcMismatch {
+            path: path.to_path_buf(),
+            expected: own(RUSTC_VERSION),
+            found,
+        });
+    }
+    let found = read(abi.features);
+    if found != own(FEATURES) {
+        return Err(PluginError::FeatureMismatch {
+            path: path.to_path_buf(),
+            expected: own(FEATURES),
+            found,
+        });
+    }
+    // SAFETY: the handshake matched, so `register` was built by
+    // `export_plugin!` against this version of the crate, with the same
+    // features and compiler.
+    let register = unsafe { library.get::<RegisterFn>(b"register\0") }
+        .map_err(|_| missing("register"))?;
+    register(world, executor);
+    // Unloading would leave the registered systems pointing at unmapped code
+    std::mem::forget(library);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_and_reject_non_plugins() {
+        let dir = std::env::temp_dir().join(format!("rusty-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let fake = dir.join(format!("fake.{}", std::env::consts::DLL_EXTENSION));
+        std::fs::write(&fake, b"not a library").unwrap();
+        std::fs::write(dir.join("readme.txt"), b"content pack").unwrap();
+
+        assert_eq!(discover(&dir), Ok(vec![fake.clone()]));
+        let mut world = World::new();
+        let mut executor = SystemExecutor::new();
+        // SAFETY: the file is not a library, so loading it fails up front.
+        let err = unsafe { load(&fake, &mut world, &mut executor) }.unwrap_err();
+        assert!(matches!(err, PluginError::Load { .. }), "{}", err);
+        assert!(discover(&dir.join("missing")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Builds `example-plugin/` with this build's features and loads it. Its
+    // dependency keeps rusty-ecs-core's default features, so a build without
+    // them has to be refused.
+    #[test]
+    fn test_load_example_plugin() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target = root.join("target").join("example-plugin");
+        let own = FEATURES.to_str().unwrap();
+        let features: Vec<String> = own
+            .split(',')
+            .filter(|f| !f.is_empty() && *f != "default" && *f != "std")
+            .map(|f| format!("rusty-ecs-core/{}", f))
+            .collect();
+        let status = std::process::Command::new(env!("CARGO"))
+            .arg("build")
+            .arg("--manifest-path")
+            .arg(root.join("../example-plugin/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target)
+            .arg("--features")
+            .arg(features.join(","))
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let library = target.join("debug").join(format!(
+            "{}example_plugin.{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_EXTENSION
+        ));
+
+        let mut world = World::new();
+        let mut executor = SystemExecutor::new();
+        // SAFETY: the example plugin is built from this repository.
+        let result = unsafe { load(&library, &mut world, &mut executor) };
+        if !own.split(',').any(|f| f == "default") {
+            assert!(matches!(result, Err(PluginError::FeatureMismatch { .. })), "{:?}", result);
+            return;
+        }
+        assert_eq!(result, Ok(()));
+        assert!(executor.export_graphviz().contains("ReinforcementSystem"));
+        executor.run(&mut world);
+        executor.run(&mut world);
+        assert_eq!(world.entities().live_count(), 2);
+    }
+}
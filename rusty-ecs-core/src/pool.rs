@@ -0,0 +1,257 @@
+use crate::bitset::BitSet;
+use crate::component::{Component, ComponentStorage, StorageReport, TypedStorage};
+use crate::entity::Entity;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::Any;
+
+const EMPTY: u32 = u32::MAX;
+
+// Sparse-set storage for components with heavy churn (projectiles, particles).
+// Components live densely in a Vec indexed through `sparse` by entity id, so
+// removals are a swap_remove and the backing allocations are kept and reused
+// instead of being returned to the allocator on every despawn.
+pub struct PooledComponentStorage<T: Component> {
+    dense: Vec<T>,
+    owners: Vec<Entity>,
+    sparse: Vec<u32>,
+    mask: BitSet,
+    pub(crate) clone_fn: Option<fn(&T) -> T>,
+}
+
+impl<T: Component> PooledComponentStorage<T> {
+    pub fn new() -> Self {
+        Self {
+            dense: Vec::new(),
+            owners: Vec::new(),
+            sparse: Vec::new(),
+            mask: BitSet::new(),
+            clone_fn: None,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            dense: Vec::with_capacity(capacity),
+            owners: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn cloneable() -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            clone_fn: Some(T::clone),
+            ..Self::new()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.dense.capacity()
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        self.owners.iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.owners.iter().copied().zip(self.dense.iter())
+    }
+
+    // Owners and components in dense order; grouped entities come first.
+    pub fn as_slices(&self) -> (&[Entity], &[T]) {
+        (&self.owners, &self.dense)
+    }
+
+    // Drops every component but keeps the allocations for the next frame.
+    pub fn clear_frame(&mut self) {
+        for &owner in &self.owners {
+            self.sparse[owner.id as usize] = EMPTY;
+        }
+        self.dense.clear();
+        self.owners.clear();
+        self.mask.clear();
+    }
+
+    fn index_of(&self, entity: Entity) -> Option<usize> {
+        let index = *self.sparse.get(entity.id as usize)?;
+        if index == EMPTY || self.owners[index as usize] != entity {
+            return None;
+        }
+        Some(index as usize)
+    }
+}
+
+impl<T: Component> Default for PooledComponentStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component> TypedStorage<T> for PooledComponentStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
+        let slot = entity.id as usize;
+        if slot >= self.sparse.len() {
+            self.sparse.resize(slot + 1, EMPTY);
+        }
+
+        let index = self.sparse[slot];
+        if index != EMPTY {
+            // Same id: either an overwrite or a stale generation being replaced
+            self.dense[index as usize] = component;
+            self.owners[index as usize] = entity;
+        } else {
+            self.sparse[slot] = self.dense.len() as u32;
+            self.dense.push(component);
+            self.owners.push(entity);
+        }
+        self.mask.insert(entity.id);
+    }
+
+    fn take(&mut self, entity: Entity) -> Option<T> {
+        let index = self.index_of(entity)?;
+        let component = self.dense.swap_remove(index);
+        self.owners.swap_remove(index);
+        if let Some(moved) = self.owners.get(index) {
+            self.sparse[moved.id as usize] = index as u32;
+        }
+        self.sparse[entity.id as usize] = EMPTY;
+        self.mask.remove(entity.id);
+        Some(component)
+    }
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        self.index_of(entity).map(|index| &self.dense[index])
+    }
+
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.index_of(entity).map(|index| &mut self.dense[index])
+    }
+
+    fn iter_mut(&mut self) -> Box<dyn Iterator<Item = (Entity, &mut T)> + '_> {
+        Box::new(self.owners.iter().copied().zip(self.dense.iter_mut()))
+    }
+}
+
+impl<T: Component> ComponentStorage for PooledComponentStorage<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.take(entity);
+    }
+
+    fn mask(&self) -> &BitSet {
+        &self.mask
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.index_of(entity).is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    fn entity_list(&self) -> Vec<Entity> {
+        self.owners.clone()
+    }
+
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<T>()
+    }
+
+    fn memory(&self) -> StorageReport {
+        let bytes = self.dense.capacity() * size_of::<T>()
+            + self.owners.capacity() * size_of::<Entity>()
+            + self.sparse.capacity() * size_of::<u32>()
+            + self.mask.heap_bytes();
+        StorageReport::new::<T>(self.dense.len(), bytes, self.dense.capacity())
+    }
+
+    fn dense_index(&self, entity: Entity) -> Option<usize> {
+        self.index_of(entity)
+    }
+
+    fn swap_dense(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        self.dense.swap(a, b);
+        self.owners.swap(a, b);
+        self.sparse[self.owners[a].id as usize] = a as u32;
+        self.sparse[self.owners[b].id as usize] = b as u32;
+    }
+
+    fn clone_component(&mut self, entity: Entity, target: Entity) -> bool {
+        let Some(clone_fn) = self.clone_fn else {
+            return false;
+        };
+        match self.get(entity).map(clone_fn) {
+            Some(component) => {
+                self.insert(target, component);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::component::{ComponentStorage, TypedStorage};
+    use crate::{Entity, PooledComponentStorage};
+
+    #[derive(Debug, PartialEq)]
+    struct Particle(u32);
+
+    #[test]
+    fn test_insert_get_take() {
+        let mut pool = PooledComponentStorage::<Particle>::new();
+        let e1 = Entity { id: 3, generation: 0 };
+        let e2 = Entity { id: 8, generation: 0 };
+        pool.insert(e1, Particle(1));
+        pool.insert(e2, Particle(2));
+
+        assert_eq!(pool.take(e1), Some(Particle(1)));
+        // The swapped-in component must still be reachable
+        assert_eq!(pool.get(e2), Some(&Particle(2)));
+        assert!(pool.get(e1).is_none());
+        assert_eq!(pool.mask().iter().collect::<Vec<_>>(), vec![8]);
+    }
+
+    #[test]
+    fn test_stale_generation_is_not_found() {
+        let mut pool = PooledComponentStorage::<Particle>::new();
+        pool.insert(Entity { id: 1, generation: 1 }, Particle(1));
+
+        assert!(pool.get(Entity { id: 1, generation: 0 }).is_none());
+        pool.remove(Entity { id: 1, generation: 0 });
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_clear_frame_keeps_capacity() {
+        let mut pool = PooledComponentStorage::<Particle>::new();
+        for id in 0..64 {
+            pool.insert(Entity { id, generation: 0 }, Particle(id));
+        }
+        let capacity = pool.capacity();
+
+        pool.clear_frame();
+        assert!(pool.is_empty());
+        assert!(pool.mask().is_empty());
+        assert_eq!(pool.capacity(), capacity);
+
+        pool.insert(Entity { id: 5, generation: 1 }, Particle(5));
+        assert_eq!(pool.get(Entity { id: 5, generation: 1 }), Some(&Particle(5)));
+        assert_eq!(pool.capacity(), capacity);
+    }
+}
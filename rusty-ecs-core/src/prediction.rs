@@ -0,0 +1,146 @@
+use crate::simulation::rebuild;
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::system::SystemExecutor;
+use crate::world::World;
+use alloc::vec::Vec;
+
+// Client-side prediction: a copy of the main world that runs the player's
+// inputs right away, before the server has confirmed them. Each input gets
+// a sequence number; when an authoritative state arrives together with the
+// last input the server applied, `reconcile` rebuilds the copy from that
+// state and replays the inputs the server has not seen yet.
+//
+// Only registered persistent components and resources are carried over,
+// and entities keep their ids, so the same `Entity` addresses both worlds.
+// Hooks, required components, watches, observers and invariants are not, as
+// with `World::simulate`.
+pub struct PredictionWorld<I> {
+    world: World,
+    apply: fn(&mut World, &I),
+    // Inputs not yet acknowledged, oldest first.
+    pending: Vec<(u64, I)>,
+    next_sequence: u64,
+}
+
+impl<I> PredictionWorld<I> {
+    // Forks `main`; `apply` turns an input into changes to the world, e.g.
+    // pushing the events the game systems react to.
+    pub fn fork(main: &World, apply: fn(&mut World, &I)) -> Result<Self, SnapshotError> {
+        Ok(Self {
+            world: rebuild(main, &main.snapshot())?,
+            apply,
+            pending: Vec::new(),
+            next_sequence: 1,
+        })
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    // Applies `input` and runs `executor` once, returning the input's
+    // sequence number to send to the server along with it.
+    pub fn predict(&mut self, input: I, executor: &mut SystemExecutor) -> u64 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        (self.apply)(&mut self.world, &input);
+        executor.run(&mut self.world);
+        self.pending.push((sequence, input));
+        sequence
+    }
+
+    // Inputs predicted but not yet acknowledged.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Resets the prediction to `authoritative`, which already includes every
+    // input up to `acknowledged`, and replays the later ones, one executor
+    // run each. Returns how many were replayed. On error the prediction is
+    // left as it was.
+    pub fn reconcile(
+        &mut self,
+        authoritative: &Snapshot,
+        acknowledged: u64,
+        executor: &mut SystemExecutor,
+    ) -> Result<usize, SnapshotError> {
+        self.world = rebuild(&self.world, authoritative)?;
+        self.pending.retain(|(sequence, _)| *sequence > acknowledged);
+        for (_, input) in &self.pending {
+            (self.apply)(&mut self.world, input);
+            executor.run(&mut self.world);
+        }
+        Ok(self.pending.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::Persist;
+    use crate::ExclusiveSystem;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Position(i32);
+
+    impl Persist for Position {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Position(i32::read(input)?))
+        }
+    }
+
+    // Moves every Position by the input's step.
+    fn step(world: &mut World, input: &i32) {
+        for e in world.query_entities::<Position>() {
+            world.get_component_mut::<Position>(e).unwrap().0 += input;
+        }
+    }
+
+    // Pulls everything back towards zero, so replays have to run systems.
+    struct Friction;
+
+    impl ExclusiveSystem for Friction {
+        fn run(&mut self, world: &mut World) {
+            for e in world.query_entities::<Position>() {
+                world.get_component_mut::<Position>(e).unwrap().0 -= 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconcile_replays_unacknowledged_inputs() {
+        let mut main = World::new();
+        main.register_persistent::<Position>("Position");
+        let player = main.create_entity();
+        main.add_component(player, Position(0));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(Friction);
+
+        let mut prediction = PredictionWorld::fork(&main, step).unwrap();
+        assert_eq!(prediction.predict(5, &mut executor), 1);
+        assert_eq!(prediction.predict(3, &mut executor), 2);
+        assert_eq!(prediction.predict(2, &mut executor), 3);
+        assert_eq!(prediction.world().get_component::<Position>(player), Some(&Position(7)));
+        // The main world is untouched
+        assert_eq!(main.get_component::<Position>(player), Some(&Position(0)));
+
+        // The server applied the first input but was blocked by a wall
+        main.get_component_mut::<Position>(player).unwrap().0 = 1;
+        let replayed = prediction.reconcile(&main.snapshot(), 1, &mut executor).unwrap();
+        assert_eq!(replayed, 2);
+        assert_eq!(prediction.pending(), 2);
+        assert_eq!(prediction.world().get_component::<Position>(player), Some(&Position(4)));
+
+        main.get_component_mut::<Position>(player).unwrap().0 = 4;
+        assert_eq!(prediction.reconcile(&main.snapshot(), 3, &mut executor), Ok(0));
+        assert_eq!(prediction.world().get_component::<Position>(player), Some(&Position(4)));
+    }
+}
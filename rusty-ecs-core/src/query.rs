@@ -0,0 +1,494 @@
+use crate::bitset::BitSet;
+use crate::component::{Component, ComponentManager};
+use crate::entity::Entity;
+use crate::snapshot::{Persist, SnapshotError};
+use crate::world::World;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::marker::PhantomData;
+
+pub trait QueryData {
+    type Item<'w>;
+
+    fn mask(components: &ComponentManager) -> Option<BitSet>;
+    fn fetch(components: &ComponentManager, entity: Entity) -> Option<Self::Item<'_>>;
+    fn reads(types: &mut Vec<(TypeId, &'static str)>);
+}
+
+impl<T: Component> QueryData for &T {
+    type Item<'w> = &'w T;
+
+    fn mask(components: &ComponentManager) -> Option<BitSet> {
+        components.mask::<T>().cloned()
+    }
+
+    fn fetch(components: &ComponentManager, entity: Entity) -> Option<Self::Item<'_>> {
+        components.get::<T>(entity)
+    }
+
+    fn reads(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<T>(), core::any::type_name::<T>()));
+    }
+}
+
+macro_rules! impl_query_data_tuple {
+    ($first:ident $(, $rest:ident)*) => {
+        impl<$first: QueryData $(, $rest: QueryData)*> QueryData for ($first, $($rest),*) {
+            type Item<'w> = ($first::Item<'w>, $($rest::Item<'w>),*);
+
+            fn mask(components: &ComponentManager) -> Option<BitSet> {
+                #[allow(unused_mut)]
+                let mut mask = $first::mask(components)?;
+                $(mask.intersect_with(&$rest::mask(components)?);)*
+                Some(mask)
+            }
+
+            fn fetch(components: &ComponentManager, entity: Entity) -> Option<Self::Item<'_>> {
+                Some(($first::fetch(components, entity)?, $($rest::fetch(components, entity)?),*))
+            }
+
+            fn reads(types: &mut Vec<(TypeId, &'static str)>) {
+                $first::reads(types);
+                $($rest::reads(types);)*
+            }
+        }
+    };
+}
+
+impl_query_data_tuple!(A);
+impl_query_data_tuple!(A, B);
+impl_query_data_tuple!(A, B, C);
+impl_query_data_tuple!(A, B, C, D);
+
+// Plain component types, as in `count_matching::<(Enemy, Health)>()`, for
+// finding entities that have all of them without fetching anything.
+pub trait ComponentSet {
+    fn mask(components: &ComponentManager) -> Option<BitSet>;
+}
+
+macro_rules! impl_component_set_tuple {
+    ($first:ident $(, $rest:ident)*) => {
+        impl<$first: Component $(, $rest: Component)*> ComponentSet for ($first, $($rest),*) {
+            fn mask(components: &ComponentManager) -> Option<BitSet> {
+                #[allow(unused_mut)]
+                let mut mask = components.mask::<$first>()?.clone();
+                $(mask.intersect_with(components.mask::<$rest>()?);)*
+                Some(mask)
+            }
+        }
+    };
+}
+
+impl_component_set_tuple!(A);
+impl_component_set_tuple!(A, B);
+impl_component_set_tuple!(A, B, C);
+impl_component_set_tuple!(A, B, C, D);
+
+// A `World::query_sorted` result kept between calls. `get` sorts again only
+// when a T was added, removed or mutably borrowed since the previous call,
+// which it learns from change detection, so it has to be called at least once
+// per executor run. Without change tracking every call sorts.
+//
+// A `manual()` query never re-sorts on its own: `get` returns what the last
+// `refresh` produced, and `is_stale` and `last_updated` tell the caller when
+// to refresh, for loops that want to pick the moment themselves.
+pub struct SortedQuery<T, K, F> {
+    key: F,
+    mask: Option<BitSet>,
+    disabled: Option<BitSet>,
+    sorted: Vec<Entity>,
+    manual: bool,
+    updated: Option<u64>,
+    _marker: PhantomData<fn(&T) -> K>,
+}
+
+impl<T: Component, K: Ord, F: Fn(&T) -> K> SortedQuery<T, K, F> {
+    pub fn new(key: F) -> Self {
+        Self {
+            key,
+            mask: None,
+            disabled: None,
+            sorted: Vec::new(),
+            manual: false,
+            updated: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn manual(mut self) -> Self {
+        self.manual = true;
+        self
+    }
+
+    pub fn get(&mut self, world: &World) -> &[Entity] {
+        if !self.manual && self.is_stale(world) {
+            self.refresh(world);
+        }
+        &self.sorted
+    }
+
+    // Sorts again whether or not anything changed.
+    pub fn refresh(&mut self, world: &World) -> &[Entity] {
+        self.sorted = world.query_sorted(&self.key);
+        self.mask = world.components().mask::<T>().cloned();
+        self.disabled = world.components().mask::<Disabled>().cloned();
+        self.updated = Some(world.tick());
+        &self.sorted
+    }
+
+    // Whether the cached order may be out of date: never refreshed, an
+    // entity gained, lost or re-enabled a T, or a T changed since changes
+    // were last cleared. Always true without change tracking.
+    pub fn is_stale(&self, world: &World) -> bool {
+        self.updated.is_none()
+            || !world.config().change_tracking
+            || self.mask.as_ref() != world.components().mask::<T>()
+            || self.disabled.as_ref() != world.components().mask::<Disabled>()
+            || !world.changed::<T>().is_empty()
+    }
+
+    // The world tick of the last refresh, None before the first.
+    pub fn last_updated(&self) -> Option<u64> {
+        self.updated
+    }
+}
+
+pub trait QueryFilter {
+    fn apply(components: &ComponentManager, candidates: &mut BitSet);
+
+    // Whether entities marked `Disabled` stay in the results.
+    fn includes_disabled() -> bool {
+        false
+    }
+}
+
+// Marker for entities that are kept but switched off, set through
+// `World::set_enabled`. Queries skip them unless their filter includes
+// `IncludeDisabled` or `With<Disabled>`; direct component access still works.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Disabled;
+
+// Every world registers `Disabled` for snapshots, so entities stay disabled
+// across a save and load.
+impl Persist for Disabled {
+    fn write(&self, _out: &mut Vec<u8>) {}
+
+    fn read(_input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Disabled)
+    }
+}
+
+pub struct With<T>(PhantomData<T>);
+pub struct Without<T>(PhantomData<T>);
+pub struct IncludeDisabled;
+
+impl QueryFilter for () {
+    fn apply(_components: &ComponentManager, _candidates: &mut BitSet) {}
+}
+
+impl<T: Component> QueryFilter for With<T> {
+    fn apply(components: &ComponentManager, candidates: &mut BitSet) {
+        match components.mask::<T>() {
+            Some(mask) => candidates.intersect_with(mask),
+            None => candidates.clear(),
+        }
+    }
+
+    fn includes_disabled() -> bool {
+        TypeId::of::<T>() == TypeId::of::<Disabled>()
+    }
+}
+
+impl QueryFilter for IncludeDisabled {
+    fn apply(_components: &ComponentManager, _candidates: &mut BitSet) {}
+
+    fn includes_disabled() -> bool {
+        true
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    fn apply(components: &ComponentManager, candidates: &mut BitSet) {
+        if let Some(mask) = components.mask::<T>() {
+            candidates.difference_with(mask);
+        }
+    }
+}
+
+impl<A: QueryFilter, B: QueryFilter> QueryFilter for (A, B) {
+    fn apply(components: &ComponentManager, candidates: &mut BitSet) {
+        A::apply(components, candidates);
+        B::apply(components, candidates);
+    }
+
+    fn includes_disabled() -> bool {
+        A::includes_disabled() || B::includes_disabled()
+    }
+}
+
+impl<A: QueryFilter, B: QueryFilter, C: QueryFilter> QueryFilter for (A, B, C) {
+    fn apply(components: &ComponentManager, candidates: &mut BitSet) {
+        A::apply(components, candidates);
+        B::apply(components, candidates);
+        C::apply(components, candidates);
+    }
+
+    fn includes_disabled() -> bool {
+        A::includes_disabled() || B::includes_disabled() || C::includes_disabled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+    use crate::query::{With, Without};
+
+    struct Health(u32);
+    struct Damage(u32);
+    struct Player;
+    struct Enemy;
+
+    #[test]
+    fn test_query_without_filter() {
+        let mut world = World::new();
+
+        let player = world.create_entity();
+        world.add_component(player, Health(40));
+        world.add_component(player, Player);
+
+        let enemy = world.create_entity();
+        world.add_component(enemy, Health(10));
+        world.add_component(enemy, Enemy);
+
+        let results: Vec<_> = world.query::<&Health, Without<Player>>().collect();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, enemy);
+        assert_eq!(results[0].1.0, 10);
+    }
+
+    #[test]
+    fn test_query_with_filter() {
+        let mut world = World::new();
+
+        let player = world.create_entity();
+        world.add_component(player, Health(40));
+        world.add_component(player, Player);
+
+        let enemy = world.create_entity();
+        world.add_component(enemy, Health(10));
+
+        let results: Vec<_> = world.query::<&Health, With<Player>>().map(|(e, _)| e).collect();
+        assert_eq!(results, vec![player]);
+    }
+
+    #[test]
+    fn test_query_with_unregistered_filter() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(1));
+
+        assert_eq!(world.query::<&Health, Without<Enemy>>().count(), 1);
+        assert_eq!(world.query::<&Health, With<Enemy>>().count(), 0);
+        assert_eq!(world.query::<&Enemy, ()>().count(), 0);
+    }
+
+    #[test]
+    fn test_query_combined_filters() {
+        let mut world = World::new();
+
+        let a = world.create_entity();
+        world.add_component(a, Health(1));
+        world.add_component(a, Enemy);
+
+        let b = world.create_entity();
+        world.add_component(b, Health(2));
+        world.add_component(b, Enemy);
+        world.add_component(b, Player);
+
+        let results: Vec<_> = world
+            .query::<&Health, (With<Enemy>, Without<Player>)>()
+            .map(|(e, _)| e)
+            .collect();
+        assert_eq!(results, vec![a]);
+    }
+
+    #[test]
+    fn test_query_skips_destroyed_entities() {
+        let mut world = World::new();
+        let e1 = world.create_entity();
+        let e2 = world.create_entity();
+        world.add_component(e1, Health(1));
+        world.add_component(e2, Health(2));
+
+        world.destroy_entity(e1);
+
+        let results: Vec<_> = world.query::<&Health, ()>().map(|(e, _)| e).collect();
+        assert_eq!(results, vec![e2]);
+    }
+
+    #[test]
+    fn test_multi_component_query_intersects_masks() {
+        let mut world = World::new();
+
+        let mut both = Vec::new();
+        for i in 0..200 {
+            let e = world.create_entity();
+            world.add_component(e, Health(i));
+            if i % 3 == 0 {
+                world.add_component(e, Damage(i * 2));
+                both.push(e);
+            }
+        }
+
+        let results: Vec<_> = world.query::<(&Health, &Damage), ()>().collect();
+
+        assert_eq!(results.len(), both.len());
+        for (entity, (health, damage)) in results {
+            assert!(both.contains(&entity));
+            assert_eq!(damage.0, health.0 * 2);
+        }
+    }
+
+    #[test]
+    fn test_multi_component_query_with_filter() {
+        let mut world = World::new();
+
+        let player = world.create_entity();
+        world.add_component(player, Health(40));
+        world.add_component(player, Damage(7));
+        world.add_component(player, Player);
+
+        let enemy = world.create_entity();
+        world.add_component(enemy, Health(10));
+        world.add_component(enemy, Damage(3));
+
+        let results: Vec<_> = world
+            .query::<(&Health, &Damage), Without<Player>>()
+            .map(|(e, (h, d))| (e, h.0, d.0))
+            .collect();
+        assert_eq!(results, vec![(enemy, 10, 3)]);
+    }
+
+    #[test]
+    fn test_multi_component_query_missing_storage() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(1));
+
+        assert_eq!(world.query::<(&Health, &Damage), ()>().count(), 0);
+    }
+
+    #[test]
+    fn test_count_and_sample_matching() {
+        let mut world = World::new();
+        let enemies: Vec<_> = (0..10)
+            .map(|hp| {
+                let e = world.create_entity();
+                world.add_component(e, Enemy);
+                if hp % 2 == 0 {
+                    world.add_component(e, Health(hp));
+                }
+                e
+            })
+            .collect();
+        world.destroy_entity(enemies[0]);
+
+        assert_eq!(world.count_matching::<(Enemy,)>(), 9);
+        assert_eq!(world.count_matching::<(Enemy, Health)>(), 4);
+        assert_eq!(world.count_matching::<(Enemy, Damage)>(), 0);
+
+        let mut rng = crate::rng::RngStream::new(7);
+        let sample = world.sample_matching::<(Enemy, Health)>(3, &mut rng);
+        assert_eq!(sample.len(), 3);
+        for e in &sample {
+            assert!(world.get_component::<Health>(*e).is_some());
+        }
+        assert_eq!(world.sample_matching::<(Enemy, Health)>(10, &mut rng).len(), 4);
+        assert!(world.sample_matching::<(Enemy,)>(0, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_query_sorted_and_cached() {
+        use crate::config::WorldConfig;
+        use crate::query::SortedQuery;
+
+        let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+        let entities: Vec<_> = [3, 1, 2, 1]
+            .into_iter()
+            .map(|hp| {
+                let e = world.create_entity();
+                world.add_component(e, Health(hp));
+                e
+            })
+            .collect();
+        let (a, b, c, d) = (entities[0], entities[1], entities[2], entities[3]);
+        assert_eq!(world.query_sorted::<Health, _>(|h| h.0), vec![b, d, c, a]);
+
+        let mut cached = SortedQuery::new(|h: &Health| core::cmp::Reverse(h.0));
+        assert_eq!(cached.get(&world), &[a, c, b, d]);
+        world.clear_changes();
+        assert_eq!(cached.get(&world), &[a, c, b, d]);
+
+        world.get_component_mut::<Health>(d).unwrap().0 = 5;
+        assert_eq!(cached.get(&world), &[d, a, c, b]);
+        world.clear_changes();
+        world.remove_component::<Health>(a);
+        assert_eq!(cached.get(&world), &[d, c, b]);
+    }
+
+    #[test]
+    fn test_manual_sorted_query_refreshes_on_request() {
+        use crate::config::WorldConfig;
+        use crate::query::SortedQuery;
+
+        let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+        let a = world.create_entity();
+        world.add_component(a, Health(1));
+        let mut cached = SortedQuery::new(|h: &Health| h.0).manual();
+        assert!(cached.is_stale(&world));
+        assert_eq!(cached.last_updated(), None);
+        assert!(cached.get(&world).is_empty());
+
+        world.advance_tick();
+        assert_eq!(cached.refresh(&world), &[a]);
+        assert_eq!(cached.last_updated(), Some(1));
+        world.clear_changes();
+        assert!(!cached.is_stale(&world));
+
+        let b = world.create_entity();
+        world.add_component(b, Health(0));
+        assert!(cached.is_stale(&world));
+        // Still the old order until the caller refreshes
+        assert_eq!(cached.get(&world), &[a]);
+        world.advance_tick();
+        assert_eq!(cached.refresh(&world), &[b, a]);
+        assert_eq!(cached.last_updated(), Some(2));
+    }
+
+    #[test]
+    fn test_disabled_entities_skipped_by_default() {
+        use crate::query::{Disabled, IncludeDisabled};
+
+        let mut world = World::new();
+        let a = world.create_entity();
+        let b = world.create_entity();
+        world.add_component(a, Health(1));
+        world.add_component(b, Health(2));
+        world.add_component(b, Enemy);
+
+        world.set_enabled(b, false);
+        assert!(!world.is_enabled(b));
+        assert_eq!(world.query::<&Health, ()>().map(|(e, _)| e).collect::<Vec<_>>(), vec![a]);
+        assert_eq!(world.query_entities::<Enemy>(), vec![]);
+        assert_eq!(world.count_matching::<(Health,)>(), 1);
+        // Components stay reachable for looting or inspection
+        assert_eq!(world.get_component::<Health>(b).map(|h| h.0), Some(2));
+        assert_eq!(world.query::<&Health, IncludeDisabled>().count(), 2);
+        assert_eq!(world.query::<&Health, With<Disabled>>().map(|(e, _)| e).collect::<Vec<_>>(), vec![b]);
+
+        world.set_enabled(b, true);
+        assert!(world.is_enabled(b));
+        assert_eq!(world.query_sorted::<Health, _>(|h| h.0), vec![a, b]);
+    }
+}
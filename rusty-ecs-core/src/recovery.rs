@@ -0,0 +1,83 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+// Pushed when a system panics and the executor, with the `catch-panics`
+// feature, caught it instead of unwinding out of `run`.
+#[derive(Debug, Clone, PartialEq, Eq, crate::Event)]
+pub struct SystemPanicked {
+    pub system: &'static str,
+    pub message: String,
+}
+
+// Resource inserted the first time a system panics: the world may hold a
+// half-finished update from then on. Later systems of the same run that read
+// what a failed system writes are skipped and listed here. Nothing clears it;
+// a game removes it once it has recovered, e.g. by loading a checkpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tainted {
+    pub panicked: Vec<&'static str>,
+    pub skipped: Vec<&'static str>,
+}
+
+// How `guard` treats a panic: let it unwind, catch it, or catch it without
+// the panic hook reporting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Catch {
+    Off,
+    On,
+    Quiet,
+}
+
+#[cfg(feature = "catch-panics")]
+std::thread_local! {
+    // Set while this thread runs a system under `Catch::Quiet`.
+    static QUIET: core::cell::Cell<bool> = const { core::cell::Cell::new(false) };
+}
+
+// At the first quiet catch, wraps the panic hook in place then so that it
+// skips panics raised while `QUIET` is set. Other panics still reach it; a
+// hook set later replaces the wrapper and reports everything again.
+#[cfg(feature = "catch-panics")]
+fn install_quiet_hook() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(alloc::boxed::Box::new(move |info| {
+            if !QUIET.with(|quiet| quiet.get()) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+// Runs `f`, returning the panic message if it panicked and `catch` is not
+// `Off`.
+#[cfg(feature = "catch-panics")]
+pub(crate) fn guard(catch: Catch, f: impl FnOnce()) -> Result<(), String> {
+    if catch == Catch::Off {
+        f();
+        return Ok(());
+    }
+    let quiet = catch == Catch::Quiet;
+    if quiet {
+        install_quiet_hook();
+    }
+    let outer = QUIET.with(|flag| flag.replace(quiet));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    QUIET.with(|flag| flag.set(outer));
+    result.map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            String::from(*message)
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("non-string panic payload")
+        }
+    })
+}
+
+#[cfg(not(feature = "catch-panics"))]
+pub(crate) fn guard(_catch: Catch, f: impl FnOnce()) -> Result<(), String> {
+    f();
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::query::{Disabled, IncludeDisabled};
+use crate::resource::Resource;
+use crate::snapshot::{ComponentData, EntityMap, MapEntities, Persist, SnapshotError};
+use crate::world::World;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+type SaveComponentsFn = fn(&World) -> Vec<(Entity, Vec<u8>)>;
+type LoadComponentFn = fn(&mut World, Entity, &[u8]) -> Result<(), SnapshotError>;
+type SaveResourceFn = fn(&World) -> Option<Vec<u8>>;
+type LoadResourceFn = fn(&mut World, &[u8]) -> Result<(), SnapshotError>;
+pub(crate) type MapEntitiesFn = fn(&mut World, Entity, &EntityMap);
+// Rewrites one saved value from an older schema version to the next.
+pub type MigrateFn = fn(&[u8]) -> Result<Vec<u8>, SnapshotError>;
+
+#[derive(Clone)]
+pub struct ComponentRegistration {
+    pub name: &'static str,
+    pub type_id: TypeId,
+    pub(crate) save: SaveComponentsFn,
+    pub(crate) load: LoadComponentFn,
+}
+
+#[derive(Clone)]
+pub struct ResourceRegistration {
+    pub name: &'static str,
+    pub type_id: TypeId,
+    pub(crate) save: SaveResourceFn,
+    pub(crate) load: LoadResourceFn,
+}
+
+// Types that take part in snapshots, under stable names that do not depend on
+// module paths, so saves survive refactors. Several worlds can share one
+// through `World::with_registry`.
+#[derive(Clone)]
+pub struct TypeRegistry {
+    components: Vec<ComponentRegistration>,
+    resources: Vec<ResourceRegistration>,
+    entity_mappers: Vec<(TypeId, MapEntitiesFn)>,
+    migrations: Vec<(TypeId, u32, u32, MigrateFn)>,
+}
+
+// Every registry starts out knowing `Disabled`, so a world sharing one never
+// has to add it and disabled entities round-trip through any snapshot.
+impl Default for TypeRegistry {
+    fn default() -> Self {
+        let mut registry = TypeRegistry {
+            components: Vec::new(),
+            resources: Vec::new(),
+            entity_mappers: Vec::new(),
+            migrations: Vec::new(),
+        };
+        registry.register_component::<Disabled>("Disabled");
+        registry
+    }
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_component<T: Component + Persist>(&mut self, name: &'static str) {
+        self.components.retain(|r| r.type_id != TypeId::of::<T>() && r.name != name);
+        self.components.push(ComponentRegistration {
+            name,
+            type_id: TypeId::of::<T>(),
+            save: |world| {
+                world
+                    .query::<&T, IncludeDisabled>()
+                    .map(|(entity, component)| {
+                        let mut bytes = Vec::new();
+                        component.write(&mut bytes);
+                        (entity, bytes)
+                    })
+                    .collect()
+            },
+            load: |world, entity, mut bytes| {
+                let component = T::read(&mut bytes)?;
+                world.add_component(entity, component);
+                Ok(())
+            },
+        });
+    }
+
+    pub fn register_resource<R: Resource + Persist>(&mut self, name: &'static str) {
+        self.resources.retain(|r| r.type_id != TypeId::of::<R>() && r.name != name);
+        self.resources.push(ResourceRegistration {
+            name,
+            type_id: TypeId::of::<R>(),
+            save: |world| {
+                let resource = world.resource::<R>()?;
+                let mut bytes = Vec::new();
+                resource.write(&mut bytes);
+                Some(bytes)
+            },
+            load: |world, mut bytes| {
+                let resource = R::read(&mut bytes)?;
+                world.insert_resource(resource);
+                Ok(())
+            },
+        });
+    }
+
+    pub fn register_map_entities<T: Component + MapEntities>(&mut self) {
+        self.entity_mappers.retain(|(type_id, _)| *type_id != TypeId::of::<T>());
+        self.entity_mappers.push((TypeId::of::<T>(), |world, entity, map| {
+            if let Some(component) = world.get_component_mut::<T>(entity) {
+                component.map_entities(map);
+            }
+        }));
+    }
+
+    // Registers how saved T values move from schema version `from` to `to`.
+    // A type's current version is the highest `to` registered for it, or 1
+    // without migrations, and snapshots record it per component.
+    pub fn register_migration<T: Component>(&mut self, from: u32, to: u32, migrate: MigrateFn) {
+        assert!(from < to, "migrations go from an older version to a newer one");
+        let type_id = TypeId::of::<T>();
+        self.migrations.retain(|(id, f, _, _)| *id != type_id || *f != from);
+        self.migrations.push((type_id, from, to, migrate));
+    }
+
+    pub fn component_version(&self, type_id: TypeId) -> u32 {
+        self.migrations
+            .iter()
+            .filter(|(id, ..)| *id == type_id)
+            .map(|(_, _, to, _)| *to)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // Brings `values` of the component registered as `name` from version
+    // `saved` to the current one by chaining migrations.
+    pub(crate) fn migrate<'a>(
+        &self,
+        name: &str,
+        saved: u32,
+        values: &'a ComponentData,
+    ) -> Result<Cow<'a, ComponentData>, SnapshotError> {
+        let type_id = self.component(name).map(|r| r.type_id).ok_or_else(|| {
+            SnapshotError::UnknownType(String::from(name))
+        })?;
+        let current = self.component_version(type_id);
+        let missing = || SnapshotError::NoMigration { name: String::from(name), saved, current };
+        // Saves from a newer build cannot be migrated back
+        if saved > current {
+            return Err(missing());
+        }
+        let mut values = Cow::Borrowed(values);
+        let mut version = saved;
+        while version < current {
+            let &(_, _, to, migrate) = self
+                .migrations
+                .iter()
+                .find(|(id, from, ..)| *id == type_id && *from == version)
+                .ok_or_else(missing)?;
+            let mut migrated = Vec::with_capacity(values.len());
+            for (entity, bytes) in values.iter() {
+                migrated.push((*entity, migrate(bytes)?));
+            }
+            values = Cow::Owned(migrated);
+            version = to;
+        }
+        Ok(values)
+    }
+
+    // Current schema version of every registered component, by name.
+    pub(crate) fn component_versions(&self) -> Vec<(String, u32)> {
+        self.components
+            .iter()
+            .map(|r| (String::from(r.name), self.component_version(r.type_id)))
+            .collect()
+    }
+
+    pub(crate) fn entity_mappers(&self) -> Vec<MapEntitiesFn> {
+        self.entity_mappers.iter().map(|(_, mapper)| *mapper).collect()
+    }
+
+    pub fn component(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.components.iter().find(|r| r.name == name)
+    }
+
+    pub fn resource(&self, name: &str) -> Option<&ResourceRegistration> {
+        self.resources.iter().find(|r| r.name == name)
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = &ComponentRegistration> {
+        self.components.iter()
+    }
+
+    pub fn resources(&self) -> impl Iterator<Item = &ResourceRegistration> {
+        self.resources.iter()
+    }
+}
@@ -0,0 +1,84 @@
+use crate::collections::HashMap;
+use alloc::boxed::Box;
+use core::any::{Any, TypeId};
+
+// Singleton values owned by the world (time, rng, settings), stored one per
+// type next to the component storages.
+pub trait Resource: Any + Send + Sync + 'static {}
+impl<T: Any + Send + Sync + 'static> Resource for T {}
+
+#[derive(Default)]
+pub struct Resources {
+    values: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<R: Resource>(&mut self, resource: R) -> Option<R> {
+        self.values
+            .insert(TypeId::of::<R>(), Box::new(resource))
+            .and_then(|old| old.downcast::<R>().ok())
+            .map(|old| *old)
+    }
+
+    pub fn get<R: Resource>(&self) -> Option<&R> {
+        self.values.get(&TypeId::of::<R>())?.downcast_ref()
+    }
+
+    pub fn get_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.values.get_mut(&TypeId::of::<R>())?.downcast_mut()
+    }
+
+    pub fn remove<R: Resource>(&mut self) -> Option<R> {
+        self.values
+            .remove(&TypeId::of::<R>())
+            .and_then(|old| old.downcast::<R>().ok())
+            .map(|old| *old)
+    }
+
+    pub fn contains<R: Resource>(&self) -> bool {
+        self.values.contains_key(&TypeId::of::<R>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::World;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Score(u32);
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut world = World::new();
+        assert!(world.resource::<Score>().is_none());
+
+        assert_eq!(world.insert_resource(Score(1)), None);
+        assert_eq!(world.insert_resource(Score(2)), Some(Score(1)));
+        world.resource_mut::<Score>().unwrap().0 += 1;
+        assert_eq!(world.resource::<Score>(), Some(&Score(3)));
+
+        assert_eq!(world.remove_resource::<Score>(), Some(Score(3)));
+        assert!(world.resource::<Score>().is_none());
+    }
+
+    #[test]
+    fn test_init_resource_keeps_existing() {
+        let mut world = World::new();
+        world.init_resource::<Score>().0 = 4;
+        world.init_resource::<Score>();
+
+        assert_eq!(world.resource::<Score>(), Some(&Score(4)));
+    }
+}
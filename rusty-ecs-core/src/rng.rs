@@ -0,0 +1,150 @@
+use crate::collections::HashMap;
+use alloc::string::String;
+use core::ops::Range;
+
+// Deterministic random numbers split into named streams. Each stream is seeded
+// from the world seed and its name only, so drawing more numbers from "loot"
+// never shifts what "ai" produces; replays just need the one seed.
+pub struct RngResource {
+    seed: u64,
+    streams: HashMap<String, RngStream>,
+}
+
+impl RngResource {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, name: &str) -> &mut RngStream {
+        if !self.streams.contains_key(name) {
+            let stream = RngStream::new(self.seed ^ fnv1a(name.as_bytes()));
+            self.streams.insert(String::from(name), stream);
+        }
+        self.streams.get_mut(name).expect("stream was just inserted")
+    }
+
+    // Restarts every stream from the beginning of its sequence.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+}
+
+impl Default for RngResource {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+// SplitMix64: tiny, fast, and every seed (including 0) gives a full sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngStream {
+    state: u64,
+}
+
+impl RngStream {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "empty range {:?}", range);
+        let span = range.end - range.start;
+        // Multiply-shift keeps the bias negligible without a rejection loop
+        range.start + ((self.next_u64() as u128 * span as u128) >> 64) as u64
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.index(items.len()))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RngResource;
+
+    #[test]
+    fn test_streams_are_independent() {
+        let mut a = RngResource::new(42);
+        let mut b = RngResource::new(42);
+
+        // Drawing loot in one world must not change the AI sequence
+        for _ in 0..10 {
+            a.stream("loot").next_u64();
+        }
+        let ai_a: Vec<_> = (0..5).map(|_| a.stream("ai").next_u64()).collect();
+        let ai_b: Vec<_> = (0..5).map(|_| b.stream("ai").next_u64()).collect();
+        assert_eq!(ai_a, ai_b);
+
+        let loot: Vec<_> = (0..5).map(|_| b.stream("loot").next_u64()).collect();
+        assert_ne!(ai_b, loot);
+    }
+
+    #[test]
+    fn test_seed_changes_sequence() {
+        let mut a = RngResource::new(1);
+        let mut b = RngResource::new(2);
+        assert_ne!(a.stream("ai").next_u64(), b.stream("ai").next_u64());
+
+        let first = a.stream("ai").next_u64();
+        a.reseed(1);
+        a.stream("ai").next_u64();
+        assert_eq!(a.stream("ai").next_u64(), first);
+    }
+
+    #[test]
+    fn test_range_and_pick() {
+        let mut rng = RngResource::new(7);
+        let stream = rng.stream("dice");
+        for _ in 0..1000 {
+            let roll = stream.range(1..7);
+            assert!((1..7).contains(&roll));
+            let f = stream.next_f32();
+            assert!((0.0..1.0).contains(&f));
+        }
+
+        assert_eq!(stream.pick::<u8>(&[]), None);
+        assert_eq!(stream.pick(&[9]), Some(&9));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::world::World;
+use alloc::vec::Vec;
+
+// What a `World::simulate` closure returned, along with the sandbox it ran
+// in, for inspecting anything the outcome did not capture.
+pub struct SimulationResult<R> {
+    pub outcome: R,
+    pub sandbox: World,
+}
+
+impl<R> SimulationResult<R> {
+    // Entities whose T in the sandbox differs from `original`'s, including
+    // ones that gained or lost it, sorted.
+    pub fn changed<T: Component + PartialEq>(&self, original: &World) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = original
+            .query_entities::<T>()
+            .into_iter()
+            .chain(self.sandbox.query_entities::<T>())
+            .collect();
+        entities.sort_unstable();
+        entities.dedup();
+        entities.retain(|&e| original.get_component::<T>(e) != self.sandbox.get_component::<T>(e));
+        entities
+    }
+}
+
+// A world with `template`'s config and type registry holding `snapshot`.
+pub(crate) fn rebuild(template: &World, snapshot: &Snapshot) -> Result<World, SnapshotError> {
+    let mut world = World::with_registry(template.config().clone(), template.shared_registry());
+    world.restore_snapshot(snapshot)?;
+    Ok(world)
+}
+
+impl World {
+    // Runs `f` on a throwaway copy of this world, e.g. an AI trying each
+    // candidate move with `run_system_once` or a whole executor run and
+    // scoring the result, and leaves this world untouched. The copy is made
+    // through a snapshot, so like `PredictionWorld` it only holds registered
+    // persistent components and resources, under the same entity ids, and
+    // starts without pending events. There is no copy-on-write: every
+    // simulation pays for a full snapshot of that state.
+    //
+    // None of this world's reactive machinery comes along either: component
+    // hooks, required-component rules, watches, observers and invariants are
+    // closures that cannot be cloned, so nothing reacts to what `f` does in
+    // the sandbox. `f` has to register again whatever the outcome depends
+    // on, e.g. the required components a spawn relies on.
+    pub fn simulate<R>(
+        &self,
+        f: impl FnOnce(&mut World) -> R,
+    ) -> Result<SimulationResult<R>, SnapshotError> {
+        let mut sandbox = rebuild(self, &self.snapshot())?;
+        let outcome = f(&mut sandbox);
+        Ok(SimulationResult { outcome, sandbox })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::{Persist, SnapshotError};
+    use crate::{Access, System, SystemData, World};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Health(i32);
+
+    impl Persist for Health {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Health(i32::read(input)?))
+        }
+    }
+
+    struct Poison(i32);
+
+    impl System for Poison {
+        fn access(&self) -> Access {
+            Access::new().write::<Health>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for e in data.query_entities::<Health>() {
+                data.get_mut::<Health>(e).unwrap().0 -= self.0;
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulate_leaves_the_world_alone() {
+        let mut world = World::new();
+        world.register_persistent::<Health>("Health");
+        let hero = world.create_entity();
+        let rat = world.create_entity();
+        world.add_component(hero, Health(10));
+        world.add_component(rat, Health(3));
+
+        // Score each candidate by how many survive it
+        let survivors: Vec<usize> = [1, 5]
+            .into_iter()
+            .map(|dose| {
+                let result = world
+                    .simulate(|sandbox| {
+                        sandbox.run_system_once(Poison(dose));
+                        sandbox.query::<&Health, ()>().filter(|(_, h)| h.0 > 0).count()
+                    })
+                    .unwrap();
+                result.outcome
+            })
+            .collect();
+        assert_eq!(survivors, [2, 1]);
+        assert_eq!(world.get_component::<Health>(rat), Some(&Health(3)));
+
+        let result = world
+            .simulate(|sandbox| {
+                sandbox.remove_component::<Health>(rat);
+            })
+            .unwrap();
+        assert_eq!(result.changed::<Health>(&world), vec![rat]);
+        assert_eq!(result.sandbox.get_component::<Health>(hero), Some(&Health(10)));
+    }
+
+    #[test]
+    fn test_simulate_runs_without_reactive_rules() {
+        #[derive(Default)]
+        struct Marked;
+        struct Cursed;
+
+        let mut world = World::new();
+        world.register_persistent::<Health>("Health");
+        world.register_required::<Cursed, Marked>();
+        let hero = world.create_entity();
+        world.add_component(hero, Health(10));
+
+        let result = world
+            .simulate(|sandbox| sandbox.add_component(hero, Cursed))
+            .unwrap();
+        assert!(result.sandbox.get_component::<Marked>(hero).is_none());
+
+        world.add_component(hero, Cursed);
+        assert!(world.get_component::<Marked>(hero).is_some());
+    }
+}
@@ -0,0 +1,873 @@
+use crate::collections::HashMap;
+use crate::entity::Entity;
+use crate::world::World;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+const MAGIC: &[u8; 4] = b"RSNP";
+// Version 1 had no entity allocator state; it still loads, with only the
+// live entities' generations known. Version 2 had no component schema
+// versions; its components load as version 1. Version 3 had no header
+// flags or checksum.
+const FORMAT_VERSION: u32 = 4;
+// Header flag: the payload is deflate-compressed.
+const COMPRESSED: u8 = 1;
+// The most a compressed snapshot may inflate to with `Snapshot::from_bytes`.
+// The size it records comes from the file, so it cannot be trusted alone.
+pub const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+// Binary encoding for snapshot data: little-endian integers, u32 length
+// prefixes for strings and sequences.
+pub trait Persist: Sized {
+    fn write(&self, out: &mut Vec<u8>);
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    UnexpectedEof,
+    BadMagic,
+    UnsupportedVersion(u32),
+    InvalidData(String),
+    UnknownType(String),
+    WorldNotEmpty,
+    // No chain of registered migrations leads from the saved schema version
+    // of a component to the current one.
+    NoMigration { name: String, saved: u32, current: u32 },
+    // The payload does not match the checksum stored with it, so the file
+    // was truncated or altered after it was written.
+    ChecksumMismatch,
+    // The snapshot is compressed and this build lacks the `compression`
+    // feature.
+    CompressionUnsupported,
+    // The compressed payload inflates past the size recorded with it.
+    DecompressedTooLarge { limit: u64 },
+    // The compressed payload records an inflated size over the reader's cap.
+    SizeOverLimit { size: u64, limit: u64 },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::UnexpectedEof => write!(f, "snapshot ended unexpectedly"),
+            SnapshotError::BadMagic => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot format version {}", v)
+            }
+            SnapshotError::InvalidData(reason) => write!(f, "invalid snapshot data: {}", reason),
+            SnapshotError::UnknownType(name) => {
+                write!(f, "snapshot contains unregistered type {}", name)
+            }
+            SnapshotError::WorldNotEmpty => {
+                write!(f, "cannot restore entity ids into a world with live entities")
+            }
+            SnapshotError::NoMigration { name, saved, current } => write!(
+                f,
+                "no migration for {} from version {} to version {}",
+                name, saved, current
+            ),
+            SnapshotError::ChecksumMismatch => {
+                write!(f, "snapshot checksum mismatch, file is corrupt")
+            }
+            SnapshotError::CompressionUnsupported => {
+                write!(f, "snapshot is compressed but compression support is not built in")
+            }
+            SnapshotError::DecompressedTooLarge { limit } => {
+                write!(f, "compressed snapshot inflates past its recorded {} bytes", limit)
+            }
+            SnapshotError::SizeOverLimit { size, limit } => write!(
+                f,
+                "compressed snapshot would inflate to {} bytes, over the {} byte limit",
+                size, limit
+            ),
+        }
+    }
+}
+
+impl core::error::Error for SnapshotError {}
+
+fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], SnapshotError> {
+    if input.len() < n {
+        return Err(SnapshotError::UnexpectedEof);
+    }
+    let (head, rest) = input.split_at(n);
+    *input = rest;
+    Ok(head)
+}
+
+macro_rules! impl_persist_number {
+    ($($ty:ty),*) => {$(
+        impl Persist for $ty {
+            fn write(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+                let bytes = take(input, core::mem::size_of::<$ty>())?;
+                Ok(<$ty>::from_le_bytes(bytes.try_into().expect("length was checked")))
+            }
+        }
+    )*};
+}
+
+impl_persist_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Persist for bool {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        match u8::read(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(SnapshotError::InvalidData(alloc::format!("bool tag {}", other))),
+        }
+    }
+}
+
+impl Persist for String {
+    fn write(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).write(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        let len = u32::read(input)? as usize;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| SnapshotError::InvalidData(String::from("string is not UTF-8")))
+    }
+}
+
+impl Persist for Entity {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.id.write(out);
+        self.generation.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Entity {
+            id: u32::read(input)?,
+            generation: u32::read(input)?,
+        })
+    }
+}
+
+impl<T: Persist> Persist for Option<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                out.push(1);
+                value.write(out);
+            }
+            None => out.push(0),
+        }
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        match bool::read(input)? {
+            true => Ok(Some(T::read(input)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).write(out);
+        for item in self {
+            item.write(out);
+        }
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        let len = u32::read(input)? as usize;
+        // Cap the preallocation so a corrupt length cannot exhaust memory
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(T::read(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.0.write(out);
+        self.1.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok((A::read(input)?, B::read(input)?))
+    }
+}
+
+// Serialized values of one component type, keyed by the entity holding them.
+pub type ComponentData = Vec<(Entity, Vec<u8>)>;
+
+// Persisted state of a world: its entities, the allocator state needed to
+// give them back their exact ids, plus every registered component and
+// resource, each stored under its registered name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub entities: Vec<Entity>,
+    // Generation of every allocated id, see `EntityManager::generations`.
+    pub generations: Vec<u32>,
+    pub generation_floor: u32,
+    pub components: Vec<(String, ComponentData)>,
+    pub resources: Vec<(String, Vec<u8>)>,
+    // Schema version each component was saved at; types missing here are at
+    // version 1.
+    pub component_versions: Vec<(String, u32)>,
+}
+
+impl Snapshot {
+    // Layout: magic, format version, flags, CRC-32 of the payload, payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode(0)
+    }
+
+    // Like `to_bytes` with the payload deflate-compressed behind its
+    // uncompressed length; `from_bytes` detects it and inflates no further
+    // than that length.
+    #[cfg(feature = "compression")]
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        self.encode(COMPRESSED)
+    }
+
+    fn encode(&self, flags: u8) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.entities.write(&mut payload);
+        self.generations.write(&mut payload);
+        self.generation_floor.write(&mut payload);
+        self.components.write(&mut payload);
+        self.resources.write(&mut payload);
+        self.component_versions.write(&mut payload);
+        #[cfg(feature = "compression")]
+        if flags & COMPRESSED != 0 {
+            let mut compressed = Vec::new();
+            (payload.len() as u64).write(&mut compressed);
+            compressed.extend(miniz_oxide::deflate::compress_to_vec(&payload, 6));
+            payload = compressed;
+        }
+
+        let mut out = Vec::from(&MAGIC[..]);
+        FORMAT_VERSION.write(&mut out);
+        flags.write(&mut out);
+        crc32(&payload).write(&mut out);
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    // Compressed snapshots are inflated up to `MAX_DECOMPRESSED_SIZE`.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, SnapshotError> {
+        Self::from_bytes_with_limit(input, MAX_DECOMPRESSED_SIZE)
+    }
+
+    // Like `from_bytes`, refusing a compressed payload that records an
+    // inflated size over `max_size` before inflating anything.
+    pub fn from_bytes_with_limit(mut input: &[u8], max_size: u64) -> Result<Self, SnapshotError> {
+        if take(&mut input, MAGIC.len()).map_err(|_| SnapshotError::BadMagic)? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = u32::read(&mut input)?;
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        if version < 4 {
+            return Self::decode(version, input);
+        }
+        let flags = u8::read(&mut input)?;
+        let checksum = u32::read(&mut input)?;
+        if crc32(input) != checksum {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+        if flags & COMPRESSED == 0 {
+            return Self::decode(version, input);
+        }
+        #[cfg(feature = "compression")]
+        {
+            use miniz_oxide::inflate::{decompress_to_vec_with_limit, TINFLStatus};
+
+            let limit = u64::read(&mut input)?;
+            if limit > max_size {
+                return Err(SnapshotError::SizeOverLimit { size: limit, limit: max_size });
+            }
+            let bad = || SnapshotError::InvalidData(String::from("bad compressed payload"));
+            let size = usize::try_from(limit).map_err(|_| bad())?;
+            let payload = decompress_to_vec_with_limit(input, size).map_err(|err| match err.status {
+                TINFLStatus::HasMoreOutput => SnapshotError::DecompressedTooLarge { limit },
+                _ => bad(),
+            })?;
+            if payload.len() != size {
+                return Err(bad());
+            }
+            Self::decode(version, &payload)
+        }
+        #[cfg(not(feature = "compression"))]
+        {
+            let _ = max_size;
+            Err(SnapshotError::CompressionUnsupported)
+        }
+    }
+
+    fn decode(version: u32, mut input: &[u8]) -> Result<Self, SnapshotError> {
+        let entities = Persist::read(&mut input)?;
+        let (generations, generation_floor) = match version {
+            1 => (Vec::new(), 0),
+            _ => (Persist::read(&mut input)?, Persist::read(&mut input)?),
+        };
+        let snapshot = Snapshot {
+            entities,
+            generations,
+            generation_floor,
+            components: Persist::read(&mut input)?,
+            resources: Persist::read(&mut input)?,
+            component_versions: match version {
+                1 | 2 => Vec::new(),
+                _ => Persist::read(&mut input)?,
+            },
+        };
+        if !input.is_empty() {
+            return Err(SnapshotError::InvalidData(String::from("trailing bytes")));
+        }
+        Ok(snapshot)
+    }
+}
+
+// CRC-32 (IEEE), bit by bit; snapshots are small enough that a table would
+// not pay off.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+// Copies of one component type, taken with `World::snapshot_component` and
+// put back with `World::restore_component`. Lives in memory only, so T just
+// needs Clone, not Persist or a registered name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentSnapshot<T> {
+    pub(crate) tick: u64,
+    // Sorted by entity.
+    pub(crate) values: Vec<(Entity, T)>,
+}
+
+impl<T> ComponentSnapshot<T> {
+    // The world tick it was taken on.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        let i = self.values.binary_search_by_key(&entity, |(e, _)| *e).ok()?;
+        Some(&self.values[i].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+// Maps entities from a snapshot to the entities they were restored as.
+pub type EntityMap = HashMap<Entity, Entity>;
+
+// Implemented by components holding `Entity` values, such as a target or an
+// owner, so the references follow their entities when those get new ids: on
+// `load_snapshot_remapped` and on `clone_entity`, where references to the
+// source entity are pointed at the clone. Register types with
+// `World::register_map_entities`. Entities missing from the map are kept.
+pub trait MapEntities {
+    fn map_entities(&mut self, map: &EntityMap);
+}
+
+impl MapEntities for Entity {
+    fn map_entities(&mut self, map: &EntityMap) {
+        if let Some(&mapped) = map.get(self) {
+            *self = mapped;
+        }
+    }
+}
+
+impl<T: MapEntities> MapEntities for Option<T> {
+    fn map_entities(&mut self, map: &EntityMap) {
+        if let Some(value) = self {
+            value.map_entities(map);
+        }
+    }
+}
+
+impl<T: MapEntities> MapEntities for Vec<T> {
+    fn map_entities(&mut self, map: &EntityMap) {
+        for value in self {
+            value.map_entities(map);
+        }
+    }
+}
+
+// With `remap` the snapshot's entities are spawned as new ones next to the
+// world's own; otherwise the world must be empty and they get their saved ids.
+pub(crate) fn restore(
+    world: &mut World,
+    snapshot: &Snapshot,
+    remap: bool,
+) -> Result<EntityMap, SnapshotError> {
+    if !remap && world.entities().live_count() > 0 {
+        return Err(SnapshotError::WorldNotEmpty);
+    }
+    // Check every name first so a bad snapshot leaves the world untouched
+    for (name, _) in &snapshot.components {
+        if world.registry().component(name).is_none() {
+            return Err(SnapshotError::UnknownType(name.clone()));
+        }
+    }
+    // Migrated up front, so a missing migration also leaves the world as is
+    let mut components = Vec::with_capacity(snapshot.components.len());
+    for (name, values) in &snapshot.components {
+        let saved = snapshot
+            .component_versions
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or(1, |(_, version)| *version);
+        components.push((name, world.registry().migrate(name, saved, values)?));
+    }
+    for (name, _) in &snapshot.resources {
+        if world.registry().resource(name).is_none() {
+            return Err(SnapshotError::UnknownType(name.clone()));
+        }
+    }
+
+    let mut map = EntityMap::default();
+    if remap {
+        for &entity in &snapshot.entities {
+            map.insert(entity, world.create_entity());
+        }
+    } else {
+        world
+            .restore_entities(&snapshot.entities, &snapshot.generations, snapshot.generation_floor)
+            .map_err(SnapshotError::InvalidData)?;
+        for &entity in &snapshot.entities {
+            map.insert(entity, entity);
+        }
+    }
+
+    for (name, values) in &components {
+        let load = world.registry().component(name).expect("checked above").load;
+        for (entity, bytes) in values.iter() {
+            let target = *map.get(entity).ok_or_else(|| {
+                SnapshotError::InvalidData(alloc::format!("{} on unknown entity {:?}", name, entity))
+            })?;
+            load(world, target, bytes)?;
+        }
+    }
+    for (name, bytes) in &snapshot.resources {
+        let load = world.registry().resource(name).expect("checked above").load;
+        load(world, bytes)?;
+    }
+    if remap {
+        world.map_entities(map.values().copied(), &map);
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::World;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+    #[derive(Debug, Clone, PartialEq)]
+    struct Label(String);
+    #[derive(Debug, PartialEq)]
+    struct Turn(u32);
+    struct Transient;
+
+    impl Persist for Health {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Health(i32::read(input)?))
+        }
+    }
+
+    impl Persist for Label {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Label(String::read(input)?))
+        }
+    }
+
+    impl Persist for Turn {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Turn(u32::read(input)?))
+        }
+    }
+
+    fn registered_world() -> World {
+        let mut world = World::new();
+        world.register_persistent::<Health>("Health");
+        world.register_persistent::<Label>("Label");
+        world.register_persistent_resource::<Turn>("Turn");
+        world
+    }
+
+    #[test]
+    fn test_round_trip_through_bytes() {
+        let mut world = registered_world();
+        let hero = world.create_entity();
+        world.add_component(hero, Health(40));
+        world.add_component(hero, Label(String::from("Hero")));
+        world.add_component(hero, Transient);
+        let goblin = world.create_entity();
+        world.add_component(goblin, Health(12));
+        world.insert_resource(Turn(3));
+
+        let bytes = world.snapshot().to_bytes();
+        let snapshot = Snapshot::from_bytes(&bytes).unwrap();
+
+        let mut restored = registered_world();
+        let map = restored.restore_snapshot(&snapshot).unwrap();
+        let new_hero = map[&hero];
+        assert_eq!(restored.get_component::<Health>(new_hero), Some(&Health(40)));
+        assert_eq!(
+            restored.get_component::<Label>(new_hero),
+            Some(&Label(String::from("Hero")))
+        );
+        assert!(restored.get_component::<Transient>(new_hero).is_none());
+        assert_eq!(restored.get_component::<Health>(map[&goblin]), Some(&Health(12)));
+        assert_eq!(restored.resource::<Turn>(), Some(&Turn(3)));
+    }
+
+    #[test]
+    fn test_restore_keeps_entity_ids_and_allocation_order() {
+        let mut world = registered_world();
+        let entities: Vec<Entity> = (0..4).map(|_| world.create_entity()).collect();
+        world.destroy_entity(entities[1]);
+        let reused = world.create_entity();
+        world.destroy_entity(entities[2]);
+        world.add_component(entities[3], Health(9));
+        let snapshot = Snapshot::from_bytes(&world.snapshot().to_bytes()).unwrap();
+
+        let mut restored = registered_world();
+        let map = restored.restore_snapshot(&snapshot).unwrap();
+        assert_eq!(map[&entities[3]], entities[3]);
+        assert!(restored.is_alive(reused));
+        assert!(!restored.is_alive(entities[1]));
+        assert_eq!(restored.get_component::<Health>(entities[3]), Some(&Health(9)));
+        // Both worlds hand out the same ids from here on
+        assert_eq!(restored.create_entity(), world.create_entity());
+        assert_eq!(restored.create_entity(), world.create_entity());
+        assert!(restored.validate().is_ok());
+
+        assert_eq!(restored.restore_snapshot(&snapshot), Err(SnapshotError::WorldNotEmpty));
+    }
+
+    #[test]
+    fn test_disabled_entities_survive_round_trip() {
+        let mut world = registered_world();
+        let body = world.create_entity();
+        world.add_component(body, Health(0));
+        world.set_enabled(body, false);
+
+        let mut restored = registered_world();
+        restored.restore_snapshot(&world.snapshot()).unwrap();
+        assert!(!restored.is_enabled(body));
+        assert_eq!(restored.get_component::<Health>(body), Some(&Health(0)));
+    }
+
+    #[test]
+    fn test_remapped_load_merges_next_to_existing_entities() {
+        let mut world = registered_world();
+        let goblin = world.create_entity();
+        world.add_component(goblin, Health(12));
+        let snapshot = world.snapshot();
+
+        let mut running = registered_world();
+        let hero = running.create_entity();
+        running.add_component(hero, Health(40));
+        let map = running.load_snapshot_remapped(&snapshot).unwrap();
+        assert_ne!(map[&goblin], hero);
+        assert_eq!(running.get_component::<Health>(hero), Some(&Health(40)));
+        assert_eq!(running.get_component::<Health>(map[&goblin]), Some(&Health(12)));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Target(Option<Entity>);
+
+    impl Persist for Target {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Target(Option::read(input)?))
+        }
+    }
+
+    impl MapEntities for Target {
+        fn map_entities(&mut self, map: &EntityMap) {
+            self.0.map_entities(map);
+        }
+    }
+
+    #[test]
+    fn test_entity_references_follow_remapping_and_clones() {
+        let mut world = registered_world();
+        world.register_persistent::<Target>("Target");
+        world.register_map_entities::<Target>();
+        let goblin = world.create_entity();
+        let archer = world.create_entity();
+        world.add_component(archer, Target(Some(goblin)));
+        let snapshot = world.snapshot();
+
+        let mut running = registered_world();
+        running.register_persistent::<Target>("Target");
+        running.register_map_entities::<Target>();
+        running.create_entity();
+        let map = running.load_snapshot_remapped(&snapshot).unwrap();
+        assert_ne!(map[&goblin], goblin);
+        assert_eq!(running.get_component::<Target>(map[&archer]), Some(&Target(Some(map[&goblin]))));
+
+        // A clone aims at whatever its source aimed at, unless that was itself
+        world.register_cloneable::<Target>();
+        world.add_component(goblin, Target(Some(goblin)));
+        let twin = world.clone_entity(goblin).unwrap();
+        assert_eq!(world.get_component::<Target>(twin), Some(&Target(Some(twin))));
+        let second = world.clone_entity(archer).unwrap();
+        assert_eq!(world.get_component::<Target>(second), Some(&Target(Some(goblin))));
+    }
+
+    #[test]
+    fn test_copy_entities_between_worlds_sharing_a_registry() {
+        let mut main = registered_world();
+        main.register_persistent::<Target>("Target");
+        main.register_map_entities::<Target>();
+        main.insert_resource(Turn(7));
+        let hero = main.create_entity();
+        main.add_component(hero, Label(String::from("Hero")));
+        main.add_component(hero, Health(30));
+        main.add_component(hero, Target(Some(hero)));
+        let goblin = main.create_entity();
+        main.add_component(goblin, Health(8));
+
+        let mut ui = World::with_registry(crate::WorldConfig::new(), main.shared_registry());
+        assert!(alloc::sync::Arc::ptr_eq(&ui.shared_registry(), &main.shared_registry()));
+        ui.create_entity();
+        let map = main.copy_entities_to::<crate::With<Label>>(&mut ui).unwrap();
+        assert_eq!(map.len(), 1);
+        let copy = map[&hero];
+        assert_eq!(ui.get_component::<Health>(copy), Some(&Health(30)));
+        assert_eq!(ui.get_component::<Target>(copy), Some(&Target(Some(copy))));
+        assert_eq!(ui.entities().live_count(), 2);
+        assert!(ui.resource::<Turn>().is_none());
+
+        // Registering on one world no longer changes the other
+        ui.register_persistent::<Turn>("Round");
+        assert!(main.registry().component("Round").is_none());
+        assert!(main.registry().component("Target").is_some());
+    }
+
+    #[test]
+    fn test_unknown_type_leaves_world_untouched() {
+        let mut world = registered_world();
+        let e = world.create_entity();
+        world.add_component(e, Health(1));
+        let snapshot = world.snapshot();
+
+        let mut other = World::new();
+        assert_eq!(
+            other.restore_snapshot(&snapshot),
+            Err(SnapshotError::UnknownType(String::from("Health")))
+        );
+        assert_eq!(other.entities().live_count(), 0);
+    }
+
+    #[test]
+    fn test_corrupt_bytes_are_rejected() {
+        let world = registered_world();
+        let bytes = world.snapshot().to_bytes();
+
+        assert_eq!(Snapshot::from_bytes(b"nope"), Err(SnapshotError::BadMagic));
+        assert_eq!(Snapshot::from_bytes(&bytes[..6]), Err(SnapshotError::UnexpectedEof));
+        // Truncated or altered payloads fail the checksum
+        assert_eq!(
+            Snapshot::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::ChecksumMismatch)
+        );
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 0x10;
+        assert_eq!(Snapshot::from_bytes(&flipped), Err(SnapshotError::ChecksumMismatch));
+        let mut versioned = bytes.clone();
+        versioned[4] = 9;
+        assert_eq!(
+            Snapshot::from_bytes(&versioned),
+            Err(SnapshotError::UnsupportedVersion(9))
+        );
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let mut world = registered_world();
+        for hp in 0..50 {
+            let e = world.create_entity();
+            world.add_component(e, Health(hp));
+            world.add_component(e, Label(String::from("goblin")));
+        }
+        let snapshot = world.snapshot();
+        let plain = snapshot.to_bytes();
+        #[cfg(feature = "compression")]
+        {
+            let compressed = snapshot.to_compressed_bytes();
+            assert!(compressed.len() < plain.len());
+            assert_eq!(Snapshot::from_bytes(&compressed).as_ref(), Ok(&snapshot));
+
+            // A recorded size smaller than the payload stops the inflate there
+            let mut shrunk = compressed.clone();
+            shrunk[13..21].copy_from_slice(&16u64.to_le_bytes());
+            let checksum = crc32(&shrunk[13..]);
+            shrunk[9..13].copy_from_slice(&checksum.to_le_bytes());
+            assert_eq!(
+                Snapshot::from_bytes(&shrunk),
+                Err(SnapshotError::DecompressedTooLarge { limit: 16 })
+            );
+
+            // A recorded size over the reader's cap is refused up front
+            let size = (plain.len() - 13) as u64;
+            assert_eq!(
+                Snapshot::from_bytes_with_limit(&compressed, size - 1),
+                Err(SnapshotError::SizeOverLimit { size, limit: size - 1 })
+            );
+            let mut huge = compressed.clone();
+            huge[13..21].copy_from_slice(&u64::MAX.to_le_bytes());
+            let checksum = crc32(&huge[13..]);
+            huge[9..13].copy_from_slice(&checksum.to_le_bytes());
+            assert_eq!(
+                Snapshot::from_bytes(&huge),
+                Err(SnapshotError::SizeOverLimit { size: u64::MAX, limit: MAX_DECOMPRESSED_SIZE })
+            );
+        }
+        // The flag byte after the version marks a compressed payload
+        let mut flagged = plain.clone();
+        flagged[8] = COMPRESSED;
+        #[cfg(not(feature = "compression"))]
+        assert_eq!(Snapshot::from_bytes(&flagged), Err(SnapshotError::CompressionUnsupported));
+        #[cfg(feature = "compression")]
+        assert!(Snapshot::from_bytes(&flagged).is_err());
+        assert_eq!(Snapshot::from_bytes(&plain).as_ref(), Ok(&snapshot));
+    }
+
+    // Health as an older build saved it, before it tracked a maximum.
+    struct OldHealth(i32);
+
+    impl Persist for OldHealth {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(OldHealth(i32::read(input)?))
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Vitals {
+        hp: i32,
+        max: i32,
+    }
+
+    impl Persist for Vitals {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.hp.write(out);
+            self.max.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Vitals { hp: i32::read(input)?, max: i32::read(input)? })
+        }
+    }
+
+    fn add_max(bytes: &[u8]) -> Result<Vec<u8>, SnapshotError> {
+        let hp = i32::read(&mut &bytes[..])?;
+        let mut out = Vec::new();
+        (hp, hp).write(&mut out);
+        Ok(out)
+    }
+
+    #[test]
+    fn test_old_component_versions_are_migrated_on_load() {
+        let mut old = World::new();
+        old.register_persistent::<OldHealth>("Health");
+        let hero = old.create_entity();
+        old.add_component(hero, OldHealth(30));
+        let bytes = old.snapshot().to_bytes();
+
+        let mut new = World::new();
+        new.register_persistent::<Vitals>("Health");
+        new.register_migration::<Vitals>(1, 2, add_max);
+        new.restore_snapshot(&Snapshot::from_bytes(&bytes).unwrap()).unwrap();
+        assert_eq!(new.get_component::<Vitals>(hero), Some(&Vitals { hp: 30, max: 30 }));
+
+        // Saved at version 2, which an older build cannot read back
+        let newer = new.snapshot();
+        assert!(newer.component_versions.contains(&(String::from("Health"), 2)));
+        let mut downgraded = World::new();
+        downgraded.register_persistent::<OldHealth>("Health");
+        assert_eq!(
+            downgraded.restore_snapshot(&newer),
+            Err(SnapshotError::NoMigration { name: String::from("Health"), saved: 2, current: 1 })
+        );
+        assert_eq!(downgraded.entities().live_count(), 0);
+    }
+
+    #[test]
+    fn test_component_snapshot_restores_one_type() {
+        let mut world = World::new();
+        let knight = world.create_entity();
+        let rat = world.create_entity();
+        let ghost = world.create_entity();
+        world.add_component(knight, Health(30));
+        world.add_component(knight, Label("Knight".into()));
+        world.add_component(rat, Health(5));
+        world.add_component(ghost, Health(1));
+
+        let saved = world.snapshot_component::<Health>();
+        assert_eq!((saved.len(), saved.get(rat)), (3, Some(&Health(5))));
+
+        // A what-if round: damage, a death, a heal on a new entity
+        world.get_component_mut::<Health>(knight).unwrap().0 = 12;
+        world.get_component_mut::<Label>(knight).unwrap().0 = "Hurt".into();
+        world.remove_component::<Health>(rat);
+        world.destroy_entity(ghost);
+        let summon = world.create_entity();
+        world.add_component(summon, Health(8));
+
+        world.restore_component(&saved);
+        assert_eq!(world.get_component::<Health>(knight), Some(&Health(30)));
+        assert_eq!(world.get_component::<Health>(rat), Some(&Health(5)));
+        assert!(world.get_component::<Health>(summon).is_none());
+        assert!(!world.is_alive(ghost));
+        // Other types are left alone
+        assert_eq!(world.get_component::<Label>(knight), Some(&Label("Hurt".into())));
+    }
+}
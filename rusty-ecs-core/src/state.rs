@@ -0,0 +1,386 @@
+use crate::access::Access;
+use crate::entity::Entity;
+use crate::system::{System, SystemData, SystemExecutor};
+use crate::world::World;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::marker::PhantomData;
+
+pub trait State: Clone + PartialEq + Send + Sync + 'static {}
+impl<S: Clone + PartialEq + Send + Sync + 'static> State for S {}
+
+// Per-entity state of type `S`, for AI modes or game phases. Transitions are
+// requested with `request` and applied by `TransitionSystem<S>`, so every
+// system in a run sees the same state. Without any `allow` rules every
+// transition is legal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMachine<S: State> {
+    current: S,
+    previous: Option<S>,
+    pending: Option<S>,
+    allowed: Vec<(S, S)>,
+    runs_in_state: u32,
+}
+
+impl<S: State> StateMachine<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            previous: None,
+            pending: None,
+            allowed: Vec::new(),
+            runs_in_state: 0,
+        }
+    }
+
+    pub fn allow(mut self, from: S, to: S) -> Self {
+        self.allowed.push((from, to));
+        self
+    }
+
+    pub fn current(&self) -> &S {
+        &self.current
+    }
+
+    pub fn previous(&self) -> Option<&S> {
+        self.previous.as_ref()
+    }
+
+    pub fn pending(&self) -> Option<&S> {
+        self.pending.as_ref()
+    }
+
+    pub fn is(&self, state: &S) -> bool {
+        self.current == *state
+    }
+
+    // Executor runs since the last transition.
+    pub fn runs_in_state(&self) -> u32 {
+        self.runs_in_state
+    }
+
+    pub fn can_transition(&self, to: &S) -> bool {
+        self.allowed.is_empty()
+            || self
+                .allowed
+                .iter()
+                .any(|(from, allowed)| *from == self.current && allowed == to)
+    }
+
+    // Queues a transition for the next `TransitionSystem` run, replacing any
+    // earlier request. Illegal transitions are refused and return false.
+    pub fn request(&mut self, to: S) -> bool {
+        if !self.can_transition(&to) {
+            return false;
+        }
+        self.pending = Some(to);
+        true
+    }
+
+    fn apply(&mut self) -> Option<S> {
+        let Some(next) = self.pending.take() else {
+            self.runs_in_state += 1;
+            return None;
+        };
+        self.runs_in_state = 0;
+        self.previous = Some(core::mem::replace(&mut self.current, next));
+        self.previous.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, crate::Event)]
+pub struct StateChanged<S: State> {
+    pub entity: Entity,
+    pub from: S,
+    pub to: S,
+}
+
+// Applies requested transitions of every `StateMachine<S>` and pushes a
+// `StateChanged<S>` for each, in entity order.
+pub struct TransitionSystem<S: State>(PhantomData<fn() -> S>);
+
+impl<S: State> TransitionSystem<S> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: State> Default for TransitionSystem<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: State> System for TransitionSystem<S> {
+    fn access(&self) -> Access {
+        Access::new().write::<StateMachine<S>>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        let mut entities = data.query_entities::<StateMachine<S>>();
+        entities.sort();
+        for entity in entities {
+            let Some(machine) = data.get_mut::<StateMachine<S>>(entity) else {
+                continue;
+            };
+            if let Some(from) = machine.apply() {
+                let to = machine.current.clone();
+                data.push_event(StateChanged { entity, from, to });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Transition<S> {
+    Enter(S),
+    Exit(S),
+}
+
+// Resource holding the world's global states, such as menus, battles or a
+// pause screen. Only the top state updates; states below it are paused, not
+// exited. Transitions are recorded and run by the executor's `StateSet`s at
+// the start of its next run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameStateStack<S: State> {
+    stack: Vec<S>,
+    transitions: Vec<Transition<S>>,
+}
+
+impl<S: State> GameStateStack<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            stack: alloc::vec![initial.clone()],
+            transitions: alloc::vec![Transition::Enter(initial)],
+        }
+    }
+
+    pub fn current(&self) -> Option<&S> {
+        self.stack.last()
+    }
+
+    pub fn is(&self, state: &S) -> bool {
+        self.current() == Some(state)
+    }
+
+    pub fn contains(&self, state: &S) -> bool {
+        self.stack.contains(state)
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    // Enters `state` on top of the current one, which is paused.
+    pub fn push(&mut self, state: S) {
+        self.transitions.push(Transition::Enter(state.clone()));
+        self.stack.push(state);
+    }
+
+    // Exits the current state and resumes the one below it.
+    pub fn pop(&mut self) -> Option<S> {
+        let state = self.stack.pop()?;
+        self.transitions.push(Transition::Exit(state.clone()));
+        Some(state)
+    }
+
+    pub fn replace(&mut self, state: S) -> Option<S> {
+        let old = self.pop();
+        self.push(state);
+        old
+    }
+}
+
+// Systems tied to one game state: `on_enter` runs once when the state is
+// entered, `on_update` on every executor run while it is on top of the stack
+// and `on_exit` once when it is left.
+#[derive(Default)]
+pub struct StateSet {
+    pub on_enter: SystemExecutor,
+    pub on_update: SystemExecutor,
+    pub on_exit: SystemExecutor,
+}
+
+// Type-erased `StateSets<S>`, so one executor can drive several state types.
+pub(crate) trait StateSchedule {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn run(&mut self, world: &mut World);
+}
+
+pub(crate) struct StateSets<S: State> {
+    sets: Vec<(S, StateSet)>,
+}
+
+impl<S: State> StateSets<S> {
+    pub(crate) fn new() -> Self {
+        Self { sets: Vec::new() }
+    }
+
+    pub(crate) fn get_or_insert(&mut self, state: S) -> &mut StateSet {
+        let index = match self.sets.iter().position(|(s, _)| *s == state) {
+            Some(index) => index,
+            None => {
+                self.sets.push((state, StateSet::default()));
+                self.sets.len() - 1
+            }
+        };
+        &mut self.sets[index].1
+    }
+
+    fn get_mut(&mut self, state: &S) -> Option<&mut StateSet> {
+        self.sets.iter_mut().find(|(s, _)| s == state).map(|(_, set)| set)
+    }
+}
+
+impl<S: State> StateSchedule for StateSets<S> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    // Transitions made by `on_enter` or `on_exit` systems are run right away,
+    // so `on_update` always sees a settled stack.
+    fn run(&mut self, world: &mut World) {
+        loop {
+            let Some(stack) = world.resource_mut::<GameStateStack<S>>() else {
+                return;
+            };
+            let transitions = core::mem::take(&mut stack.transitions);
+            if transitions.is_empty() {
+                break;
+            }
+            for transition in transitions {
+                match transition {
+                    Transition::Exit(state) => {
+                        if let Some(set) = self.get_mut(&state) {
+                            set.on_exit.run_systems(world);
+                        }
+                    }
+                    Transition::Enter(state) => {
+                        if let Some(set) = self.get_mut(&state) {
+                            set.on_enter.run_systems(world);
+                        }
+                    }
+                }
+            }
+        }
+
+        let current = world
+            .resource::<GameStateStack<S>>()
+            .and_then(|stack| stack.current().cloned());
+        if let Some(set) = current.and_then(|state| self.get_mut(&state)) {
+            set.on_update.run_systems(world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SystemExecutor, World};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Ai {
+        Idle,
+        Attacking,
+        Dead,
+    }
+
+    fn guard() -> StateMachine<Ai> {
+        StateMachine::new(Ai::Idle)
+            .allow(Ai::Idle, Ai::Attacking)
+            .allow(Ai::Attacking, Ai::Idle)
+            .allow(Ai::Attacking, Ai::Dead)
+    }
+
+    #[test]
+    fn test_transitions_follow_rules() {
+        let mut machine = guard();
+        assert!(!machine.request(Ai::Dead));
+        assert!(machine.request(Ai::Attacking));
+        // Requests only take effect when the system applies them
+        assert!(machine.is(&Ai::Idle));
+        assert!(StateMachine::new(Ai::Idle).request(Ai::Dead));
+    }
+
+    #[test]
+    fn test_transition_system_emits_state_changed() {
+        let mut world = World::new();
+        let a = world.create_entity();
+        let b = world.create_entity();
+        world.add_component(a, guard());
+        world.add_component(b, guard());
+        let mut executor = SystemExecutor::new();
+        executor.add_system(TransitionSystem::<Ai>::new());
+
+        world.get_component_mut::<StateMachine<Ai>>(a).unwrap().request(Ai::Attacking);
+        executor.run(&mut world);
+        let changes = world.take_events::<StateChanged<Ai>>();
+        assert_eq!(changes, vec![StateChanged { entity: a, from: Ai::Idle, to: Ai::Attacking }]);
+
+        world.get_component_mut::<StateMachine<Ai>>(a).unwrap().request(Ai::Dead);
+        executor.run(&mut world);
+        executor.run(&mut world);
+        let machine = world.get_component::<StateMachine<Ai>>(a).unwrap();
+        assert_eq!((machine.current(), machine.previous()), (&Ai::Dead, Some(&Ai::Attacking)));
+        assert_eq!(machine.runs_in_state(), 1);
+        assert_eq!(world.get_component::<StateMachine<Ai>>(b).unwrap().runs_in_state(), 3);
+        assert_eq!(world.take_events::<StateChanged<Ai>>().len(), 1);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Screen {
+        Menu,
+        Battle,
+        Pause,
+    }
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    struct Note(&'static str);
+
+    impl crate::ExclusiveSystem for Note {
+        fn run(&mut self, world: &mut World) {
+            world.resource_mut::<Log>().unwrap().0.push(self.0);
+        }
+    }
+
+    #[test]
+    fn test_state_stack_runs_enter_update_exit() {
+        let mut world = World::new();
+        world.insert_resource(Log::default());
+        world.insert_resource(GameStateStack::new(Screen::Menu));
+        let mut executor = SystemExecutor::new();
+        executor.state_set(Screen::Menu).on_enter.add_exclusive_system(Note("menu"));
+        executor.state_set(Screen::Menu).on_exit.add_exclusive_system(Note("leave menu"));
+        executor.state_set(Screen::Battle).on_enter.add_exclusive_system(Note("battle"));
+        executor.state_set(Screen::Battle).on_update.add_exclusive_system(Note("fight"));
+        executor.state_set(Screen::Pause).on_exit.add_exclusive_system(Note("resume"));
+
+        executor.run(&mut world);
+        world.resource_mut::<GameStateStack<Screen>>().unwrap().replace(Screen::Battle);
+        executor.run(&mut world);
+        // Battle is paused, not exited, while Pause is on top
+        world.resource_mut::<GameStateStack<Screen>>().unwrap().push(Screen::Pause);
+        executor.run(&mut world);
+        let stack = world.resource_mut::<GameStateStack<Screen>>().unwrap();
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack.pop(), Some(Screen::Pause));
+        assert!(stack.is(&Screen::Battle));
+        executor.run(&mut world);
+
+        assert_eq!(
+            world.resource::<Log>().unwrap().0,
+            vec!["menu", "leave menu", "battle", "fight", "resume", "fight"]
+        );
+    }
+}
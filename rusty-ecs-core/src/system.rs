@@ -1,35 +1,881 @@
+use crate::access::Access;
+use crate::commands::Commands;
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::error::WorldError;
+use crate::event::{EntityEvent, Event, LoggedEvent, StaleEventCheck};
+use crate::frame::{FrameRecorder, Stopwatch};
+use crate::query::{QueryData, QueryFilter};
+use crate::recovery::{guard, Catch, SystemPanicked, Tainted};
+use crate::resource::Resource;
+use crate::state::{State, StateSchedule, StateSet, StateSets};
+use crate::time::{Time, Timer, TimerMode};
+use crate::trace::SystemSpan;
 use crate::world::World;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::fmt::Write;
+use core::time::Duration;
 
+// A data system declares the components it touches and only sees the world
+// through `SystemData`, which enforces that declaration. Structural changes go
+// through `Commands` and are applied at the executor's flush points.
 pub trait System {
+    fn access(&self) -> Access;
+    fn run(&mut self, data: SystemData<'_>);
+
+    fn name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+// Gets the whole world, for structural changes and anything that cannot be
+// declared up front. Pending commands are flushed before it runs.
+pub trait ExclusiveSystem {
     fn run(&mut self, world: &mut World);
+
+    fn name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+// A system that only reads the world. Consecutive read-only systems are run
+// in parallel by the executor (sequentially without `std`), so any output has
+// to go through the system's own state.
+pub trait ReadOnlySystem: Send {
+    fn run(&mut self, world: &World);
+
+    fn name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+pub struct SystemData<'w> {
+    world: &'w mut World,
+    commands: &'w mut Commands,
+    access: &'w Access,
+    system: &'static str,
+}
+
+impl<'w> SystemData<'w> {
+    pub fn new(
+        world: &'w mut World,
+        commands: &'w mut Commands,
+        access: &'w Access,
+        system: &'static str,
+    ) -> Self {
+        Self {
+            world,
+            commands,
+            access,
+            system,
+        }
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.check_read(TypeId::of::<T>(), core::any::type_name::<T>());
+        self.world.get_component(entity)
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.check_write(TypeId::of::<T>(), core::any::type_name::<T>());
+        self.world.get_component_mut(entity)
+    }
+
+    pub fn query_entities<T: Component>(&self) -> Vec<Entity> {
+        self.check_read(TypeId::of::<T>(), core::any::type_name::<T>());
+        self.world.query_entities::<T>()
+    }
+
+    pub fn query<D: QueryData, F: QueryFilter>(
+        &self,
+    ) -> impl Iterator<Item = (Entity, D::Item<'_>)> + '_ {
+        let mut reads = Vec::new();
+        D::reads(&mut reads);
+        for (type_id, name) in reads {
+            self.check_read(type_id, name);
+        }
+        self.world.query::<D, F>()
+    }
+
+    pub fn single<D: QueryData, F: QueryFilter>(
+        &self,
+    ) -> Result<(Entity, D::Item<'_>), WorldError> {
+        let mut reads = Vec::new();
+        D::reads(&mut reads);
+        for (type_id, name) in reads {
+            self.check_read(type_id, name);
+        }
+        self.world.single::<D, F>()
+    }
+
+    pub fn single_mut<T: Component, F: QueryFilter>(
+        &mut self,
+    ) -> Result<(Entity, &mut T), WorldError> {
+        self.check_write(TypeId::of::<T>(), core::any::type_name::<T>());
+        self.world.single_mut::<T, F>()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.world.is_alive(entity)
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.world.tick()
+    }
+
+    pub fn entities_created_since(&self, tick: u64) -> Vec<Entity> {
+        self.world.entities_created_since(tick)
+    }
+
+    // Resources are declared in `Access` the same way as components.
+    pub fn resource<R: Resource>(&self) -> Option<&R> {
+        self.check_read(TypeId::of::<R>(), core::any::type_name::<R>());
+        self.world.resource::<R>()
+    }
+
+    pub fn resource_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.check_write(TypeId::of::<R>(), core::any::type_name::<R>());
+        self.world.resource_mut::<R>()
+    }
+
+    pub fn push_event<E: Event>(&mut self, event: E) {
+        self.world.push_event(event);
+    }
+
+    pub fn take_events<E: Event>(&mut self) -> Vec<E> {
+        self.world.take_events::<E>()
+    }
+
+    pub fn read_events<E: Event>(&self) -> impl Iterator<Item = &E> + '_ {
+        self.world.read_events::<E>()
+    }
+
+    pub fn take_event_log(&mut self) -> Vec<LoggedEvent> {
+        self.world.take_event_log()
+    }
+
+    // `World::resolve_events`, with D checked against the declared reads.
+    pub fn resolve_events<E: EntityEvent, D: QueryData, R>(
+        &mut self,
+        f: impl FnMut(&E, D::Item<'_>) -> R,
+    ) -> Vec<(E, Option<R>)> {
+        let mut reads = Vec::new();
+        D::reads(&mut reads);
+        for (type_id, name) in reads {
+            self.check_read(type_id, name);
+        }
+        self.world.resolve_events::<E, D, R>(f)
+    }
+
+    pub fn commands(&mut self) -> &mut Commands {
+        self.commands
+    }
+
+    fn check_read(&self, type_id: TypeId, name: &'static str) {
+        if !self.access.can_read(type_id) {
+            panic!("{} reads {} without declaring it", self.system, name);
+        }
+    }
+
+    fn check_write(&self, type_id: TypeId, name: &'static str) {
+        if !self.access.can_write(type_id) {
+            panic!("{} writes {} without declaring it", self.system, name);
+        }
+    }
+}
+
+enum Scheduled {
+    Data(Box<dyn System>, Access),
+    Exclusive(Box<dyn ExclusiveSystem>),
+    ReadOnly(Box<dyn ReadOnlySystem>),
+    Barrier,
+}
+
+impl Scheduled {
+    fn name(&self) -> &'static str {
+        match self {
+            Scheduled::Data(system, _) => system.name(),
+            Scheduled::Exclusive(system) => system.name(),
+            Scheduled::ReadOnly(system) => system.name(),
+            Scheduled::Barrier => "barrier",
+        }
+    }
+
+    // Whether `self` has to finish before a later `other` starts. Read-only
+    // systems do not declare access, so they are ordered against any writer.
+    fn must_precede(&self, other: &Scheduled) -> bool {
+        match (self, other) {
+            (Scheduled::Exclusive(_) | Scheduled::Barrier, _)
+            | (_, Scheduled::Exclusive(_) | Scheduled::Barrier) => true,
+            (Scheduled::Data(_, a), Scheduled::Data(_, b)) => a.conflicts_with(b),
+            (Scheduled::Data(_, a), Scheduled::ReadOnly(_))
+            | (Scheduled::ReadOnly(_), Scheduled::Data(_, a)) => a.writes().next().is_some(),
+            (Scheduled::ReadOnly(_), Scheduled::ReadOnly(_)) => false,
+        }
+    }
+
+    // Whether `self` has to be skipped after `failed` panicked this run.
+    // Exclusive and read-only systems cannot say what they read, so any
+    // failed writer affects them.
+    fn affected_by(&self, failed: &Failure) -> bool {
+        match (self, failed) {
+            (Scheduled::Barrier, _) => false,
+            (_, Failure::Everything) => true,
+            (Scheduled::Data(_, access), failed) => failed.affects(access),
+            (_, Failure::Writes(writes)) => writes.writes().next().is_some(),
+        }
+    }
+}
+
+// What a system that panicked may have left half-written.
+enum Failure {
+    Writes(Access),
+    Everything,
+}
+
+impl Failure {
+    fn affects(&self, access: &Access) -> bool {
+        match self {
+            Failure::Writes(writes) => access.depends_on(writes),
+            Failure::Everything => true,
+        }
+    }
+}
+
+enum Cadence {
+    Frames { every: u32, count: u32 },
+    Seconds(Timer),
+}
+
+// Runs the wrapped system only when its cadence comes due: after every
+// `every` executor runs, or each time the interval elapses on the `Time`
+// resource. Without a `Time` resource, second-based systems never run.
+struct Interval<S> {
+    system: S,
+    cadence: Cadence,
+}
+
+impl<S: System> System for Interval<S> {
+    fn access(&self) -> Access {
+        let access = self.system.access();
+        match self.cadence {
+            Cadence::Frames { .. } => access,
+            Cadence::Seconds(_) => access.read::<Time>(),
+        }
+    }
+
+    fn run(&mut self, data: SystemData<'_>) {
+        let due = match &mut self.cadence {
+            Cadence::Frames { every, count } => {
+                *count += 1;
+                let due = *count >= *every;
+                if due {
+                    *count = 0;
+                }
+                due
+            }
+            Cadence::Seconds(timer) => {
+                let delta = data.resource::<Time>().map_or(Duration::ZERO, Time::delta);
+                timer.tick(delta).just_finished()
+            }
+        };
+        if due {
+            self.system.run(data);
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
 }
 
+// Runs systems in insertion order. Commands recorded by data systems are
+// flushed before each exclusive system, before each batch of read-only
+// systems, and at the end of the run. Double-buffered components are copied
+// before the first system runs, then the world's tick advances, delivering
+// scheduled events, followed by the systems of any game state transitions
+// and of the current game states.
 pub struct SystemExecutor {
-    systems: Vec<Box<dyn System>>,
+    systems: Vec<Scheduled>,
+    commands: Commands,
+    validate: bool,
+    stale_events: Option<StaleEventCheck>,
+    states: Vec<Box<dyn StateSchedule>>,
+    // Index of the next system `step` runs.
+    cursor: usize,
+    // Systems queued with `queue_once`, run after the others on the next run.
+    once: Vec<(Box<dyn System>, Access)>,
+    frame_reports: bool,
+    // Timings of the run in progress while frame reports are on.
+    frame: Option<FrameRecorder>,
+    catch_panics: bool,
+    quiet_panics: bool,
+    // Systems that panicked during the run in progress.
+    failures: Vec<Failure>,
 }
 
+// Runs an event type may sit unread before debug builds warn about it.
+pub const STALE_EVENT_FRAMES: u32 = 3;
+
 impl SystemExecutor {
     pub fn new() -> Self {
         Self {
             systems: Vec::new(),
+            commands: Commands::new(),
+            validate: false,
+            stale_events: cfg!(debug_assertions).then(|| StaleEventCheck::new(STALE_EVENT_FRAMES)),
+            states: Vec::new(),
+            cursor: 0,
+            once: Vec::new(),
+            frame_reports: false,
+            frame: None,
+            catch_panics: true,
+            quiet_panics: false,
+            failures: Vec::new(),
         }
     }
 
+    pub fn set_validation(&mut self, enabled: bool) {
+        self.validate = enabled;
+    }
+
+    // Push a `StaleEventWarning` when an event type goes unread for `frames`
+    // runs in a row. On by default in debug builds; `None` turns it off.
+    pub fn set_stale_event_check(&mut self, frames: Option<u32>) {
+        self.stale_events = frames.map(StaleEventCheck::new);
+    }
+
+    // Push a `FrameCompleted` with the run's timings and entity churn at the
+    // end of every run.
+    pub fn set_frame_reports(&mut self, enabled: bool) {
+        self.frame_reports = enabled;
+    }
+
+    // With the `catch-panics` feature a panicking system does not unwind out
+    // of `run`: see `SystemPanicked` and `Tainted`. On by default; turning it
+    // off lets panics through again, e.g. under a debugger.
+    #[cfg(feature = "catch-panics")]
+    pub fn set_catch_panics(&mut self, enabled: bool) {
+        self.catch_panics = enabled;
+    }
+
+    // A caught panic still goes through the panic hook, which by default
+    // prints its message and location to stderr. Quiet panics keep the hook
+    // from reporting the panics this executor catches, leaving them to
+    // `SystemPanicked`. The first quiet catch wraps the hook installed at
+    // that point, once per process; panics elsewhere still reach it. Off by
+    // default.
+    #[cfg(feature = "catch-panics")]
+    pub fn set_quiet_panics(&mut self, enabled: bool) {
+        self.quiet_panics = enabled;
+    }
+
+    fn catch(&self) -> Catch {
+        match (self.catch_panics, self.quiet_panics) {
+            (false, _) => Catch::Off,
+            (true, false) => Catch::On,
+            (true, true) => Catch::Quiet,
+        }
+    }
+
+    // Panics if the system declares access to a type more than once.
     pub fn add_system<S: System + 'static>(&mut self, system: S) {
-        self.systems.push(Box::new(system));
+        let access = checked_access(&system);
+        self.systems.push(Scheduled::Data(Box::new(system), access));
+    }
+
+    // Runs `system` a single time, after the registered systems of the next
+    // run (or of the step that completes it), then drops it. For setup tasks
+    // and scripted effects that should not stay in the schedule.
+    pub fn queue_once<S: System + 'static>(&mut self, system: S) {
+        let access = checked_access(&system);
+        self.once.push((Box::new(system), access));
+    }
+
+    // Runs `system` on every `frames`-th executor run, starting with run
+    // number `frames`.
+    pub fn add_system_every<S: System + 'static>(&mut self, frames: u32, system: S) {
+        self.add_system(Interval {
+            system,
+            cadence: Cadence::Frames { every: frames.max(1), count: 0 },
+        });
+    }
+
+    // Runs `system` once each time `secs` of `Time` have passed, at most once
+    // per executor run.
+    pub fn add_system_every_seconds<S: System + 'static>(&mut self, secs: f32, system: S) {
+        self.add_system(Interval {
+            system,
+            cadence: Cadence::Seconds(Timer::from_secs(secs, TimerMode::Repeating)),
+        });
+    }
+
+    pub fn add_exclusive_system<S: ExclusiveSystem + 'static>(&mut self, system: S) {
+        self.systems.push(Scheduled::Exclusive(Box::new(system)));
+    }
+
+    pub fn add_read_only_system<S: ReadOnlySystem + 'static>(&mut self, system: S) {
+        self.systems.push(Scheduled::ReadOnly(Box::new(system)));
+    }
+
+    // A sync point: commands queued by the systems before it are applied here,
+    // so entities they spawn or components they add are visible to the
+    // systems after it in the same run. Events need no flushing, they are
+    // readable as soon as they are pushed.
+    pub fn add_barrier(&mut self) {
+        self.systems.push(Scheduled::Barrier);
+    }
+
+    // Systems that only run around or during `state` of the world's
+    // `GameStateStack<S>` resource.
+    pub fn state_set<S: State>(&mut self, state: S) -> &mut StateSet {
+        let index = match self
+            .states
+            .iter()
+            .position(|sets| sets.as_any().is::<StateSets<S>>())
+        {
+            Some(index) => index,
+            None => {
+                self.states.push(Box::new(StateSets::<S>::new()));
+                self.states.len() - 1
+            }
+        };
+        self.states[index]
+            .as_any_mut()
+            .downcast_mut::<StateSets<S>>()
+            .unwrap()
+            .get_or_insert(state)
+    }
+
+    // Advances the world's `Time` resource (inserting it on first use) and
+    // then runs every system.
+    pub fn run_with_delta(&mut self, world: &mut World, delta: Duration) {
+        world.init_resource::<Time>().advance(delta);
+        self.run(world);
     }
 
     pub fn run(&mut self, world: &mut World) {
-        for system in &mut self.systems {
-            system.run(world);
+        self.cursor = 0;
+        self.begin_run(world);
+        self.run_systems(world);
+        self.end_run(world);
+    }
+
+    // Runs only the next system and reports it, for step debugging. The first
+    // step of a run also does what `run` does before its systems, state sets
+    // included, and the last one what it does after them, so stepping through
+    // every system amounts to one `run`. Queued commands are applied after
+    // each step so its effects show. None when there are no systems.
+    pub fn step(&mut self, world: &mut World) -> Option<StepReport> {
+        if self.systems.is_empty() {
+            return None;
+        }
+        if self.cursor == 0 {
+            self.begin_run(world);
+        }
+        let index = self.cursor;
+        let system = self.systems[index].name();
+        let before = world.change_counts();
+        self.cursor = self.run_at(index, world, false);
+        self.commands.apply(world);
+        world.run_watches();
+        let changes = world.config().change_tracking.then(|| {
+            world
+                .change_counts()
+                .into_iter()
+                .filter_map(|(name, count)| {
+                    let earlier = before.iter().find(|(n, _)| *n == name).map_or(0, |(_, c)| *c);
+                    (count > earlier).then_some((name, count - earlier))
+                })
+                .collect()
+        });
+        let run_complete = self.cursor == self.systems.len();
+        if run_complete {
+            self.run_queued(world);
+            self.end_run(world);
+            self.cursor = 0;
+        }
+        Some(StepReport { system, index, changes, run_complete })
+    }
+
+    fn begin_run(&mut self, world: &mut World) {
+        self.frame = self.frame_reports.then(|| FrameRecorder::start(world));
+        self.failures.clear();
+        world.store_previous();
+        world.advance_tick();
+        for states in &mut self.states {
+            states.run(world);
+        }
+        world.run_watches();
+    }
+
+    fn end_run(&mut self, world: &mut World) {
+        let report = self.frame.take().map(|frame| frame.finish(world));
+        world.clear_changes();
+        if world.config().frame_events {
+            world.end_event_frame();
+        }
+        if let Some(check) = &mut self.stale_events {
+            for warning in check.update(world.events()) {
+                world.push_event(warning);
+            }
+        }
+        // After any frame-event clearing, so it is readable next run
+        if let Some(report) = report {
+            world.push_event(report);
+        }
+    }
+
+    pub(crate) fn run_systems(&mut self, world: &mut World) {
+        let mut i = 0;
+        while i < self.systems.len() {
+            i = self.run_at(i, world, true);
+        }
+        self.run_queued(world);
+        self.commands.apply(world);
+        world.run_watches();
+    }
+
+    fn run_queued(&mut self, world: &mut World) {
+        let catch = self.catch();
+        for (mut system, access) in core::mem::take(&mut self.once) {
+            let name = system.name();
+            if self.failures.iter().any(|failed| failed.affects(&access)) {
+                world.init_resource::<Tainted>().skipped.push(name);
+                continue;
+            }
+            let recorded = self.commands.len();
+            let clock = self.frame.is_some().then(Stopwatch::start);
+            let span = SystemSpan::enter(world, name);
+            let result = guard(catch, || {
+                system.run(SystemData::new(world, &mut self.commands, &access, name));
+            });
+            span.exit(world);
+            record(&mut self.frame, name, clock);
+            if let Err(message) = result {
+                self.commands.truncate(recorded);
+                report_panic(world, name, message);
+                self.failures.push(Failure::Writes(access));
+            }
+            world.run_watches();
+            if self.validate {
+                report_violations(world, name);
+            }
+        }
+    }
+
+    // Runs the system at `i`, or with `batch` the whole run of read-only
+    // systems starting there, and returns the index after it.
+    fn run_at(&mut self, i: usize, world: &mut World, batch: bool) -> usize {
+        let scheduled = &self.systems[i];
+        if self.failures.iter().any(|failed| scheduled.affected_by(failed)) {
+            world.init_resource::<Tainted>().skipped.push(scheduled.name());
+            return i + 1;
+        }
+        let catch = self.catch();
+        match &mut self.systems[i] {
+            Scheduled::Data(system, access) => {
+                let name = system.name();
+                let recorded = self.commands.len();
+                let clock = self.frame.is_some().then(Stopwatch::start);
+                let span = SystemSpan::enter(world, name);
+                let result = guard(catch, || {
+                    system.run(SystemData::new(world, &mut self.commands, access, name));
+                });
+                span.exit(world);
+                record(&mut self.frame, name, clock);
+                if let Err(message) = result {
+                    // Its half-recorded commands go too
+                    self.commands.truncate(recorded);
+                    report_panic(world, name, message);
+                    self.failures.push(Failure::Writes(access.clone()));
+                }
+                world.run_watches();
+                if self.validate {
+                    report_violations(world, name);
+                }
+                i + 1
+            }
+            Scheduled::Exclusive(system) => {
+                self.commands.apply(world);
+                let clock = self.frame.is_some().then(Stopwatch::start);
+                let span = SystemSpan::enter(world, system.name());
+                let result = guard(catch, || system.run(world));
+                span.exit(world);
+                record(&mut self.frame, system.name(), clock);
+                if let Err(message) = result {
+                    report_panic(world, system.name(), message);
+                    self.failures.push(Failure::Everything);
+                }
+                world.run_watches();
+                if self.validate {
+                    report_violations(world, system.name());
+                }
+                i + 1
+            }
+            Scheduled::ReadOnly(_) => {
+                self.commands.apply(world);
+                let end = if batch {
+                    self.systems[i..]
+                        .iter()
+                        .position(|s| !matches!(s, Scheduled::ReadOnly(_)))
+                        .map_or(self.systems.len(), |offset| i + offset)
+                } else {
+                    i + 1
+                };
+                // Read-only systems cannot break invariants, so no check
+                let timed = self.frame.is_some();
+                let ran = run_read_only(&mut self.systems[i..end], world, timed, catch);
+                for run in ran {
+                    if let (Some(frame), Some(took)) = (&mut self.frame, run.took) {
+                        frame.record(run.system, took);
+                    }
+                    // Nothing depends on a read-only system, so none is skipped
+                    if let Some(message) = run.panic {
+                        report_panic(world, run.system, message);
+                    }
+                }
+                end
+            }
+            Scheduled::Barrier => {
+                self.commands.apply(world);
+                world.run_watches();
+                i + 1
+            }
+        }
+    }
+}
+
+// What one `SystemExecutor::step` ran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepReport {
+    pub system: &'static str,
+    pub index: usize,
+    // Entities newly marked changed per component type name; None when the
+    // world does not track changes.
+    pub changes: Option<Vec<(&'static str, usize)>>,
+    // Whether this step finished the run, so the next one starts a new run.
+    pub run_complete: bool,
+}
+
+impl core::fmt::Display for StepReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "#{} {}: ", self.index, self.system)?;
+        match &self.changes {
+            None => writeln!(f, "change tracking off"),
+            Some(changes) if changes.is_empty() => writeln!(f, "no changes"),
+            Some(changes) => {
+                let changes: Vec<String> = changes
+                    .iter()
+                    .map(|(name, count)| alloc::format!("{} x{}", name, count))
+                    .collect();
+                writeln!(f, "changed {}", changes.join(", "))
+            }
         }
     }
 }
 
+impl SystemExecutor {
+    // DOT graph of the systems, with an edge wherever insertion order matters
+    // because of conflicting access.
+    pub fn export_graphviz(&self) -> String {
+        self.graphviz(false)
+    }
+
+    // Like `export_graphviz`, plus a node per component type with read
+    // (dashed) and write (bold) edges from the data systems that declare them.
+    pub fn export_graphviz_with_components(&self) -> String {
+        self.graphviz(true)
+    }
+
+    fn graphviz(&self, components: bool) -> String {
+        let mut dot = String::from("digraph systems {\n    rankdir=LR;\n");
+        for (i, system) in self.systems.iter().enumerate() {
+            let shape = match system {
+                Scheduled::Data(..) => "box",
+                Scheduled::Exclusive(_) => "doubleoctagon",
+                Scheduled::ReadOnly(_) => "box, style=rounded",
+                Scheduled::Barrier => "hexagon",
+            };
+            let _ = writeln!(
+                dot,
+                "    s{} [label={}, shape={}];",
+                i,
+                quoted(system.name()),
+                shape
+            );
+        }
+
+        for (j, later) in self.systems.iter().enumerate() {
+            for (i, earlier) in self.systems[..j].iter().enumerate() {
+                if earlier.must_precede(later) {
+                    let _ = writeln!(dot, "    s{} -> s{};", i, j);
+                }
+            }
+        }
+
+        if components {
+            let mut types: Vec<&'static str> = Vec::new();
+            for (i, system) in self.systems.iter().enumerate() {
+                let Scheduled::Data(_, access) = system else {
+                    continue;
+                };
+                for (name, style) in access
+                    .reads()
+                    .map(|n| (n, "dashed"))
+                    .chain(access.writes().map(|n| (n, "bold")))
+                {
+                    let c = types.iter().position(|t| *t == name).unwrap_or_else(|| {
+                        types.push(name);
+                        types.len() - 1
+                    });
+                    let _ = writeln!(dot, "    s{} -> c{} [style={}];", i, c, style);
+                }
+            }
+            for (c, name) in types.iter().enumerate() {
+                let _ = writeln!(dot, "    c{} [label={}, shape=ellipse];", c, quoted(name));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn quoted(name: &str) -> String {
+    alloc::format!("\"{}\"", name.replace('"', "\\\""))
+}
+
+// Panics if `system` declares access to a type more than once.
+fn checked_access<S: System>(system: &S) -> Access {
+    let access = system.access();
+    if let Some(name) = access.duplicate() {
+        panic!(
+            "system {} declares access to {} more than once; write already implies read",
+            system.name(),
+            name
+        );
+    }
+    access
+}
+
+impl World {
+    // Runs a data system a single time without an executor, applying the
+    // commands it records right after.
+    pub fn run_system_once<S: System>(&mut self, mut system: S) {
+        let access = checked_access(&system);
+        let mut commands = Commands::new();
+        system.run(SystemData::new(self, &mut commands, &access, system.name()));
+        commands.apply(self);
+        self.run_watches();
+    }
+}
+
+fn report_panic(world: &mut World, system: &'static str, message: String) {
+    world.init_resource::<Tainted>().panicked.push(system);
+    world.push_event(SystemPanicked { system, message });
+}
+
+fn report_violations(world: &mut World, system: &'static str) {
+    for mut violation in world.check_invariants() {
+        violation.system = Some(system);
+        world.push_event(violation);
+    }
+}
+
+fn read_only_systems(
+    batch: &mut [Scheduled],
+) -> impl Iterator<Item = &mut Box<dyn ReadOnlySystem>> {
+    batch.iter_mut().filter_map(|scheduled| match scheduled {
+        Scheduled::ReadOnly(system) => Some(system),
+        _ => None,
+    })
+}
+
+fn record(frame: &mut Option<FrameRecorder>, system: &'static str, clock: Option<Stopwatch>) {
+    if let (Some(frame), Some(clock)) = (frame, clock) {
+        frame.record(system, clock.elapsed());
+    }
+}
+
+// How one read-only system of a batch went.
+struct ReadOnlyRun {
+    system: &'static str,
+    took: Option<Duration>,
+    panic: Option<String>,
+}
+
+// With `timed`, also measures how long the system took.
+fn run_read_only_system(
+    system: &mut Box<dyn ReadOnlySystem>,
+    world: &World,
+    timed: bool,
+    catch: Catch,
+) -> ReadOnlyRun {
+    let clock = timed.then(Stopwatch::start);
+    let span = SystemSpan::enter(world, system.name());
+    let result = guard(catch, || system.run(world));
+    span.exit(world);
+    ReadOnlyRun {
+        system: system.name(),
+        took: clock.map(|clock| clock.elapsed()),
+        panic: result.err(),
+    }
+}
+
+#[cfg(feature = "std")]
+fn run_read_only(
+    batch: &mut [Scheduled],
+    world: &World,
+    timed: bool,
+    catch: Catch,
+) -> Vec<ReadOnlyRun> {
+    if batch.len() == 1 {
+        return read_only_systems(batch)
+            .map(|system| run_read_only_system(system, world, timed, catch))
+            .collect();
+    }
+    std::thread::scope(|scope| {
+        let running: Vec<_> = read_only_systems(batch)
+            .map(|system| scope.spawn(move || run_read_only_system(system, world, timed, catch)))
+            .collect();
+        running
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|p| std::panic::resume_unwind(p)))
+            .collect()
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn run_read_only(
+    batch: &mut [Scheduled],
+    world: &World,
+    timed: bool,
+    catch: Catch,
+) -> Vec<ReadOnlyRun> {
+    read_only_systems(batch)
+        .map(|system| run_read_only_system(system, world, timed, catch))
+        .collect()
+}
+
+impl Default for SystemExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::frame::FrameCompleted;
     use crate::world::World;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Arc;
+    #[cfg(feature = "std")]
+    use std::sync::Barrier;
 
     struct CounterComponent(i32);
     struct FlagComponent(bool);
@@ -37,10 +883,13 @@ mod tests {
     struct CounterIncrementorSystem;
 
     impl System for CounterIncrementorSystem {
-        fn run(&mut self, world: &mut World) {
-            let entities = world.query_entities::<CounterComponent>();
-            for entity in entities {
-                if let Some(c) = world.get_component_mut::<CounterComponent>(entity) {
+        fn access(&self) -> Access {
+            Access::new().write::<CounterComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<CounterComponent>() {
+                if let Some(c) = data.get_mut::<CounterComponent>(entity) {
                     c.0 += 1;
                 }
             }
@@ -50,10 +899,13 @@ mod tests {
     struct CounterDoublerSystem;
 
     impl System for CounterDoublerSystem {
-        fn run(&mut self, world: &mut World) {
-            let entities = world.query_entities::<CounterComponent>();
-            for entity in entities {
-                if let Some(c) = world.get_component_mut::<CounterComponent>(entity) {
+        fn access(&self) -> Access {
+            Access::new().write::<CounterComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<CounterComponent>() {
+                if let Some(c) = data.get_mut::<CounterComponent>(entity) {
                     c.0 *= 2;
                 }
             }
@@ -62,7 +914,7 @@ mod tests {
 
     struct FlagToggleSystem;
 
-    impl System for FlagToggleSystem {
+    impl ExclusiveSystem for FlagToggleSystem {
         fn run(&mut self, world: &mut World) {
             let entities = world.query_entities::<FlagComponent>();
             for entity in entities {
@@ -73,6 +925,42 @@ mod tests {
         }
     }
 
+    // Flags every counter that reached the limit; the flag is only visible
+    // once the executor flushes commands.
+    struct CounterLimitSystem(i32);
+
+    impl System for CounterLimitSystem {
+        fn access(&self) -> Access {
+            Access::new().read::<CounterComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            let limit = self.0;
+            let reached: Vec<_> = data
+                .query::<&CounterComponent, ()>()
+                .filter(|(_, c)| c.0 >= limit)
+                .map(|(e, _)| e)
+                .collect();
+            for entity in reached {
+                data.commands().add_component(entity, FlagComponent(false));
+            }
+        }
+    }
+
+    struct UndeclaredWriter;
+
+    impl System for UndeclaredWriter {
+        fn access(&self) -> Access {
+            Access::new().read::<CounterComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<CounterComponent>() {
+                data.get_mut::<CounterComponent>(entity);
+            }
+        }
+    }
+
     #[test]
     fn test_multiple_entities() {
         let mut world = World::new();
@@ -146,12 +1034,475 @@ mod tests {
 
         let mut executor = SystemExecutor::new();
         executor.add_system(CounterIncrementorSystem);
-        executor.add_system(FlagToggleSystem);
+        executor.add_exclusive_system(FlagToggleSystem);
 
         executor.run(&mut world);
 
         assert_eq!(world.get_component::<CounterComponent>(e1).unwrap().0, 2);
-        assert_eq!(world.get_component::<FlagComponent>(e2).unwrap().0, false);
+        assert!(!world.get_component::<FlagComponent>(e2).unwrap().0);
+    }
+
+    struct CounterSum(Arc<AtomicI32>);
+
+    impl ReadOnlySystem for CounterSum {
+        fn run(&mut self, world: &World) {
+            let sum = world
+                .query::<&CounterComponent, ()>()
+                .map(|(_, c)| c.0)
+                .sum();
+            self.0.store(sum, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_read_only_systems_see_preceding_writes() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(1));
+
+        let before = Arc::default();
+        let after = Arc::default();
+        let mut executor = SystemExecutor::new();
+        executor.add_read_only_system(CounterSum(Arc::clone(&before)));
+        executor.add_system(CounterDoublerSystem);
+        executor.add_read_only_system(CounterSum(Arc::clone(&after)));
+        executor.run(&mut world);
+
+        assert_eq!(before.load(Ordering::SeqCst), 1);
+        assert_eq!(after.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(feature = "std")]
+    struct Rendezvous(Arc<Barrier>);
+
+    #[cfg(feature = "std")]
+    impl ReadOnlySystem for Rendezvous {
+        fn run(&mut self, _world: &World) {
+            // Only returns once every system in the batch is running at once
+            self.0.wait();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_consecutive_read_only_systems_run_in_parallel() {
+        let mut world = World::new();
+        let barrier = Arc::new(Barrier::new(3));
+
+        let mut executor = SystemExecutor::new();
+        for _ in 0..3 {
+            executor.add_read_only_system(Rendezvous(barrier.clone()));
+        }
+        executor.run(&mut world);
+    }
+
+    #[test]
+    fn test_commands_flush_before_exclusive_systems() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(5));
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterLimitSystem(5));
+        // Toggles the flag the limit system queued, so it must see it
+        executor.add_exclusive_system(FlagToggleSystem);
+        executor.run(&mut world);
+
+        assert!(world.get_component::<FlagComponent>(e).unwrap().0);
+    }
+
+    // Bumps every flagged counter.
+    struct FlaggedBonusSystem;
+
+    impl System for FlaggedBonusSystem {
+        fn access(&self) -> Access {
+            Access::new().read::<FlagComponent>().write::<CounterComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            let flagged: Vec<_> = data.query::<&FlagComponent, ()>().map(|(e, _)| e).collect();
+            for entity in flagged {
+                data.get_mut::<CounterComponent>(entity).unwrap().0 += 100;
+            }
+        }
+    }
+
+    #[test]
+    fn test_barrier_makes_commands_visible_within_a_run() {
+        let run = |barrier: bool| {
+            let mut world = World::new();
+            let e = world.create_entity();
+            world.add_component(e, CounterComponent(5));
+            let mut executor = SystemExecutor::new();
+            executor.add_system(CounterLimitSystem(5));
+            if barrier {
+                executor.add_barrier();
+            }
+            executor.add_system(FlaggedBonusSystem);
+            executor.run(&mut world);
+            world.get_component::<CounterComponent>(e).unwrap().0
+        };
+        assert_eq!(run(false), 5);
+        assert_eq!(run(true), 105);
+    }
+
+    #[test]
+    fn test_commands_flush_at_end_of_run() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(5));
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterLimitSystem(5));
+        executor.run(&mut world);
+
+        assert!(!world.get_component::<FlagComponent>(e).unwrap().0);
+    }
+
+    #[test]
+    fn test_run_system_once_applies_its_commands() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(5));
+        world.run_system_once(CounterLimitSystem(5));
+        assert!(world.get_component::<FlagComponent>(e).is_some());
+    }
+
+    #[test]
+    fn test_queued_system_runs_once_after_the_others() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(1));
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterIncrementorSystem);
+        executor.queue_once(CounterDoublerSystem);
+
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 4);
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 5);
+    }
+
+    // Replaces the counters incremented once with a fresh one.
+    struct Spawner;
+
+    impl ExclusiveSystem for Spawner {
+        fn run(&mut self, world: &mut World) {
+            for e in world.query_entities::<CounterComponent>() {
+                if world.get_component::<CounterComponent>(e).unwrap().0 == 1 {
+                    world.destroy_entity(e);
+                }
+            }
+            let e = world.create_entity();
+            world.add_component(e, CounterComponent(0));
+        }
+    }
+
+    #[test]
+    fn test_frame_reports_list_systems_and_churn() {
+        let mut world = World::with_config(crate::WorldConfig::new().change_tracking(true));
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(1));
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterIncrementorSystem);
+        executor.add_read_only_system(CounterSum(Arc::default()));
+        executor.add_read_only_system(CounterSum(Arc::default()));
+        executor.add_exclusive_system(Spawner);
+
+        executor.run(&mut world);
+        assert!(world.take_events::<FrameCompleted>().is_empty());
+
+        executor.set_frame_reports(true);
+        executor.run(&mut world);
+        executor.run(&mut world);
+        let reports = world.take_events::<FrameCompleted>();
+        assert_eq!(reports.len(), 2);
+        let names: Vec<_> = reports[0].systems.iter().map(|(name, _)| *name).collect();
+        let sum = core::any::type_name::<CounterSum>();
+        let order = [
+            core::any::type_name::<CounterIncrementorSystem>(),
+            sum,
+            sum,
+            core::any::type_name::<Spawner>(),
+        ];
+        assert_eq!(names, order);
+        assert_eq!((reports[0].frame, reports[0].spawned, reports[0].despawned), (2, 1, 1));
+        assert!(reports[0].elapsed >= reports[0].slowest().unwrap().1);
+        let counters = core::any::type_name::<CounterComponent>();
+        assert_eq!(reports[0].changes, Some(vec![(counters, 2)]));
+        assert_eq!((reports[1].frame, reports[1].spawned, reports[1].despawned), (3, 1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "without declaring it")]
+    fn test_undeclared_write_panics() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(0));
+
+        let mut executor = SystemExecutor::new();
+        #[cfg(feature = "catch-panics")]
+        executor.set_catch_panics(false);
+        executor.add_system(UndeclaredWriter);
+        executor.run(&mut world);
+    }
+
+    // Flags every counter, then panics on its first run.
+    #[cfg(feature = "catch-panics")]
+    struct FlakyCounterSystem(bool);
+
+    #[cfg(feature = "catch-panics")]
+    impl System for FlakyCounterSystem {
+        fn access(&self) -> Access {
+            Access::new().write::<CounterComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<CounterComponent>() {
+                data.commands().add_component(entity, FlagComponent(true));
+            }
+            if !std::mem::replace(&mut self.0, true) {
+                panic!("counter overflow");
+            }
+        }
+    }
+
+    #[cfg(feature = "catch-panics")]
+    struct FlagClearer;
+
+    #[cfg(feature = "catch-panics")]
+    impl System for FlagClearer {
+        fn access(&self) -> Access {
+            Access::new().write::<FlagComponent>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<FlagComponent>() {
+                data.get_mut::<FlagComponent>(entity).unwrap().0 = false;
+            }
+        }
+    }
+
+    #[cfg(feature = "catch-panics")]
+    #[test]
+    fn test_panicking_system_skips_its_dependents() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(1));
+        world.add_component(e, FlagComponent(true));
+        let sum = Arc::new(AtomicI32::new(-1));
+        let mut executor = SystemExecutor::new();
+        executor.add_system(FlakyCounterSystem(false));
+        executor.add_system(CounterDoublerSystem);
+        executor.add_system(FlagClearer);
+        executor.add_read_only_system(CounterSum(Arc::clone(&sum)));
+
+        executor.run(&mut world);
+        let panicked = world.take_events::<SystemPanicked>();
+        let flaky = core::any::type_name::<FlakyCounterSystem>();
+        let message = "counter overflow".into();
+        assert_eq!(panicked, [SystemPanicked { system: flaky, message }]);
+        let tainted = world.resource::<Tainted>().unwrap();
+        assert_eq!(tainted.panicked, [flaky]);
+        let skipped = [
+            core::any::type_name::<CounterDoublerSystem>(),
+            core::any::type_name::<CounterSum>(),
+        ];
+        assert_eq!(tainted.skipped, skipped);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 1);
+        assert_eq!(sum.load(Ordering::SeqCst), -1);
+        // FlagClearer ran, and the failed system's commands were dropped
+        assert!(!world.get_component::<FlagComponent>(e).unwrap().0);
+
+        // The next run starts clean, the taint stays until removed
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 2);
+        assert_eq!(sum.load(Ordering::SeqCst), 2);
+        assert!(world.take_events::<SystemPanicked>().is_empty());
+        assert!(world.remove_resource::<Tainted>().is_some());
+    }
+
+    #[cfg(feature = "catch-panics")]
+    struct Crash;
+
+    #[cfg(feature = "catch-panics")]
+    impl ExclusiveSystem for Crash {
+        fn run(&mut self, _world: &mut World) {
+            panic!("crashed with {}", 7);
+        }
+    }
+
+    #[cfg(feature = "catch-panics")]
+    #[test]
+    fn test_panicking_exclusive_system_skips_the_rest_of_the_run() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(1));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(Crash);
+        executor.add_system(FlagReader);
+        executor.queue_once(CounterIncrementorSystem);
+
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<SystemPanicked>()[0].message, "crashed with 7");
+        let skipped = &world.resource::<Tainted>().unwrap().skipped;
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 1);
+    }
+
+    #[cfg(feature = "catch-panics")]
+    #[test]
+    fn test_quiet_panics_are_still_caught_and_reported() {
+        let mut world = World::new();
+        let mut executor = SystemExecutor::new();
+        executor.set_quiet_panics(true);
+        executor.add_exclusive_system(Crash);
+
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<SystemPanicked>()[0].message, "crashed with 7");
+        assert_eq!(world.resource::<Tainted>().unwrap().panicked.len(), 1);
+    }
+
+    struct DoubleDeclared;
+
+    impl System for DoubleDeclared {
+        fn access(&self) -> Access {
+            Access::new().read::<CounterComponent>().write::<CounterComponent>()
+        }
+
+        fn run(&mut self, _data: SystemData<'_>) {}
+    }
+
+    #[test]
+    #[should_panic(expected = "more than once")]
+    fn test_duplicate_access_is_rejected_at_registration() {
+        SystemExecutor::new().add_system(DoubleDeclared);
+    }
+
+    struct FlagReader;
+
+    impl System for FlagReader {
+        fn access(&self) -> Access {
+            Access::new().read::<FlagComponent>()
+        }
+
+        fn run(&mut self, _data: SystemData<'_>) {}
+    }
+
+    #[test]
+    fn test_export_graphviz_edges() {
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterIncrementorSystem); // s0
+        executor.add_system(FlagReader); // s1
+        executor.add_system(CounterDoublerSystem); // s2
+        executor.add_exclusive_system(FlagToggleSystem); // s3
+
+        let dot = executor.export_graphviz();
+        assert!(dot.starts_with("digraph systems {"));
+        assert!(
+            dot.contains("s0 [label=\"") && dot.contains("CounterIncrementorSystem\", shape=box]")
+        );
+        assert!(dot.contains("s3 [label=") && dot.contains("shape=doubleoctagon"));
+        assert!(dot.contains("s0 -> s2;"));
+        assert!(!dot.contains("s0 -> s1;"));
+        assert!(!dot.contains("s1 -> s2;"));
+        assert!(dot.contains("s1 -> s3;"));
+        assert!(!dot.contains(" -> c"));
+    }
+
+    #[test]
+    fn test_export_graphviz_with_components() {
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterIncrementorSystem);
+        executor.add_system(CounterLimitSystem(1));
+
+        let dot = executor.export_graphviz_with_components();
+        assert!(dot.contains("s0 -> c0 [style=bold];"));
+        assert!(dot.contains("s1 -> c0 [style=dashed];"));
+        assert!(dot.contains("c0 [label=") && dot.contains("CounterComponent\", shape=ellipse]"));
+        assert_eq!(dot.matches("shape=ellipse").count(), 1);
+    }
+
+    struct Cooldown(crate::Timer);
+    struct Ready;
+
+    struct CooldownSystem;
+
+    impl System for CooldownSystem {
+        fn access(&self) -> Access {
+            Access::new().read::<Time>().write::<Cooldown>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            let delta = data.resource::<Time>().unwrap().delta();
+            for entity in data.query_entities::<Cooldown>() {
+                let cooldown = data.get_mut::<Cooldown>(entity).unwrap();
+                if cooldown.0.tick(delta).just_finished() {
+                    data.commands().add_component(entity, Ready);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_interval_systems() {
+        let mut world = World::new();
+        let frames = world.create_entity();
+        let seconds = world.create_entity();
+        world.add_component(frames, CounterComponent(0));
+        world.add_component(seconds, FlagComponent(false));
+
+        struct Toggle;
+
+        impl System for Toggle {
+            fn access(&self) -> Access {
+                Access::new().write::<FlagComponent>()
+            }
+
+            fn run(&mut self, mut data: SystemData<'_>) {
+                for entity in data.query_entities::<FlagComponent>() {
+                    let flag = data.get_mut::<FlagComponent>(entity).unwrap();
+                    flag.0 = !flag.0;
+                }
+            }
+        }
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system_every(3, CounterIncrementorSystem);
+        executor.add_system_every_seconds(0.5, Toggle);
+        for _ in 0..7 {
+            executor.run_with_delta(&mut world, Duration::from_millis(200));
+        }
+        // Runs 3 and 6; the 0.5s interval elapsed at 0.6s and 1.0s
+        assert_eq!(world.get_component::<CounterComponent>(frames).unwrap().0, 2);
+        assert!(!world.get_component::<FlagComponent>(seconds).unwrap().0);
+        executor.run_with_delta(&mut world, Duration::from_millis(200));
+        assert!(world.get_component::<FlagComponent>(seconds).unwrap().0);
+        assert!(executor.export_graphviz().contains("CounterIncrementorSystem"));
+    }
+
+    #[test]
+    fn test_run_with_delta_updates_time() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(
+            e,
+            Cooldown(crate::Timer::new(
+                Duration::from_millis(50),
+                crate::TimerMode::Once,
+            )),
+        );
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CooldownSystem);
+        executor.run_with_delta(&mut world, Duration::from_millis(30));
+        assert!(world.get_component::<Ready>(e).is_none());
+
+        executor.run_with_delta(&mut world, Duration::from_millis(30));
+        assert!(world.get_component::<Ready>(e).is_some());
+        let time = world.resource::<Time>().unwrap();
+        assert_eq!(time.frame(), 2);
+        assert_eq!(time.elapsed(), Duration::from_millis(60));
     }
 
     #[test]
@@ -168,4 +1519,163 @@ mod tests {
 
         assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 5);
     }
+
+    #[test]
+    fn test_stale_events_warn_once() {
+        #[derive(crate::Event)]
+        struct Ping;
+        #[derive(crate::Event)]
+        struct Unheard;
+
+        struct Chatter;
+
+        impl ExclusiveSystem for Chatter {
+            fn run(&mut self, world: &mut World) {
+                world.take_events::<Ping>();
+                world.push_event(Ping);
+                world.push_event(Unheard);
+            }
+        }
+
+        let mut world = World::new();
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(Chatter);
+        executor.set_stale_event_check(Some(2));
+
+        executor.run(&mut world);
+        assert!(world.events().get_queue::<crate::StaleEventWarning>().is_none());
+        for _ in 0..4 {
+            executor.run(&mut world);
+        }
+        let warnings = world.take_events::<crate::StaleEventWarning>();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].event.ends_with("Unheard"));
+        assert_eq!((warnings[0].pending, warnings[0].frames), (2, 2));
+
+        executor.set_stale_event_check(None);
+        for _ in 0..3 {
+            executor.run(&mut world);
+        }
+        assert!(world.take_events::<crate::StaleEventWarning>().is_empty());
+    }
+
+    #[test]
+    fn test_frame_events_are_shared_then_reset() {
+        use crate::config::WorldConfig;
+
+        #[derive(crate::Event)]
+        struct Ping(i32);
+
+        struct Emitter;
+
+        impl ExclusiveSystem for Emitter {
+            fn run(&mut self, world: &mut World) {
+                for n in 1..=3 {
+                    world.push_event(Ping(n));
+                }
+            }
+        }
+
+        // Adds up the pings without consuming them
+        struct PingTotal;
+
+        impl System for PingTotal {
+            fn access(&self) -> Access {
+                Access::new().write::<CounterComponent>()
+            }
+
+            fn run(&mut self, mut data: SystemData<'_>) {
+                let total: i32 = data.read_events::<Ping>().map(|p| p.0).sum();
+                for entity in data.query_entities::<CounterComponent>() {
+                    data.get_mut::<CounterComponent>(entity).unwrap().0 += total;
+                }
+            }
+        }
+
+        let mut world = World::with_config(WorldConfig::new().frame_events(true));
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(0));
+        let mut executor = SystemExecutor::new();
+        executor.set_stale_event_check(Some(1));
+        executor.add_exclusive_system(Emitter);
+        executor.add_system(PingTotal);
+        executor.add_system(PingTotal);
+
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 12);
+        assert_eq!(world.read_events::<Ping>().count(), 0);
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 24);
+        let metrics = world.events_metrics();
+        let pings = metrics.iter().find(|m| m.type_name.ends_with("Ping")).unwrap();
+        assert_eq!((pings.pushed, pings.dropped), (6, 6));
+        assert!(world.read_events::<crate::StaleEventWarning>().next().is_none());
+    }
+
+    #[test]
+    fn test_frame_events_keep_diagnostics_for_the_caller() {
+        use crate::config::WorldConfig;
+        use crate::DeadEntityWarning;
+
+        #[derive(crate::Event)]
+        struct Ping;
+
+        // Pings, then touches an entity that is already gone
+        struct Careless;
+
+        impl ExclusiveSystem for Careless {
+            fn run(&mut self, world: &mut World) {
+                world.push_event(Ping);
+                let e = world.create_entity();
+                world.destroy_entity(e);
+                world.add_component(e, CounterComponent(0));
+            }
+        }
+
+        let mut world = World::with_config(WorldConfig::new().frame_events(true));
+        let mut executor = SystemExecutor::new();
+        executor.set_frame_reports(true);
+        executor.add_exclusive_system(Careless);
+
+        executor.run(&mut world);
+        executor.run(&mut world);
+        assert_eq!(world.read_events::<Ping>().count(), 0);
+        assert_eq!(world.take_events::<DeadEntityWarning>().len(), 2);
+        assert_eq!(world.take_events::<crate::FrameCompleted>().len(), 2);
+    }
+
+    #[test]
+    fn test_step_runs_one_system_at_a_time() {
+        use crate::config::WorldConfig;
+
+        let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+        let e = world.create_entity();
+        world.add_component(e, CounterComponent(2));
+        world.add_component(e, FlagComponent(false));
+        world.clear_changes();
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(CounterDoublerSystem);
+        executor.add_exclusive_system(FlagToggleSystem);
+        assert!(SystemExecutor::new().step(&mut world).is_none());
+
+        let first = executor.step(&mut world).unwrap();
+        assert_eq!((first.index, first.run_complete), (0, false));
+        assert!(first.system.ends_with("CounterDoublerSystem"));
+        let counter = core::any::type_name::<CounterComponent>();
+        assert_eq!(first.changes, Some(vec![(counter, 1)]));
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 4);
+        assert!(!world.get_component::<FlagComponent>(e).unwrap().0);
+        assert_eq!(world.tick(), 1);
+
+        let second = executor.step(&mut world).unwrap();
+        assert!(second.run_complete);
+        assert!(second.to_string().contains("FlagComponent x1"));
+        assert!(world.change_counts().is_empty());
+
+        // The next step starts a new run
+        executor.step(&mut world);
+        assert_eq!(world.tick(), 2);
+        assert_eq!(world.get_component::<CounterComponent>(e).unwrap().0, 8);
+    }
 }
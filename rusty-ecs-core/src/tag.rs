@@ -0,0 +1,203 @@
+use crate::bitset::BitSet;
+use crate::component::{Component, ComponentStorage, StorageReport, TypedStorage};
+use crate::entity::Entity;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::Any;
+
+// Storage for zero-sized marker components. Membership is a bit per entity id
+// plus the tagged generation, instead of a hash map entry per entity. The
+// values themselves take no memory, a Vec of a ZST never allocates, but are
+// kept so taking a tag hands back a real instance and drops run as usual.
+pub struct TagStorage<T: Component> {
+    mask: BitSet,
+    generations: Vec<u32>,
+    values: Vec<T>,
+    pub(crate) clone_fn: Option<fn(&T) -> T>,
+}
+
+impl<T: Component> TagStorage<T> {
+    pub fn new() -> Self {
+        debug_assert!(core::mem::size_of::<T>() == 0, "TagStorage only holds zero-sized types");
+        Self {
+            mask: BitSet::new(),
+            generations: Vec::new(),
+            values: Vec::new(),
+            clone_fn: None,
+        }
+    }
+
+    pub fn cloneable() -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            clone_fn: Some(T::clone),
+            ..Self::new()
+        }
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.mask.iter().map(|id| Entity {
+            id,
+            generation: self.generations[id as usize],
+        })
+    }
+
+    fn tagged(&self, entity: Entity) -> bool {
+        self.mask.contains(entity.id) && self.generations[entity.id as usize] == entity.generation
+    }
+}
+
+impl<T: Component> Default for TagStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component> TypedStorage<T> for TagStorage<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
+        let slot = entity.id as usize;
+        if slot >= self.generations.len() {
+            self.generations.resize(slot + 1, 0);
+        }
+        // Same id: either an overwrite or a stale generation being replaced
+        if self.mask.contains(entity.id) {
+            self.values.pop();
+        }
+        self.values.push(component);
+        self.generations[slot] = entity.generation;
+        self.mask.insert(entity.id);
+    }
+
+    fn take(&mut self, entity: Entity) -> Option<T> {
+        if !self.tagged(entity) {
+            return None;
+        }
+        self.mask.remove(entity.id);
+        self.values.pop()
+    }
+
+    fn get(&self, entity: Entity) -> Option<&T> {
+        if self.tagged(entity) { self.values.last() } else { None }
+    }
+
+    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        if self.tagged(entity) { self.values.last_mut() } else { None }
+    }
+
+    // One value is kept per tagged entity, so they pair up one to one.
+    fn iter_mut(&mut self) -> Box<dyn Iterator<Item = (Entity, &mut T)> + '_> {
+        let generations = &self.generations;
+        let entities = self.mask.iter().map(|id| Entity {
+            id,
+            generation: generations[id as usize],
+        });
+        Box::new(entities.zip(self.values.iter_mut()))
+    }
+}
+
+impl<T: Component> ComponentStorage for TagStorage<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.take(entity);
+    }
+
+    fn mask(&self) -> &BitSet {
+        &self.mask
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.tagged(entity)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn entity_list(&self) -> Vec<Entity> {
+        self.entities().collect()
+    }
+
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<T>()
+    }
+
+    // Slots are the ids the generation table covers.
+    fn memory(&self) -> StorageReport {
+        let bytes = self.generations.capacity() * size_of::<u32>() + self.mask.heap_bytes();
+        StorageReport::new::<T>(self.values.len(), bytes, self.generations.len())
+    }
+
+    fn clone_component(&mut self, entity: Entity, target: Entity) -> bool {
+        let Some(clone_fn) = self.clone_fn else {
+            return false;
+        };
+        match self.get(entity).map(clone_fn) {
+            Some(component) => {
+                self.insert(target, component);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::component::{ComponentStorage, TypedStorage};
+    use crate::{ComponentManager, Entity, TagStorage, World};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Frozen;
+
+    #[test]
+    fn test_tags_track_generation() {
+        let mut tags = TagStorage::<Frozen>::new();
+        let e1 = Entity { id: 2, generation: 0 };
+        let e2 = Entity { id: 9, generation: 1 };
+        tags.insert(e1, Frozen);
+        tags.insert(e2, Frozen);
+        tags.insert(e2, Frozen);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags.entity_list(), vec![e1, e2]);
+
+        // A stale handle neither finds nor removes the live tag
+        let stale = Entity { id: 9, generation: 0 };
+        assert!(tags.get(stale).is_none());
+        assert_eq!(tags.take(stale), None);
+        assert!(tags.contains(e2));
+
+        assert_eq!(tags.take(e1), Some(Frozen));
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags.mask().iter().collect::<Vec<_>>(), vec![9]);
+    }
+
+    #[test]
+    fn test_zero_sized_types_get_tag_storage() {
+        let mut manager = ComponentManager::new();
+        manager.register::<Frozen>();
+        manager.register::<u32>();
+        assert!(manager.storage::<Frozen>().unwrap().as_any().is::<TagStorage<Frozen>>());
+        assert!(manager.get_storage::<u32>().is_some());
+
+        let mut world = World::new();
+        world.register_cloneable::<Frozen>();
+        let e = world.create_entity();
+        world.add_component(e, Frozen);
+        let copy = world.clone_entity(e).unwrap();
+        assert!(world.has::<Frozen>(copy));
+        assert_eq!(world.count::<Frozen>(), 2);
+
+        world.destroy_entity(e);
+        assert!(!world.has::<Frozen>(e));
+        assert_eq!(world.query_entities::<Frozen>(), vec![copy]);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::commands::Commands;
+use crate::event::Event;
+use crate::system::{ExclusiveSystem, System, SystemData};
+use crate::world::World;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+// Helpers for unit testing one system at a time: seed the world with the
+// events it reacts to, run it alone and check what it pushed.
+//
+//     world.with_events(vec![AttackEvent { attacker, target, damage: 5 }]);
+//     world.run_system(&mut DamageSystem);
+//     world.assert_event_emitted::<MsgEvent>(|m| matches!(m, MsgEvent::Hit { .. }));
+pub trait WorldTestExt {
+    // Queues `events` as if an earlier system had pushed them.
+    fn with_events<E: Event>(&mut self, events: Vec<E>) -> &mut Self;
+
+    // Pending events of type E, oldest first, without consuming them.
+    fn events_of<E: Event>(&self) -> Vec<&E>;
+
+    // Panics, listing the pending events, unless one matches `predicate`.
+    fn assert_event_emitted<E: Event + Debug>(&self, predicate: impl Fn(&E) -> bool);
+
+    fn assert_no_event<E: Event + Debug>(&self);
+
+    // Runs `system` once with its declared access enforced, then applies the
+    // commands it queued.
+    fn run_system<S: System>(&mut self, system: &mut S);
+
+    fn run_exclusive<S: ExclusiveSystem>(&mut self, system: &mut S);
+}
+
+impl WorldTestExt for World {
+    fn with_events<E: Event>(&mut self, events: Vec<E>) -> &mut Self {
+        for event in events {
+            self.push_event(event);
+        }
+        self
+    }
+
+    fn events_of<E: Event>(&self) -> Vec<&E> {
+        self.read_events::<E>().collect()
+    }
+
+    #[track_caller]
+    fn assert_event_emitted<E: Event + Debug>(&self, predicate: impl Fn(&E) -> bool) {
+        let events = self.events_of::<E>();
+        assert!(
+            events.iter().any(|event| predicate(event)),
+            "no matching {} among pending events {:?}",
+            core::any::type_name::<E>(),
+            events
+        );
+    }
+
+    #[track_caller]
+    fn assert_no_event<E: Event + Debug>(&self) {
+        let events = self.events_of::<E>();
+        assert!(
+            events.is_empty(),
+            "expected no {}, found {:?}",
+            core::any::type_name::<E>(),
+            events
+        );
+    }
+
+    fn run_system<S: System>(&mut self, system: &mut S) {
+        let access = system.access();
+        let mut commands = Commands::new();
+        system.run(SystemData::new(self, &mut commands, &access, system.name()));
+        commands.apply(self);
+    }
+
+    fn run_exclusive<S: ExclusiveSystem>(&mut self, system: &mut S) {
+        system.run(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::Access;
+
+    #[derive(Debug, Clone, Copy, PartialEq, crate::Event)]
+    struct Ping(u32);
+    #[derive(Debug, Clone, Copy, PartialEq, crate::Event)]
+    struct Pong(u32);
+
+    struct Score(u32);
+
+    // Answers every ping and spawns a score for it.
+    struct Responder;
+
+    impl System for Responder {
+        fn access(&self) -> Access {
+            Access::new()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for Ping(n) in data.take_events::<Ping>() {
+                data.push_event(Pong(n * 2));
+                data.commands().spawn(move |world, e| world.add_component(e, Score(n)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_system_with_injected_events() {
+        let mut world = World::new();
+        world.with_events(vec![Ping(1), Ping(4)]);
+        world.assert_no_event::<Pong>();
+
+        world.run_system(&mut Responder);
+        world.assert_no_event::<Ping>();
+        world.assert_event_emitted::<Pong>(|p| p.0 == 8);
+        assert_eq!(world.events_of::<Pong>(), vec![&Pong(2), &Pong(8)]);
+        // Commands are applied before the helper returns
+        assert_eq!(world.query::<&Score, ()>().map(|(_, s)| s.0).sum::<u32>(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "no matching")]
+    fn test_assert_event_emitted_fails_without_match() {
+        let mut world = World::new();
+        world.with_events(vec![Pong(3)]);
+        world.assert_event_emitted::<Pong>(|p| p.0 == 4);
+    }
+}
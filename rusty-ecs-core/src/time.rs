@@ -0,0 +1,246 @@
+use core::time::Duration;
+
+// Frame timing, kept up to date by `SystemExecutor::run_with_delta`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+        self.frame += 1;
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerMode {
+    Once,
+    Repeating,
+}
+
+// Counts down a fixed duration; usable as a component for cooldowns and buffs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timer {
+    duration: Duration,
+    elapsed: Duration,
+    mode: TimerMode,
+    finished: bool,
+    times_finished_this_tick: u32,
+}
+
+impl Timer {
+    pub fn new(duration: Duration, mode: TimerMode) -> Self {
+        Self {
+            duration,
+            elapsed: Duration::ZERO,
+            mode,
+            finished: false,
+            times_finished_this_tick: 0,
+        }
+    }
+
+    pub fn from_secs(secs: f32, mode: TimerMode) -> Self {
+        Self::new(Duration::from_secs_f32(secs), mode)
+    }
+
+    pub fn tick(&mut self, delta: Duration) -> &Self {
+        self.times_finished_this_tick = 0;
+        if self.mode == TimerMode::Once && self.finished {
+            return self;
+        }
+
+        self.elapsed += delta;
+        if self.elapsed < self.duration {
+            return self;
+        }
+
+        match self.mode {
+            TimerMode::Once => {
+                self.elapsed = self.duration;
+                self.times_finished_this_tick = 1;
+            }
+            TimerMode::Repeating if self.duration.is_zero() => {
+                self.elapsed = Duration::ZERO;
+                self.times_finished_this_tick = 1;
+            }
+            TimerMode::Repeating => {
+                let duration = self.duration.as_nanos();
+                let elapsed = self.elapsed.as_nanos();
+                self.times_finished_this_tick = (elapsed / duration) as u32;
+                self.elapsed = Duration::from_nanos((elapsed % duration) as u64);
+            }
+        }
+        self.finished = true;
+        self
+    }
+
+    // Repeating timers report finished only on ticks where they wrapped.
+    pub fn finished(&self) -> bool {
+        match self.mode {
+            TimerMode::Once => self.finished,
+            TimerMode::Repeating => self.times_finished_this_tick > 0,
+        }
+    }
+
+    pub fn just_finished(&self) -> bool {
+        self.times_finished_this_tick > 0
+    }
+
+    pub fn times_finished_this_tick(&self) -> u32 {
+        self.times_finished_this_tick
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed)
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.finished = false;
+        self.times_finished_this_tick = 0;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stopwatch {
+    elapsed: Duration,
+    paused: bool,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tick(&mut self, delta: Duration) -> &Self {
+        if !self.paused {
+            self.elapsed += delta;
+        }
+        self
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_time_advance() {
+        let mut time = Time::new();
+        time.advance(16 * MS);
+        time.advance(17 * MS);
+
+        assert_eq!(time.delta(), 17 * MS);
+        assert_eq!(time.elapsed(), 33 * MS);
+        assert_eq!(time.frame(), 2);
+    }
+
+    #[test]
+    fn test_once_timer() {
+        let mut timer = Timer::new(100 * MS, TimerMode::Once);
+        assert!(!timer.tick(60 * MS).finished());
+        assert!(timer.tick(60 * MS).just_finished());
+        assert_eq!(timer.remaining(), Duration::ZERO);
+
+        // Stays finished, but only "just" finished once
+        assert!(timer.tick(60 * MS).finished());
+        assert!(!timer.just_finished());
+
+        timer.reset();
+        assert!(!timer.finished());
+        assert_eq!(timer.fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_repeating_timer_wraps() {
+        let mut timer = Timer::new(100 * MS, TimerMode::Repeating);
+        timer.tick(250 * MS);
+        assert_eq!(timer.times_finished_this_tick(), 2);
+        assert_eq!(timer.elapsed(), 50 * MS);
+
+        timer.tick(10 * MS);
+        assert!(!timer.finished());
+        timer.tick(40 * MS);
+        assert!(timer.just_finished());
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_stopwatch_pause() {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.tick(10 * MS);
+        stopwatch.pause();
+        stopwatch.tick(10 * MS);
+        stopwatch.resume();
+        stopwatch.tick(5 * MS);
+
+        assert_eq!(stopwatch.elapsed(), 15 * MS);
+        stopwatch.reset();
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::world::World;
+
+// With the `tracing` feature every system runs inside a `system` span named
+// after it, recording the live entity count before and after the run, so a
+// subscriber can build flamegraphs per system. Without it this compiles to
+// nothing.
+#[cfg(feature = "tracing")]
+pub(crate) struct SystemSpan(tracing::span::EnteredSpan);
+
+#[cfg(feature = "tracing")]
+impl SystemSpan {
+    pub(crate) fn enter(world: &World, name: &'static str) -> Self {
+        let span = tracing::info_span!(
+            "system",
+            name,
+            entities = world.entities().live_count(),
+            entities_after = tracing::field::Empty,
+        );
+        Self(span.entered())
+    }
+
+    pub(crate) fn exit(self, world: &World) {
+        self.0.record("entities_after", world.entities().live_count());
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct SystemSpan;
+
+#[cfg(not(feature = "tracing"))]
+impl SystemSpan {
+    pub(crate) fn enter(_world: &World, _name: &'static str) -> Self {
+        Self
+    }
+
+    pub(crate) fn exit(self, _world: &World) {}
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{ExclusiveSystem, SystemExecutor, World};
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    // Collects span and event names.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut names = self.0.lock().unwrap();
+            names.push(span.metadata().name().to_string());
+            Id::from_u64(names.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            self.0.lock().unwrap().push(event.metadata().name().to_string());
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    struct Spawn;
+
+    impl ExclusiveSystem for Spawn {
+        fn run(&mut self, world: &mut World) {
+            let e = world.create_entity();
+            world.add_component(e, 1u8);
+        }
+    }
+
+    #[test]
+    fn test_systems_run_in_spans() {
+        let recorder = Recorder::default();
+        let mut world = World::new();
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(Spawn);
+        tracing::subscriber::with_default(recorder.clone(), || executor.run(&mut world));
+
+        let names = recorder.0.lock().unwrap();
+        assert_eq!(names[0], "system");
+        assert_eq!(names.len(), 3);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::entity::Entity;
+use crate::snapshot::SnapshotError;
+use crate::system::ExclusiveSystem;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+// Rates how good `action` would be for the acting entity, from 0 (never)
+// to 1 (ideal); values outside that range are clamped.
+pub type Consideration<A> = Box<dyn Fn(&World, Entity, &A) -> f32 + Send + Sync>;
+
+// Component for utility AI: how its entity weighs actions of type A. An
+// action's score is the product of every consideration, so any one of them
+// can veto it with a 0 and an entity without considerations rates all
+// actions 1.
+pub struct Considerations<A> {
+    considerations: Vec<(&'static str, Consideration<A>)>,
+}
+
+impl<A> Default for Considerations<A> {
+    fn default() -> Self {
+        Self { considerations: Vec::new() }
+    }
+}
+
+impl<A> Considerations<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(
+        mut self,
+        name: &'static str,
+        consideration: impl Fn(&World, Entity, &A) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        self.considerations.push((name, Box::new(consideration)));
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.considerations.iter().map(|(name, _)| *name)
+    }
+
+    pub fn score(&self, world: &World, actor: Entity, action: &A) -> f32 {
+        self.considerations
+            .iter()
+            .map(|(_, consider)| consider(world, actor, action).clamp(0.0, 1.0))
+            .product()
+    }
+
+    // The highest scoring candidate, the earliest on ties. None when there
+    // are none or all of them are vetoed.
+    pub fn best(&self, world: &World, actor: Entity, candidates: Vec<A>) -> Option<(A, f32)> {
+        best_of(candidates.into_iter().map(|action| {
+            let score = self.score(world, actor, &action);
+            (action, score)
+        }))
+    }
+}
+
+fn best_of<A>(scored: impl Iterator<Item = (A, f32)>) -> Option<(A, f32)> {
+    scored
+        .filter(|(_, score)| *score > 0.0)
+        .fold(None, |best: Option<(A, f32)>, (action, score)| match best {
+            Some((_, top)) if top >= score => best,
+            _ => Some((action, score)),
+        })
+}
+
+// What an entity decided to do, pushed by `AIDecisionSystem` for the game's
+// own systems to carry out.
+#[derive(Debug, Clone, PartialEq, crate::Event)]
+pub struct Intent<A> {
+    pub actor: Entity,
+    pub action: A,
+    pub score: f32,
+}
+
+// Pushed by `AIDecisionSystem` when `actor`'s candidates could not be
+// simulated because the world failed to round-trip through a snapshot. They
+// were rated on the current world instead.
+#[derive(Debug, Clone, PartialEq, crate::Event)]
+pub struct SimulationFailed {
+    pub actor: Entity,
+    pub error: SnapshotError,
+}
+
+// Picks an action for each deciding entity with `Considerations<A>` and
+// pushes it as an `Intent<A>`. By default every enabled entity with
+// considerations decides on every run; `actors` narrows that, e.g. to the
+// entities whose turn it is.
+//
+// With `simulated`, each candidate is first applied to a `World::simulate`
+// sandbox and the considerations rate the world it leads to rather than the
+// current one, at the cost of one snapshot per candidate. If the sandbox
+// cannot be built, the candidates are rated on the current world and a
+// `SimulationFailed` is pushed, rather than every candidate being dropped.
+pub struct AIDecisionSystem<A> {
+    candidates: fn(&World, Entity) -> Vec<A>,
+    actors: fn(&World) -> Vec<Entity>,
+    simulate: Option<fn(&mut World, Entity, &A)>,
+}
+
+impl<A: Send + Sync + 'static> AIDecisionSystem<A> {
+    pub fn new(candidates: fn(&World, Entity) -> Vec<A>) -> Self {
+        Self {
+            candidates,
+            actors: |world| {
+                let mut actors = world.query_entities::<Considerations<A>>();
+                actors.sort();
+                actors
+            },
+            simulate: None,
+        }
+    }
+
+    pub fn actors(mut self, actors: fn(&World) -> Vec<Entity>) -> Self {
+        self.actors = actors;
+        self
+    }
+
+    pub fn simulated(mut self, apply: fn(&mut World, Entity, &A)) -> Self {
+        self.simulate = Some(apply);
+        self
+    }
+
+    fn decide(
+        &self,
+        world: &World,
+        actor: Entity,
+        failed: &mut Vec<SimulationFailed>,
+    ) -> Option<Intent<A>> {
+        let considerations = world.get_component::<Considerations<A>>(actor)?;
+        let candidates = (self.candidates)(world, actor);
+        let (action, score) = match self.simulate {
+            None => considerations.best(world, actor, candidates)?,
+            Some(apply) => {
+                let mut error = None;
+                let best = best_of(candidates.into_iter().map(|action| {
+                    let outcome = world.simulate(|sandbox| {
+                        apply(sandbox, actor, &action);
+                        considerations.score(sandbox, actor, &action)
+                    });
+                    let score = match outcome {
+                        Ok(result) => result.outcome,
+                        Err(err) => {
+                            error = Some(err);
+                            considerations.score(world, actor, &action)
+                        }
+                    };
+                    (action, score)
+                }));
+                if let Some(error) = error {
+                    failed.push(SimulationFailed { actor, error });
+                }
+                best?
+            }
+        };
+        Some(Intent { actor, action, score })
+    }
+}
+
+impl<A: Send + Sync + 'static> ExclusiveSystem for AIDecisionSystem<A> {
+    fn run(&mut self, world: &mut World) {
+        let mut failed = Vec::new();
+        let intents: Vec<Intent<A>> = (self.actors)(world)
+            .into_iter()
+            .filter_map(|actor| self.decide(world, actor, &mut failed))
+            .collect();
+        for warning in failed {
+            world.push_event(warning);
+        }
+        for intent in intents {
+            world.push_event(intent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{Persist, SnapshotError};
+    use crate::SystemExecutor;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Health(i32);
+
+    impl Persist for Health {
+        fn write(&self, out: &mut Vec<u8>) {
+            self.0.write(out);
+        }
+
+        fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+            Ok(Health(i32::read(input)?))
+        }
+    }
+
+    struct Foe;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Move {
+        Hit(Entity, i32),
+        Wait,
+    }
+
+    fn moves(world: &World, _actor: Entity) -> Vec<Move> {
+        let mut moves: Vec<Move> = world
+            .query_entities::<Foe>()
+            .into_iter()
+            .flat_map(|foe| [Move::Hit(foe, 2), Move::Hit(foe, 5)])
+            .collect();
+        moves.push(Move::Wait);
+        moves
+    }
+
+    fn hurt(world: &World, action: &Move) -> f32 {
+        match action {
+            Move::Hit(foe, _) => {
+                let hp = world.get_component::<Health>(*foe).map_or(0, |h| h.0);
+                1.0 - hp as f32 / 10.0
+            }
+            Move::Wait => 0.1,
+        }
+    }
+
+    #[test]
+    fn test_best_action_and_vetoes() {
+        let mut world = World::new();
+        let actor = world.create_entity();
+        let healthy = world.create_entity();
+        let weak = world.create_entity();
+        world.add_component(healthy, Health(8));
+        world.add_component(weak, Health(3));
+        world.add_component(healthy, Foe);
+        world.add_component(weak, Foe);
+
+        // Go for the weakest foe, never with the big hit
+        let considerations = Considerations::new()
+            .with("weakest first", |world, _, action| hurt(world, action))
+            .with("no big hits", |_, _, action| match action {
+                Move::Hit(_, 5) => 0.0,
+                _ => 1.0,
+            });
+        assert_eq!(considerations.names().collect::<Vec<_>>(), ["weakest first", "no big hits"]);
+        let (best, score) = considerations.best(&world, actor, moves(&world, actor)).unwrap();
+        assert_eq!(best, Move::Hit(weak, 2));
+        assert!((score - 0.7).abs() < 1e-6);
+
+        let veto = Considerations::new().with("never", |_, _, _: &Move| 0.0);
+        assert!(veto.best(&world, actor, moves(&world, actor)).is_none());
+    }
+
+    #[test]
+    fn test_decision_system_scores_simulated_outcomes() {
+        let mut world = World::new();
+        world.register_persistent::<Health>("Health");
+        let actor = world.create_entity();
+        let foe = world.create_entity();
+        world.add_component(foe, Health(4));
+        world.add_component(foe, Foe);
+        // Only counts whether the foe would be down afterwards
+        let finisher = Considerations::new().with("finish", |world, _, action: &Move| {
+            match action {
+                Move::Hit(foe, _) if world.get_component::<Health>(*foe).unwrap().0 <= 0 => 1.0,
+                _ => 0.2,
+            }
+        });
+        world.add_component(actor, finisher);
+
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(AIDecisionSystem::new(moves).simulated(|world, _, action| {
+            if let Move::Hit(foe, damage) = action {
+                world.get_component_mut::<Health>(*foe).unwrap().0 -= damage;
+            }
+        }));
+        executor.run(&mut world);
+
+        let intents = world.take_events::<Intent<Move>>();
+        assert_eq!(intents, [Intent { actor, action: Move::Hit(foe, 5), score: 1.0 }]);
+        assert_eq!(world.get_component::<Health>(foe), Some(&Health(4)));
+
+        // Judged on the current world every move looks the same, so the
+        // first one wins
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(AIDecisionSystem::new(moves));
+        executor.add_exclusive_system(AIDecisionSystem::new(moves).actors(|_| Vec::new()));
+        executor.run(&mut world);
+        let intents = world.take_events::<Intent<Move>>();
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].action, Move::Hit(foe, 2));
+    }
+
+    #[test]
+    fn test_failed_simulation_falls_back_to_the_current_world() {
+        // Saves fine but never loads, so no sandbox can be built
+        struct Cursed;
+
+        impl Persist for Cursed {
+            fn write(&self, _out: &mut Vec<u8>) {}
+
+            fn read(_input: &mut &[u8]) -> Result<Self, SnapshotError> {
+                Err(SnapshotError::InvalidData("cursed".into()))
+            }
+        }
+
+        let mut world = World::new();
+        world.register_persistent::<Cursed>("Cursed");
+        let actor = world.create_entity();
+        let foe = world.create_entity();
+        world.add_component(foe, Health(4));
+        world.add_component(foe, Foe);
+        world.add_component(foe, Cursed);
+        world.add_component(actor, Considerations::new().with("hurt", |w, _, a| hurt(w, a)));
+
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(AIDecisionSystem::new(moves).simulated(|_, _, _| {}));
+        executor.run(&mut world);
+
+        let intents = world.take_events::<Intent<Move>>();
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].action, Move::Hit(foe, 2));
+        let failed = world.take_events::<SimulationFailed>();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].actor, actor);
+        assert_eq!(failed[0].error, SnapshotError::InvalidData("cursed".into()));
+    }
+}
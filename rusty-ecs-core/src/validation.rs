@@ -0,0 +1,161 @@
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::Event;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+pub type InvariantCheck = Box<dyn Fn(&World) -> Vec<Entity> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub struct InvariantViolation {
+    pub invariant: &'static str,
+    pub entity: Entity,
+    pub system: Option<&'static str>,
+}
+
+#[derive(Default)]
+pub struct Invariants {
+    checks: Vec<(&'static str, InvariantCheck)>,
+}
+
+impl Invariants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &'static str, check: InvariantCheck) {
+        self.checks.push((name, check));
+    }
+
+    pub fn len(&self) -> usize {
+        self.checks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    pub fn check(&self, world: &World) -> Vec<InvariantViolation> {
+        self.checks
+            .iter()
+            .flat_map(|(name, check)| {
+                check(world).into_iter().map(|entity| InvariantViolation {
+                    invariant: name,
+                    entity,
+                    system: None,
+                })
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn component_check<T: Component>(
+    predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+) -> InvariantCheck {
+    Box::new(move |world| {
+        world
+            .query::<&T, ()>()
+            .filter(|(_, component)| !predicate(component))
+            .map(|(entity, _)| entity)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::With;
+    use crate::system::{System, SystemData, SystemExecutor};
+    use crate::Access;
+    use crate::validation::InvariantViolation;
+    use crate::world::World;
+
+    struct Health {
+        hp: i32,
+        max: i32,
+    }
+    struct Player;
+    struct Enemy;
+
+    struct OverhealSystem;
+
+    impl System for OverhealSystem {
+        fn access(&self) -> Access {
+            Access::new().write::<Health>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            for entity in data.query_entities::<Health>() {
+                if let Some(h) = data.get_mut::<Health>(entity) {
+                    h.hp += 10;
+                }
+            }
+        }
+    }
+
+    fn health_world() -> (World, crate::Entity) {
+        let mut world = World::new();
+        world.add_invariant::<Health>("hp <= max", |h| h.hp <= h.max);
+        let e = world.create_entity();
+        world.add_component(e, Health { hp: 5, max: 10 });
+        (world, e)
+    }
+
+    #[test]
+    fn test_component_invariant() {
+        let (mut world, e) = health_world();
+        assert!(world.check_invariants().is_empty());
+
+        world.get_component_mut::<Health>(e).unwrap().hp = 11;
+        assert_eq!(
+            world.check_invariants(),
+            vec![InvariantViolation { invariant: "hp <= max", entity: e, system: None }]
+        );
+    }
+
+    #[test]
+    fn test_world_invariant() {
+        let mut world = World::new();
+        world.add_world_invariant("player is not an enemy", |world| {
+            world
+                .query::<&Player, With<Enemy>>()
+                .map(|(e, _)| e)
+                .collect()
+        });
+
+        let e = world.create_entity();
+        world.add_component(e, Player);
+        assert!(world.check_invariants().is_empty());
+
+        world.add_component(e, Enemy);
+        let violations = world.check_invariants();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entity, e);
+    }
+
+    #[test]
+    fn test_executor_reports_violating_system() {
+        let (mut world, e) = health_world();
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(OverhealSystem);
+        executor.set_validation(true);
+        executor.run(&mut world);
+
+        let violations = world.take_events::<InvariantViolation>();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entity, e);
+        assert!(violations[0].system.unwrap().ends_with("OverhealSystem"));
+    }
+
+    #[test]
+    fn test_executor_validation_disabled_by_default() {
+        let (mut world, _) = health_world();
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(OverhealSystem);
+        executor.run(&mut world);
+
+        assert!(world.take_events::<InvariantViolation>().is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::bitset::BitSet;
+use crate::pathfinding::{GridMap, Position};
+use crate::system::ExclusiveSystem;
+use crate::world::World;
+use alloc::vec::Vec;
+
+// Maps the first octant onto each of the eight: (xx, xy, yx, yy).
+const OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+struct Shadowcast<'a> {
+    grid: &'a GridMap,
+    seen: BitSet,
+    origin: Position,
+    range: i32,
+    octant: (i32, i32, i32, i32),
+}
+
+impl Shadowcast<'_> {
+    // Scans rows from `row` outwards between the slopes `start` and `end`,
+    // recursing past each wall into the part of the octant it leaves lit.
+    fn cast(&mut self, row: i32, mut start: f32, end: f32) {
+        if start < end {
+            return;
+        }
+        let (xx, xy, yx, yy) = self.octant;
+        let mut next_start = start;
+        for distance in row..=self.range {
+            let dy = -distance;
+            let mut blocked = false;
+            for dx in -distance..=0 {
+                let (x, y) = (dx * xx + dy * xy, dx * yx + dy * yy);
+                let cell = Position::new(self.origin.x + x, self.origin.y + y);
+                let left = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right {
+                    continue;
+                }
+                if end > left {
+                    break;
+                }
+                if dx * dx + dy * dy <= self.range * self.range
+                    && let Some(index) = self.grid.index(cell)
+                {
+                    self.seen.insert(index as u32);
+                }
+                let wall = !self.grid.is_walkable(cell);
+                if blocked {
+                    if wall {
+                        next_start = right;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if wall && distance < self.range {
+                    blocked = true;
+                    self.cast(distance + 1, start, left);
+                    next_start = right;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+impl GridMap {
+    // The cells visible from `origin` within a circle of `range` cells,
+    // found by recursive shadowcasting with walls blocking sight. The walls
+    // in view are included, so a room's edges show. In row order.
+    pub fn field_of_view(&self, origin: Position, range: u32) -> Vec<Position> {
+        self.seen_from(origin, range).iter().map(|index| self.position(index as usize)).collect()
+    }
+
+    fn seen_from(&self, origin: Position, range: u32) -> BitSet {
+        let mut cast = Shadowcast {
+            grid: self,
+            seen: BitSet::new(),
+            origin,
+            range: range as i32,
+            octant: OCTANTS[0],
+        };
+        let Some(index) = self.index(origin) else {
+            return cast.seen;
+        };
+        cast.seen.insert(index as u32);
+        for octant in OCTANTS {
+            cast.octant = octant;
+            cast.cast(1, 1.0, 0.0);
+        }
+        cast.seen
+    }
+}
+
+// Component: lets its entity see `range` cells around its `Position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vision {
+    pub range: u32,
+}
+
+// Marker `VisionSystem` keeps on entities standing in a cell some `Vision`
+// sees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visible;
+
+// Marker for entities that have been `Visible` at least once; it is never
+// taken away, e.g. for drawing stairs the player has found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explored;
+
+// Resource kept by `VisionSystem`: the cells in view as of its last run and
+// every cell seen since the map was last reset. It resets itself when the
+// `GridMap` changes size; remove it or call `reset` when one map replaces
+// another of the same size.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VisibilityMap {
+    width: u32,
+    height: u32,
+    visible: BitSet,
+    explored: BitSet,
+}
+
+impl VisibilityMap {
+    fn index(&self, cell: Position) -> Option<u32> {
+        let inside = (0..self.width as i32).contains(&cell.x)
+            && (0..self.height as i32).contains(&cell.y);
+        inside.then(|| cell.y as u32 * self.width + cell.x as u32)
+    }
+
+    pub fn is_visible(&self, cell: Position) -> bool {
+        self.index(cell).is_some_and(|index| self.visible.contains(index))
+    }
+
+    pub fn is_explored(&self, cell: Position) -> bool {
+        self.index(cell).is_some_and(|index| self.explored.contains(index))
+    }
+
+    pub fn reset(&mut self) {
+        self.visible.clear();
+        self.explored.clear();
+    }
+}
+
+// Works out what every entity with a `Vision` and a `Position` sees on the
+// `GridMap`, records it in the `VisibilityMap` and moves the `Visible` and
+// `Explored` markers onto the entities in view.
+pub struct VisionSystem;
+
+impl ExclusiveSystem for VisionSystem {
+    fn run(&mut self, world: &mut World) {
+        let Some(grid) = world.resource::<GridMap>() else {
+            return;
+        };
+        let mut visible = BitSet::new();
+        for viewer in world.query_entities::<Vision>() {
+            let range = world.get_component::<Vision>(viewer).unwrap().range;
+            if let Some(&at) = world.get_component::<Position>(viewer) {
+                for index in grid.seen_from(at, range).iter() {
+                    visible.insert(index);
+                }
+            }
+        }
+        let size = (grid.width(), grid.height());
+        let mut map = world.remove_resource::<VisibilityMap>().unwrap_or_default();
+        if (map.width, map.height) != size {
+            map = VisibilityMap { width: size.0, height: size.1, ..Default::default() };
+        }
+        for index in visible.iter() {
+            map.explored.insert(index);
+        }
+        map.visible = visible;
+
+        // Only touch markers that change, so change tracking stays quiet
+        for e in world.query_entities::<Position>() {
+            let seen = world.get_component::<Position>(e).is_some_and(|&at| map.is_visible(at));
+            let marked = world.get_component::<Visible>(e).is_some();
+            if seen && !marked {
+                world.add_component(e, Visible);
+                if world.get_component::<Explored>(e).is_none() {
+                    world.add_component(e, Explored);
+                }
+            } else if !seen && marked {
+                world.remove_component::<Visible>(e);
+            }
+        }
+        world.insert_resource(map);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemExecutor;
+
+    const MAP: &str = "
+        #########
+        #...#...#
+        #...#...#
+        #.......#
+        #########
+    ";
+
+    fn render(grid: &GridMap, seen: &[Position]) -> Vec<String> {
+        (0..grid.height() as i32)
+            .map(|y| {
+                (0..grid.width() as i32)
+                    .map(|x| if seen.contains(&Position::new(x, y)) { 'x' } else { ' ' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_walls_cast_shadows() {
+        let grid = GridMap::parse(MAP).unwrap();
+        let seen = grid.field_of_view(Position::new(1, 1), 10);
+        assert_eq!(render(&grid, &seen), [
+            "xxxxx    ",
+            "xxxxx    ",
+            "xxxxx    ",
+            "xxxxxx   ",
+            "xxxxxxx  ",
+        ]);
+        assert!(seen.windows(2).all(|w| (w[0].y, w[0].x) < (w[1].y, w[1].x)));
+
+        // Range limits it to a circle
+        let seen = grid.field_of_view(Position::new(1, 3), 2);
+        assert_eq!(render(&grid, &seen), [
+            "         ",
+            " x       ",
+            "xxx      ",
+            "xxxx     ",
+            "xxx      ",
+        ]);
+        assert!(grid.field_of_view(Position::new(-1, 0), 5).is_empty());
+    }
+
+    #[test]
+    fn test_vision_system_marks_entities() {
+        let mut world = World::new();
+        world.insert_resource(GridMap::parse(MAP).unwrap());
+        let hero = world.create_entity();
+        let stairs = world.create_entity();
+        let bat = world.create_entity();
+        world.add_component(hero, Position::new(1, 1));
+        world.add_component(hero, Vision { range: 10 });
+        world.add_component(stairs, Position::new(5, 3));
+        world.add_component(bat, Position::new(6, 1));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(VisionSystem);
+
+        executor.run(&mut world);
+        assert!(world.get_component::<Visible>(hero).is_some());
+        assert!(world.get_component::<Visible>(stairs).is_some());
+        assert!(world.get_component::<Visible>(bat).is_none());
+
+        // Round the corner the bat comes into view and the stairs stay
+        // explored once out of it
+        world.add_component(hero, Position::new(5, 1));
+        world.get_component_mut::<Vision>(hero).unwrap().range = 1;
+        executor.run(&mut world);
+        assert!(world.get_component::<Visible>(bat).is_some());
+        assert!(world.get_component::<Visible>(stairs).is_none());
+        assert!(world.get_component::<Explored>(stairs).is_some());
+        let map = world.resource::<VisibilityMap>().unwrap();
+        assert!(map.is_visible(Position::new(6, 1)) && !map.is_visible(Position::new(5, 3)));
+        assert!(map.is_explored(Position::new(5, 3)) && !map.is_explored(Position::new(7, 1)));
+    }
+}
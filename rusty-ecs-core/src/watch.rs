@@ -0,0 +1,131 @@
+use crate::collections::HashMap;
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::TypeId;
+
+type Condition = Box<dyn Fn(&World, Entity) -> bool + Send + Sync>;
+pub type WatchReaction = Box<dyn FnMut(&mut World, Entity) + Send + Sync>;
+
+// A reactive query over one component type: the reaction fires once when an
+// entity's component starts satisfying the condition, and can fire again
+// only after it stopped. Only entities whose component changed are checked.
+struct Watch {
+    type_id: TypeId,
+    condition: Condition,
+    reaction: WatchReaction,
+    // Entities currently satisfying the condition, id to generation.
+    matched: HashMap<u32, u32>,
+}
+
+#[derive(Default)]
+pub(crate) struct Watches(Vec<Watch>);
+
+impl Watches {
+    pub(crate) fn add<T: Component>(
+        &mut self,
+        condition: impl Fn(&T) -> bool + Send + Sync + 'static,
+        reaction: WatchReaction,
+    ) {
+        self.0.push(Watch {
+            type_id: TypeId::of::<T>(),
+            condition: Box::new(move |world, entity| {
+                world.get_component::<T>(entity).is_some_and(&condition)
+            }),
+            reaction,
+            matched: HashMap::default(),
+        });
+    }
+
+    pub(crate) fn watches(&self, type_id: TypeId) -> bool {
+        self.0.iter().any(|watch| watch.type_id == type_id)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl World {
+    // Checks every watch against the entities whose watched component
+    // changed and runs the reactions that became due. Executors call this
+    // at the start of a run and after each system.
+    pub fn run_watches(&mut self) {
+        if self.watches.is_empty() {
+            return;
+        }
+        // Moved out while reactions get `&mut World`
+        let mut watches = core::mem::take(&mut self.watches);
+        for watch in &mut watches.0 {
+            let Some(changed) = self.changed_ids(watch.type_id) else {
+                continue;
+            };
+            for id in changed {
+                let Some(entity) = self.entities().entity_at(id) else {
+                    continue;
+                };
+                let now = self.is_alive(entity) && (watch.condition)(self, entity);
+                let before = watch.matched.get(&id) == Some(&entity.generation);
+                if now && !before {
+                    watch.matched.insert(id, entity.generation);
+                    (watch.reaction)(self, entity);
+                } else if !now {
+                    watch.matched.remove(&id);
+                }
+            }
+        }
+        // Keep watches added by reactions
+        watches.0.append(&mut self.watches.0);
+        self.watches = watches;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExclusiveSystem, SystemExecutor, World};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Health(i32);
+    #[derive(Debug, Clone, Copy, PartialEq, crate::Event)]
+    struct Died(crate::Entity);
+
+    struct Hurt(i32);
+
+    impl ExclusiveSystem for Hurt {
+        fn run(&mut self, world: &mut World) {
+            for e in world.query_entities::<Health>() {
+                world.get_component_mut::<Health>(e).unwrap().0 -= self.0;
+            }
+        }
+    }
+
+    #[test]
+    fn test_watch_fires_once_per_transition() {
+        // Watched types are tracked even without change tracking
+        let mut world = World::new();
+        world.watch::<Health>(|h| h.0 <= 0, |world, e| world.push_event(Died(e)));
+        let a = world.create_entity();
+        let b = world.create_entity();
+        world.add_component(a, Health(5));
+        world.add_component(b, Health(12));
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(Hurt(5));
+
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<Died>(), vec![Died(a)]);
+        // Still at or below zero, so no second reaction
+        executor.run(&mut world);
+        assert!(world.take_events::<Died>().is_empty());
+
+        // Healing resets the watch; changes between runs are seen next run
+        world.get_component_mut::<Health>(a).unwrap().0 = 20;
+        executor.run(&mut world);
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<Died>(), vec![Died(b)]);
+        executor.run(&mut world);
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<Died>(), vec![Died(a)]);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::component::Component;
+use crate::entity::Entity;
+use crate::snapshot::{EntityMap, MapEntities};
+use crate::world::World;
+use alloc::vec::Vec;
+
+// A reference to an entity that may be gone by the time it is read, such as
+// an owner or a target kept in a component. The entity inside is only handed
+// out by `get`, which checks its generation, so once the entity dies the
+// handle reads as None for good, even after its id is reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WeakEntity(Entity);
+
+impl WeakEntity {
+    pub fn new(entity: Entity) -> Self {
+        WeakEntity(entity)
+    }
+
+    pub fn get(&self, world: &World) -> Option<Entity> {
+        world.is_alive(self.0).then_some(self.0)
+    }
+
+    pub fn is_alive(&self, world: &World) -> bool {
+        world.is_alive(self.0)
+    }
+
+    // The entity's T, if it is still alive and has one.
+    pub fn component<'w, T: Component>(&self, world: &'w World) -> Option<&'w T> {
+        world.get_component::<T>(self.get(world)?)
+    }
+}
+
+impl From<Entity> for WeakEntity {
+    fn from(entity: Entity) -> Self {
+        WeakEntity(entity)
+    }
+}
+
+// Remapped like a plain Entity, so a handle to an entity that was saved
+// follows it into the loaded world.
+impl MapEntities for WeakEntity {
+    fn map_entities(&mut self, map: &EntityMap) {
+        self.0.map_entities(map);
+    }
+}
+
+impl World {
+    // Removes every T whose `link` points at a dead entity, e.g. a `Target`
+    // once what it targets is despawned, and returns the entities it was
+    // removed from. OnRemove hooks run as usual.
+    pub fn remove_dangling<T: Component>(
+        &mut self,
+        link: impl Fn(&T) -> WeakEntity,
+    ) -> Vec<Entity> {
+        let mut dangling: Vec<Entity> = self
+            .components()
+            .entity_list::<T>()
+            .into_iter()
+            .filter(|&e| self.get_component::<T>(e).is_some_and(|c| !link(c).is_alive(self)))
+            .collect();
+        dangling.sort();
+        for &entity in &dangling {
+            self.remove_component::<T>(entity);
+        }
+        dangling
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Target(WeakEntity);
+
+    #[test]
+    fn test_weak_entity_dies_with_its_entity() {
+        let mut world = World::new();
+        let goblin = world.create_entity();
+        world.add_component(goblin, 7u32);
+        let weak = WeakEntity::new(goblin);
+        assert_eq!(weak.get(&world), Some(goblin));
+        assert_eq!(weak.component::<u32>(&world), Some(&7));
+
+        world.destroy_entity(goblin);
+        // The id is reused, but the handle still points at the old entity
+        let newcomer = world.create_entity();
+        world.add_component(newcomer, 9u32);
+        assert_eq!(newcomer.id, goblin.id);
+        assert_eq!(weak.get(&world), None);
+        assert_eq!(weak.component::<u32>(&world), None);
+    }
+
+    #[test]
+    fn test_remove_dangling_targets() {
+        let mut world = World::new();
+        let archer = world.create_entity();
+        let knight = world.create_entity();
+        let goblin = world.create_entity();
+        let ogre = world.create_entity();
+        world.add_component(archer, Target(goblin.into()));
+        world.add_component(knight, Target(ogre.into()));
+        world.destroy_entity(goblin);
+
+        assert_eq!(world.remove_dangling::<Target>(|t| t.0), vec![archer]);
+        assert!(world.get_component::<Target>(archer).is_none());
+        assert!(world.get_component::<Target>(knight).is_some());
+    }
+}
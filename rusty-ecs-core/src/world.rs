@@ -1,73 +1,1165 @@
+use crate::buffer::PreviousState;
 use crate::entity::{Entity, EntityManager};
 use crate::component::{Component, ComponentManager};
-use crate::event::{Event, EventManager};
+use crate::change::ChangeTracker;
+use crate::config::{DeadEntityPolicy, WorldConfig};
+use crate::diagnostics::WorldDiagnostics;
+use crate::dynamic::{
+    DynamicComponent, DynamicComponentId, DynamicComponents, DynamicError, DynamicEvent,
+    DynamicEventId, DynamicKey, Schema, Value,
+};
+use crate::error::WorldError;
+use crate::event::{
+    EntityEvent, EventManager, EventMetrics, EventSchedule, LoggedEvent, StaleEventWarning,
+};
+use crate::frame::FrameCompleted;
+use crate::group::GroupTypes;
+use crate::Event;
+use crate::hooks::{ComponentHooks, HookKind};
+use crate::meta::EntityMeta;
+use crate::observer::EntityObservers;
+use crate::bitset::BitSet;
+use crate::collections::HashMap;
+use crate::query::{ComponentSet, Disabled, QueryData, QueryFilter, Without};
+use crate::recovery::SystemPanicked;
+use crate::registry::{MigrateFn, TypeRegistry};
+use crate::resource::{Resource, Resources};
+use crate::rng::RngStream;
+use crate::snapshot::{
+    self, ComponentSnapshot, EntityMap, MapEntities, Persist, Snapshot, SnapshotError,
+};
+use crate::validation::{component_check, InvariantViolation, Invariants};
+use crate::watch::Watches;
+use alloc::format;
+use alloc::string::String;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::ops::RangeBounds;
 
 pub struct World {
     entities: EntityManager,
     components: ComponentManager,
     events: EventManager,
+    hooks: ComponentHooks,
+    invariants: Invariants,
+    config: WorldConfig,
+    changes: ChangeTracker,
+    resources: Resources,
+    registry: Arc<TypeRegistry>,
+    previous: PreviousState,
+    tick: u64,
+    scheduled: EventSchedule,
+    pub(crate) watches: Watches,
+    pub(crate) observers: EntityObservers,
+    meta: HashMap<u32, EntityMeta>,
+    dynamic: DynamicComponents,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct DeadEntityWarning {
+    pub entity: Entity,
+    pub component: &'static str,
 }
 
 impl World {
     pub fn new() -> Self {
+        Self::with_config(WorldConfig::default())
+    }
+
+    pub fn with_config(config: WorldConfig) -> Self {
+        let mut events = EventManager::new();
+        events.set_limit(config.event_limit);
+        if config.event_log {
+            events.enable_log();
+        }
         Self {
             entities: EntityManager::new(),
             components: ComponentManager::new(),
-            events: EventManager::new(),
+            events,
+            hooks: ComponentHooks::new(),
+            invariants: Invariants::new(),
+            config,
+            changes: ChangeTracker::new(),
+            resources: Resources::new(),
+            registry: Arc::new(TypeRegistry::new()),
+            previous: PreviousState::new(),
+            tick: 0,
+            scheduled: EventSchedule::default(),
+            watches: Watches::default(),
+            observers: EntityObservers::new(),
+            meta: HashMap::default(),
+            dynamic: DynamicComponents::default(),
         }
     }
 
+    // A world using `registry`, e.g. a UI or prediction world next to the
+    // main one, so snapshots and `copy_entities_to` agree on type names.
+    // Registering a type on one world afterwards only affects that world.
+    pub fn with_registry(config: WorldConfig, registry: Arc<TypeRegistry>) -> Self {
+        let mut world = Self::with_config(config);
+        world.registry = registry;
+        world
+    }
+
     pub fn create_entity(&mut self) -> Entity {
-        self.entities.create()
+        let entity = self.entities.create();
+        self.record_meta(entity);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?entity, "spawn");
+        entity
     }
 
     pub fn destroy_entity(&mut self, entity: Entity) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?entity, "despawn");
+        for type_id in self.components.component_types(entity) {
+            self.run_hooks(HookKind::OnRemove, type_id, entity);
+        }
         self.components.remove_all_components(entity);
+        if self.is_alive(entity) {
+            self.meta.remove(&entity.id);
+            self.dynamic.remove_all(entity);
+            self.observers.remove_all(entity);
+        }
         self.entities.destroy(entity);
     }
 
+    fn record_meta(&mut self, entity: Entity) {
+        if self.config.entity_metadata {
+            self.meta.insert(entity.id, EntityMeta::new(self.tick));
+        }
+    }
+
+    // Creation tick and debug label of `entity`. None when it is dead or was
+    // created without `WorldConfig::entity_metadata`.
+    pub fn entity_meta(&self, entity: Entity) -> Option<&EntityMeta> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.meta.get(&entity.id)
+    }
+
+    // Live entities created on or after `tick`, oldest first, e.g.
+    // `entities_created_since(world.tick())` for the ones spawned this run.
+    // Only entities created with `WorldConfig::entity_metadata` are found.
+    pub fn entities_created_since(&self, tick: u64) -> Vec<Entity> {
+        self.entities_created_in(tick..)
+    }
+
+    // Live entities whose creation tick falls in `ticks`, oldest first.
+    pub fn entities_created_in(&self, ticks: impl RangeBounds<u64>) -> Vec<Entity> {
+        let mut found: Vec<(u64, Entity)> = self
+            .meta
+            .iter()
+            .filter(|(_, meta)| ticks.contains(&meta.created_tick))
+            .filter_map(|(&id, meta)| Some((meta.created_tick, self.entities.entity_at(id)?)))
+            .collect();
+        found.sort_unstable();
+        found.into_iter().map(|(_, entity)| entity).collect()
+    }
+
+    // Ticks since `entity` was created, 0 during its first tick. None under
+    // the same conditions as `entity_meta`.
+    pub fn entity_age(&self, entity: Entity) -> Option<u64> {
+        self.entity_meta(entity).map(|meta| self.tick - meta.created_tick)
+    }
+
+    // Names `entity` for debugging, e.g. "wave 3 spawner". Does nothing
+    // without metadata for it.
+    pub fn set_debug_label(&mut self, entity: Entity, label: impl Into<String>) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        if let Some(meta) = self.meta.get_mut(&entity.id) {
+            meta.label = Some(label.into());
+        }
+    }
+
+    pub fn try_destroy_entity(&mut self, entity: Entity) -> Result<(), WorldError> {
+        self.ensure_alive(entity)?;
+        self.destroy_entity(entity);
+        Ok(())
+    }
+
+    pub fn entities(&self) -> &EntityManager {
+        &self.entities
+    }
+
+    pub fn components(&self) -> &ComponentManager {
+        &self.components
+    }
+
+    pub fn events(&self) -> &EventManager {
+        &self.events
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn register_cloneable<T: Component + Clone>(&mut self) {
+        self.components.register_cloneable::<T>();
+    }
+
+    pub fn register_pooled<T: Component>(&mut self) {
+        self.components.register_pooled::<T>();
+    }
+
+    // Drops every T at once without running OnRemove hooks; meant for
+    // per-frame pools like particles. Does nothing for non-pooled types.
+    pub fn clear_frame<T: Component>(&mut self) {
+        self.components.clear_frame::<T>();
+    }
+
+    // Packs the storages of a tuple of types, e.g. `(Position, Velocity)`, so
+    // `group_iter` walks entities with all of them as a dense zip. Grouped
+    // types move to pooled storage.
+    pub fn group<G: GroupTypes>(&mut self) {
+        self.components.group::<G>();
+    }
+
+    // Entities with both A and B, in group order. None unless a group owns
+    // both types.
+    pub fn group_iter<A: Component, B: Component>(
+        &self,
+    ) -> Option<impl Iterator<Item = (Entity, &A, &B)>> {
+        let len = self
+            .components
+            .group_len(&[TypeId::of::<A>(), TypeId::of::<B>()])?;
+        let (owners, a) = self.components.pooled::<A>()?.as_slices();
+        let (_, b) = self.components.pooled::<B>()?.as_slices();
+        Some(
+            owners[..len]
+                .iter()
+                .copied()
+                .zip(&a[..len])
+                .zip(&b[..len])
+                .map(|((entity, a), b)| (entity, a, b)),
+        )
+    }
+
+    pub fn clone_entity(&mut self, entity: Entity) -> Option<Entity> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        let clone = self.entities.create();
+        self.record_meta(clone);
+        self.components.clone_components(entity, clone);
+        self.dynamic.clone_all(entity, clone);
+        let mut map = EntityMap::default();
+        map.insert(entity, clone);
+        self.map_entities(core::iter::once(clone), &map);
+        for type_id in self.components.component_types(clone) {
+            self.run_hooks(HookKind::OnAdd, type_id, clone);
+        }
+        Some(clone)
+    }
+
+    pub fn config(&self) -> &WorldConfig {
+        &self.config
+    }
+
     pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
+        if !self.is_alive(entity) {
+            let component = core::any::type_name::<T>();
+            match self.config.dead_entity_policy {
+                DeadEntityPolicy::Ignore => {}
+                DeadEntityPolicy::Warn => self.push_event(DeadEntityWarning { entity, component }),
+                DeadEntityPolicy::Panic => {
+                    panic!("add_component::<{}> on dead entity {:?}", component, entity)
+                }
+            }
+            return;
+        }
+
+        let is_new = self.get_component::<T>(entity).is_none();
         self.components.add_component(entity, component);
+        self.mark_changed::<T>(entity);
+        if is_new {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(?entity, component = core::any::type_name::<T>(), "insert");
+            self.run_hooks(HookKind::OnAdd, TypeId::of::<T>(), entity);
+        }
+    }
+
+    pub fn try_add_component<T: Component>(
+        &mut self,
+        entity: Entity,
+        component: T,
+    ) -> Result<(), WorldError> {
+        self.ensure_alive(entity)?;
+        self.add_component(entity, component);
+        Ok(())
+    }
+
+    pub fn try_get_component<T: Component>(&self, entity: Entity) -> Result<&T, WorldError> {
+        self.ensure_alive(entity)?;
+        self.components
+            .storage::<T>()
+            .ok_or(WorldError::StorageMissing(core::any::type_name::<T>()))?
+            .get(entity)
+            .ok_or(WorldError::ComponentMissing {
+                entity,
+                component: core::any::type_name::<T>(),
+            })
+    }
+
+    pub fn try_get_component_mut<T: Component>(
+        &mut self,
+        entity: Entity,
+    ) -> Result<&mut T, WorldError> {
+        self.ensure_alive(entity)?;
+        self.components
+            .storage_mut::<T>()
+            .ok_or(WorldError::StorageMissing(core::any::type_name::<T>()))?
+            .get_mut(entity)
+            .ok_or(WorldError::ComponentMissing {
+                entity,
+                component: core::any::type_name::<T>(),
+            })
+    }
+
+    pub fn try_remove_component<T: Component>(&mut self, entity: Entity) -> Result<T, WorldError> {
+        self.try_get_component::<T>(entity)?;
+        Ok(self
+            .remove_component::<T>(entity)
+            .expect("component presence was just checked"))
+    }
+
+    fn ensure_alive(&self, entity: Entity) -> Result<(), WorldError> {
+        if self.is_alive(entity) {
+            Ok(())
+        } else {
+            Err(WorldError::EntityDead(entity))
+        }
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        if self.get_component::<T>(entity).is_some() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(?entity, component = core::any::type_name::<T>(), "remove");
+            self.run_hooks(HookKind::OnRemove, TypeId::of::<T>(), entity);
+        }
+        self.components.remove_component::<T>(entity)
+    }
+
+    pub fn on_add<T: Component>(
+        &mut self,
+        hook: impl FnMut(&mut World, Entity) + Send + Sync + 'static,
+    ) {
+        self.hooks
+            .register(HookKind::OnAdd, TypeId::of::<T>(), Box::new(hook));
+    }
+
+    pub fn on_remove<T: Component>(
+        &mut self,
+        hook: impl FnMut(&mut World, Entity) + Send + Sync + 'static,
+    ) {
+        self.hooks
+            .register(HookKind::OnRemove, TypeId::of::<T>(), Box::new(hook));
+    }
+
+    // Adding a `T` inserts a default `R` when the entity has none. A missing
+    // `R` is filled in silently, never reported; use `require` to have it
+    // flagged as an invariant violation instead.
+    pub fn register_required<T: Component, R: Component + Default>(&mut self) {
+        self.register_required_with::<T, R>(R::default);
+    }
+
+    pub fn register_required_with<T: Component, R: Component>(&mut self, make: fn() -> R) {
+        self.on_add::<T>(move |world, entity| {
+            if world.get_component::<R>(entity).is_none() {
+                world.add_component(entity, make());
+            }
+        });
+    }
+
+    // The strict form of `register_required`: nothing is inserted, and every
+    // entity with a `T` but no `R` shows up in `check_invariants` under `name`.
+    pub fn require<T: Component, R: Component>(&mut self, name: &'static str) {
+        self.add_world_invariant(name, |world| {
+            world.query::<&T, Without<R>>().map(|(e, _)| e).collect()
+        });
+    }
+
+    fn run_hooks(&mut self, kind: HookKind, type_id: TypeId, entity: Entity) {
+        let Some(mut hooks) = self.hooks.take(kind, type_id) else {
+            return;
+        };
+        for hook in hooks.iter_mut() {
+            hook(self, entity);
+        }
+        self.hooks.restore(kind, type_id, hooks);
     }
 
     pub fn get_component<T: Component>(&self, entity: Entity) -> Option<&T> {
-        self.components.get_storage::<T>()?.get(entity)
+        self.components.get::<T>(entity)
     }
 
     pub fn get_component_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
-        self.components.get_storage_mut::<T>()?.get_mut(entity)
+        self.mark_changed::<T>(entity);
+        self.components.get_mut::<T>(entity)
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.get_component::<T>(entity).is_some()
+    }
+
+    pub fn count<T: Component>(&self) -> usize {
+        self.components.storage_len::<T>()
+    }
+
+    pub fn any<T: Component>(&self) -> bool {
+        self.count::<T>() > 0
+    }
+
+    // Adds or removes a marker (usually a zero-sized tag) so that its presence
+    // matches `on`. Hooks only fire when that changes something.
+    pub fn toggle_marker<T: Component + Default>(&mut self, entity: Entity, on: bool) {
+        match (on, self.has::<T>(entity)) {
+            (true, false) => self.add_component(entity, T::default()),
+            (false, true) => {
+                self.remove_component::<T>(entity);
+            }
+            _ => {}
+        }
+    }
+
+    // Disabled entities keep their components but drop out of queries, e.g. a
+    // defeated enemy kept around for looting.
+    pub fn set_enabled(&mut self, entity: Entity, enabled: bool) {
+        self.toggle_marker::<Disabled>(entity, !enabled);
+    }
+
+    pub fn is_enabled(&self, entity: Entity) -> bool {
+        self.is_alive(entity) && !self.has::<Disabled>(entity)
+    }
+
+    fn skip_disabled(&self, candidates: &mut BitSet) {
+        if let Some(disabled) = self.components.mask::<Disabled>() {
+            candidates.difference_with(disabled);
+        }
+    }
+
+    fn disabled_id(&self, id: u32) -> bool {
+        self.components.mask::<Disabled>().is_some_and(|mask| mask.contains(id))
+    }
+
+    pub fn is_changed<T: Component>(&self, entity: Entity) -> bool {
+        self.changes.is_changed(TypeId::of::<T>(), entity.id)
+            && self.get_component::<T>(entity).is_some()
+    }
+
+    pub fn changed<T: Component>(&self) -> Vec<Entity> {
+        let Some(changed) = self.changes.changed(TypeId::of::<T>()) else {
+            return Vec::new();
+        };
+        changed
+            .iter()
+            .filter_map(|id| self.entities.entity_at(id))
+            .filter(|&entity| self.get_component::<T>(entity).is_some())
+            .collect()
+    }
+
+    // How many entities are marked changed per component type, by type name.
+    pub fn change_counts(&self) -> Vec<(&'static str, usize)> {
+        let mut counts: Vec<(&'static str, usize)> = self
+            .components
+            .storages()
+            .filter_map(|(type_id, storage)| {
+                let changed = self.changes.changed(type_id)?;
+                (!changed.is_empty()).then(|| (storage.type_name(), changed.len()))
+            })
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    pub fn clear_changes(&mut self) {
+        self.changes.clear();
+    }
+
+    // Keeps a copy of T from the start of each frame, readable through
+    // `previous`. The executor refreshes the copies before running systems.
+    pub fn register_double_buffered<T: Component + Clone>(&mut self) {
+        self.previous.register::<T>();
+    }
+
+    pub fn previous<T: Component>(&self, entity: Entity) -> Option<&T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.previous.get::<T>(entity)
+    }
+
+    pub fn store_previous(&mut self) {
+        self.previous.capture(&self.components);
+    }
+
+    pub(crate) fn changed_ids(&self, type_id: TypeId) -> Option<Vec<u32>> {
+        self.changes.changed(type_id).map(|set| set.iter().collect())
+    }
+
+    // Runs `reaction` once each time an entity's T starts satisfying
+    // `condition`, e.g. `world.watch::<Health>(|h| h.hp <= 0, ...)`. Checked
+    // by `run_watches` against changed entities only, so watched types are
+    // change tracked whether or not the config enables it.
+    pub fn watch<T: Component>(
+        &mut self,
+        condition: impl Fn(&T) -> bool + Send + Sync + 'static,
+        reaction: impl FnMut(&mut World, Entity) + Send + Sync + 'static,
+    ) {
+        self.watches.add::<T>(condition, Box::new(reaction));
+    }
+
+    fn mark_changed<T: Component>(&mut self, entity: Entity) {
+        if self.config.change_tracking || self.watches.watches(TypeId::of::<T>()) {
+            self.changes.mark(TypeId::of::<T>(), entity.id);
+        }
+    }
+
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> Option<R> {
+        self.resources.insert(resource)
+    }
+
+    pub fn init_resource<R: Resource + Default>(&mut self) -> &mut R {
+        if !self.resources.contains::<R>() {
+            self.resources.insert(R::default());
+        }
+        self.resources.get_mut::<R>().expect("resource was just inserted")
+    }
+
+    pub fn resource<R: Resource>(&self) -> Option<&R> {
+        self.resources.get::<R>()
+    }
+
+    pub fn resource_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut::<R>()
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self) -> Option<R> {
+        self.resources.remove::<R>()
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn diagnostics(&self) -> WorldDiagnostics {
+        WorldDiagnostics {
+            live_entities: self.entities.live_count(),
+            entity_capacity: self.entities.capacity(),
+            resources: self.resources.len(),
+            components: self.components.memory_report(),
+        }
+    }
+
+    pub fn register_persistent<T: Component + Persist>(&mut self, name: &'static str) {
+        self.registry_mut().register_component::<T>(name);
+    }
+
+    pub fn register_persistent_resource<R: Resource + Persist>(&mut self, name: &'static str) {
+        self.registry_mut().register_resource::<R>(name);
+    }
+
+    pub fn register_map_entities<T: Component + MapEntities>(&mut self) {
+        self.registry_mut().register_map_entities::<T>();
+    }
+
+    // See `TypeRegistry::register_migration`, e.g.
+    // `register_migration::<Health>(1, 2, add_max_hp)` after Health gained a
+    // field, so saves from before still load.
+    pub fn register_migration<T: Component>(&mut self, from: u32, to: u32, migrate: MigrateFn) {
+        self.registry_mut().register_migration::<T>(from, to, migrate);
+    }
+
+    // Copies the registry first if other worlds share it.
+    fn registry_mut(&mut self) -> &mut TypeRegistry {
+        Arc::make_mut(&mut self.registry)
+    }
+
+    // Rewrites the entity references of every registered `MapEntities`
+    // component on `entities`.
+    pub(crate) fn map_entities(&mut self, entities: impl Iterator<Item = Entity>, map: &EntityMap) {
+        let mappers = self.registry.entity_mappers();
+        if mappers.is_empty() {
+            return;
+        }
+        for entity in entities {
+            for mapper in &mappers {
+                mapper(self, entity, map);
+            }
+        }
+    }
+
+    pub fn registry(&self) -> &TypeRegistry {
+        &self.registry
+    }
+
+    // A handle to this world's registry for `with_registry`.
+    pub fn shared_registry(&self) -> Arc<TypeRegistry> {
+        Arc::clone(&self.registry)
+    }
+
+    // Copies the entities matching `F`, e.g. `With<Player>`, with all their
+    // registered components into `dest` as new entities, and returns how they
+    // were mapped. References to copied entities are remapped; resources are
+    // not copied.
+    pub fn copy_entities_to<F: QueryFilter>(
+        &self,
+        dest: &mut World,
+    ) -> Result<EntityMap, SnapshotError> {
+        let mut selected = BitSet::new();
+        for entity in self.entities.alive_entities() {
+            selected.insert(entity.id);
+        }
+        if !F::includes_disabled() {
+            self.skip_disabled(&mut selected);
+        }
+        F::apply(&self.components, &mut selected);
+
+        let mut entities: Vec<Entity> = selected
+            .iter()
+            .filter_map(|id| self.entities.entity_at(id))
+            .collect();
+        entities.sort();
+        let components = self
+            .registry
+            .components()
+            .map(|r| {
+                let mut values = (r.save)(self);
+                values.retain(|(entity, _)| selected.contains(entity.id));
+                (String::from(r.name), values)
+            })
+            .filter(|(_, values)| !values.is_empty())
+            .collect();
+        let selection = Snapshot {
+            entities,
+            generations: Vec::new(),
+            generation_floor: 0,
+            components,
+            resources: Vec::new(),
+            component_versions: self.registry.component_versions(),
+        };
+        dest.load_snapshot_remapped(&selection)
+    }
+
+    // Captures all live entities and every registered component and resource.
+    pub fn snapshot(&self) -> Snapshot {
+        let mut entities: Vec<Entity> = self.entities.alive_entities().collect();
+        entities.sort();
+        Snapshot {
+            entities,
+            generations: self.entities.generations(),
+            generation_floor: self.entities.generation_floor(),
+            components: self
+                .registry
+                .components()
+                .map(|r| (String::from(r.name), (r.save)(self)))
+                .collect(),
+            resources: self
+                .registry
+                .resources()
+                .filter_map(|r| Some((String::from(r.name), (r.save)(self)?)))
+                .collect(),
+            component_versions: self.registry.component_versions(),
+        }
+    }
+
+    // Restores the snapshot's entities with their saved ids and generations,
+    // so entities stored in component fields still point at the right ones,
+    // and ids are handed out afterwards as they were before the save. Only
+    // works on a world without live entities; the returned map is the
+    // identity.
+    pub fn restore_snapshot(&mut self, snapshot: &Snapshot) -> Result<EntityMap, SnapshotError> {
+        snapshot::restore(self, snapshot, false)
+    }
+
+    // Spawns the snapshot's entities as new entities next to the existing
+    // ones (ids may differ), for merging saved content into a running world,
+    // and returns how they were mapped.
+    pub fn load_snapshot_remapped(
+        &mut self,
+        snapshot: &Snapshot,
+    ) -> Result<EntityMap, SnapshotError> {
+        snapshot::restore(self, snapshot, true)
+    }
+
+    // Copies every T, disabled entities included, e.g. Health before a
+    // simulated what-if fight. Far cheaper than a full `snapshot` when only
+    // one type is about to be disturbed.
+    pub fn snapshot_component<T: Component + Clone>(&self) -> ComponentSnapshot<T> {
+        let mut values: Vec<(Entity, T)> = match self.components.storage::<T>() {
+            Some(storage) => storage
+                .entity_list()
+                .into_iter()
+                .filter_map(|entity| Some((entity, storage.get(entity)?.clone())))
+                .collect(),
+            None => Vec::new(),
+        };
+        values.sort_unstable_by_key(|(entity, _)| *entity);
+        ComponentSnapshot { tick: self.tick, values }
+    }
+
+    // Puts every T back as it was in `snapshot`: saved values overwrite the
+    // current ones, entities that gained a T since lose it and live ones that
+    // lost theirs get it back. Entities destroyed since stay destroyed. Hooks
+    // and change tracking see this like any other edit.
+    pub fn restore_component<T: Component + Clone>(&mut self, snapshot: &ComponentSnapshot<T>) {
+        let holders = self
+            .components
+            .storage::<T>()
+            .map_or_else(Vec::new, |storage| storage.entity_list());
+        for entity in holders {
+            if snapshot.get(entity).is_none() {
+                self.remove_component::<T>(entity);
+            }
+        }
+        for (entity, value) in &snapshot.values {
+            if self.is_alive(*entity) {
+                self.add_component(*entity, value.clone());
+            }
+        }
+    }
+
+    pub(crate) fn restore_entities(
+        &mut self,
+        alive: &[Entity],
+        generations: &[u32],
+        generation_floor: u32,
+    ) -> Result<(), String> {
+        self.entities.restore(alive, generations, generation_floor)
     }
 
     pub fn push_event<E: Event>(&mut self, event: E) {
         self.events.push(event);
     }
 
+    // Number of executor runs so far; the first run is tick 1.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    // Moves to the next tick and delivers the events scheduled for it. The
+    // executor calls this before running any system.
+    pub fn advance_tick(&mut self) {
+        self.tick += 1;
+        self.scheduled.deliver_due(self.tick, &mut self.events);
+    }
+
+    // Pushes `event` at the start of `tick`, or right away if that tick has
+    // already begun.
+    pub fn schedule_event_at<E: Event>(&mut self, tick: u64, event: E) {
+        if tick <= self.tick {
+            self.push_event(event);
+        } else {
+            self.scheduled.schedule(tick, event);
+        }
+    }
+
+    pub fn scheduled_events(&self) -> usize {
+        self.scheduled.len()
+    }
+
+    pub fn events_metrics(&self) -> Vec<EventMetrics> {
+        self.events.metrics()
+    }
+
     pub fn take_events<E: Event>(&mut self) -> Vec<E> {
-        let mut events = Vec::new();
-        if let Some(queue) = self.events.get_queue_mut::<E>() {
-            while let Some(event) = queue.pop() {
-                events.push(event);
-            }
+        self.drain_events::<E>().collect()
+    }
+
+    // Declares an event type at runtime, for scripting layers or network
+    // messages whose types the game was not compiled with. Its events are
+    // pushed and taken by name or by the returned id.
+    pub fn register_dynamic_event(
+        &mut self,
+        schema: Schema,
+    ) -> Result<DynamicEventId, DynamicError> {
+        self.events.dynamic_mut().register(schema)
+    }
+
+    // Fails, pushing nothing, unless `values` match the schema's fields in
+    // order and kind.
+    pub fn push_dynamic_event(
+        &mut self,
+        key: impl DynamicKey<DynamicEventId>,
+        values: Vec<Value>,
+    ) -> Result<(), DynamicError> {
+        self.events.dynamic_mut().push(key, values)
+    }
+
+    pub fn take_dynamic_events(
+        &mut self,
+        key: impl DynamicKey<DynamicEventId>,
+    ) -> Vec<DynamicEvent> {
+        self.events.dynamic_mut().take(key)
+    }
+
+    // Declares a component type at runtime, so mods and scripts can attach
+    // data to entities without recompiling. Its components are added and
+    // read by name or by the returned id.
+    pub fn register_dynamic_component(
+        &mut self,
+        schema: Schema,
+    ) -> Result<DynamicComponentId, DynamicError> {
+        self.dynamic.register(schema)
+    }
+
+    pub fn dynamic_components(&self) -> &DynamicComponents {
+        &self.dynamic
+    }
+
+    // Fails, adding nothing, unless `values` match the schema's fields in
+    // order and kind. Replaces a component of the same type.
+    pub fn add_dynamic_component(
+        &mut self,
+        entity: Entity,
+        key: impl DynamicKey<DynamicComponentId>,
+        values: Vec<Value>,
+    ) -> Result<(), DynamicError> {
+        if !self.is_alive(entity) {
+            return Err(DynamicError::EntityDead(entity));
         }
+        self.dynamic.insert(entity, key, values)
+    }
+
+    pub fn get_dynamic_component(
+        &self,
+        entity: Entity,
+        key: impl DynamicKey<DynamicComponentId>,
+    ) -> Option<&DynamicComponent> {
+        self.dynamic.get(entity, self.dynamic.id(key)?)
+    }
+
+    pub fn get_dynamic_component_mut(
+        &mut self,
+        entity: Entity,
+        key: impl DynamicKey<DynamicComponentId>,
+    ) -> Option<&mut DynamicComponent> {
+        let id = self.dynamic.id(key)?;
+        self.dynamic.get_mut(entity, id)
+    }
+
+    pub fn remove_dynamic_component(
+        &mut self,
+        entity: Entity,
+        key: impl DynamicKey<DynamicComponentId>,
+    ) -> Option<DynamicComponent> {
+        let id = self.dynamic.id(key)?;
+        self.dynamic.remove(entity, id)
+    }
+
+    // Enabled entities having every dynamic component in `names`, sorted.
+    // Empty if any name is not registered.
+    pub fn dynamic_entities(&self, names: &[&str]) -> Vec<Entity> {
+        let Some(ids) = self.dynamic_ids(names) else {
+            return Vec::new();
+        };
+        let Some((first, rest)) = ids.split_first() else {
+            return Vec::new();
+        };
+        let mut entities: Vec<Entity> = self
+            .dynamic
+            .entities(*first)
+            .filter(|&e| {
+                self.is_enabled(e) && rest.iter().all(|&id| self.dynamic.get(e, id).is_some())
+            })
+            .collect();
+        entities.sort();
+        entities
+    }
+
+    // A typed query narrowed to entities that also have the dynamic
+    // components in `names`, which come with each match in that order:
+    //
+    //     for (e, health, dynamic) in world.query_dynamic::<&Health, ()>(&["Poisoned"]) {
+    //         let ticks = dynamic[0].get("ticks").and_then(Value::as_int);
+    //     }
+    pub fn query_dynamic<D: QueryData, F: QueryFilter>(
+        &self,
+        names: &[&str],
+    ) -> Vec<(Entity, D::Item<'_>, Vec<&DynamicComponent>)> {
+        let Some(ids) = self.dynamic_ids(names) else {
+            return Vec::new();
+        };
+        self.query::<D, F>()
+            .filter_map(|(entity, item)| {
+                let dynamic: Option<Vec<&DynamicComponent>> =
+                    ids.iter().map(|&id| self.dynamic.get(entity, id)).collect();
+                Some((entity, item, dynamic?))
+            })
+            .collect()
+    }
+
+    fn dynamic_ids(&self, names: &[&str]) -> Option<Vec<DynamicComponentId>> {
+        names.iter().map(|&name| self.dynamic.id(name)).collect()
+    }
+
+    // Pending events of type E, oldest first, left in the queue.
+    pub fn read_events<E: Event>(&self) -> impl Iterator<Item = &E> + '_ {
+        self.events.get_queue::<E>().into_iter().flat_map(|queue| queue.iter())
+    }
+
+    // Drops every pending event, unread ones counting as dropped in the
+    // metrics. The queues keep their buffers, so the next frame's pushes
+    // reuse them instead of allocating. The executor calls this at the end
+    // of each run with `WorldConfig::frame_events`.
+    //
+    // The diagnostics the world and executor report about a run (panics,
+    // dead-entity and stale-event warnings, invariant violations and frame
+    // reports) are kept until taken, so the caller can still read them
+    // after `run` returns.
+    pub fn end_event_frame(&mut self) {
+        self.events.clear_except(&[
+            TypeId::of::<DeadEntityWarning>(),
+            TypeId::of::<InvariantViolation>(),
+            TypeId::of::<StaleEventWarning>(),
+            TypeId::of::<SystemPanicked>(),
+            TypeId::of::<FrameCompleted>(),
+        ]);
+    }
+
+    // Drains the pending events of every type, interleaved in the order they
+    // were pushed, e.g. an attack before the death it caused. Needs
+    // `WorldConfig::event_log`; empty without it.
+    pub fn take_event_log(&mut self) -> Vec<LoggedEvent> {
+        self.events.take_log()
+    }
+
+    // Drains every E and joins it with D read from the entity it is about:
+    // `f` sees the event and the fetched components and its result is
+    // returned next to the event, or None when the entity is dead or lacks part of D.
+    //
+    //     world.resolve_events::<AttackEvent, &Name, _>(|_, name| name.0)
+    pub fn resolve_events<E: EntityEvent, D: QueryData, R>(
+        &mut self,
+        mut f: impl FnMut(&E, D::Item<'_>) -> R,
+    ) -> Vec<(E, Option<R>)> {
+        let events = self.take_events::<E>();
         events
+            .into_iter()
+            .map(|event| {
+                let entity = event.entity();
+                let resolved = self
+                    .is_alive(entity)
+                    .then(|| D::fetch(&self.components, entity))
+                    .flatten()
+                    .map(|item| f(&event, item));
+                (event, resolved)
+            })
+            .collect()
+    }
+
+    pub fn drain_events<E: Event>(&mut self) -> impl Iterator<Item = E> + '_ {
+        self.events
+            .get_queue_mut::<E>()
+            .into_iter()
+            .flat_map(|queue| queue.drain())
+    }
+
+    // Every enabled entity's T, mutably, for heavy per-entity work. With the
+    // `rayon` feature this is a rayon parallel iterator that splits the
+    // components across threads (bring `rayon::iter::ParallelIterator` into
+    // scope); without it the same calls run sequentially. Every yielded
+    // entity counts as changed.
+    //
+    //     world.par_iter_mut::<Position>().for_each(|(_, p)| p.integrate(dt));
+    #[cfg(feature = "rayon")]
+    pub fn par_iter_mut<T: Component>(
+        &mut self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = (Entity, &mut T)> + '_ {
+        use rayon::iter::IntoParallelIterator;
+        self.enabled_mut::<T>().into_par_iter()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    pub fn par_iter_mut<T: Component>(
+        &mut self,
+    ) -> impl ExactSizeIterator<Item = (Entity, &mut T)> + '_ {
+        self.enabled_mut::<T>().into_iter()
+    }
+
+    fn enabled_mut<T: Component>(&mut self) -> Vec<(Entity, &mut T)> {
+        for entity in self.query_entities::<T>() {
+            self.mark_changed::<T>(entity);
+        }
+        let disabled = self.components.mask::<Disabled>().cloned().unwrap_or_default();
+        let deterministic = self.config.deterministic_iteration;
+        let Some(storage) = self.components.storage_mut::<T>() else {
+            return Vec::new();
+        };
+        let mut enabled: Vec<(Entity, &mut T)> =
+            storage.iter_mut().filter(|(e, _)| !disabled.contains(e.id)).collect();
+        if deterministic {
+            enabled.sort_by_key(|(e, _)| *e);
+        }
+        enabled
     }
 
     pub fn query_entities<T: Component>(&self) -> Vec<Entity> {
-        if let Some(storage) = self.components.get_storage::<T>() {
-            storage.entities().cloned().collect()
-        } else {
-            Vec::new()
+        let mut entities = self.components.entity_list::<T>();
+        entities.retain(|e| !self.disabled_id(e.id));
+        if self.config.deterministic_iteration {
+            entities.sort();
+        }
+        entities
+    }
+
+    // Entities with a T, ordered by `key` and then by entity, e.g.
+    // `query_sorted::<Initiative, _>(|i| i.value)`.
+    pub fn query_sorted<T: Component, K: Ord>(&self, key: impl Fn(&T) -> K) -> Vec<Entity> {
+        let Some(storage) = self.components.storage::<T>() else {
+            return Vec::new();
+        };
+        let mut keyed: Vec<(K, Entity)> = storage
+            .entity_list()
+            .into_iter()
+            .filter(|e| !self.disabled_id(e.id))
+            .filter_map(|e| Some((key(storage.get(e)?), e)))
+            .collect();
+        keyed.sort();
+        keyed.into_iter().map(|(_, e)| e).collect()
+    }
+
+    pub fn query<D: QueryData, F: QueryFilter>(
+        &self,
+    ) -> impl Iterator<Item = (Entity, D::Item<'_>)> + '_ {
+        let mut candidates = D::mask(&self.components).unwrap_or_default();
+        if !F::includes_disabled() {
+            self.skip_disabled(&mut candidates);
+        }
+        F::apply(&self.components, &mut candidates);
+
+        candidates.into_ids().filter_map(move |id| {
+                let entity = self.entities.entity_at(id)?;
+                Some((entity, D::fetch(&self.components, entity)?))
+            })
+    }
+
+    // Enabled entities having every component in `S`, counted from the storage masks
+    // without collecting them.
+    pub fn count_matching<S: ComponentSet>(&self) -> usize {
+        self.matching::<S>().map_or(0, |mask| mask.len())
+    }
+
+    fn matching<S: ComponentSet>(&self) -> Option<BitSet> {
+        let mut mask = S::mask(&self.components)?;
+        self.skip_disabled(&mut mask);
+        Some(mask)
+    }
+
+    // Up to `n` entities drawn uniformly from those matching `S`. Reservoir
+    // sampling keeps the allocation to the sample itself.
+    pub fn sample_matching<S: ComponentSet>(&self, n: usize, rng: &mut RngStream) -> Vec<Entity> {
+        let Some(mask) = self.matching::<S>().filter(|_| n > 0) else {
+            return Vec::new();
+        };
+        let mut sample: Vec<u32> = Vec::with_capacity(n);
+        for (seen, id) in mask.iter().enumerate() {
+            if sample.len() < n {
+                sample.push(id);
+            } else {
+                let slot = rng.index(seen + 1);
+                if slot < n {
+                    sample[slot] = id;
+                }
+            }
+        }
+        sample
+            .into_iter()
+            .filter_map(|id| self.entities.entity_at(id))
+            .collect()
+    }
+
+    // The only entity matching the query, for things like "the player" that
+    // must be unique.
+    pub fn single<D: QueryData, F: QueryFilter>(
+        &self,
+    ) -> Result<(Entity, D::Item<'_>), WorldError> {
+        let query = core::any::type_name::<(D, F)>();
+        let mut matches = self.query::<D, F>();
+        let first = matches.next().ok_or(WorldError::NoMatch { query })?;
+        let extra = matches.count();
+        if extra > 0 {
+            return Err(WorldError::MultipleMatches { query, count: extra + 1 });
         }
+        Ok(first)
+    }
+
+    pub fn single_mut<T: Component, F: QueryFilter>(
+        &mut self,
+    ) -> Result<(Entity, &mut T), WorldError> {
+        let (entity, _) = self.single::<&T, F>()?;
+        let component = self.get_component_mut::<T>(entity).expect("matched by the query");
+        Ok((entity, component))
+    }
+
+    pub fn add_invariant<T: Component>(
+        &mut self,
+        name: &'static str,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) {
+        self.invariants.add(name, component_check(predicate));
+    }
+
+    pub fn add_world_invariant(
+        &mut self,
+        name: &'static str,
+        check: impl Fn(&World) -> Vec<Entity> + Send + Sync + 'static,
+    ) {
+        self.invariants.add(name, Box::new(check));
+    }
+
+    pub fn check_invariants(&self) -> Vec<InvariantViolation> {
+        self.invariants.check(self)
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = self.entities.validate().err().unwrap_or_default();
+
+        for (_, storage) in self.components.storages() {
+            for id in storage.mask().iter() {
+                let alive = self
+                    .entities
+                    .entity_at(id)
+                    .is_some_and(|e| self.entities.is_alive(e));
+                if !alive {
+                    errors.push(format!(
+                        "component {} is stored for dead entity id {}",
+                        storage.type_name(),
+                        id
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::Index;
+    use std::collections::HashMap;
 
     struct Health(u32);
     struct Tag();
+    #[derive(crate::Event)]
     struct DamageEvent(u32);
+    #[derive(crate::Event)]
+    struct HealEvent;
 
     #[test]
     fn test_world_basics() {
@@ -117,6 +1209,558 @@ mod tests {
         assert_eq!(empty_events.len(), 0);
     }
 
+    #[test]
+    fn test_world_drain_events() {
+        let mut world = World::new();
+        world.push_event(DamageEvent(5));
+        world.push_event(DamageEvent(7));
+
+        let total: u32 = world.drain_events::<DamageEvent>().map(|e| e.0).sum();
+        assert_eq!(total, 12);
+        assert_eq!(world.drain_events::<DamageEvent>().count(), 0);
+
+        // Draining an unregistered event type yields nothing
+        assert_eq!(world.drain_events::<HealEvent>().count(), 0);
+    }
+
+    #[test]
+    fn test_par_iter_mut_skips_disabled_entities() {
+        #[cfg(feature = "rayon")]
+        use rayon::iter::ParallelIterator;
+
+        let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+        let entities: Vec<Entity> = (0..100)
+            .map(|i| {
+                let e = world.create_entity();
+                world.add_component(e, Health(i));
+                e
+            })
+            .collect();
+        world.set_enabled(entities[7], false);
+        world.clear_changes();
+
+        world.par_iter_mut::<Health>().for_each(|(_, health)| health.0 *= 2);
+        assert_eq!(world.get_component::<Health>(entities[9]).unwrap().0, 18);
+        assert_eq!(world.get_component::<Health>(entities[7]).unwrap().0, 7);
+        assert_eq!(world.changed::<Health>().len(), 99);
+        assert_eq!(world.par_iter_mut::<Tag>().count(), 0);
+    }
+
+    #[test]
+    fn test_event_log_interleaves_types_in_push_order() {
+        let mut world = World::with_config(WorldConfig::new().event_log(true));
+        world.push_event(DamageEvent(3));
+        world.push_event(HealEvent);
+        world.push_event(DamageEvent(4));
+        // Events read through their own queue leave the log
+        let target = world.create_entity();
+        world.push_event(HitEvent(target, 1));
+        world.take_events::<HitEvent>();
+
+        let log = world.take_event_log();
+        let sequence: Vec<u64> = log.iter().map(|e| e.sequence).collect();
+        assert_eq!(sequence, vec![0, 1, 2]);
+        assert!(log[1].is::<HealEvent>());
+        assert_eq!(log[2].downcast_ref::<DamageEvent>().map(|e| e.0), Some(4));
+        let first = log.into_iter().next().unwrap();
+        assert_eq!(first.downcast::<DamageEvent>().ok().map(|e| e.0), Some(3));
+        assert!(world.take_events::<DamageEvent>().is_empty());
+
+        let mut unlogged = World::new();
+        unlogged.push_event(HealEvent);
+        assert!(unlogged.take_event_log().is_empty());
+        assert_eq!(unlogged.take_events::<HealEvent>().len(), 1);
+    }
+
+    #[derive(crate::Event)]
+    struct HitEvent(Entity, u32);
+
+    impl EntityEvent for HitEvent {
+        fn entity(&self) -> Entity {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_resolve_events_joins_components() {
+        let mut world = World::new();
+        let named = world.create_entity();
+        world.add_component(named, Health(10));
+        world.add_component(named, Name("Orc"));
+        let nameless = world.create_entity();
+        world.add_component(nameless, Health(4));
+        let gone = world.create_entity();
+        world.add_component(gone, Health(1));
+        world.add_component(gone, Name("Ghost"));
+        world.destroy_entity(gone);
+        for target in [named, nameless, gone] {
+            world.push_event(HitEvent(target, 3));
+        }
+
+        let resolved = world.resolve_events::<HitEvent, (&Name, &Health), _>(|hit, (name, health)| {
+            (name.0, health.0 - hit.1)
+        });
+        let results: Vec<_> = resolved.into_iter().map(|(hit, r)| (hit.0, r)).collect();
+        assert_eq!(results, vec![(named, Some(("Orc", 7))), (nameless, None), (gone, None)]);
+        assert!(world.take_events::<HitEvent>().is_empty());
+    }
+
+    #[test]
+    fn test_try_operations_report_errors() {
+        let mut world = World::new();
+        let e = world.create_entity();
+
+        assert_eq!(
+            world.try_get_component::<Health>(e).err(),
+            Some(WorldError::StorageMissing(core::any::type_name::<Health>()))
+        );
+
+        world.try_add_component(e, Tag()).unwrap();
+        let other = world.create_entity();
+        world.add_component(other, Health(1));
+        assert!(matches!(
+            world.try_get_component::<Health>(e),
+            Err(WorldError::ComponentMissing { entity, .. }) if entity == e
+        ));
+        assert!(world.try_remove_component::<Health>(e).is_err());
+        assert!(world.try_remove_component::<Tag>(e).is_ok());
+
+        world.try_destroy_entity(e).unwrap();
+        assert_eq!(world.try_destroy_entity(e), Err(WorldError::EntityDead(e)));
+        assert_eq!(world.try_add_component(e, Health(5)), Err(WorldError::EntityDead(e)));
+        assert!(world.get_component::<Health>(e).is_none());
+        assert_eq!(world.try_get_component_mut::<Health>(e).err(), Some(WorldError::EntityDead(e)));
+    }
+
+    #[test]
+    fn test_world_error_display() {
+        let e = Entity { id: 3, generation: 1 };
+        assert_eq!(
+            WorldError::EntityDead(e).to_string(),
+            "entity 3 (generation 1) is not alive"
+        );
+        assert_eq!(
+            WorldError::MultipleMatches { query: "&Health", count: 2 }.to_string(),
+            "expected one entity to match &Health, found 2"
+        );
+    }
+
+    #[test]
+    fn test_marker_helpers() {
+        #[derive(Default)]
+        struct Stunned;
+        let mut world = World::new();
+        let a = world.create_entity();
+        let b = world.create_entity();
+        assert!(!world.any::<Stunned>());
+
+        world.toggle_marker::<Stunned>(a, true);
+        world.toggle_marker::<Stunned>(a, true);
+        world.toggle_marker::<Stunned>(b, true);
+        assert_eq!(world.count::<Stunned>(), 2);
+        assert!(world.has::<Stunned>(a));
+
+        world.toggle_marker::<Stunned>(a, false);
+        world.toggle_marker::<Stunned>(a, false);
+        assert!(!world.has::<Stunned>(a));
+        world.destroy_entity(b);
+        assert_eq!(world.count::<Stunned>(), 0);
+        assert!(!world.any::<Stunned>());
+    }
+
+    #[test]
+    fn test_single_reports_zero_or_many_matches() {
+        use crate::query::With;
+        struct Player;
+        let mut world = World::new();
+        assert!(matches!(
+            world.single::<&Health, With<Player>>(),
+            Err(WorldError::NoMatch { .. })
+        ));
+
+        let player = world.create_entity();
+        world.add_component(player, Player);
+        world.add_component(player, Health(10));
+        let other = world.create_entity();
+        world.add_component(other, Health(3));
+
+        let (entity, health) = world.single::<&Health, With<Player>>().unwrap();
+        assert_eq!((entity, health.0), (player, 10));
+        world.single_mut::<Health, With<Player>>().unwrap().1 .0 = 7;
+        assert_eq!(world.get_component::<Health>(player).unwrap().0, 7);
+
+        assert_eq!(
+            world.single::<&Health, ()>().err(),
+            Some(WorldError::MultipleMatches {
+                query: core::any::type_name::<(&Health, ())>(),
+                count: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_component_on_dead_entity_warns_by_default() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.destroy_entity(e);
+        let recycled = world.create_entity();
+
+        world.add_component(e, Health(10));
+
+        assert!(world.get_component::<Health>(recycled).is_none());
+        assert!(world.query_entities::<Health>().is_empty());
+        let warnings = world.take_events::<DeadEntityWarning>();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].entity, e);
+        assert_eq!(world.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_add_component_on_dead_entity_ignore_policy() {
+        let mut world =
+            World::with_config(WorldConfig::new().dead_entity_policy(DeadEntityPolicy::Ignore));
+        let e = world.create_entity();
+        world.destroy_entity(e);
+
+        world.add_component(e, Health(10));
+
+        assert!(world.take_events::<DeadEntityWarning>().is_empty());
+        assert!(world.query_entities::<Health>().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "dead entity")]
+    fn test_add_component_on_dead_entity_panic_policy() {
+        let mut world =
+            World::with_config(WorldConfig::new().dead_entity_policy(DeadEntityPolicy::Panic));
+        let e = world.create_entity();
+        world.destroy_entity(e);
+
+        world.add_component(e, Health(10));
+    }
+
+    #[test]
+    fn test_pooled_component_storage() {
+        let mut world = World::new();
+        world.register_pooled::<Health>();
+
+        let entities: Vec<_> = (0..10).map(|_| world.create_entity()).collect();
+        for (i, &e) in entities.iter().enumerate() {
+            world.add_component(e, Health(i as u32));
+        }
+        world.destroy_entity(entities[0]);
+        world.get_component_mut::<Health>(entities[9]).unwrap().0 = 90;
+
+        assert_eq!(world.get_component::<Health>(entities[9]).unwrap().0, 90);
+        assert_eq!(world.query::<&Health, ()>().count(), 9);
+
+        world.clear_frame::<Health>();
+        assert!(world.query_entities::<Health>().is_empty());
+        assert!(world.get_component::<Health>(entities[1]).is_none());
+    }
+
+    #[test]
+    fn test_scheduled_events_arrive_on_their_tick() {
+        let mut world = World::new();
+        world.schedule_event_at(3, DamageEvent(30));
+        world.schedule_event_at(2, DamageEvent(20));
+        world.schedule_event_at(3, DamageEvent(31));
+        world.schedule_event_at(0, DamageEvent(0));
+        assert_eq!(world.scheduled_events(), 3);
+        assert_eq!(world.take_events::<DamageEvent>().len(), 1);
+
+        let mut executor = crate::SystemExecutor::new();
+        executor.run(&mut world);
+        assert_eq!(world.tick(), 1);
+        assert!(world.take_events::<DamageEvent>().is_empty());
+        executor.run(&mut world);
+        let damage: Vec<u32> = world.drain_events::<DamageEvent>().map(|e| e.0).collect();
+        assert_eq!(damage, vec![20]);
+        world.advance_tick();
+        let damage: Vec<u32> = world.drain_events::<DamageEvent>().map(|e| e.0).collect();
+        assert_eq!(damage, vec![30, 31]);
+        assert_eq!(world.scheduled_events(), 0);
+    }
+
+    #[test]
+    fn test_event_limit_from_config() {
+        let mut world = World::with_config(
+            WorldConfig::new().event_limit(1, crate::config::EventOverflowPolicy::DropOldest),
+        );
+        world.push_event(DamageEvent(1));
+        world.push_event(DamageEvent(2));
+
+        let events = world.take_events::<DamageEvent>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, 2);
+    }
+
+    #[test]
+    fn test_deterministic_query_entities() {
+        let mut world = World::with_config(WorldConfig::new().deterministic_iteration(true));
+        let entities: Vec<_> = (0..50).map(|_| world.create_entity()).collect();
+        for e in entities.iter().rev() {
+            world.add_component(*e, Health(1));
+        }
+
+        assert_eq!(world.query_entities::<Health>(), entities);
+    }
+
+    #[test]
+    fn test_change_tracking() {
+        let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+        let e1 = world.create_entity();
+        let e2 = world.create_entity();
+        world.add_component(e1, Health(1));
+        world.add_component(e2, Health(2));
+        world.clear_changes();
+        assert!(world.changed::<Health>().is_empty());
+
+        world.get_component_mut::<Health>(e2).unwrap().0 = 5;
+        assert_eq!(world.changed::<Health>(), vec![e2]);
+        assert!(world.is_changed::<Health>(e2));
+        assert!(!world.is_changed::<Health>(e1));
+
+        world.clear_changes();
+        assert!(!world.is_changed::<Health>(e2));
+    }
+
+    #[test]
+    fn test_change_tracking_disabled_by_default() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(1));
+
+        assert!(world.changed::<Health>().is_empty());
+    }
+
+    #[test]
+    fn test_remove_component() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.add_component(e, Health(30));
+        world.add_component(e, Tag());
+
+        assert_eq!(world.remove_component::<Health>(e).map(|h| h.0), Some(30));
+        assert!(world.get_component::<Health>(e).is_none());
+        assert!(world.get_component::<Tag>(e).is_some());
+        assert!(world.remove_component::<Health>(e).is_none());
+        assert!(world.query_entities::<Health>().is_empty());
+    }
+
+    #[derive(Clone)]
+    struct Name(&'static str);
+
+    #[derive(Default)]
+    struct Defending(bool);
+
+    #[test]
+    fn test_required_component_is_inserted() {
+        let mut world = World::new();
+        world.register_required::<Tag, Defending>();
+
+        let e = world.create_entity();
+        world.add_component(e, Tag());
+
+        assert!(!world.get_component::<Defending>(e).unwrap().0);
+    }
+
+    #[test]
+    fn test_required_component_keeps_existing_value() {
+        let mut world = World::new();
+        world.register_required::<Tag, Defending>();
+
+        let e = world.create_entity();
+        world.add_component(e, Defending(true));
+        world.add_component(e, Tag());
+
+        assert!(world.get_component::<Defending>(e).unwrap().0);
+    }
+
+    #[test]
+    fn test_required_components_chain() {
+        let mut world = World::new();
+        world.register_required::<Tag, Defending>();
+        world.register_required_with::<Defending, Health>(|| Health(1));
+
+        let e = world.create_entity();
+        world.add_component(e, Tag());
+
+        assert!(world.get_component::<Defending>(e).is_some());
+        assert_eq!(world.get_component::<Health>(e).unwrap().0, 1);
+    }
+
+    #[test]
+    fn test_require_reports_missing_component() {
+        let mut world = World::new();
+        world.require::<Tag, Health>("tagged entities have health");
+
+        let healthy = world.create_entity();
+        world.add_component(healthy, Tag());
+        world.add_component(healthy, Health(3));
+        let broken = world.create_entity();
+        world.add_component(broken, Tag());
+
+        // Nothing was inserted; the gap is reported instead
+        assert!(world.get_component::<Health>(broken).is_none());
+        let violations = world.check_invariants();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entity, broken);
+        assert_eq!(violations[0].invariant, "tagged entities have health");
+    }
+
+    #[test]
+    fn test_entity_meta_records_creation_tick_and_label() {
+        let mut world = World::with_config(WorldConfig::new().entity_metadata(true));
+        world.advance_tick();
+        world.advance_tick();
+        let goblin = world.create_entity();
+        world.set_debug_label(goblin, "wave 1 goblin");
+        let meta = world.entity_meta(goblin).unwrap();
+        assert_eq!(meta.created_tick, 2);
+        assert_eq!(meta.label.as_deref(), Some("wave 1 goblin"));
+
+        // A reused id starts over, and stale handles leave it alone
+        world.destroy_entity(goblin);
+        assert!(world.entity_meta(goblin).is_none());
+        world.advance_tick();
+        let next = world.create_entity();
+        world.destroy_entity(goblin);
+        assert_eq!(world.entity_meta(next), Some(&EntityMeta::new(3)));
+
+        let mut plain = World::new();
+        let e = plain.create_entity();
+        plain.set_debug_label(e, "ignored");
+        assert!(plain.entity_meta(e).is_none());
+    }
+
+    #[test]
+    fn test_entities_created_since() {
+        let mut world = World::with_config(WorldConfig::new().entity_metadata(true));
+        let first = world.create_entity();
+        world.advance_tick();
+        let second = world.create_entity();
+        let doomed = world.create_entity();
+        world.advance_tick();
+        world.advance_tick();
+        let third = world.create_entity();
+        world.destroy_entity(doomed);
+
+        assert_eq!(world.entities_created_since(1), vec![second, third]);
+        assert_eq!(world.entities_created_since(world.tick()), vec![third]);
+        assert_eq!(world.entities_created_in(..2), vec![first, second]);
+        assert!(world.entities_created_since(4).is_empty());
+        assert_eq!(world.entity_age(first), Some(3));
+        assert_eq!(world.entity_age(third), Some(0));
+        assert_eq!(world.entity_age(doomed), None);
+    }
+
+    #[test]
+    fn test_clone_entity() {
+        let mut world = World::new();
+        world.register_cloneable::<Name>();
+
+        let goblin = world.create_entity();
+        world.add_component(goblin, Name("Goblin"));
+        world.add_component(goblin, Tag());
+
+        let copy = world.clone_entity(goblin).unwrap();
+        assert_ne!(copy, goblin);
+        assert_eq!(world.get_component::<Name>(copy).unwrap().0, "Goblin");
+        assert!(world.get_component::<Tag>(copy).is_none());
+
+        world.destroy_entity(goblin);
+        assert!(world.clone_entity(goblin).is_none());
+    }
+
+    #[test]
+    fn test_validate_detects_component_on_dead_entity() {
+        let mut world = World::new();
+        let e = world.create_entity();
+        world.destroy_entity(e);
+        assert!(world.validate().is_ok());
+
+        // Bypass the liveness check in add_component to corrupt the world
+        world.components.add_component(e, Health(1));
+        let errors = world.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("Health"));
+    }
+
+    // One step of a random world workout; indices pick among the entities
+    // alive or destroyed at that point.
+    #[derive(Debug, Clone)]
+    enum Op {
+        Create,
+        Destroy(Index),
+        DestroyStale(Index),
+        Add(Index, u32),
+        Remove(Index),
+        Query,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            2 => Just(Op::Create),
+            1 => any::<Index>().prop_map(Op::Destroy),
+            1 => any::<Index>().prop_map(Op::DestroyStale),
+            1 => (any::<Index>(), 0..100u32).prop_map(|(e, hp)| Op::Add(e, hp)),
+            1 => any::<Index>().prop_map(Op::Remove),
+            1 => Just(Op::Query),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_random_operations_preserve_invariants(ops in vec(op(), 0..300)) {
+            let mut world = World::new();
+            let mut live: Vec<Entity> = Vec::new();
+            let mut dead: Vec<Entity> = Vec::new();
+            let mut max_generation: HashMap<u32, u32> = HashMap::new();
+
+            for op in ops {
+                match op {
+                    Op::Create => {
+                        let e = world.create_entity();
+                        prop_assert!(!live.iter().any(|l| l.id == e.id), "id handed out twice");
+                        if let Some(&prev) = max_generation.get(&e.id) {
+                            prop_assert!(e.generation > prev, "generation did not increase");
+                        }
+                        max_generation.insert(e.id, e.generation);
+                        live.push(e);
+                    }
+                    Op::Destroy(i) if !live.is_empty() => {
+                        let e = live.swap_remove(i.index(live.len()));
+                        world.destroy_entity(e);
+                        dead.push(e);
+                    }
+                    // Destroying a stale handle must be a no-op
+                    Op::DestroyStale(i) if !dead.is_empty() => {
+                        world.destroy_entity(dead[i.index(dead.len())]);
+                    }
+                    Op::Add(i, hp) if !live.is_empty() => {
+                        world.add_component(live[i.index(live.len())], Health(hp));
+                    }
+                    Op::Remove(i) if !live.is_empty() => {
+                        world.remove_component::<Health>(live[i.index(live.len())]);
+                    }
+                    Op::Query => {
+                        for (e, _) in world.query::<&Health, ()>() {
+                            prop_assert!(world.is_alive(e));
+                        }
+                    }
+                    _ => {}
+                }
+
+                for e in &dead {
+                    prop_assert!(!world.is_alive(*e));
+                    prop_assert!(world.get_component::<Health>(*e).is_none());
+                }
+                prop_assert_eq!(world.validate(), Ok(()));
+            }
+        }
+    }
+
     #[test]
     fn test_entity_destruction() {
         let mut world = World::new();
@@ -0,0 +1,132 @@
+// Derive macros for rusty-ecs-core. Written against the bare `proc_macro` API
+// so the crate has no dependencies.
+
+use proc_macro::{TokenStream, TokenTree};
+
+#[proc_macro_derive(Event)]
+pub fn derive_event(input: TokenStream) -> TokenStream {
+    match parse_type_header(input) {
+        Ok(header) => header
+            .impl_block("::rusty_ecs_core::event::EventMarker")
+            .parse()
+            .unwrap(),
+        Err(message) => format!("compile_error!({:?});", message).parse().unwrap(),
+    }
+}
+
+struct TypeHeader {
+    name: String,
+    impl_params: Vec<String>,
+    type_args: Vec<String>,
+}
+
+impl TypeHeader {
+    fn impl_block(&self, trait_path: &str) -> String {
+        format!(
+            "impl<{}> {} for {}<{}> {{}}",
+            self.impl_params.join(", "),
+            trait_path,
+            self.name,
+            self.type_args.join(", ")
+        )
+    }
+}
+
+fn parse_type_header(input: TokenStream) -> Result<TypeHeader, String> {
+    let mut tokens = input.into_iter().peekable();
+
+    // Skip attributes and visibility up to the item keyword
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident))
+                if matches!(ident.to_string().as_str(), "struct" | "enum" | "union") =>
+            {
+                break;
+            }
+            Some(_) => continue,
+            None => return Err("expected a struct, enum, or union".to_string()),
+        }
+    }
+
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("expected a type name".to_string()),
+    };
+
+    let mut params = Vec::new();
+    if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+        tokens.next();
+        let mut depth = 1;
+        let mut current = Vec::new();
+        for token in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &token {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    '>' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    ',' if depth == 1 => {
+                        params.push(std::mem::take(&mut current));
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            current.push(token);
+        }
+        if !current.is_empty() {
+            params.push(current);
+        }
+    }
+
+    let mut impl_params = Vec::new();
+    let mut type_args = Vec::new();
+    for param in params {
+        let (decl, arg) = generic_param(&param)?;
+        impl_params.push(decl);
+        type_args.push(arg);
+    }
+
+    if let Some(TokenTree::Ident(ident)) = tokens.peek()
+        && ident.to_string() == "where"
+    {
+        return Err("where clauses are not supported by this derive".to_string());
+    }
+
+    Ok(TypeHeader {
+        name,
+        impl_params,
+        type_args,
+    })
+}
+
+// Splits one generic parameter into its declaration (bounds kept, default
+// dropped) and the argument used to name it in the impl target.
+fn generic_param(tokens: &[TokenTree]) -> Result<(String, String), String> {
+    let without_default: Vec<_> = tokens
+        .iter()
+        .take_while(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == '='))
+        .collect();
+    let decl = without_default
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("' ", "'");
+
+    let arg = match tokens {
+        [TokenTree::Punct(p), TokenTree::Ident(lifetime), ..] if p.as_char() == '\'' => {
+            format!("'{}", lifetime)
+        }
+        [TokenTree::Ident(kw), TokenTree::Ident(name), ..] if kw.to_string() == "const" => {
+            name.to_string()
+        }
+        [TokenTree::Ident(name), ..] => name.to_string(),
+        _ => return Err("unsupported generic parameter".to_string()),
+    };
+
+    Ok((decl, arg))
+}
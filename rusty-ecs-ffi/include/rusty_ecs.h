@@ -0,0 +1,53 @@
+#ifndef RUSTY_ECS_H
+#define RUSTY_ECS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RUSTY_OK 0
+#define RUSTY_ERR_NULL -1
+#define RUSTY_ERR_DEAD_ENTITY -2
+#define RUSTY_ERR_UNKNOWN_TYPE -3
+#define RUSTY_ERR_SIZE -4
+#define RUSTY_ERR_NOT_FOUND -5
+
+/* Registering a type with this size accepts blobs of any length. */
+#define RUSTY_SIZE_DYNAMIC SIZE_MAX
+
+/* Opaque world handle. */
+typedef struct FfiWorld RustyWorld;
+
+/* Packed entity handle: (generation << 32) | id. */
+typedef uint64_t RustyEntity;
+
+RustyWorld *rusty_world_new(void);
+void rusty_world_free(RustyWorld *world);
+
+/* Returns the new type id, or UINT32_MAX if world is NULL. */
+uint32_t rusty_register_type(RustyWorld *world, size_t size);
+
+/* Returns the new entity, or UINT64_MAX if world is NULL. */
+RustyEntity rusty_entity_spawn(RustyWorld *world);
+int32_t rusty_entity_despawn(RustyWorld *world, RustyEntity entity);
+bool rusty_entity_is_alive(const RustyWorld *world, RustyEntity entity);
+
+int32_t rusty_component_attach(RustyWorld *world, RustyEntity entity, uint32_t type_id,
+                               const uint8_t *data, size_t len);
+int32_t rusty_component_get(const RustyWorld *world, RustyEntity entity, uint32_t type_id,
+                            uint8_t *out, size_t len);
+int64_t rusty_component_len(const RustyWorld *world, RustyEntity entity, uint32_t type_id);
+int32_t rusty_component_detach(RustyWorld *world, RustyEntity entity, uint32_t type_id);
+
+int32_t rusty_event_push(RustyWorld *world, uint32_t type_id, const uint8_t *data, size_t len);
+int32_t rusty_event_pop(RustyWorld *world, uint32_t type_id, uint8_t *out, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RUSTY_ECS_H */
@@ -0,0 +1,477 @@
+// C interface for embedding the ECS in other engines. See
+// `include/rusty_ecs.h` for the matching declarations.
+//
+// Components and events crossing the boundary are opaque byte blobs tagged
+// with a type id handed out by `rusty_register_type`. Entities are packed into
+// a `u64` as `(generation << 32) | id`.
+
+use rusty_ecs_core::{Entity, World};
+use std::collections::{HashMap, VecDeque};
+use std::slice;
+
+#[cfg(feature = "python")]
+mod python;
+
+pub const RUSTY_OK: i32 = 0;
+pub const RUSTY_ERR_NULL: i32 = -1;
+pub const RUSTY_ERR_DEAD_ENTITY: i32 = -2;
+pub const RUSTY_ERR_UNKNOWN_TYPE: i32 = -3;
+pub const RUSTY_ERR_SIZE: i32 = -4;
+pub const RUSTY_ERR_NOT_FOUND: i32 = -5;
+
+// Registering a type with this size accepts blobs of any length.
+pub const RUSTY_SIZE_DYNAMIC: usize = usize::MAX;
+
+// All blob components of one entity, stored as a regular component so that
+// destroying the entity drops them with everything else.
+struct Blobs(HashMap<u32, Vec<u8>>);
+
+pub struct FfiWorld {
+    world: World,
+    type_sizes: Vec<usize>,
+    // Blob events, one queue per type id. Kept here rather than in the world's
+    // event queues so a pop only touches the front of its own type's queue.
+    events: Vec<VecDeque<Vec<u8>>>,
+}
+
+impl FfiWorld {
+    fn check(&self, entity: Entity, type_id: u32, len: usize) -> Result<(), i32> {
+        if !self.world.is_alive(entity) {
+            return Err(RUSTY_ERR_DEAD_ENTITY);
+        }
+        self.check_type(type_id, len)
+    }
+
+    fn check_type(&self, type_id: u32, len: usize) -> Result<(), i32> {
+        match self.type_sizes.get(type_id as usize) {
+            None => Err(RUSTY_ERR_UNKNOWN_TYPE),
+            Some(&size) if size != len && size != RUSTY_SIZE_DYNAMIC => Err(RUSTY_ERR_SIZE),
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn blob(&self, entity: Entity, type_id: u32) -> Option<&Vec<u8>> {
+        self.world
+            .get_component::<Blobs>(entity)
+            .and_then(|blobs| blobs.0.get(&type_id))
+    }
+}
+
+fn pack(entity: Entity) -> u64 {
+    ((entity.generation as u64) << 32) | entity.id as u64
+}
+
+fn unpack(handle: u64) -> Entity {
+    Entity {
+        id: handle as u32,
+        generation: (handle >> 32) as u32,
+    }
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        // SAFETY: the caller guarantees `data` points to `len` readable bytes.
+        unsafe { slice::from_raw_parts(data, len) }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rusty_world_new() -> *mut FfiWorld {
+    Box::into_raw(Box::new(FfiWorld {
+        world: World::new(),
+        type_sizes: Vec::new(),
+        events: Vec::new(),
+    }))
+}
+
+/// # Safety
+/// `world` must be null or a pointer returned by `rusty_world_new` that has
+/// not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_world_free(world: *mut FfiWorld) {
+    if !world.is_null() {
+        // SAFETY: ownership is handed back by the caller exactly once.
+        drop(unsafe { Box::from_raw(world) });
+    }
+}
+
+/// Registers a blob type of `size` bytes and returns its type id, or
+/// `u32::MAX` if `world` is null.
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_register_type(world: *mut FfiWorld, size: usize) -> u32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_mut() }) else {
+        return u32::MAX;
+    };
+    world.type_sizes.push(size);
+    world.events.push(VecDeque::new());
+    (world.type_sizes.len() - 1) as u32
+}
+
+/// Returns the packed entity handle, or `u64::MAX` if `world` is null.
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_entity_spawn(world: *mut FfiWorld) -> u64 {
+    // SAFETY: see function contract.
+    match unsafe { world.as_mut() } {
+        Some(world) => pack(world.world.create_entity()),
+        None => u64::MAX,
+    }
+}
+
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_entity_despawn(world: *mut FfiWorld, entity: u64) -> i32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_mut() }) else {
+        return RUSTY_ERR_NULL;
+    };
+    let entity = unpack(entity);
+    if !world.world.is_alive(entity) {
+        return RUSTY_ERR_DEAD_ENTITY;
+    }
+    world.world.destroy_entity(entity);
+    RUSTY_OK
+}
+
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_entity_is_alive(world: *const FfiWorld, entity: u64) -> bool {
+    // SAFETY: see function contract.
+    unsafe { world.as_ref() }.is_some_and(|w| w.world.is_alive(unpack(entity)))
+}
+
+/// Copies `len` bytes from `data` into the entity's component of `type_id`,
+/// replacing any previous value.
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`, and `data`
+/// must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_component_attach(
+    world: *mut FfiWorld,
+    entity: u64,
+    type_id: u32,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_mut() }) else {
+        return RUSTY_ERR_NULL;
+    };
+    if data.is_null() && len > 0 {
+        return RUSTY_ERR_NULL;
+    }
+    let entity = unpack(entity);
+    if let Err(code) = world.check(entity, type_id, len) {
+        return code;
+    }
+
+    // SAFETY: see function contract.
+    let data = unsafe { bytes(data, len) }.to_vec();
+    match world.world.get_component_mut::<Blobs>(entity) {
+        Some(blobs) => {
+            blobs.0.insert(type_id, data);
+        }
+        None => world
+            .world
+            .add_component(entity, Blobs(HashMap::from([(type_id, data)]))),
+    }
+    RUSTY_OK
+}
+
+/// Copies the entity's component of `type_id` into `out`, which must be
+/// exactly the component's size (see `rusty_component_len`).
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`, and `out`
+/// must point to `len` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_component_get(
+    world: *const FfiWorld,
+    entity: u64,
+    type_id: u32,
+    out: *mut u8,
+    len: usize,
+) -> i32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_ref() }) else {
+        return RUSTY_ERR_NULL;
+    };
+    if out.is_null() && len > 0 {
+        return RUSTY_ERR_NULL;
+    }
+    let entity = unpack(entity);
+    if let Err(code) = world.check(entity, type_id, len) {
+        return code;
+    }
+
+    match world.blob(entity, type_id) {
+        Some(data) if data.len() != len => RUSTY_ERR_SIZE,
+        Some(data) => {
+            // SAFETY: `out` holds `len` bytes, which equals the blob length.
+            unsafe { out.copy_from_nonoverlapping(data.as_ptr(), len) };
+            RUSTY_OK
+        }
+        None => RUSTY_ERR_NOT_FOUND,
+    }
+}
+
+/// Returns the byte length of the entity's component of `type_id`, or a
+/// negative error code. Needed to size the buffer for dynamic types.
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_component_len(
+    world: *const FfiWorld,
+    entity: u64,
+    type_id: u32,
+) -> i64 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_ref() }) else {
+        return RUSTY_ERR_NULL as i64;
+    };
+    let entity = unpack(entity);
+    if !world.world.is_alive(entity) {
+        return RUSTY_ERR_DEAD_ENTITY as i64;
+    }
+    if type_id as usize >= world.type_sizes.len() {
+        return RUSTY_ERR_UNKNOWN_TYPE as i64;
+    }
+    match world.blob(entity, type_id) {
+        Some(data) => data.len() as i64,
+        None => RUSTY_ERR_NOT_FOUND as i64,
+    }
+}
+
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_component_detach(
+    world: *mut FfiWorld,
+    entity: u64,
+    type_id: u32,
+) -> i32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_mut() }) else {
+        return RUSTY_ERR_NULL;
+    };
+    let entity = unpack(entity);
+    if !world.world.is_alive(entity) {
+        return RUSTY_ERR_DEAD_ENTITY;
+    }
+    if type_id as usize >= world.type_sizes.len() {
+        return RUSTY_ERR_UNKNOWN_TYPE;
+    }
+
+    match world
+        .world
+        .get_component_mut::<Blobs>(entity)
+        .and_then(|blobs| blobs.0.remove(&type_id))
+    {
+        Some(_) => RUSTY_OK,
+        None => RUSTY_ERR_NOT_FOUND,
+    }
+}
+
+/// Queues an event of `type_id` carrying a copy of `len` bytes from `data`.
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`, and `data`
+/// must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_event_push(
+    world: *mut FfiWorld,
+    type_id: u32,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_mut() }) else {
+        return RUSTY_ERR_NULL;
+    };
+    if data.is_null() && len > 0 {
+        return RUSTY_ERR_NULL;
+    }
+    if let Err(code) = world.check_type(type_id, len) {
+        return code;
+    }
+
+    // SAFETY: see function contract.
+    let data = unsafe { bytes(data, len) }.to_vec();
+    world.events[type_id as usize].push_back(data);
+    RUSTY_OK
+}
+
+/// Pops the oldest queued event of `type_id` into `out`. If it does not have
+/// exactly `len` bytes nothing is popped and `RUSTY_ERR_SIZE` is returned.
+///
+/// # Safety
+/// `world` must be null or a live pointer from `rusty_world_new`, and `out`
+/// must point to `len` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rusty_event_pop(
+    world: *mut FfiWorld,
+    type_id: u32,
+    out: *mut u8,
+    len: usize,
+) -> i32 {
+    // SAFETY: see function contract.
+    let Some(world) = (unsafe { world.as_mut() }) else {
+        return RUSTY_ERR_NULL;
+    };
+    if out.is_null() && len > 0 {
+        return RUSTY_ERR_NULL;
+    }
+    if let Err(code) = world.check_type(type_id, len) {
+        return code;
+    }
+
+    let queue = &mut world.events[type_id as usize];
+    match queue.front() {
+        Some(data) if data.len() != len => RUSTY_ERR_SIZE,
+        Some(data) => {
+            // SAFETY: `out` holds `len` bytes, which equals the event length.
+            unsafe { out.copy_from_nonoverlapping(data.as_ptr(), len) };
+            queue.pop_front();
+            RUSTY_OK
+        }
+        None => RUSTY_ERR_NOT_FOUND,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_spawn_attach_get_despawn() {
+        unsafe {
+            let world = rusty_world_new();
+            let position = rusty_register_type(world, 8);
+            let e = rusty_entity_spawn(world);
+
+            let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+            assert_eq!(rusty_component_attach(world, e, position, data.as_ptr(), 8), RUSTY_OK);
+
+            let mut out = [0u8; 8];
+            assert_eq!(rusty_component_get(world, e, position, out.as_mut_ptr(), 8), RUSTY_OK);
+            assert_eq!(out, data);
+
+            assert_eq!(rusty_entity_despawn(world, e), RUSTY_OK);
+            assert!(!rusty_entity_is_alive(world, e));
+            assert_eq!(
+                rusty_component_get(world, e, position, out.as_mut_ptr(), 8),
+                RUSTY_ERR_DEAD_ENTITY
+            );
+            rusty_world_free(world);
+        }
+    }
+
+    #[test]
+    fn test_attach_validates_type_and_size() {
+        unsafe {
+            let world = rusty_world_new();
+            let health = rusty_register_type(world, 4);
+            let e = rusty_entity_spawn(world);
+            let data = [0u8; 4];
+
+            assert_eq!(rusty_component_attach(world, e, 99, data.as_ptr(), 4), RUSTY_ERR_UNKNOWN_TYPE);
+            assert_eq!(rusty_component_attach(world, e, health, data.as_ptr(), 2), RUSTY_ERR_SIZE);
+            assert_eq!(rusty_component_detach(world, e, health), RUSTY_ERR_NOT_FOUND);
+            assert_eq!(rusty_component_attach(ptr::null_mut(), e, health, data.as_ptr(), 4), RUSTY_ERR_NULL);
+            rusty_world_free(world);
+        }
+    }
+
+    #[test]
+    fn test_recycled_entity_does_not_see_old_blobs() {
+        unsafe {
+            let world = rusty_world_new();
+            let tag = rusty_register_type(world, 1);
+            let e1 = rusty_entity_spawn(world);
+            rusty_component_attach(world, e1, tag, [7u8].as_ptr(), 1);
+            rusty_entity_despawn(world, e1);
+
+            let e2 = rusty_entity_spawn(world);
+            assert_eq!(e1 as u32, e2 as u32);
+            let mut out = [0u8; 1];
+            assert_eq!(rusty_component_get(world, e2, tag, out.as_mut_ptr(), 1), RUSTY_ERR_NOT_FOUND);
+            rusty_world_free(world);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_size_components() {
+        unsafe {
+            let world = rusty_world_new();
+            let doc = rusty_register_type(world, RUSTY_SIZE_DYNAMIC);
+            let e = rusty_entity_spawn(world);
+
+            let data = b"{\"hp\": 10}";
+            assert_eq!(rusty_component_attach(world, e, doc, data.as_ptr(), data.len()), RUSTY_OK);
+            assert_eq!(rusty_component_len(world, e, doc), data.len() as i64);
+
+            let mut short = [0u8; 2];
+            assert_eq!(rusty_component_get(world, e, doc, short.as_mut_ptr(), 2), RUSTY_ERR_SIZE);
+
+            let mut out = vec![0u8; data.len()];
+            assert_eq!(rusty_component_get(world, e, doc, out.as_mut_ptr(), out.len()), RUSTY_OK);
+            assert_eq!(&out, data);
+            rusty_world_free(world);
+        }
+    }
+
+    #[test]
+    fn test_events_are_fifo_per_type() {
+        unsafe {
+            let world = rusty_world_new();
+            let hit = rusty_register_type(world, 1);
+            let heal = rusty_register_type(world, 1);
+
+            rusty_event_push(world, hit, [1u8].as_ptr(), 1);
+            rusty_event_push(world, heal, [9u8].as_ptr(), 1);
+            rusty_event_push(world, hit, [2u8].as_ptr(), 1);
+
+            let mut out = [0u8; 1];
+            assert_eq!(rusty_event_pop(world, hit, out.as_mut_ptr(), 1), RUSTY_OK);
+            assert_eq!(out[0], 1);
+            assert_eq!(rusty_event_pop(world, hit, out.as_mut_ptr(), 1), RUSTY_OK);
+            assert_eq!(out[0], 2);
+            assert_eq!(rusty_event_pop(world, hit, out.as_mut_ptr(), 1), RUSTY_ERR_NOT_FOUND);
+            assert_eq!(rusty_event_pop(world, heal, out.as_mut_ptr(), 1), RUSTY_OK);
+            assert_eq!(out[0], 9);
+            rusty_world_free(world);
+        }
+    }
+
+    #[test]
+    fn test_failed_pop_leaves_the_queue_unchanged() {
+        unsafe {
+            let world = rusty_world_new();
+            let msg = rusty_register_type(world, RUSTY_SIZE_DYNAMIC);
+
+            rusty_event_push(world, msg, b"abc".as_ptr(), 3);
+            rusty_event_push(world, msg, b"de".as_ptr(), 2);
+
+            let mut out = [0u8; 3];
+            assert_eq!(rusty_event_pop(world, msg, out.as_mut_ptr(), 2), RUSTY_ERR_SIZE);
+            assert_eq!(rusty_event_pop(world, msg, out.as_mut_ptr(), 3), RUSTY_OK);
+            assert_eq!(&out, b"abc");
+            assert_eq!(rusty_event_pop(world, msg, out.as_mut_ptr(), 2), RUSTY_OK);
+            assert_eq!(&out[..2], b"de");
+            rusty_world_free(world);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// The `rusty_ecs` Python extension module, enabled by the `python` feature.
+//
+// Components are registered by name at runtime and hold dicts. The world
+// keeps the dict itself, so edits made through `World.get` are seen by
+// everything else holding the entity:
+//
+//     world = World()
+//     hero = world.spawn(Health={"hp": 45, "max": 45})
+//     world.get(hero, "Health")["hp"] -= 5
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rusty_ecs_core::Entity;
+use std::collections::HashMap;
+
+create_exception!(rusty_ecs, EcsError, PyException);
+create_exception!(rusty_ecs, DeadEntityError, EcsError);
+
+// All dict components of one entity, stored as a regular component so that
+// destroying the entity drops them with everything else.
+struct Dicts(HashMap<u32, Py<PyDict>>);
+
+#[pyclass(name = "Entity", module = "rusty_ecs", frozen, eq, hash, from_py_object)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PyEntity(Entity);
+
+#[pymethods]
+impl PyEntity {
+    #[getter]
+    fn id(&self) -> u32 {
+        self.0.id
+    }
+
+    #[getter]
+    fn generation(&self) -> u32 {
+        self.0.generation
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Entity(id={}, generation={})", self.0.id, self.0.generation)
+    }
+}
+
+#[pyclass(name = "World", module = "rusty_ecs", unsendable)]
+pub struct PyWorld {
+    world: rusty_ecs_core::World,
+    types: HashMap<String, u32>,
+}
+
+impl PyWorld {
+    fn check_alive(&self, entity: PyEntity) -> PyResult<()> {
+        match self.world.is_alive(entity.0) {
+            true => Ok(()),
+            false => Err(DeadEntityError::new_err("entity is not alive")),
+        }
+    }
+
+    // The registered type id of `name` once `entity` is known to be alive.
+    fn type_of(&self, entity: PyEntity, name: &str) -> PyResult<Option<u32>> {
+        self.check_alive(entity)?;
+        Ok(self.types.get(name).copied())
+    }
+}
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    fn new() -> Self {
+        PyWorld { world: rusty_ecs_core::World::new(), types: HashMap::new() }
+    }
+
+    fn register_component(&mut self, name: &str) -> u32 {
+        let next = self.types.len() as u32;
+        *self.types.entry(name.to_string()).or_insert(next)
+    }
+
+    #[pyo3(signature = (**components))]
+    fn spawn(&mut self, components: Option<&Bound<'_, PyDict>>) -> PyResult<PyEntity> {
+        let entity = PyEntity(self.world.create_entity());
+        for (name, data) in components.into_iter().flatten() {
+            self.add(entity, &name.extract::<String>()?, &data)?;
+        }
+        Ok(entity)
+    }
+
+    fn despawn(&mut self, entity: PyEntity) -> PyResult<()> {
+        self.check_alive(entity)?;
+        self.world.destroy_entity(entity.0);
+        Ok(())
+    }
+
+    fn is_alive(&self, entity: PyEntity) -> bool {
+        self.world.is_alive(entity.0)
+    }
+
+    // Stores a copy of the mapping `data`, replacing any previous value.
+    fn add(&mut self, entity: PyEntity, name: &str, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.check_alive(entity)?;
+        let dict = data.py().get_type::<PyDict>().call1((data,))?.cast_into::<PyDict>()?;
+        let type_id = self.register_component(name);
+        match self.world.get_component_mut::<Dicts>(entity.0) {
+            Some(dicts) => {
+                dicts.0.insert(type_id, dict.unbind());
+            }
+            None => self
+                .world
+                .add_component(entity.0, Dicts(HashMap::from([(type_id, dict.unbind())]))),
+        }
+        Ok(())
+    }
+
+    fn get<'py>(
+        &self,
+        py: Python<'py>,
+        entity: PyEntity,
+        name: &str,
+    ) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(type_id) = self.type_of(entity, name)? else {
+            return Ok(None);
+        };
+        Ok(self
+            .world
+            .get_component::<Dicts>(entity.0)
+            .and_then(|dicts| dicts.0.get(&type_id))
+            .map(|dict| dict.bind(py).clone()))
+    }
+
+    fn has(&self, entity: PyEntity, name: &str) -> PyResult<bool> {
+        let Some(type_id) = self.type_of(entity, name)? else {
+            return Ok(false);
+        };
+        Ok(self
+            .world
+            .get_component::<Dicts>(entity.0)
+            .is_some_and(|dicts| dicts.0.contains_key(&type_id)))
+    }
+
+    fn remove(&mut self, entity: PyEntity, name: &str) -> PyResult<bool> {
+        let Some(type_id) = self.type_of(entity, name)? else {
+            return Ok(false);
+        };
+        Ok(self
+            .world
+            .get_component_mut::<Dicts>(entity.0)
+            .and_then(|dicts| dicts.0.remove(&type_id))
+            .is_some())
+    }
+}
+
+#[pymodule]
+fn rusty_ecs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyWorld>()?;
+    m.add_class::<PyEntity>()?;
+    m.add("EcsError", m.py().get_type::<EcsError>())?;
+    m.add("DeadEntityError", m.py().get_type::<DeadEntityError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::wrap_pymodule;
+    use std::ffi::CStr;
+
+    // Runs `script` with the module imported as `rusty_ecs`.
+    fn run(script: &CStr) {
+        Python::initialize();
+        Python::attach(|py| {
+            let globals = PyDict::new(py);
+            globals.set_item("rusty_ecs", wrap_pymodule!(rusty_ecs)(py)).unwrap();
+            if let Err(err) = py.run(script, Some(&globals), None) {
+                err.print(py);
+                panic!("script failed");
+            }
+        });
+    }
+
+    #[test]
+    fn test_add_and_get_component() {
+        run(c"
+world = rusty_ecs.World()
+hero = world.spawn(Health={'hp': 45, 'max': 45})
+assert world.get(hero, 'Health') == {'hp': 45, 'max': 45}
+assert world.get(hero, 'Damage') is None
+assert world.has(hero, 'Health') and not world.has(hero, 'Damage')
+");
+    }
+
+    #[test]
+    fn test_component_edits_write_back() {
+        run(c"
+world = rusty_ecs.World()
+stats = {'hp': 45}
+hero = world.spawn(Health=stats)
+world.get(hero, 'Health')['hp'] -= 5
+assert world.get(hero, 'Health')['hp'] == 40
+assert stats['hp'] == 45
+");
+    }
+
+    #[test]
+    fn test_remove_component() {
+        run(c"
+world = rusty_ecs.World()
+hero = world.spawn(Player={})
+assert world.remove(hero, 'Player')
+assert not world.has(hero, 'Player')
+assert not world.remove(hero, 'Player')
+");
+    }
+
+    #[test]
+    fn test_despawn_invalidates_entity() {
+        run(c"
+world = rusty_ecs.World()
+goblin = world.spawn(Health={'hp': 12})
+world.despawn(goblin)
+assert not world.is_alive(goblin)
+try:
+    world.get(goblin, 'Health')
+    raise AssertionError('expected DeadEntityError')
+except rusty_ecs.DeadEntityError:
+    pass
+
+orc = world.spawn()
+assert orc.id == goblin.id and orc != goblin
+assert len({orc, goblin}) == 2
+assert world.get(orc, 'Health') is None
+assert repr(orc) == f'Entity(id={orc.id}, generation={orc.generation})'
+");
+    }
+}
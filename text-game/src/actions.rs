@@ -0,0 +1,240 @@
+use crate::components::{Damage, Name};
+use crate::events::{AttackEvent, FleeAttemptEvent, PlayerActionEvent};
+use crate::input::Input;
+use crate::messages::MsgEvent;
+use crate::prompt::{Menu, Rejection};
+use crate::systems::set_defending;
+use rusty_ecs_core::{ExclusiveSystem, World};
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+// What a party member can do on their turn, whatever was typed for it.
+// `Debug` and `Step` are the hidden debugging commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Attack,
+    Defend,
+    Flee,
+    Quit,
+    Debug,
+    Step,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] =
+        [Action::Attack, Action::Defend, Action::Flee, Action::Quit, Action::Debug, Action::Step];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Attack => "attack",
+            Action::Defend => "defend",
+            Action::Flee => "flee",
+            Action::Quit => "quit",
+            Action::Debug => "debug",
+            Action::Step => "step",
+        }
+    }
+
+    pub fn is_hidden(self) -> bool {
+        matches!(self, Action::Debug | Action::Step)
+    }
+
+    // The actions by name, matched by prefix except for the hidden ones.
+    pub fn menu() -> Menu {
+        let (hidden, shown): (Vec<Action>, Vec<Action>) =
+            Action::ALL.into_iter().partition(|a| a.is_hidden());
+        let names =
+            |actions: Vec<Action>| actions.into_iter().map(Action::name).collect::<Vec<_>>();
+        Menu::new(&names(shown)).hidden(&names(hidden))
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Action::ALL.into_iter().find(|a| a.name() == name).ok_or_else(|| {
+            let names: Vec<_> = Action::ALL.iter().map(|a| a.name()).collect();
+            format!("unknown action '{}', expected one of {}", s.trim(), names.join(", "))
+        })
+    }
+}
+
+// Resource: the keys bound to each `Action` for the party's turn. A bound
+// key wins; otherwise answers resolve against the action names as in
+// `Menu`. The default bindings come from `keys/default.txt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionMap {
+    bindings: Vec<(String, Action)>,
+}
+
+impl ActionMap {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    // One `key = action` per line; binding a key again moves it.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut map = ActionMap { bindings: Vec::new() };
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, action) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = action`", number + 1))?;
+            let action = action.parse().map_err(|err| format!("line {}: {}", number + 1, err))?;
+            let key = key.trim().to_lowercase();
+            if key.is_empty() {
+                return Err(format!("line {}: missing key", number + 1));
+            }
+            map.bind(key, action);
+        }
+        Ok(map)
+    }
+
+    pub fn bind(&mut self, key: impl Into<String>, action: Action) {
+        let key = key.into();
+        self.bindings.retain(|(bound, _)| *bound != key);
+        self.bindings.push((key, action));
+    }
+
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = &str> {
+        self.bindings.iter().filter(move |(_, a)| *a == action).map(|(key, _)| key.as_str())
+    }
+
+    // The shown actions with their keys, as the turn prompt lists them:
+    // `attack(a)/defend(d)/...`.
+    pub fn describe(&self) -> String {
+        let listed: Vec<String> = Action::ALL
+            .into_iter()
+            .filter(|a| !a.is_hidden())
+            .map(|action| match self.keys(action).collect::<Vec<_>>() {
+                keys if keys.is_empty() => action.name().to_string(),
+                keys => format!("{}({})", action.name(), keys.join(",")),
+            })
+            .collect();
+        listed.join("/")
+    }
+
+    pub fn resolve(&self, answer: &str) -> Result<Action, Rejection> {
+        if let Some(&(_, action)) = self.bindings.iter().find(|(key, _)| key == answer) {
+            return Ok(action);
+        }
+        let name = Action::menu().resolve(answer)?;
+        Ok(name.parse().expect("menu options are action names"))
+    }
+
+    // Asks until an answer resolves, passing each rejection to `reject`.
+    // `None` when the input runs out.
+    pub fn ask(
+        &self,
+        input: &mut Input,
+        prompt: &str,
+        mut reject: impl FnMut(Rejection),
+    ) -> Option<Action> {
+        loop {
+            match self.resolve(&input.ask(prompt)?) {
+                Ok(action) => return Some(action),
+                Err(rejection) => reject(rejection),
+            }
+        }
+    }
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        Self::parse(include_str!("../keys/default.txt")).expect("built-in keys are valid")
+    }
+}
+
+// Carries out each `PlayerActionEvent`: an attack with the member's
+// `Damage`, raising their guard or a flee attempt. An attack without a
+// target, or an action that is not a move in battle, wastes the turn.
+// Runs first in a battle step so the rest of it sees the outcome.
+pub struct PlayerTurnSystem;
+
+impl ExclusiveSystem for PlayerTurnSystem {
+    fn run(&mut self, world: &mut World) {
+        for event in world.take_events::<PlayerActionEvent>() {
+            let PlayerActionEvent { entity, action, target } = event;
+            match (action, target) {
+                (Action::Attack, Some(target)) => {
+                    let damage = world.get_component::<Damage>(entity).map_or(0, |d| d.value);
+                    world.push_event(AttackEvent { attacker: entity, target, damage });
+                }
+                (Action::Defend, _) => {
+                    let name = world.get_component::<Name>(entity).map_or("Unknown", |n| n.0);
+                    set_defending(world, entity, true);
+                    world.push_event(MsgEvent::Defend { name });
+                }
+                (Action::Flee, _) => world.push_event(FleeAttemptEvent { entity }),
+                _ => world.push_event(MsgEvent::Hesitate),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ScriptedInput;
+    use crate::systems::is_defending;
+
+    #[test]
+    fn test_keys_resolve_to_actions() {
+        let keys = ActionMap::default();
+        assert_eq!(keys.describe(), "attack(a)/defend(d)/flee(f)/quit(q)");
+        assert_eq!(keys.resolve("a"), Ok(Action::Attack));
+        assert_eq!(keys.resolve("def"), Ok(Action::Defend));
+        assert_eq!(keys.resolve("step"), Ok(Action::Step));
+        assert_eq!(keys.resolve("st"), Err(Rejection::Unknown { answer: "st".into() }));
+
+        // Bound keys win over name prefixes, and a key rebound moves
+        let keys = ActionMap::parse("# vi-ish\nh = attack\nf = defend\nX = flee\n").unwrap();
+        assert_eq!(keys.describe(), "attack(h)/defend(f)/flee(x)/quit");
+        assert_eq!(keys.resolve("f"), Ok(Action::Defend));
+        assert_eq!(keys.resolve("fl"), Ok(Action::Flee));
+        assert_eq!(keys.resolve("x"), Ok(Action::Flee));
+        assert!(ActionMap::parse("k = kick").unwrap_err().contains("line 1"));
+        assert!(ActionMap::parse("attack").is_err());
+
+        let mut input = Input::new(ScriptedInput::new(["z", "h"]));
+        let mut rejected = Vec::new();
+        assert_eq!(keys.ask(&mut input, ">", |r| rejected.push(r)), Some(Action::Attack));
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(keys.ask(&mut input, ">", |_| ()), None);
+    }
+
+    #[test]
+    fn test_player_turn_system_carries_out_actions() {
+        let mut world = World::new();
+        let hero = world.create_entity();
+        let orc = world.create_entity();
+        world.add_component(hero, Name("Knight"));
+        world.add_component(hero, Damage { value: 7 });
+        let act = |world: &mut World, action, target| {
+            world.push_event(PlayerActionEvent { entity: hero, action, target });
+            PlayerTurnSystem.run(world);
+        };
+
+        act(&mut world, Action::Attack, Some(orc));
+        let attacks = world.take_events::<AttackEvent>();
+        assert_eq!((attacks[0].attacker, attacks[0].target, attacks[0].damage), (hero, orc, 7));
+
+        act(&mut world, Action::Defend, None);
+        assert!(is_defending(&world, hero));
+        assert_eq!(world.take_events::<MsgEvent>(), [MsgEvent::Defend { name: "Knight" }]);
+
+        act(&mut world, Action::Flee, None);
+        assert_eq!(world.take_events::<FleeAttemptEvent>().len(), 1);
+
+        act(&mut world, Action::Attack, None);
+        assert!(world.take_events::<AttackEvent>().is_empty());
+        assert_eq!(world.take_events::<MsgEvent>(), [MsgEvent::Hesitate]);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::attacks::{Attack, AttackEffect, AttackSet};
+use crate::components::{Defending, Health};
+use crate::events::EnemyTurnEvent;
+use crate::party::turn_order;
+use rusty_ecs_core::{AIDecisionSystem, Considerations, Entity, World};
+
+// One thing an enemy can do on its turn: an attack from its set on a hero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnemyMove {
+    pub target: Entity,
+    pub attack: Attack,
+}
+
+// Every attack of `enemy` against every living hero.
+pub fn enemy_moves(world: &World, enemy: Entity) -> Vec<EnemyMove> {
+    let Some(set) = world.get_component::<AttackSet>(enemy) else {
+        return Vec::new();
+    };
+    turn_order(world)
+        .into_iter()
+        .flat_map(|target| {
+            set.0.iter().map(move |attack| EnemyMove { target, attack: attack.clone() })
+        })
+        .collect()
+}
+
+fn health_share(world: &World, entity: Entity) -> f32 {
+    world
+        .get_component::<Health>(entity)
+        .map_or(1.0, |h| h.hp.max(0) as f32 / h.max.max(1) as f32)
+}
+
+// How enemies weigh their moves: they go after wounded heroes, save their
+// piercing attacks for guarding ones, drain life only when hurt and favour
+// heavier attacks.
+pub fn enemy_considerations() -> Considerations<EnemyMove> {
+    Considerations::new()
+        .with("wounded target", |world, _, m: &EnemyMove| {
+            1.0 - 0.5 * health_share(world, m.target)
+        })
+        .with("guard", |world, _, m: &EnemyMove| {
+            let guarding = world.get_component::<Defending>(m.target).is_some();
+            match (guarding, m.attack.effect) {
+                (true, AttackEffect::Pierce) => 1.0,
+                (true, _) => 0.5,
+                (false, AttackEffect::Pierce) => 0.8,
+                (false, _) => 1.0,
+            }
+        })
+        .with("own health", |world, enemy, m: &EnemyMove| match m.attack.effect {
+            AttackEffect::Heal(_) => 1.2 - health_share(world, enemy),
+            _ => 1.0,
+        })
+        .with("damage", |_, _, m: &EnemyMove| {
+            0.5 + m.attack.damage_percent as f32 / 400.0
+        })
+}
+
+// Decides the moves of the enemies whose turn it is, as `Intent<EnemyMove>`s
+// for `EnemyAttackSystem`, which runs right after it.
+pub fn enemy_decisions() -> AIDecisionSystem<EnemyMove> {
+    AIDecisionSystem::new(enemy_moves)
+        .actors(|world| world.read_events::<EnemyTurnEvent>().map(|turn| turn.enemy).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Damage, Enemy, Name};
+    use crate::party::spawn_party;
+    use rusty_ecs_core::{Intent, SystemExecutor};
+
+    #[test]
+    fn test_enemies_prefer_wounded_heroes_and_break_guards() {
+        let mut world = World::new();
+        let heroes = spawn_party(&mut world, 2);
+        let ogre = world.create_entity();
+        world.add_component(ogre, Name("Ogre"));
+        world.add_component(ogre, Enemy);
+        world.add_component(ogre, Health { hp: 20, max: 20 });
+        world.add_component(ogre, Damage { value: 10 });
+        world.add_component(ogre, AttackSet::parse("Club, Gore 80% pierce").unwrap());
+        world.add_component(ogre, enemy_considerations());
+        world.get_component_mut::<Health>(heroes[1]).unwrap().hp = 5;
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(enemy_decisions());
+
+        world.push_event(EnemyTurnEvent { enemy: ogre });
+        executor.run(&mut world);
+        let intent = world.take_events::<Intent<EnemyMove>>().remove(0);
+        assert_eq!((intent.action.target, intent.action.attack.name), (heroes[1], "Club"));
+        // Only enemies whose turn it is decide
+        world.take_events::<EnemyTurnEvent>();
+
+        world.add_component(heroes[1], Defending);
+        executor.run(&mut world);
+        assert!(world.take_events::<Intent<EnemyMove>>().is_empty());
+        world.push_event(EnemyTurnEvent { enemy: ogre });
+        executor.run(&mut world);
+        let intent = world.take_events::<Intent<EnemyMove>>().remove(0);
+        assert_eq!((intent.action.target, intent.action.attack.name), (heroes[1], "Gore"));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::ai::EnemyMove;
+use crate::components::{Damage, Defending, Health, Name};
+use crate::events::{AttackEvent, EnemyTurnEvent};
+use crate::messages::MsgEvent;
+use crate::party::turn_order;
+use rusty_ecs_core::{Entity, ExclusiveSystem, Intent, RngResource, World};
+
+// What an attack does besides dealing damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackEffect {
+    None,
+    // Breaks the target's guard, so defending does not halve the hit.
+    Pierce,
+    // The attacker recovers this much health.
+    Heal(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attack {
+    pub name: &'static str,
+    // Share of the attacker's Damage this attack deals.
+    pub damage_percent: i32,
+    pub effect: AttackEffect,
+}
+
+// The attacks an enemy picks from on its turn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttackSet(pub Vec<Attack>);
+
+impl AttackSet {
+    // Parses comma-separated attacks, each a name optionally followed by a
+    // damage share and an effect, e.g. `Slash, Heavy Swing 150%, Gore 80% pierce,
+    // Drain Life 60% heal+3`.
+    pub fn parse(text: &'static str) -> Result<Self, String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(parse_attack)
+            .collect::<Result<_, _>>()
+            .map(AttackSet)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.0.iter().map(|attack| attack.name).collect()
+    }
+}
+
+fn parse_attack(entry: &'static str) -> Result<Attack, String> {
+    let mut attack = Attack {
+        name: entry,
+        damage_percent: 100,
+        effect: AttackEffect::None,
+    };
+    // Modifiers are trailing words; whatever precedes them is the name
+    while let Some((rest, word)) = attack.name.rsplit_once(' ') {
+        if let Some(percent) = word.strip_suffix('%') {
+            attack.damage_percent = percent
+                .parse()
+                .ok()
+                .filter(|p| *p >= 0)
+                .ok_or_else(|| format!("attack '{}' has a bad damage share '{}'", entry, word))?;
+        } else if word == "pierce" {
+            attack.effect = AttackEffect::Pierce;
+        } else if let Some(amount) = word.strip_prefix("heal+") {
+            let amount = amount
+                .parse()
+                .map_err(|_| format!("attack '{}' has a bad heal '{}'", entry, word))?;
+            attack.effect = AttackEffect::Heal(amount);
+        } else {
+            break;
+        }
+        attack.name = rest.trim_end();
+    }
+    Ok(attack)
+}
+
+// Plays each EnemyTurnEvent: the enemy carries out the move the AI decided
+// on for it or, without an `Intent<EnemyMove>`, picks a living hero and one
+// of its attacks from the "ai" stream. It applies the attack's effect and
+// pushes the AttackEvent that DamageSystem resolves in the same run.
+pub struct EnemyAttackSystem;
+
+impl ExclusiveSystem for EnemyAttackSystem {
+    fn run(&mut self, world: &mut World) {
+        let mut intents = world.take_events::<Intent<EnemyMove>>();
+        for turn in world.take_events::<EnemyTurnEvent>() {
+            let enemy = turn.enemy;
+            if !world.is_enabled(enemy) {
+                continue;
+            }
+            let Some(damage) = world.get_component::<Damage>(enemy).map(|d| d.value) else {
+                continue;
+            };
+            let decided = intents.iter().position(|intent| intent.actor == enemy);
+            let Some((target, attack)) = decided
+                .map(|i| intents.swap_remove(i).action)
+                .map(|EnemyMove { target, attack }| (target, attack))
+                .or_else(|| random_move(world, enemy))
+            else {
+                continue;
+            };
+            let name = world.get_component::<Name>(enemy).map_or("Unknown", |n| n.0);
+
+            world.push_event(MsgEvent::UsesAttack { name, attack: attack.name });
+            match attack.effect {
+                AttackEffect::None => {}
+                AttackEffect::Pierce => {
+                    if world.remove_component::<Defending>(target).is_some() {
+                        let guard = world.get_component::<Name>(target).map_or("Unknown", |n| n.0);
+                        world.push_event(MsgEvent::GuardBroken { name: guard });
+                    }
+                }
+                AttackEffect::Heal(amount) => {
+                    if let Some(h) = world.get_component_mut::<Health>(enemy) {
+                        h.hp = (h.hp + amount).min(h.max);
+                        let (hp, max) = (h.hp, h.max);
+                        world.push_event(MsgEvent::Recovers { name, amount, hp, max });
+                    }
+                }
+            }
+            world.push_event(AttackEvent {
+                attacker: enemy,
+                target,
+                damage: damage * attack.damage_percent / 100,
+            });
+        }
+    }
+}
+
+fn random_move(world: &mut World, enemy: Entity) -> Option<(Entity, Attack)> {
+    let targets = turn_order(world);
+    let attacks = world.get_component::<AttackSet>(enemy)?.0.clone();
+    let ai = world.resource_mut::<RngResource>()?.stream("ai");
+    let target = *ai.pick(&targets)?;
+    Some((target, ai.pick(&attacks)?.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Enemy;
+    use crate::party::spawn_party;
+    use crate::systems::DamageSystem;
+    use rusty_ecs_core::SystemExecutor;
+
+    #[test]
+    fn test_parse_attacks() {
+        let set = AttackSet::parse("Slash, Heavy Swing 150%, Gore 80% pierce, Drain Life heal+3")
+            .unwrap();
+        assert_eq!(set.names(), vec!["Slash", "Heavy Swing", "Gore", "Drain Life"]);
+        assert_eq!(set.0[1].damage_percent, 150);
+        assert_eq!(set.0[2].effect, AttackEffect::Pierce);
+        let drain = Attack { name: "Drain Life", damage_percent: 100, effect: AttackEffect::Heal(3) };
+        assert_eq!(set.0[3], drain);
+        assert!(AttackSet::parse("Bite x%").is_err());
+        assert!(AttackSet::parse("Bite heal+lots").is_err());
+    }
+
+    #[test]
+    fn test_enemy_attacks_from_its_set() {
+        let mut world = World::new();
+        world.insert_resource(RngResource::new(7));
+        let hero = spawn_party(&mut world, 1)[0];
+        world.add_component(hero, Defending);
+        let max = world.get_component::<Health>(hero).unwrap().max;
+        let ogre = world.create_entity();
+        world.add_component(ogre, Name("Ogre"));
+        world.add_component(ogre, Enemy);
+        world.add_component(ogre, Health { hp: 10, max: 20 });
+        world.add_component(ogre, Damage { value: 10 });
+        let gore = Attack { name: "Gore", damage_percent: 50, effect: AttackEffect::Pierce };
+        world.add_component(ogre, AttackSet(vec![gore]));
+
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(EnemyAttackSystem);
+        executor.add_system(DamageSystem);
+        world.push_event(EnemyTurnEvent { enemy: ogre });
+        executor.run(&mut world);
+
+        let messages = world.take_events::<MsgEvent>();
+        assert_eq!(messages[0], MsgEvent::UsesAttack { name: "Ogre", attack: "Gore" });
+        assert!(messages.contains(&MsgEvent::GuardBroken { name: "Knight" }));
+        // Half the ogre's damage, not halved again by the broken guard
+        assert_eq!(world.get_component::<Health>(hero).unwrap().hp, max - 5);
+        assert!(world.get_component::<Defending>(hero).is_none());
+    }
+}
@@ -0,0 +1,472 @@
+use crate::attacks::AttackSet;
+use crate::components::{Dead, Enemy, Health, Name, Player};
+use crate::events::HealthDepleted;
+use crate::loot::{Equipment, LootTable};
+use crate::messages::MsgEvent;
+use crate::party::PartyMember;
+use crate::script::EncounterScript;
+use crate::spawner::{EnemyPrefab, Spawner};
+use rusty_ecs_core::{
+    Disabled, Entity, ExclusiveSystem, IncludeDisabled, Persist, Snapshot, SnapshotError, With,
+    World,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Encounter {
+    Battle(Vec<EnemyPrefab>),
+    // Restores this percentage of each player's max HP, reviving fallen ones.
+    Rest(i32),
+}
+
+// The ordered list of encounters a run goes through, read from a campaign
+// file (see `campaigns/default.txt` for the format).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Campaign {
+    encounters: Vec<Encounter>,
+}
+
+impl Campaign {
+    pub fn builtin() -> Self {
+        Self::parse(include_str!("../campaigns/default.txt")).expect("built-in campaign is valid")
+    }
+
+    // Takes `'static` text because enemy names end up in `Name` components.
+    pub fn parse(text: &'static str) -> Result<Self, String> {
+        let mut encounters = Vec::new();
+        // Scripts are parsed once every enemy they could summon is known:
+        // (encounter, enemy, script text, line number)
+        let mut scripts = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |reason: &str| format!("line {}: {}", number + 1, reason);
+            let (kind, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `battle = ...` or `rest = ...`"))?;
+            let encounter = match kind.trim() {
+                "battle" => {
+                    let mut enemies = Vec::new();
+                    for enemy in value.split(';') {
+                        let (prefab, script) = parse_enemy(enemy).map_err(|reason| err(&reason))?;
+                        scripts.push((encounters.len(), enemies.len(), script, number));
+                        enemies.push(prefab);
+                    }
+                    Encounter::Battle(enemies)
+                }
+                "rest" => Encounter::Rest(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| err("rest takes a percentage"))?,
+                ),
+                other => return Err(err(&format!("unknown encounter kind '{}'", other))),
+            };
+            encounters.push(encounter);
+        }
+        if !encounters.iter().any(|e| matches!(e, Encounter::Battle(_))) {
+            return Err(String::from("campaign has no battles"));
+        }
+
+        // Summons copy the first enemy of that name, without its own script
+        let bestiary: Vec<EnemyPrefab> = encounters
+            .iter()
+            .flat_map(|e| match e {
+                Encounter::Battle(enemies) => enemies.clone(),
+                Encounter::Rest(_) => Vec::new(),
+            })
+            .collect();
+        let lookup = |name: &str| bestiary.iter().find(|p| p.name == name).cloned();
+        for (encounter, enemy, script, number) in scripts {
+            let script = EncounterScript::parse(script, lookup)
+                .map_err(|reason| format!("line {}: {}", number + 1, reason))?;
+            if let Encounter::Battle(enemies) = &mut encounters[encounter] {
+                enemies[enemy].script = script;
+            }
+        }
+        Ok(Self { encounters })
+    }
+
+    pub fn encounters(&self) -> &[Encounter] {
+        &self.encounters
+    }
+
+    pub fn battle_count(&self) -> usize {
+        self.battles_before(self.encounters.len())
+    }
+
+    // Every battle as one wave, for a horde spawner.
+    pub fn waves(&self) -> Vec<Vec<EnemyPrefab>> {
+        self.encounters
+            .iter()
+            .filter_map(|e| match e {
+                Encounter::Battle(enemies) => Some(enemies.clone()),
+                Encounter::Rest(_) => None,
+            })
+            .collect()
+    }
+
+    fn battles_before(&self, stage: usize) -> usize {
+        self.encounters[..stage]
+            .iter()
+            .filter(|e| matches!(e, Encounter::Battle(_)))
+            .count()
+    }
+}
+
+// Returns the prefab without its script, along with the script's text.
+fn parse_enemy(text: &'static str) -> Result<(EnemyPrefab, &'static str), String> {
+    let fields: Vec<&'static str> = text.split('|').map(str::trim).collect();
+    let (name, hp, damage, attacks, loot, script) = match fields[..] {
+        [name, hp, damage, attacks] => (name, hp, damage, attacks, "", ""),
+        [name, hp, damage, attacks, loot] => (name, hp, damage, attacks, loot, ""),
+        [name, hp, damage, attacks, loot, script] => (name, hp, damage, attacks, loot, script),
+        _ => {
+            return Err(String::from(
+                "enemies are `Name | hp | damage | attacks [| loot [| script]]`",
+            ))
+        }
+    };
+    let number = |field: &str| {
+        field
+            .parse::<i32>()
+            .map_err(|_| format!("'{}' is not a number", field))
+    };
+    let attacks = AttackSet::parse(attacks)?;
+    if attacks.0.is_empty() {
+        return Err(format!("{} has no attacks", name));
+    }
+    let prefab = EnemyPrefab {
+        name,
+        hp: number(hp)?,
+        damage: number(damage)?,
+        attacks,
+        loot: LootTable::parse(loot)?,
+        script: EncounterScript::default(),
+    };
+    Ok((prefab, script))
+}
+
+// Where the player is in the campaign. `stage` indexes `Campaign::encounters`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CampaignState {
+    pub stage: u32,
+    pub in_battle: bool,
+    pub complete: bool,
+}
+
+impl Persist for CampaignState {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.stage.write(out);
+        self.in_battle.write(out);
+        self.complete.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(CampaignState {
+            stage: u32::read(input)?,
+            in_battle: bool::read(input)?,
+            complete: bool::read(input)?,
+        })
+    }
+}
+
+// World state captured right before a battle starts, left as a resource for
+// the front-end to write out. Restoring it into a fresh world resumes the
+// campaign at that battle.
+pub struct Checkpoint(pub Snapshot);
+
+pub fn register_persistent(world: &mut World) {
+    crate::components::register_persistent(world);
+    world.register_persistent::<PartyMember>("PartyMember");
+    world.register_persistent::<Equipment>("Equipment");
+    world.register_persistent_resource::<CampaignState>("CampaignState");
+}
+
+// Pushes a HealthDepleted as soon as an entity's health drops to zero, for
+// DeathSystem to handle in the same run.
+pub fn register_watches(world: &mut World) {
+    world.watch::<Health>(
+        |h| h.hp <= 0,
+        |world, entity| world.push_event(HealthDepleted { entity }),
+    );
+}
+
+pub fn resume(world: &mut World, bytes: &[u8]) -> Result<(), SnapshotError> {
+    let snapshot = Snapshot::from_bytes(bytes)?;
+    world.restore_snapshot(&snapshot)?;
+    Ok(())
+}
+
+// Once no enemy is left standing, clears away the bodies DeathSystem left
+// disabled. If no spawner has waves to come either, the battle is over: it
+// applies rest points, checkpoints and starts the next battle by placing a
+// `Spawner` for it. `SpawnerSystem` has to run after this system.
+//
+// An endless spawner never runs out, so placing one before the first run
+// turns the campaign into a horde run that only ends with the party.
+pub struct ProgressionSystem {
+    campaign: Campaign,
+}
+
+impl ProgressionSystem {
+    pub fn new(campaign: Campaign) -> Self {
+        Self { campaign }
+    }
+}
+
+impl ExclusiveSystem for ProgressionSystem {
+    fn run(&mut self, world: &mut World) {
+        let mut state = world.resource::<CampaignState>().copied().unwrap_or_default();
+        if state.complete {
+            return;
+        }
+
+        let field_clear = world.query_entities::<Enemy>().is_empty();
+        if field_clear {
+            world.run_scoped(|commands, world| {
+                for (body, _) in world.query::<&Enemy, With<Disabled>>() {
+                    commands.destroy_entity(body);
+                }
+            });
+        }
+
+        let spawners = world.query_entities::<Spawner>();
+        let waves_left = spawners
+            .iter()
+            .any(|&s| world.get_component::<Spawner>(s).is_some_and(|s| !s.is_exhausted()));
+        if waves_left || !field_clear {
+            return;
+        }
+        for spawner in spawners {
+            world.destroy_entity(spawner);
+        }
+        if state.in_battle {
+            state.stage += 1;
+            state.in_battle = false;
+        }
+
+        let encounters = self.campaign.encounters();
+        while let Some(Encounter::Rest(percent)) = encounters.get(state.stage as usize) {
+            rest(world, *percent);
+            state.stage += 1;
+        }
+
+        match encounters.get(state.stage as usize) {
+            Some(Encounter::Battle(enemies)) => {
+                world.insert_resource(state);
+                let checkpoint = world.snapshot();
+                world.insert_resource(Checkpoint(checkpoint));
+
+                let spawner = world.create_entity();
+                world.add_component(spawner, Spawner::new(vec![enemies.clone()], 0));
+                state.in_battle = true;
+                world.push_event(MsgEvent::EncounterBegins {
+                    number: self.campaign.battles_before(state.stage as usize) as u32 + 1,
+                    total: self.campaign.battle_count() as u32,
+                });
+            }
+            // Only battles and rests exist, so this is the end of the campaign
+            _ => {
+                state.complete = true;
+                world.push_event(MsgEvent::Victory);
+            }
+        }
+        world.insert_resource(state);
+    }
+}
+
+fn rest(world: &mut World, percent: i32) {
+    let players: Vec<Entity> = world
+        .query::<&Health, (With<Player>, IncludeDisabled)>()
+        .map(|(e, _)| e)
+        .collect();
+    for player in players {
+        let name = world.get_component::<Name>(player).map_or("Unknown", |n| n.0);
+        if let Some(h) = world.get_component_mut::<Health>(player) {
+            h.hp = (h.hp + h.max * percent / 100).min(h.max);
+            let (hp, max) = (h.hp, h.max);
+            world.push_event(MsgEvent::Rested { name, hp, max });
+            if hp > 0 && world.remove_component::<Dead>(player).is_some() {
+                world.set_enabled(player, true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Damage;
+    use crate::spawner::SpawnerSystem;
+    use crate::script::TriggerAction;
+    use crate::systems::{DeathSystem, StatScalingSystem};
+    use rusty_ecs_core::SystemExecutor;
+
+    const CAMPAIGN: &str = "
+        # two short fights
+        battle = Rat | 4 | 1 | Nibble
+        rest = 50
+        battle = Bat | 5 | 2 | Bite ; Bat | 5 | 2 | Screech, Bite | Bat Wing +1/+0 50% | 50% summon Rat x2
+    ";
+
+    fn setup() -> (World, SystemExecutor, Entity) {
+        let mut world = World::new();
+        register_persistent(&mut world);
+        register_watches(&mut world);
+        let hero = world.create_entity();
+        world.add_component(hero, Name("Hero"));
+        world.add_component(hero, Player);
+        world.add_component(hero, Health { hp: 10, max: 20 });
+        world.add_component(hero, Damage { value: 5 });
+
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(DeathSystem::new());
+        executor.add_exclusive_system(ProgressionSystem::new(Campaign::parse(CAMPAIGN).unwrap()));
+        executor.add_exclusive_system(SpawnerSystem);
+        executor.add_system(StatScalingSystem);
+        executor.run(&mut world);
+        (world, executor, hero)
+    }
+
+    fn defeat_all(world: &mut World) {
+        for e in world.query_entities::<Enemy>() {
+            world.get_component_mut::<Health>(e).unwrap().hp = 0;
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let campaign = Campaign::parse(CAMPAIGN).unwrap();
+        assert_eq!(campaign.encounters().len(), 3);
+        assert_eq!(campaign.battle_count(), 2);
+        assert_eq!(campaign.encounters()[1], Encounter::Rest(50));
+        let Encounter::Battle(bats) = &campaign.encounters()[2] else {
+            panic!("expected a battle");
+        };
+        assert_eq!(bats[1].attacks.names(), vec!["Screech", "Bite"]);
+        assert_eq!(bats[1].loot.0[0].item.name, "Bat Wing");
+        let TriggerAction::Summon(rats) = &bats[1].script.0[0].action else {
+            panic!("expected a summon");
+        };
+        assert_eq!(rats.len(), 2);
+        assert_eq!(rats[0].name, "Rat");
+        assert!(Campaign::parse("battle = Rat | 4 | 1 | Nibble | | 50% summon Ghost").is_err());
+
+        assert!(Campaign::parse("rest = 10").is_err());
+        assert!(Campaign::parse("battle = Rat | 4 | x | Nibble").is_err());
+        assert!(Campaign::parse("ambush = Rat").is_err());
+        assert_eq!(Campaign::builtin().battle_count(), 3);
+    }
+
+    #[test]
+    fn test_progression_rests_and_finishes() {
+        let (mut world, mut executor, hero) = setup();
+        assert_eq!(world.query_entities::<Enemy>().len(), 1);
+        assert_eq!(
+            world.take_events::<MsgEvent>(),
+            vec![MsgEvent::EncounterBegins { number: 1, total: 2 }]
+        );
+
+        // The hero falls with the rat and the rest revives them
+        defeat_all(&mut world);
+        world.get_component_mut::<Health>(hero).unwrap().hp = 0;
+        executor.run(&mut world);
+        assert_eq!(
+            world.take_events::<MsgEvent>(),
+            vec![
+                MsgEvent::Collapses { name: "Hero" },
+                MsgEvent::EnemyDefeated { name: "Rat" },
+                MsgEvent::Rested { name: "Hero", hp: 10, max: 20 },
+                MsgEvent::EncounterBegins { number: 2, total: 2 },
+            ]
+        );
+        assert_eq!(world.query_entities::<Enemy>().len(), 2);
+        assert_eq!(world.get_component::<Health>(hero).unwrap().hp, 10);
+        assert!(world.is_enabled(hero) && !world.has::<Dead>(hero));
+
+        defeat_all(&mut world);
+        executor.run(&mut world);
+        assert!(world.resource::<CampaignState>().unwrap().complete);
+        assert_eq!(world.take_events::<MsgEvent>().last(), Some(&MsgEvent::Victory));
+        assert!(world.query_entities::<Enemy>().is_empty());
+    }
+
+    #[test]
+    fn test_defeated_enemies_are_disabled_until_the_field_is_clear() {
+        let (mut world, mut executor, _) = setup();
+        defeat_all(&mut world);
+        executor.run(&mut world);
+        world.take_events::<MsgEvent>();
+
+        let bats = world.query_entities::<Enemy>();
+        world.get_component_mut::<Health>(bats[0]).unwrap().hp = 0;
+        executor.run(&mut world);
+        assert_eq!(world.query_entities::<Enemy>(), vec![bats[1]]);
+        // The body keeps its components for looting and inspection
+        assert!(!world.is_enabled(bats[0]));
+        assert_eq!(world.get_component::<Name>(bats[0]).map(|n| n.0), Some("Bat"));
+        assert_eq!(world.count::<Enemy>(), 2);
+
+        defeat_all(&mut world);
+        executor.run(&mut world);
+        assert!(!world.is_alive(bats[0]));
+        assert_eq!(world.count::<Enemy>(), 0);
+    }
+
+    #[test]
+    fn test_checkpoint_resumes_at_the_next_battle() {
+        let (mut world, mut executor, hero) = setup();
+        defeat_all(&mut world);
+        world.get_component_mut::<Health>(hero).unwrap().hp = 3;
+        executor.run(&mut world);
+        let bytes = world.remove_resource::<Checkpoint>().unwrap().0.to_bytes();
+
+        let mut resumed = World::new();
+        register_persistent(&mut resumed);
+        resume(&mut resumed, &bytes).unwrap();
+        let players = resumed.query_entities::<Player>();
+        assert_eq!(players.len(), 1);
+        let health = *resumed.get_component::<Health>(players[0]).unwrap();
+        assert_eq!((health.hp, health.max), (13, 20));
+
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(ProgressionSystem::new(Campaign::parse(CAMPAIGN).unwrap()));
+        executor.add_exclusive_system(SpawnerSystem);
+        executor.run(&mut resumed);
+        assert_eq!(resumed.query_entities::<Enemy>().len(), 2);
+        assert_eq!(
+            resumed.take_events::<MsgEvent>(),
+            vec![MsgEvent::EncounterBegins { number: 2, total: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_endless_spawner_turns_the_campaign_into_a_horde() {
+        let mut world = World::new();
+        register_watches(&mut world);
+        let campaign = Campaign::parse(CAMPAIGN).unwrap();
+        let horde = world.create_entity();
+        world.add_component(horde, Spawner::new(campaign.waves(), 3).endless());
+
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(DeathSystem::new());
+        executor.add_exclusive_system(ProgressionSystem::new(campaign));
+        executor.add_exclusive_system(SpawnerSystem);
+        executor.add_system(StatScalingSystem);
+        for _ in 0..4 {
+            executor.run(&mut world);
+            defeat_all(&mut world);
+        }
+        executor.run(&mut world);
+
+        let messages = world.take_events::<MsgEvent>();
+        assert!(messages.contains(&MsgEvent::WaveIncoming { number: 5 }));
+        assert!(!messages.iter().any(|m| matches!(m, MsgEvent::EncounterBegins { .. })));
+        assert_eq!(world.get_component::<Spawner>(horde).unwrap().waves_spawned(), 5);
+        // Only the latest wave is left; earlier bodies were cleared away
+        assert_eq!(world.query_entities::<Enemy>().len(), 1);
+        assert_eq!(world.count::<Enemy>(), 1);
+    }
+}
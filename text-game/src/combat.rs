@@ -0,0 +1,153 @@
+// Everything a damage model needs to know about one attack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttackContext {
+    pub damage: i32,
+    pub attack_bonus: i32,
+    pub armor: i32,
+    pub defending: bool,
+    // Uniform rolls in [0, 1) drawn by `DamageSystem` from the "combat" RNG
+    // stream, so models stay deterministic for a given seed.
+    pub hit_roll: f32,
+    pub crit_roll: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitOutcome {
+    Miss,
+    Hit(i32),
+    Critical(i32),
+}
+
+// The combat math behind `DamageSystem`. Insert a `CombatRules` resource
+// with another model to change how attacks resolve.
+pub trait DamageModel: Send + Sync {
+    fn resolve(&self, attack: &AttackContext) -> HitOutcome;
+
+    // How `outcome` was computed, shown at verbose output.
+    fn explain(&self, _attack: &AttackContext, _outcome: HitOutcome) -> Option<String> {
+        None
+    }
+}
+
+// Armor subtracts from the attack, crits multiply what is left and defending
+// halves the result. With the default settings nothing misses or crits,
+// which is the game's original formula.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StandardModel {
+    pub miss_chance: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+}
+
+impl Default for StandardModel {
+    fn default() -> Self {
+        Self {
+            miss_chance: 0.0,
+            crit_chance: 0.0,
+            crit_multiplier: 2.0,
+        }
+    }
+}
+
+impl DamageModel for StandardModel {
+    fn resolve(&self, attack: &AttackContext) -> HitOutcome {
+        if attack.hit_roll < self.miss_chance {
+            return HitOutcome::Miss;
+        }
+        let critical = attack.crit_roll < self.crit_chance;
+        let mut damage = (attack.damage + attack.attack_bonus - attack.armor).max(0);
+        if critical {
+            damage = (damage as f32 * self.crit_multiplier) as i32;
+        }
+        if attack.defending {
+            damage /= 2;
+        }
+        if critical {
+            HitOutcome::Critical(damage)
+        } else {
+            HitOutcome::Hit(damage)
+        }
+    }
+
+    fn explain(&self, attack: &AttackContext, outcome: HitOutcome) -> Option<String> {
+        let (result, critical) = match outcome {
+            HitOutcome::Miss => return None,
+            HitOutcome::Hit(damage) => (damage, false),
+            HitOutcome::Critical(damage) => (damage, true),
+        };
+        let mut text = format!(
+            "{} damage + {} bonus - {} armor",
+            attack.damage, attack.attack_bonus, attack.armor
+        );
+        if critical {
+            text = format!("({}) x {} critical", text, self.crit_multiplier);
+        }
+        if attack.defending {
+            text = format!("({}) / 2 defending", text);
+        }
+        Some(format!("{} = {}", text, result))
+    }
+}
+
+// Resource holding the active damage model. `DamageSystem` falls back to
+// `StandardModel::default()` when it is absent.
+pub struct CombatRules(pub Box<dyn DamageModel>);
+
+impl CombatRules {
+    pub fn new(model: impl DamageModel + 'static) -> Self {
+        Self(Box::new(model))
+    }
+}
+
+impl Default for CombatRules {
+    fn default() -> Self {
+        Self::new(StandardModel::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attack(hit_roll: f32, crit_roll: f32, defending: bool) -> AttackContext {
+        AttackContext {
+            damage: 7,
+            attack_bonus: 2,
+            armor: 1,
+            defending,
+            hit_roll,
+            crit_roll,
+        }
+    }
+
+    #[test]
+    fn test_default_matches_halve_on_defend() {
+        let model = StandardModel::default();
+        assert_eq!(model.resolve(&attack(0.0, 0.0, false)), HitOutcome::Hit(8));
+        assert_eq!(model.resolve(&attack(0.0, 0.0, true)), HitOutcome::Hit(4));
+    }
+
+    #[test]
+    fn test_explain_shows_the_formula() {
+        let model = StandardModel::default();
+        let defended = attack(0.0, 0.0, true);
+        assert_eq!(
+            model.explain(&defended, model.resolve(&defended)).as_deref(),
+            Some("(7 damage + 2 bonus - 1 armor) / 2 defending = 4")
+        );
+        assert_eq!(model.explain(&defended, HitOutcome::Miss), None);
+    }
+
+    #[test]
+    fn test_miss_and_crit_follow_rolls() {
+        let model = StandardModel {
+            miss_chance: 0.1,
+            crit_chance: 0.25,
+            crit_multiplier: 1.5,
+        };
+        assert_eq!(model.resolve(&attack(0.05, 0.0, false)), HitOutcome::Miss);
+        assert_eq!(model.resolve(&attack(0.5, 0.2, false)), HitOutcome::Critical(12));
+        assert_eq!(model.resolve(&attack(0.5, 0.2, true)), HitOutcome::Critical(6));
+        assert_eq!(model.resolve(&attack(0.5, 0.9, false)), HitOutcome::Hit(8));
+    }
+}
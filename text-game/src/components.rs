@@ -0,0 +1,187 @@
+use crate::loot::Equipment;
+use rusty_ecs_core::{Persist, SnapshotError, WeakEntity, With, World};
+
+#[derive(Clone, Copy)]
+pub struct Name(pub &'static str);
+
+#[derive(Clone, Copy)]
+pub struct Health {
+    pub hp: i32,
+    pub max: i32,
+}
+
+#[derive(Clone, Copy)]
+pub struct Damage {
+    pub value: i32,
+}
+
+// Marker for a hero bracing this turn; DamageSystem halves damage to them.
+#[derive(Clone, Copy, Default)]
+pub struct Defending;
+
+// How quick an entity is; heroes faster than the enemies they face are more
+// likely to flee. Entities without one count as `Speed::DEFAULT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Speed(pub i32);
+
+impl Speed {
+    pub const DEFAULT: Speed = Speed(5);
+}
+
+// Unscaled enemy stats, turned into Health and Damage by StatScalingSystem.
+#[derive(Clone, Copy)]
+pub struct BaseStats {
+    pub hp: i32,
+    pub damage: i32,
+}
+
+// Marker DeathSystem puts on entities whose Health ran out, alongside
+// disabling them. Resting removes it again.
+#[derive(Clone, Copy, Default)]
+pub struct Dead;
+
+// An enemy brought into the battle by another one. It takes its turns
+// right after its summoner, for as long as the summoner exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summoned {
+    pub by: WeakEntity,
+}
+
+#[derive(Clone, Copy)]
+pub struct Player;
+
+#[derive(Clone, Copy)]
+pub struct Enemy;
+
+// Anything that deals damage can be hit back, so DamageSystem expects it to
+// have Health. One spawned without it gets a single hit point rather than
+// dropping out of the fight unnoticed.
+pub fn register_required_components(world: &mut World) {
+    world.register_required::<Player, Equipment>();
+    world.register_required_with::<Damage, Health>(|| Health { hp: 1, max: 1 });
+}
+
+// Player state carried between battles through campaign checkpoints. Names
+// are static strings and are reattached by `party::restore_names` after loading.
+pub fn register_persistent(world: &mut World) {
+    world.register_persistent::<Player>("Player");
+    world.register_persistent::<Health>("Health");
+    world.register_persistent::<Damage>("Damage");
+    world.register_persistent::<Defending>("Defending");
+    world.register_persistent::<Dead>("Dead");
+    world.register_persistent::<Speed>("Speed");
+}
+
+impl Persist for Player {
+    fn write(&self, _out: &mut Vec<u8>) {}
+
+    fn read(_input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Player)
+    }
+}
+
+impl Persist for Health {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.hp.write(out);
+        self.max.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Health {
+            hp: i32::read(input)?,
+            max: i32::read(input)?,
+        })
+    }
+}
+
+impl Persist for Damage {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.value.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Damage { value: i32::read(input)? })
+    }
+}
+
+impl Persist for Defending {
+    fn write(&self, _out: &mut Vec<u8>) {}
+
+    fn read(_input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Defending)
+    }
+}
+
+impl Persist for Speed {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.0.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Speed(i32::read(input)?))
+    }
+}
+
+impl Persist for Dead {
+    fn write(&self, _out: &mut Vec<u8>) {}
+
+    fn read(_input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Dead)
+    }
+}
+
+pub fn register_invariants(world: &mut World) {
+    world.add_invariant::<Health>("hp within 0..=max", |h| (0..=h.max).contains(&h.hp));
+    world.add_world_invariant("player is not an enemy", |world| {
+        world
+            .query::<&Player, With<Enemy>>()
+            .map(|(e, _)| e)
+            .collect()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_gets_equipment() {
+        let mut world = World::new();
+        register_required_components(&mut world);
+
+        let player = world.create_entity();
+        world.add_component(player, Player);
+
+        assert!(world.get_component::<Equipment>(player).is_some());
+    }
+
+    #[test]
+    fn test_damage_dealer_gets_health() {
+        let mut world = World::new();
+        register_required_components(&mut world);
+
+        let goblin = world.create_entity();
+        world.add_component(goblin, Damage { value: 3 });
+        let hp = world.get_component::<Health>(goblin).map(|h| (h.hp, h.max));
+        assert_eq!(hp, Some((1, 1)));
+
+        // Health given up front is left alone
+        let ogre = world.create_entity();
+        world.add_component(ogre, Health { hp: 30, max: 30 });
+        world.add_component(ogre, Damage { value: 10 });
+        assert_eq!(world.get_component::<Health>(ogre).unwrap().hp, 30);
+    }
+
+    #[test]
+    fn test_invariants_flag_overhealed_entity() {
+        let mut world = World::new();
+        register_invariants(&mut world);
+
+        let e = world.create_entity();
+        world.add_component(e, Health { hp: 10, max: 10 });
+        assert!(world.check_invariants().is_empty());
+
+        world.get_component_mut::<Health>(e).unwrap().hp = 11;
+        assert_eq!(world.check_invariants()[0].entity, e);
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::str::FromStr;
+
+// Resource selecting how hard spawned enemies are. Enemies carry their
+// unscaled `BaseStats` and `StatScalingSystem` derives Health/Damage from them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn health_percent(self) -> i32 {
+        match self {
+            Difficulty::Easy => 75,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 150,
+        }
+    }
+
+    pub fn damage_percent(self) -> i32 {
+        match self {
+            Difficulty::Easy => 70,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 130,
+        }
+    }
+
+    // Scaled values never drop below 1 so no enemy spawns dead or harmless.
+    pub fn scale_health(self, base: i32) -> i32 {
+        (base * self.health_percent() / 100).max(1)
+    }
+
+    pub fn scale_damage(self, base: i32) -> i32 {
+        (base * self.damage_percent() / 100).max(1)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            other => Err(format!(
+                "unknown difficulty '{}', expected easy, normal, or hard",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("HARD".parse(), Ok(Difficulty::Hard));
+        assert_eq!("easy".parse(), Ok(Difficulty::Easy));
+        assert!("nightmare".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_scaling_has_a_floor() {
+        assert_eq!(Difficulty::Hard.scale_health(12), 18);
+        assert_eq!(Difficulty::Normal.scale_damage(5), 5);
+        assert_eq!(Difficulty::Easy.scale_damage(1), 1);
+    }
+}
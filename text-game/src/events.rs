@@ -0,0 +1,64 @@
+use crate::actions::Action;
+use crate::spawner::EnemyPrefab;
+use rusty_ecs_core::{Entity, EntityEvent, Event};
+
+#[derive(Event)]
+pub struct AttackEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+    pub damage: i32,
+}
+
+impl EntityEvent for AttackEvent {
+    fn entity(&self) -> Entity {
+        self.target
+    }
+}
+
+// It is `enemy`'s turn; EnemyAttackSystem picks its target and attack.
+#[derive(Event)]
+pub struct EnemyTurnEvent {
+    pub enemy: Entity,
+}
+
+// `entity` tries to flee the battle instead of acting; FleeSystem rolls it.
+#[derive(Event)]
+pub struct FleeAttemptEvent {
+    pub entity: Entity,
+}
+
+// `summoner` calls `prefab` into the battle; SummonSystem spawns it.
+#[derive(Event)]
+pub struct SummonEvent {
+    pub summoner: Entity,
+    pub prefab: EnemyPrefab,
+}
+
+// An enemy's Health reached zero; `by` landed the final blow.
+#[derive(Event)]
+pub struct EnemyDefeatedEvent {
+    pub enemy: Entity,
+    pub by: Entity,
+}
+
+// An entity's Health reached zero, pushed by the watch from
+// `campaign::register_watches` whatever lowered it.
+#[derive(Event)]
+pub struct HealthDepleted {
+    pub entity: Entity,
+}
+
+// DeathSystem marked `entity` as Dead and disabled it.
+#[derive(Event)]
+pub struct EntityDied {
+    pub entity: Entity,
+}
+
+// `entity` takes its turn doing `action`, at `target` if it needs one;
+// PlayerTurnSystem carries it out.
+#[derive(Event)]
+pub struct PlayerActionEvent {
+    pub entity: Entity,
+    pub action: Action,
+    pub target: Option<Entity>,
+}
@@ -0,0 +1,86 @@
+use crate::actions::PlayerTurnSystem;
+use crate::ai::enemy_decisions;
+use crate::attacks::EnemyAttackSystem;
+use crate::campaign::{Campaign, ProgressionSystem};
+use crate::loot::{LootSystem, StatAggregationSystem};
+use crate::messages::{ColoredLogSystem, MessageFilterSystem, MsgEvent};
+use crate::script::TriggerSystem;
+use crate::spawner::{SpawnerSystem, SummonSystem};
+use crate::stats::StatsSystem;
+use crate::systems::{DamageSystem, DeathSystem, FleeSystem, StatScalingSystem};
+use rusty_ecs_core::{ExclusiveSystem, SystemExecutor, World};
+
+// Top-level screens, kept in a `GameStateStack<GameState>` resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    MainMenu,
+    Battle,
+    GameOver,
+}
+
+pub struct FarewellSystem;
+
+impl ExclusiveSystem for FarewellSystem {
+    fn run(&mut self, world: &mut World) {
+        world.push_event(MsgEvent::Goodbye);
+    }
+}
+
+// The game's systems, which only run while a battle is on top of the stack.
+pub fn game_executor(campaign: Campaign) -> SystemExecutor {
+    let mut executor = SystemExecutor::new();
+    let battle = &mut executor.state_set(GameState::Battle).on_update;
+    battle.add_exclusive_system(PlayerTurnSystem);
+    battle.add_exclusive_system(enemy_decisions());
+    battle.add_exclusive_system(EnemyAttackSystem);
+    battle.add_system(DamageSystem);
+    battle.add_exclusive_system(DeathSystem::new());
+    battle.add_exclusive_system(TriggerSystem);
+    battle.add_system(SummonSystem);
+    battle.add_exclusive_system(FleeSystem);
+    battle.add_exclusive_system(StatsSystem);
+    battle.add_system(LootSystem);
+    battle.add_system(StatAggregationSystem);
+    battle.add_exclusive_system(ProgressionSystem::new(campaign));
+    battle.add_exclusive_system(SpawnerSystem);
+    battle.add_system(StatScalingSystem);
+    battle.add_system(MessageFilterSystem);
+    battle.set_validation(cfg!(debug_assertions));
+    executor
+        .state_set(GameState::GameOver)
+        .on_enter
+        .add_exclusive_system(FarewellSystem);
+    // After the state sets, so it renders everything they said this run
+    executor.add_system(ColoredLogSystem);
+    executor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Enemy;
+    use crate::party::spawn_party;
+    use rusty_ecs_core::GameStateStack;
+
+    #[test]
+    fn test_battle_systems_wait_for_battle_state() {
+        let mut world = World::new();
+        spawn_party(&mut world, 1);
+        world.insert_resource(GameStateStack::new(GameState::MainMenu));
+        let mut executor = game_executor(Campaign::builtin());
+
+        executor.run(&mut world);
+        assert!(!world.any::<Enemy>());
+
+        let stack = world.resource_mut::<GameStateStack<GameState>>().unwrap();
+        stack.replace(GameState::Battle);
+        executor.run(&mut world);
+        assert!(world.any::<Enemy>());
+        world.take_events::<MsgEvent>();
+
+        let stack = world.resource_mut::<GameStateStack<GameState>>().unwrap();
+        stack.replace(GameState::GameOver);
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<MsgEvent>(), vec![MsgEvent::Goodbye]);
+    }
+}
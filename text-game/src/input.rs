@@ -0,0 +1,266 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+// Where the player's answers come from. The game reads every prompt through
+// the `Input` resource, so a terminal, a script or a socket can drive it.
+pub trait InputSource: Send + Sync {
+    // Shows `prompt` and returns the next line without its line ending, or
+    // `None` once the source is exhausted or closed.
+    fn read_line(&mut self, prompt: &str) -> Option<String>;
+}
+
+// Resource holding the active input source.
+pub struct Input(Box<dyn InputSource>);
+
+impl Input {
+    pub fn new(source: impl InputSource + 'static) -> Self {
+        Input(Box::new(source))
+    }
+
+    // Reads an answer trimmed and lowercased, the form every menu matches on.
+    pub fn ask(&mut self, prompt: &str) -> Option<String> {
+        self.0.read_line(prompt).map(|line| line.trim().to_lowercase())
+    }
+}
+
+// So a source can wrap whatever `Input` was going to read from.
+impl InputSource for Input {
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        self.0.read_line(prompt)
+    }
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Input::new(TerminalInput)
+    }
+}
+
+// Reads from stdin, printing the prompt to stdout.
+pub struct TerminalInput;
+
+impl InputSource for TerminalInput {
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        print!("{} ", prompt);
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end_matches(['\r', '\n']).to_string()),
+        }
+    }
+}
+
+// Reads from any buffered reader: a script file, or a `BufReader` around a
+// `TcpStream` for a remote frontend. Answers are echoed after the prompt so
+// the output reads like a terminal session.
+pub struct ReaderInput<R>(R);
+
+impl<R: BufRead + Send + Sync> ReaderInput<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderInput(reader)
+    }
+}
+
+impl<R: BufRead + Send + Sync> InputSource for ReaderInput<R> {
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        let mut line = String::new();
+        match self.0.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                println!("{} {}", prompt, line);
+                Some(line)
+            }
+        }
+    }
+}
+
+// Answers from a fixed list without printing anything, recording the prompts
+// it was asked. For tests.
+#[derive(Default)]
+pub struct ScriptedInput {
+    lines: VecDeque<String>,
+    prompts: Vec<String>,
+}
+
+impl ScriptedInput {
+    pub fn new<S: Into<String>>(lines: impl IntoIterator<Item = S>) -> Self {
+        ScriptedInput {
+            lines: lines.into_iter().map(Into::into).collect(),
+            prompts: Vec::new(),
+        }
+    }
+
+    pub fn prompts(&self) -> &[String] {
+        &self.prompts
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        self.prompts.push(prompt.to_string());
+        self.lines.pop_front()
+    }
+}
+
+// A played session, for reproducing bugs: the RNG seed and the options it
+// started with, then every answer given. Its file form is
+//
+//     seed 1234
+//     arg --difficulty
+//     arg hard
+//     > s
+//     > a
+//
+// with `#` comments and blank lines ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    pub seed: u64,
+    pub args: Vec<String>,
+    pub answers: Vec<String>,
+}
+
+impl Recording {
+    pub fn new(seed: u64, args: Vec<String>) -> Self {
+        Recording { seed, args, answers: Vec::new() }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Recording::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut recording = Recording::default();
+        let mut seed = None;
+        for (number, line) in text.lines().enumerate() {
+            if let Some(answer) = line.strip_prefix('>') {
+                let answer = answer.strip_prefix(' ').unwrap_or(answer);
+                recording.answers.push(answer.to_string());
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "seed" => match value.trim().parse() {
+                    Ok(value) => seed = Some(value),
+                    Err(_) => return Err(format!("line {}: bad seed '{}'", number + 1, value)),
+                },
+                "arg" => recording.args.push(value.to_string()),
+                other => return Err(format!("line {}: unknown entry '{}'", number + 1, other)),
+            }
+        }
+        recording.seed = seed.ok_or("recording has no seed")?;
+        Ok(recording)
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Rusty Text Battle recording; replay it with --play <file>")?;
+        writeln!(f, "seed {}", self.seed)?;
+        for arg in &self.args {
+            writeln!(f, "arg {}", arg)?;
+        }
+        for answer in &self.answers {
+            writeln!(f, "> {}", answer)?;
+        }
+        Ok(())
+    }
+}
+
+// Passes another source's answers through, appending each to a `Recording`
+// as it is given, so the file is whole even if the game crashes.
+pub struct RecordingInput<S, W> {
+    source: S,
+    out: W,
+}
+
+impl<S: InputSource, W: Write + Send + Sync> RecordingInput<S, W> {
+    // Writes `recording` out first; answers follow as they come.
+    pub fn new(source: S, recording: &Recording, mut out: W) -> io::Result<Self> {
+        write!(out, "{}", recording)?;
+        out.flush()?;
+        Ok(RecordingInput { source, out })
+    }
+}
+
+impl<S: InputSource, W: Write + Send + Sync> InputSource for RecordingInput<S, W> {
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        let line = self.source.read_line(prompt)?;
+        let _ = writeln!(self.out, "> {}", line).and_then(|_| self.out.flush());
+        Some(line)
+    }
+}
+
+// Gives a `Recording`'s answers back in order, echoed after their prompts
+// like `ReaderInput`.
+pub struct PlaybackInput(VecDeque<String>);
+
+impl PlaybackInput {
+    pub fn new(recording: Recording) -> Self {
+        PlaybackInput(recording.answers.into())
+    }
+}
+
+impl InputSource for PlaybackInput {
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        let line = self.0.pop_front()?;
+        println!("{} {}", prompt, line);
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_normalize_and_run_out() {
+        let mut input = Input::new(ScriptedInput::new(["  Attack ", "2"]));
+        assert_eq!(input.ask("Hero>").as_deref(), Some("attack"));
+        assert_eq!(input.ask("Target>").as_deref(), Some("2"));
+        assert_eq!(input.ask("Hero>"), None);
+
+        let mut scripted = ScriptedInput::new(["d"]);
+        scripted.read_line("Mage>");
+        assert_eq!(scripted.prompts(), ["Mage>"]);
+
+        let mut reader = ReaderInput::new("defend\r\nquit\n".as_bytes());
+        assert_eq!(reader.read_line(">").as_deref(), Some("defend"));
+        assert_eq!(reader.read_line(">").as_deref(), Some("quit"));
+        assert_eq!(reader.read_line(">"), None);
+    }
+
+    #[test]
+    fn test_recording_round_trip() {
+        let recording = Recording::new(42, vec!["--party".into(), "2".into()]);
+        let mut file = Vec::new();
+        let mut input = RecordingInput::new(ScriptedInput::new(["s", " A "]), &recording, &mut file)
+            .unwrap();
+        assert_eq!(input.read_line(">").as_deref(), Some("s"));
+        assert_eq!(input.read_line(">").as_deref(), Some(" A "));
+        assert_eq!(input.read_line(">"), None);
+        drop(input);
+
+        let text = String::from_utf8(file).unwrap();
+        assert!(text.ends_with("seed 42\narg --party\narg 2\n> s\n>  A \n"), "{}", text);
+        let played = Recording::parse(&text).unwrap();
+        assert_eq!((played.seed, &played.args), (42, &recording.args));
+        assert_eq!(played.answers, ["s", " A "]);
+        let mut playback = Input::new(PlaybackInput::new(played));
+        assert_eq!(playback.ask(">").as_deref(), Some("s"));
+        assert_eq!(playback.ask(">").as_deref(), Some("a"));
+        assert_eq!(playback.ask(">"), None);
+
+        assert!(Recording::parse("> s\n").is_err());
+        assert!(Recording::parse("seed x\n").is_err());
+        assert!(Recording::parse("seed 1\nkey 2\n").is_err());
+    }
+}
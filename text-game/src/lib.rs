@@ -0,0 +1,19 @@
+pub mod actions;
+pub mod ai;
+pub mod attacks;
+pub mod campaign;
+pub mod combat;
+pub mod components;
+pub mod difficulty;
+pub mod events;
+pub mod flow;
+pub mod input;
+pub mod loot;
+pub mod messages;
+pub mod party;
+pub mod prompt;
+pub mod script;
+pub mod spawner;
+pub mod stats;
+pub mod style;
+pub mod systems;
@@ -0,0 +1,250 @@
+use crate::components::Name;
+use crate::events::EnemyDefeatedEvent;
+use crate::messages::MsgEvent;
+use rusty_ecs_core::{Access, Persist, RngResource, RngStream, SnapshotError, System, SystemData};
+
+// Flat bonuses granted while an item is equipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub name: String,
+    pub attack: i32,
+    pub defense: i32,
+}
+
+impl Persist for Item {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.name.write(out);
+        self.attack.write(out);
+        self.defense.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Item {
+            name: String::read(input)?,
+            attack: i32::read(input)?,
+            defense: i32::read(input)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LootDrop {
+    pub item: Item,
+    pub chance: f32,
+}
+
+// What an enemy may drop when defeated. Every entry is rolled on its own.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LootTable(pub Vec<LootDrop>);
+
+impl LootTable {
+    // Parses `Name +attack/+defense chance%` entries separated by commas,
+    // e.g. `Iron Sword +2/+0 40%, Leather Cap +0/+1 25%`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(parse_drop)
+            .collect::<Result<_, _>>()
+            .map(LootTable)
+    }
+
+    // Rolls every entry with its chance scaled by `share`, returning the drops.
+    pub fn roll(&self, stream: &mut RngStream, share: f32) -> Vec<Item> {
+        self.0
+            .iter()
+            .filter(|drop| stream.chance(drop.chance * share))
+            .map(|drop| drop.item.clone())
+            .collect()
+    }
+}
+
+fn parse_drop(entry: &str) -> Result<LootDrop, String> {
+    let bad = || format!("loot '{}' is not `Name +attack/+defense chance%`", entry);
+    let mut words = entry.rsplitn(3, ' ');
+    let (Some(chance), Some(bonus), Some(name)) = (words.next(), words.next(), words.next()) else {
+        return Err(bad());
+    };
+    let chance: f32 = chance
+        .strip_suffix('%')
+        .and_then(|c| c.parse().ok())
+        .filter(|c| (0.0..=100.0).contains(c))
+        .ok_or_else(bad)?;
+    let (attack, defense) = bonus.split_once('/').ok_or_else(bad)?;
+    Ok(LootDrop {
+        item: Item {
+            name: name.trim().to_string(),
+            attack: attack.parse().map_err(|_| bad())?,
+            defense: defense.parse().map_err(|_| bad())?,
+        },
+        chance: chance / 100.0,
+    })
+}
+
+// Items a hero has equipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Equipment(pub Vec<Item>);
+
+impl Persist for Equipment {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.0.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(Equipment(Vec::read(input)?))
+    }
+}
+
+// Summed equipment bonuses, kept up to date by `StatAggregationSystem` and
+// applied by `DamageSystem` on both sides of an attack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatModifiers {
+    pub attack: i32,
+    pub defense: i32,
+}
+
+pub struct StatAggregationSystem;
+
+impl System for StatAggregationSystem {
+    fn access(&self) -> Access {
+        Access::new().read::<Equipment>().write::<StatModifiers>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        let totals: Vec<_> = data
+            .query::<&Equipment, ()>()
+            .map(|(e, equipment)| {
+                let modifiers = equipment.0.iter().fold(StatModifiers::default(), |sum, item| {
+                    StatModifiers {
+                        attack: sum.attack + item.attack,
+                        defense: sum.defense + item.defense,
+                    }
+                });
+                (e, modifiers)
+            })
+            .collect();
+
+        for (entity, modifiers) in totals {
+            match data.get_mut::<StatModifiers>(entity) {
+                Some(current) => *current = modifiers,
+                None => data.commands().add_component(entity, modifiers),
+            }
+        }
+    }
+}
+
+// Rolls the loot table of each defeated enemy and hands the drops to whoever
+// landed the final blow, if they can carry equipment.
+pub struct LootSystem;
+
+impl System for LootSystem {
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<LootTable>()
+            .read::<Name>()
+            .write::<Equipment>()
+            .write::<RngResource>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        for defeated in data.take_events::<EnemyDefeatedEvent>() {
+            let Some(table) = data.get::<LootTable>(defeated.enemy).cloned() else {
+                continue;
+            };
+            let Some(to) = data.get::<Name>(defeated.by).map(|n| n.0) else {
+                continue;
+            };
+            if data.get::<Equipment>(defeated.by).is_none() {
+                continue;
+            }
+            let Some(rng) = data.resource_mut::<RngResource>() else {
+                continue;
+            };
+            let drops = table.roll(rng.stream("loot"), 1.0);
+
+            for item in drops {
+                data.push_event(MsgEvent::LootDropped { item: item.name.clone(), to });
+                data.get_mut::<Equipment>(defeated.by).unwrap().0.push(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Damage, Health};
+    use crate::events::AttackEvent;
+    use crate::systems::DamageSystem;
+    use rusty_ecs_core::{SystemExecutor, World};
+
+    fn item(name: &str, attack: i32, defense: i32) -> Item {
+        Item { name: name.to_string(), attack, defense }
+    }
+
+    #[test]
+    fn test_parse_loot_table() {
+        let table = LootTable::parse("Iron Sword +2/+0 40%, Leather Cap +0/+1 100%").unwrap();
+        assert_eq!(
+            table.0,
+            vec![
+                LootDrop { item: item("Iron Sword", 2, 0), chance: 0.4 },
+                LootDrop { item: item("Leather Cap", 0, 1), chance: 1.0 },
+            ]
+        );
+        assert_eq!(LootTable::parse(""), Ok(LootTable::default()));
+        assert!(LootTable::parse("Sword 40%").is_err());
+        assert!(LootTable::parse("Sword +1/+0 140%").is_err());
+
+        let mut rng = RngResource::new(1);
+        let sure = LootTable::parse("Sword +1/+0 100%").unwrap();
+        assert_eq!(sure.roll(rng.stream("loot"), 1.0).len(), 1);
+        assert!(sure.roll(rng.stream("loot"), 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_loot_feeds_damage_and_defense() {
+        let mut world = World::new();
+        world.insert_resource(RngResource::new(1));
+        let hero = world.create_entity();
+        world.add_component(hero, Name("Knight"));
+        world.add_component(hero, Health { hp: 20, max: 20 });
+        world.add_component(hero, Damage { value: 5 });
+        world.add_component(hero, Equipment::default());
+        let rat = world.create_entity();
+        world.add_component(rat, crate::components::Enemy);
+        world.add_component(rat, Name("Rat"));
+        world.add_component(rat, Health { hp: 5, max: 5 });
+        world.add_component(
+            rat,
+            LootTable::parse("Fang Dagger +3/+0 100%, Hide Vest +0/+2 100%, Pebble +0/+0 0%").unwrap(),
+        );
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(DamageSystem);
+        executor.add_system(LootSystem);
+        executor.add_system(StatAggregationSystem);
+
+        world.push_event(AttackEvent { attacker: hero, target: rat, damage: 5 });
+        executor.run(&mut world);
+        assert_eq!(
+            world.get_component::<Equipment>(hero),
+            Some(&Equipment(vec![item("Fang Dagger", 3, 0), item("Hide Vest", 0, 2)]))
+        );
+        assert_eq!(
+            world.get_component::<StatModifiers>(hero),
+            Some(&StatModifiers { attack: 3, defense: 2 })
+        );
+        assert!(world
+            .take_events::<MsgEvent>()
+            .contains(&MsgEvent::LootDropped { item: "Hide Vest".to_string(), to: "Knight" }));
+
+        let wolf = world.create_entity();
+        world.add_component(wolf, Health { hp: 20, max: 20 });
+        world.push_event(AttackEvent { attacker: hero, target: wolf, damage: 5 });
+        world.push_event(AttackEvent { attacker: wolf, target: hero, damage: 3 });
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Health>(wolf).unwrap().hp, 12);
+        assert_eq!(world.get_component::<Health>(hero).unwrap().hp, 19);
+    }
+}
@@ -1,235 +1,558 @@
-use rusty_ecs_core::{Entity, World, System, SystemExecutor};
-use std::io::{self, Write};
+use rusty_ecs_core::{
+    Entity, GameStateStack, InvariantViolation, RngResource, StaleEventWarning, SystemExecutor, World,
+    WorldConfig,
+};
+use text_game::actions::{Action, ActionMap};
+use text_game::campaign::{register_persistent, register_watches, resume, Campaign, CampaignState, Checkpoint};
+use text_game::components::{
+    register_invariants, register_required_components, Health, Name,
+};
+use text_game::difficulty::Difficulty;
+use text_game::attacks::AttackSet;
+use text_game::events::{EnemyTurnEvent, PlayerActionEvent};
+use text_game::flow::{game_executor, GameState};
+use text_game::input::{Input, PlaybackInput, ReaderInput, Recording, RecordingInput};
+use text_game::messages::{Localization, LogLine, MsgEvent, Verbosity};
+use text_game::spawner::{SpawnEvent, Spawner};
+use text_game::prompt::{ask_number, Menu, Rejection};
+use text_game::party::{
+    enemy_turn_order, living_enemies, party_wiped, restore_names, spawn_party, turn_order, ROSTER,
+};
+use text_game::stats::{Records, Stats};
+use text_game::style::ColorConfig;
+use text_game::systems::set_defending;
+use std::fs::File;
+use std::io::BufReader;
+
+// Options left out of recordings: how answers were given and where the
+// game was saved, which a replay should not touch.
+const UNRECORDED: [&str; 4] = ["--record", "--play", "--script", "--save"];
 
-// Components
-#[derive(Clone, Copy)]
-struct Name(&'static str);
-
-#[derive(Clone, Copy)]
-struct Health {
-    pub hp: i32,
-    pub max: i32,
-}
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+    // `--play <file>` replays a `--record`ed session with its seed, options
+    // and answers
+    let replay = arg_value(&args, "--play").map(|path| {
+        Recording::load(path).unwrap_or_else(|err| {
+            eprintln!("Could not load recording {}: {}", path, err);
+            std::process::exit(2);
+        })
+    });
+    if let Some(recording) = &replay {
+        args = recording.args.clone();
+    }
+    let seed = replay.as_ref().map_or_else(time_seed, |recording| recording.seed);
+    let locale = load_locale(&args);
+    let difficulty = match arg_value(&args, "--difficulty").map(|d| d.parse::<Difficulty>()) {
+        Some(Ok(difficulty)) => difficulty,
+        Some(Err(err)) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+        None => Difficulty::default(),
+    };
+    // `--verbosity quiet` keeps only what is needed to play, `verbose` adds
+    // the damage formula behind every hit
+    let verbosity = match arg_value(&args, "--verbosity").map(|v| v.parse::<Verbosity>()) {
+        Some(Ok(verbosity)) => verbosity,
+        Some(Err(err)) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+        None => Verbosity::default(),
+    };
+    let party_size = match arg_value(&args, "--party").map(|n| n.parse::<usize>()) {
+        Some(Ok(size)) if (1..=ROSTER.len()).contains(&size) => size,
+        Some(_) => {
+            eprintln!("--party takes a size from 1 to {}", ROSTER.len());
+            std::process::exit(2);
+        }
+        None => ROSTER.len(),
+    };
+    say(&locale, MsgEvent::Welcome);
+    println!();
+
+    // Change tracking lets the step debugger report what each system changed
+    let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+    world.insert_resource(RngResource::new(seed));
+    world.insert_resource(difficulty);
+    world.insert_resource(Stats::default());
+    world.insert_resource(verbosity);
+    // Messages are colored on a terminal unless NO_COLOR or `--no-color` says
+    // otherwise
+    let colors = match args.iter().any(|a| a == "--no-color") {
+        true => ColorConfig::default(),
+        false => ColorConfig::from_env(),
+    };
+    world.insert_resource(colors);
+    world.insert_resource(locale.clone());
+    world.insert_resource(load_keys(&args));
+    register_required_components(&mut world);
+    register_invariants(&mut world);
+    register_persistent(&mut world);
+    register_watches(&mut world);
+    // `--script <file>` plays the answers in a file instead of asking on the
+    // terminal, one per prompt, quitting when it runs out
+    let input = match (replay, arg_value(&args, "--script").map(File::open)) {
+        (Some(recording), _) => Input::new(PlaybackInput::new(recording)),
+        (None, Some(Ok(file))) => Input::new(ReaderInput::new(BufReader::new(file))),
+        (None, Some(Err(err))) => {
+            eprintln!("Could not open script: {}", err);
+            std::process::exit(2);
+        }
+        (None, None) => Input::default(),
+    };
+    // `--record <file>` writes the seed, options and every answer to a file
+    // to attach to a bug report
+    let input = match arg_value(&args, "--record") {
+        Some(path) => {
+            let recording = Recording::new(seed, recorded_args(&args));
+            match File::create(path).and_then(|file| RecordingInput::new(input, &recording, file)) {
+                Ok(recorder) => Input::new(recorder),
+                Err(err) => {
+                    eprintln!("Could not record to {}: {}", path, err);
+                    std::process::exit(2);
+                }
+            }
+        }
+        None => input,
+    };
+    world.insert_resource(input);
+
+    match arg_value(&args, "--load") {
+        Some(path) => load_checkpoint(&mut world, path),
+        None => {
+            spawn_party(&mut world, party_size);
+        }
+    }
+    let save_path = arg_value(&args, "--save");
+    let stats_path = arg_value(&args, "--stats");
+    // `--horde` sends the campaign's battles as endless waves, a new one every
+    // few turns, and never reaches a rest point
+    let horde = args.iter().any(|a| a == "--horde");
+    if horde {
+        let spawner = world.create_entity();
+        world.add_component(spawner, Spawner::new(Campaign::builtin().waves(), HORDE_COOLDOWN).endless());
+    }
 
-#[derive(Clone, Copy)]
-struct Damage {
-    pub value: i32,
-}
+    world.insert_resource(GameStateStack::new(GameState::MainMenu));
+    let mut executor = game_executor(Campaign::builtin());
+    #[cfg(feature = "plugins")]
+    if let Some(dir) = arg_value(&args, "--plugins") {
+        load_plugins(&mut world, &mut executor, dir);
+    }
+    executor.run(&mut world);
+    let main_menu = Menu::new(&["start", "quit"]);
+    let next = match choose(&mut world, &locale, &main_menu, MsgEvent::MenuPrompt) {
+        "quit" => GameState::GameOver,
+        _ => GameState::Battle,
+    };
+    set_state(&mut world, next);
+    // Entering the battle spawns its first encounter and applies the
+    // difficulty to its enemies
+    executor.run(&mut world);
+    print_messages(&mut world, &locale);
+    save_checkpoint(&mut world, save_path);
+
+    let mut stepping = false;
+    'game: loop {
+        if !in_state(&world, GameState::Battle) {
+            break;
+        }
 
-#[derive(Clone, Copy, Default)]
-struct Defending(pub bool);
+        if party_wiped(&world) {
+            say(&locale, MsgEvent::PlayerFell);
+            break;
+        }
 
-#[derive(Clone, Copy)]
-struct Player;
+        if world.resource::<CampaignState>().is_some_and(|c| c.complete) {
+            break;
+        }
 
-#[derive(Clone, Copy)]
-struct Enemy;
+        let enemies = living_enemies(&world);
+        if enemies.is_empty() {
+            break;
+        }
+        if let Some(stats) = world.resource_mut::<Stats>() {
+            stats.record(|t| t.turns += 1);
+        }
+        println!();
+        say(
+            &locale,
+            MsgEvent::Status {
+                party: roll_call(&world, &turn_order(&world)),
+                enemies: roll_call(&world, &enemies),
+            },
+        );
 
-// Events
-struct AttackEvent {
-    pub attacker: Entity,
-    pub target: Entity,
-    pub damage: i32,
-}
+        // Party turns. Only this round's enemies can be targeted, so a battle
+        // spawned mid-round waits for the next round.
+        for member in turn_order(&world) {
+            let targets = still_standing(&world, &enemies);
+            if targets.is_empty() {
+                break;
+            }
+            let name = name_of(&world, member);
+            set_defending(&mut world, member, false);
+            // `debug` prints world diagnostics and `step` toggles stepping
+            // through the systems, neither using up the turn
+            let action = loop {
+                match choose_action(&mut world, &locale, name) {
+                    Action::Debug => eprint!("[debug] {}", world.diagnostics()),
+                    Action::Step => {
+                        stepping = !stepping;
+                        eprintln!("[debug] step mode {}", if stepping { "on" } else { "off" });
+                    }
+                    action => break action,
+                }
+            };
+            let target = match action {
+                Action::Attack => choose_target(&mut world, &locale, &targets),
+                Action::Quit => {
+                    say(&locale, MsgEvent::Retreat);
+                    break 'game;
+                }
+                _ => None,
+            };
+            // PlayerTurnSystem carries it out at the start of the step
+            world.push_event(PlayerActionEvent { entity: member, action, target });
+
+            advance(&mut world, &mut executor, stepping);
+            print_messages(&mut world, &locale);
+            report_violations(&mut world);
+            save_checkpoint(&mut world, save_path);
+        }
 
-// Systems
-struct DamageSystem;
+        // Enemy turns, each played by EnemyAttackSystem from the enemy's
+        // AttackSet; enemies summoned this round act after their summoner
+        for enemy in enemy_turn_order(&world, &still_standing(&world, &enemies)) {
+            world.push_event(EnemyTurnEvent { enemy });
+            advance(&mut world, &mut executor, stepping);
+            print_messages(&mut world, &locale);
+            report_violations(&mut world);
+        }
+    }
 
-impl System for DamageSystem {
-    fn run(&mut self, world: &mut World) {
-        let attacks = world.take_events::<AttackEvent>();
-        for attack in attacks {
-            let mut damage = attack.damage;
-            if is_defending(world, attack.target) {
-                damage = (damage / 2).max(0);
+    if !in_state(&world, GameState::Battle) {
+        return;
+    }
+    // The horde spawner never runs out, so it is the only spawner left
+    if horde {
+        match world.single::<&Spawner, ()>() {
+            Ok((_, spawner)) => {
+                say(&locale, MsgEvent::HordeSurvived { waves: spawner.waves_spawned() as u32 });
             }
+            Err(err) => eprintln!("[debug] horde spawner: {}", err),
+        }
+    }
+    report_stats(&world, &locale, stats_path);
+    set_state(&mut world, GameState::GameOver);
+    executor.run(&mut world);
+    print_messages(&mut world, &locale);
+}
 
-            let target_name = world
-                .get_component::<Name>(attack.target)
-                .map(|n| n.0)
-                .unwrap_or("Unknown");
-            let attacker_name = world
-                .get_component::<Name>(attack.attacker)
-                .map(|n| n.0)
-                .unwrap_or("Unknown");
-            let attacker_is_player = world.get_component::<Player>(attack.attacker).is_some();
-
-            if let Some(h) = world.get_component_mut::<Health>(attack.target) {
-                h.hp = (h.hp - damage).max(0);
-
-                if attacker_is_player {
-                    println!(
-                        "You strike {} for {} damage! (HP: {}/{})",
-                        target_name, damage, h.hp, h.max
-                    );
-                } else {
-                    println!(
-                        "{} hits you for {} damage! (HP: {}/{})",
-                        attacker_name, damage, h.hp, h.max
-                    );
-                }
+// Prints the run's totals and, with `--stats <file>`, adds them to the
+// records kept in that file and prints those.
+fn report_stats(world: &World, locale: &Localization, path: Option<&str>) {
+    let Some(run) = world.resource::<Stats>().map(|s| s.run) else {
+        return;
+    };
+    say(
+        locale,
+        MsgEvent::RunSummary {
+            dealt: run.damage_dealt,
+            taken: run.damage_taken,
+            defeated: run.enemies_defeated,
+            turns: run.turns,
+        },
+    );
+    let Some(path) = path else {
+        return;
+    };
+    // A missing file just means no runs yet
+    let mut records = match std::fs::read_to_string(path) {
+        Ok(text) => match Records::parse(&text) {
+            Ok(records) => records,
+            Err(err) => {
+                eprintln!("Could not read records from {}: {}", path, err);
+                return;
             }
-        }
+        },
+        Err(_) => Records::default(),
+    };
+    records.add_run(&run);
+    if let Err(err) = std::fs::write(path, records.to_text()) {
+        eprintln!("Could not save records to {}: {}", path, err);
     }
+    say(
+        locale,
+        MsgEvent::Records {
+            runs: records.runs,
+            best_dealt: records.best_damage_dealt,
+            most_defeated: records.most_enemies_defeated,
+        },
+    );
 }
 
-fn main() {
-    println!("Welcome to Rusty Text Battle!\n");
-
-    let mut world = World::new();
-
-    let player = world.create_entity();
-    world.add_component(player, Name("Hero"));
-    world.add_component(player, Player);
-    world.add_component(player, Health { hp: 45, max: 45 });
-    world.add_component(player, Damage { value: 7 });
-    world.add_component(player, Defending(false));
-
-    let enemies_data = vec![
-        ("Goblin", 12, 3, vec!["Slash", "Bite"]),
-        ("Orc", 18, 5, vec!["Heavy Swing", "Headbutt"]),
-        ("Necromancer", 22, 6, vec!["Shadow Bolt", "Bone Spike"]),
-    ];
-
-    let mut enemy_entities: Vec<Entity> = Vec::new();
-    for (name, hp, dmg, _attacks) in &enemies_data {
-        let e = world.create_entity();
-        world.add_component(e, Name(*name));
-        world.add_component(e, Enemy);
-        world.add_component(e, Health { hp: *hp, max: *hp });
-        world.add_component(e, Damage { value: *dmg });
-        enemy_entities.push(e);
-    }
-
-    let mut executor = SystemExecutor::new();
-    executor.add_system(DamageSystem);
-
-    let mut current_enemy_index = 0usize;
-
-    loop {
-        let player_alive = world
-            .get_component::<Health>(player)
-            .map(|h| h.hp > 0)
-            .unwrap_or(false);
-        if !player_alive {
-            println!("You have fallen. Game Over.");
+// Runs the game systems once. In step mode the battle systems run one at a
+// time, each reported on stderr and waiting for Enter before the next.
+fn advance(world: &mut World, executor: &mut SystemExecutor, stepping: bool) {
+    if !stepping {
+        executor.run(world);
+        return;
+    }
+    let battle = &mut executor.state_set(GameState::Battle).on_update;
+    while let Some(report) = battle.step(world) {
+        eprint!("[step] {}", report);
+        if report.run_complete {
             break;
         }
+        prompt_line(world, "");
+    }
+}
 
-        if current_enemy_index >= enemy_entities.len() {
-            println!("All enemies are defeated! You win!");
-            break;
+fn in_state(world: &World, state: GameState) -> bool {
+    world
+        .resource::<GameStateStack<GameState>>()
+        .is_some_and(|stack| stack.is(&state))
+}
+
+fn set_state(world: &mut World, state: GameState) {
+    if let Some(stack) = world.resource_mut::<GameStateStack<GameState>>() {
+        stack.replace(state);
+    }
+}
+
+const HORDE_COOLDOWN: u32 = 4;
+
+// `--plugins <dir>` loads every content pack in `dir` before the game starts.
+// A pack that fails to load is reported and skipped.
+#[cfg(feature = "plugins")]
+fn load_plugins(world: &mut World, executor: &mut SystemExecutor, dir: &str) {
+    use rusty_ecs_core::plugin;
+    let paths = match plugin::discover(std::path::Path::new(dir)) {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
         }
+    };
+    for path in paths {
+        // SAFETY: the player chose to install the packs in `dir`.
+        if let Err(err) = unsafe { plugin::load(&path, world, executor) } {
+            eprintln!("{}", err);
+        }
+    }
+}
 
-        let enemy = enemy_entities[current_enemy_index];
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
 
-        let enemy_alive = world
-            .get_component::<Health>(enemy)
-            .map(|h| h.hp > 0)
-            .unwrap_or(false);
-        if !enemy_alive {
-            println!(
-                "{} has been defeated!",
-                world.get_component::<Name>(enemy).unwrap().0
-            );
-            current_enemy_index += 1;
-            continue;
+// The options after the program name, without the `UNRECORDED` ones and
+// their values.
+fn recorded_args(args: &[String]) -> Vec<String> {
+    let mut recorded = Vec::new();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        if UNRECORDED.contains(&arg.as_str()) {
+            rest.next();
+        } else {
+            recorded.push(arg.clone());
         }
+    }
+    recorded
+}
 
-        let en_name = world.get_component::<Name>(enemy).unwrap().0;
-        let attacks = &enemies_data[current_enemy_index].3;
-        println!("An enemy approaches: {}", en_name);
-        println!("It brandishes these attacks: {}\n", attacks.join(", "));
+// `--keys <file>` rebinds the keys for the party's turn, one `key = action`
+// per line.
+fn load_keys(args: &[String]) -> ActionMap {
+    let Some(path) = arg_value(args, "--keys") else {
+        return ActionMap::default();
+    };
+    ActionMap::load(path).unwrap_or_else(|err| {
+        eprintln!("Could not load keys {}: {}", path, err);
+        std::process::exit(2);
+    })
+}
 
-        let p_hp = world.get_component::<Health>(player).unwrap();
-        let e_hp = world.get_component::<Health>(enemy).unwrap();
-        println!(
-            "Status => You: {}/{} | {}: {}/{}",
-            p_hp.hp, p_hp.max, en_name, e_hp.hp, e_hp.max
-        );
+// `--locale <file>` loads a translated message table; English is built in.
+fn load_locale(args: &[String]) -> Localization {
+    let Some(path) = arg_value(args, "--locale") else {
+        return Localization::english();
+    };
+    Localization::load(path).unwrap_or_else(|err| {
+        eprintln!("Could not load locale {}: {}", path, err);
+        Localization::english()
+    })
+}
 
-        set_defending(&mut world, player, false);
-        let action = prompt_player_action();
-        match action.as_str() {
-            "attack" | "a" => {
-                let dmg = world.get_component::<Damage>(player).unwrap().value;
-                world.push_event(AttackEvent {
-                    attacker: player,
-                    target: enemy,
-                    damage: dmg,
-                });
-            }
-            "defend" | "d" => {
-                set_defending(&mut world, player, true);
-                println!("You brace yourself, reducing incoming damage this turn!");
-            }
-            "quit" | "q" => {
-                println!("You chose to retreat. Game Over.");
-                break;
-            }
-            _ => {
-                println!("Unrecognized action. You hesitate and lose your turn!");
-            }
-        }
+fn name_of(world: &World, entity: Entity) -> &'static str {
+    world.get_component::<Name>(entity).map_or("Unknown", |n| n.0)
+}
 
-        // Run systems to process player's attack
-        executor.run(&mut world);
-
-        let enemy_alive = world
-            .get_component::<Health>(enemy)
-            .map(|h| h.hp > 0)
-            .unwrap_or(false);
-        
-        if !enemy_alive {
-            println!("{} collapses!", en_name);
-            continue;
-        }
-
-        // Enemy turn
-        let enemy_attack_name = &enemies_data[current_enemy_index].3[rand_index(attacks.len())];
-        let enemy_damage = world.get_component::<Damage>(enemy).unwrap().value;
-        
-        println!("{} uses {}!", en_name, enemy_attack_name);
-        world.push_event(AttackEvent {
-            attacker: enemy,
-            target: player,
-            damage: enemy_damage,
-        });
-
-        // Run systems to process enemy's attack
-        executor.run(&mut world);
-        println!();
+fn still_standing(world: &World, entities: &[Entity]) -> Vec<Entity> {
+    entities
+        .iter()
+        .copied()
+        .filter(|&e| world.is_enabled(e))
+        .collect()
+}
+
+fn roll_call(world: &World, entities: &[Entity]) -> String {
+    let entries: Vec<String> = entities
+        .iter()
+        .map(|&e| {
+            let h = world.get_component::<Health>(e).unwrap();
+            format!("{} {}/{}", name_of(world, e), h.hp, h.max)
+        })
+        .collect();
+    entries.join(", ")
+}
+
+// Asks which enemy to attack when there is a choice, by number. An empty
+// answer picks the first one.
+fn choose_target(world: &mut World, locale: &Localization, targets: &[Entity]) -> Option<Entity> {
+    if targets.len() == 1 {
+        return Some(targets[0]);
     }
+    let listed: Vec<String> = targets
+        .iter()
+        .enumerate()
+        .map(|(i, &e)| format!("{}) {}", i + 1, roll_call(world, &[e])))
+        .collect();
+    let text = locale.format(&MsgEvent::TargetPrompt { targets: listed.join(", ") });
+    let input = world.resource_mut::<Input>()?;
+    let index = ask_number(input, &text, targets.len(), |r| {
+        say(locale, rejection_message(r, &[]))
+    })?;
+    Some(targets[index])
+}
+
+// `--load <file>` resumes a campaign from a checkpoint written by `--save`.
+fn load_checkpoint(world: &mut World, path: &str) {
+    let loaded = std::fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| resume(world, &bytes).map_err(|err| err.to_string()));
+    if let Err(err) = loaded {
+        eprintln!("Could not load checkpoint {}: {}", path, err);
+        std::process::exit(2);
+    }
+    if party_wiped(world) {
+        eprintln!("Checkpoint {} has no living party", path);
+        std::process::exit(2);
+    }
+    restore_names(world);
+}
+
+// Writes the checkpoint taken before the latest battle, if there is a new one.
+fn save_checkpoint(world: &mut World, path: Option<&str>) {
+    let Some(checkpoint) = world.remove_resource::<Checkpoint>() else {
+        return;
+    };
+    if let Some(path) = path {
+        if let Err(err) = std::fs::write(path, checkpoint.0.to_bytes()) {
+            eprintln!("Could not save checkpoint to {}: {}", path, err);
+        }
+    }
+}
 
-    println!("Thanks for playing!");
+fn say(locale: &Localization, msg: MsgEvent) {
+    println!("{}", locale.format(&msg));
 }
 
-fn prompt_player_action() -> String {
-    print!("Choose action [attack(a)/defend(d)/quit(q)]: ");
-    let _ = io::stdout().flush();
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).is_ok() {
-        input = input.trim().to_lowercase();
+// Prints the lines ColoredLogSystem rendered and any messages it has not
+// seen yet, as stepping skips it, then introduces enemies spawned since the
+// last call.
+fn print_messages(world: &mut World, locale: &Localization) {
+    for line in world.take_events::<LogLine>() {
+        println!("{}", line.0);
+    }
+    let colors = world.resource::<ColorConfig>().copied().unwrap_or_default();
+    let mut messages = world.take_events::<MsgEvent>();
+    for spawn in world.take_events::<SpawnEvent>() {
+        let name = name_of(world, spawn.entity);
+        let attacks = world.get_component::<AttackSet>(spawn.entity).unwrap().names().join(", ");
+        messages.push(MsgEvent::EnemyApproaches { name });
+        let listed = MsgEvent::EnemyAttacks { attacks };
+        if world.resource::<Verbosity>().is_none_or(|v| v.shows(&listed)) {
+            messages.push(listed);
+        }
+    }
+    for msg in messages {
+        println!("{}", locale.format_styled(&msg, &colors));
     }
-    input
 }
 
-fn set_defending(world: &mut World, entity: Entity, value: bool) {
-    if let Some(d) = world.get_component_mut::<Defending>(entity) {
-        d.0 = value;
+// Asks `menu` through the `Input` resource until the answer names one of its
+// commands, explaining rejected answers. A closed input quits.
+// Asks `name` what to do this turn, answered through the `ActionMap`
+// resource. A closed input quits.
+fn choose_action(world: &mut World, locale: &Localization, name: &'static str) -> Action {
+    let keys = world.resource::<ActionMap>().cloned().unwrap_or_default();
+    let text = locale.format(&MsgEvent::Prompt { name, actions: keys.describe() });
+    let Some(input) = world.resource_mut::<Input>() else {
+        return Action::Quit;
+    };
+    let options = Action::menu();
+    keys.ask(input, &text, |r| say(locale, rejection_message(r, options.options())))
+        .unwrap_or(Action::Quit)
+}
+
+fn choose(world: &mut World, locale: &Localization, menu: &Menu, msg: MsgEvent) -> &'static str {
+    let text = locale.format(&msg);
+    let Some(input) = world.resource_mut::<Input>() else {
+        return "quit";
+    };
+    menu.ask(input, &text, |r| say(locale, rejection_message(r, menu.options())))
+        .unwrap_or("quit")
+}
+
+fn rejection_message(rejection: Rejection, options: &[&str]) -> MsgEvent {
+    match rejection {
+        Rejection::Unknown { answer } => {
+            MsgEvent::InvalidChoice { answer, options: options.join(", ") }
+        }
+        Rejection::Ambiguous { answer, matches } => {
+            MsgEvent::AmbiguousChoice { answer, matches: matches.join(", ") }
+        }
+        Rejection::OutOfRange { count, .. } => MsgEvent::InvalidNumber { count: count as u32 },
     }
 }
 
-fn is_defending(world: &World, entity: Entity) -> bool {
+// Reads the next answer from the `Input` resource as typed; a closed input
+// quits.
+fn prompt_line(world: &mut World, text: &str) -> String {
     world
-        .get_component::<Defending>(entity)
-        .map(|d| d.0)
-        .unwrap_or(false)
+        .resource_mut::<Input>()
+        .and_then(|input| input.ask(text))
+        .unwrap_or_else(|| "quit".to_string())
+}
+
+fn report_violations(world: &mut World) {
+    for v in world.take_events::<InvariantViolation>() {
+        eprintln!(
+            "[debug] invariant '{}' violated by {:?} after {}",
+            v.invariant,
+            v.entity,
+            v.system.unwrap_or("unknown system")
+        );
+    }
+    for w in world.take_events::<StaleEventWarning>() {
+        eprintln!(
+            "[debug] {} {} events unread for {} turns",
+            w.pending,
+            w.event,
+            w.frames
+        );
+    }
 }
 
-fn rand_index(n: usize) -> usize {
+fn time_seed() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let seed = now.as_nanos() as u64;
-    (seed as usize) % n
+    now.as_nanos() as u64
 }
@@ -0,0 +1,472 @@
+use crate::style::{Color, ColorConfig, Style};
+use rusty_ecs_core::{Access, Event, System, SystemData};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+// Everything the game tells the player, as a message key plus parameters.
+// Systems push these as events and the front-end renders them through a
+// `Localization`, so output can be translated and asserted on structurally.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub enum MsgEvent {
+    Welcome,
+    PlayerFell,
+    Victory,
+    EnemyDefeated { name: &'static str },
+    EnemyApproaches { name: &'static str },
+    EnemyAttacks { attacks: String },
+    Status { party: String, enemies: String },
+    Defend { name: &'static str },
+    Retreat,
+    FleeFailed { name: &'static str },
+    Fled { name: &'static str },
+    Hesitate,
+    Collapses { name: &'static str },
+    UsesAttack { name: &'static str, attack: &'static str },
+    GuardBroken { name: &'static str },
+    Summons { name: &'static str },
+    Enraged { name: &'static str },
+    Recovers { name: &'static str, amount: i32, hp: i32, max: i32 },
+    PlayerHits { attacker: &'static str, target: &'static str, dmg: i32, hp: i32, max: i32 },
+    Hit { attacker: &'static str, target: &'static str, dmg: i32, hp: i32, max: i32 },
+    EncounterBegins { number: u32, total: u32 },
+    Rested { name: &'static str, hp: i32, max: i32 },
+    Misses { attacker: &'static str, target: &'static str },
+    CriticalHit,
+    WaveIncoming { number: u32 },
+    HordeSurvived { waves: u32 },
+    LootDropped { item: String, to: &'static str },
+    Goodbye,
+    BattleSummary { dealt: u32, taken: u32, defeated: u32, turns: u32 },
+    RunSummary { dealt: u32, taken: u32, defeated: u32, turns: u32 },
+    Records { runs: u32, best_dealt: u32, most_defeated: u32 },
+    Formula { text: String },
+    MenuPrompt,
+    Prompt { name: &'static str, actions: String },
+    TargetPrompt { targets: String },
+    InvalidChoice { answer: String, options: String },
+    AmbiguousChoice { answer: String, matches: String },
+    InvalidNumber { count: u32 },
+}
+
+impl MsgEvent {
+    pub fn key(&self) -> &'static str {
+        match self {
+            MsgEvent::Welcome => "welcome",
+            MsgEvent::PlayerFell => "player_fell",
+            MsgEvent::Victory => "victory",
+            MsgEvent::EnemyDefeated { .. } => "enemy_defeated",
+            MsgEvent::EnemyApproaches { .. } => "enemy_approaches",
+            MsgEvent::EnemyAttacks { .. } => "enemy_attacks",
+            MsgEvent::Status { .. } => "status",
+            MsgEvent::Defend { .. } => "defend",
+            MsgEvent::Retreat => "retreat",
+            MsgEvent::FleeFailed { .. } => "flee_failed",
+            MsgEvent::Fled { .. } => "fled",
+            MsgEvent::Hesitate => "hesitate",
+            MsgEvent::Collapses { .. } => "collapses",
+            MsgEvent::UsesAttack { .. } => "uses_attack",
+            MsgEvent::GuardBroken { .. } => "guard_broken",
+            MsgEvent::Summons { .. } => "summons",
+            MsgEvent::Enraged { .. } => "enraged",
+            MsgEvent::Recovers { .. } => "recovers",
+            MsgEvent::PlayerHits { .. } => "player_hits",
+            MsgEvent::Hit { .. } => "hit",
+            MsgEvent::EncounterBegins { .. } => "encounter_begins",
+            MsgEvent::Rested { .. } => "rested",
+            MsgEvent::Misses { .. } => "misses",
+            MsgEvent::CriticalHit => "critical_hit",
+            MsgEvent::WaveIncoming { .. } => "wave_incoming",
+            MsgEvent::HordeSurvived { .. } => "horde_survived",
+            MsgEvent::LootDropped { .. } => "loot_dropped",
+            MsgEvent::Goodbye => "goodbye",
+            MsgEvent::BattleSummary { .. } => "battle_summary",
+            MsgEvent::RunSummary { .. } => "run_summary",
+            MsgEvent::Records { .. } => "records",
+            MsgEvent::Formula { .. } => "formula",
+            MsgEvent::MenuPrompt => "menu_prompt",
+            MsgEvent::Prompt { .. } => "prompt",
+            MsgEvent::TargetPrompt { .. } => "target_prompt",
+            MsgEvent::InvalidChoice { .. } => "invalid_choice",
+            MsgEvent::AmbiguousChoice { .. } => "ambiguous_choice",
+            MsgEvent::InvalidNumber { .. } => "invalid_number",
+        }
+    }
+
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            MsgEvent::EnemyDefeated { name }
+            | MsgEvent::EnemyApproaches { name }
+            | MsgEvent::Collapses { name }
+            | MsgEvent::Defend { name }
+            | MsgEvent::GuardBroken { name }
+            | MsgEvent::Summons { name }
+            | MsgEvent::Enraged { name }
+            | MsgEvent::FleeFailed { name }
+            | MsgEvent::Fled { name } => vec![("name", name.to_string())],
+            MsgEvent::Prompt { name, actions } => {
+                vec![("name", name.to_string()), ("actions", actions.clone())]
+            }
+            MsgEvent::EnemyAttacks { attacks } => vec![("attacks", attacks.clone())],
+            MsgEvent::Status { party, enemies } => {
+                vec![("party", party.clone()), ("enemies", enemies.clone())]
+            }
+            MsgEvent::TargetPrompt { targets } => vec![("targets", targets.clone())],
+            MsgEvent::InvalidChoice { answer, options } => {
+                vec![("answer", answer.clone()), ("options", options.clone())]
+            }
+            MsgEvent::AmbiguousChoice { answer, matches } => {
+                vec![("answer", answer.clone()), ("matches", matches.clone())]
+            }
+            MsgEvent::InvalidNumber { count } => vec![("count", count.to_string())],
+            MsgEvent::UsesAttack { name, attack } => {
+                vec![("name", name.to_string()), ("attack", attack.to_string())]
+            }
+            MsgEvent::PlayerHits { attacker, target, dmg, hp, max }
+            | MsgEvent::Hit { attacker, target, dmg, hp, max } => vec![
+                ("attacker", attacker.to_string()),
+                ("target", target.to_string()),
+                ("dmg", dmg.to_string()),
+                ("hp", hp.to_string()),
+                ("max", max.to_string()),
+            ],
+            MsgEvent::EncounterBegins { number, total } => {
+                vec![("number", number.to_string()), ("total", total.to_string())]
+            }
+            MsgEvent::Recovers { name, amount, hp, max } => vec![
+                ("name", name.to_string()),
+                ("amount", amount.to_string()),
+                ("hp", hp.to_string()),
+                ("max", max.to_string()),
+            ],
+            MsgEvent::Rested { name, hp, max } => vec![
+                ("name", name.to_string()),
+                ("hp", hp.to_string()),
+                ("max", max.to_string()),
+            ],
+            MsgEvent::Misses { attacker, target } => {
+                vec![("attacker", attacker.to_string()), ("target", target.to_string())]
+            }
+            MsgEvent::BattleSummary { dealt, taken, defeated, turns }
+            | MsgEvent::RunSummary { dealt, taken, defeated, turns } => vec![
+                ("dealt", dealt.to_string()),
+                ("taken", taken.to_string()),
+                ("defeated", defeated.to_string()),
+                ("turns", turns.to_string()),
+            ],
+            MsgEvent::Records { runs, best_dealt, most_defeated } => vec![
+                ("runs", runs.to_string()),
+                ("best_dealt", best_dealt.to_string()),
+                ("most_defeated", most_defeated.to_string()),
+            ],
+            MsgEvent::Formula { text } => vec![("text", text.clone())],
+            MsgEvent::WaveIncoming { number } => vec![("number", number.to_string())],
+            MsgEvent::HordeSurvived { waves } => vec![("waves", waves.to_string())],
+            MsgEvent::LootDropped { item, to } => {
+                vec![("item", item.clone()), ("to", to.to_string())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // How a parameter stands out on a color terminal: damage in red, healing
+    // in green and enemy names in bold.
+    pub fn param_style(&self, param: &str) -> Style {
+        let enemy = Style::PLAIN.bold();
+        match (self, param) {
+            (MsgEvent::PlayerHits { .. } | MsgEvent::Hit { .. }, "dmg") => Style::fg(Color::Red),
+            (MsgEvent::Recovers { .. }, "amount") | (MsgEvent::Rested { .. }, "hp") => {
+                Style::fg(Color::Green)
+            }
+            (MsgEvent::PlayerHits { .. }, "target") | (MsgEvent::Hit { .. }, "attacker") => enemy,
+            (
+                MsgEvent::EnemyApproaches { .. }
+                | MsgEvent::EnemyDefeated { .. }
+                | MsgEvent::UsesAttack { .. }
+                | MsgEvent::Summons { .. }
+                | MsgEvent::Enraged { .. }
+                | MsgEvent::Recovers { .. },
+                "name",
+            ) => enemy,
+            _ => Style::PLAIN,
+        }
+    }
+
+    // The lowest verbosity that shows this message. Quiet keeps what is
+    // needed to follow and play the game; normal adds the blow-by-blow.
+    pub fn verbosity(&self) -> Verbosity {
+        match self {
+            MsgEvent::Formula { .. } => Verbosity::Verbose,
+            MsgEvent::EnemyAttacks { .. }
+            | MsgEvent::EnemyDefeated { .. }
+            | MsgEvent::Defend { .. }
+            | MsgEvent::UsesAttack { .. }
+            | MsgEvent::GuardBroken { .. }
+            | MsgEvent::Recovers { .. }
+            | MsgEvent::PlayerHits { .. }
+            | MsgEvent::Hit { .. }
+            | MsgEvent::Misses { .. }
+            | MsgEvent::CriticalHit
+            | MsgEvent::Rested { .. }
+            | MsgEvent::LootDropped { .. }
+            | MsgEvent::Enraged { .. }
+            | MsgEvent::BattleSummary { .. } => Verbosity::Normal,
+            _ => Verbosity::Quiet,
+        }
+    }
+}
+
+// Resource choosing how much the game tells the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    Quiet,
+    #[default]
+    Normal,
+    // Also shows the damage formula behind every hit.
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn shows(self, msg: &MsgEvent) -> bool {
+        msg.verbosity() <= self
+    }
+}
+
+impl FromStr for Verbosity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "quiet" => Ok(Verbosity::Quiet),
+            "normal" => Ok(Verbosity::Normal),
+            "verbose" => Ok(Verbosity::Verbose),
+            other => Err(format!(
+                "unknown verbosity '{}', expected quiet, normal, or verbose",
+                other
+            )),
+        }
+    }
+}
+
+// Drops the pending messages the `Verbosity` resource does not show, so the
+// front-end prints whatever is left. Runs after every system that talks.
+pub struct MessageFilterSystem;
+
+impl System for MessageFilterSystem {
+    fn access(&self) -> Access {
+        Access::new().read::<Verbosity>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        let verbosity = data.resource::<Verbosity>().copied().unwrap_or_default();
+        if verbosity == Verbosity::Verbose {
+            return;
+        }
+        for msg in data.take_events::<MsgEvent>() {
+            if verbosity.shows(&msg) {
+                data.push_event(msg);
+            }
+        }
+    }
+}
+
+// A message rendered for the terminal.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct LogLine(pub String);
+
+// Renders the pending messages through the `Localization` resource into
+// `LogLine`s, colored as the `ColorConfig` resource allows, for the terminal
+// front-end to print. Without a `Localization` the messages are left alone
+// for other front-ends. Runs after every system that talks.
+pub struct ColoredLogSystem;
+
+impl System for ColoredLogSystem {
+    fn access(&self) -> Access {
+        Access::new().read::<Localization>().read::<ColorConfig>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        if data.resource::<Localization>().is_none() {
+            return;
+        }
+        let messages = data.take_events::<MsgEvent>();
+        let colors = data.resource::<ColorConfig>().copied().unwrap_or_default();
+        let locale = data.resource::<Localization>().unwrap();
+        let lines: Vec<LogLine> =
+            messages.iter().map(|msg| LogLine(locale.format_styled(msg, &colors))).collect();
+        for line in lines {
+            data.push_event(line);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Localization {
+    table: HashMap<String, String>,
+}
+
+impl Localization {
+    pub fn english() -> Self {
+        Self::parse(include_str!("../locales/en.txt")).expect("built-in locale is valid")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut table = HashMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = text`", number + 1))?;
+            table.insert(key.trim().to_string(), value.trim().to_string());
+        }
+        Ok(Self { table })
+    }
+
+    // Missing keys render as the key and its parameters, so an incomplete
+    // translation stays readable instead of failing.
+    pub fn format(&self, msg: &MsgEvent) -> String {
+        self.format_styled(msg, &ColorConfig::default())
+    }
+
+    // Like `format`, with each parameter in its `MsgEvent::param_style` when
+    // `colors` allows.
+    pub fn format_styled(&self, msg: &MsgEvent, colors: &ColorConfig) -> String {
+        let params: Vec<_> = msg
+            .params()
+            .into_iter()
+            .map(|(name, value)| (name, colors.paint(&value, msg.param_style(name))))
+            .collect();
+        match self.table.get(msg.key()) {
+            Some(template) => params
+                .iter()
+                .fold(template.clone(), |text, (name, value)| {
+                    text.replace(&format!("{{{}}}", name), value)
+                }),
+            None => {
+                let args: Vec<_> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                format!("{}({})", msg.key(), args.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_covers_every_key() {
+        let english = Localization::english();
+        let samples = [
+            MsgEvent::Welcome,
+            MsgEvent::Status { party: "Knight 1/2".into(), enemies: "Orc 3/4".into() },
+            MsgEvent::Hit { attacker: "Orc", target: "Knight", dmg: 5, hp: 40, max: 45 },
+            MsgEvent::TargetPrompt { targets: "1) Orc 3/4".into() },
+            MsgEvent::EncounterBegins { number: 1, total: 3 },
+            MsgEvent::Rested { name: "Hero", hp: 30, max: 45 },
+            MsgEvent::LootDropped { item: "Iron Sword".into(), to: "Knight" },
+            MsgEvent::Misses { attacker: "Orc", target: "Knight" },
+            MsgEvent::CriticalHit,
+            MsgEvent::WaveIncoming { number: 2 },
+            MsgEvent::HordeSurvived { waves: 7 },
+            MsgEvent::Prompt { name: "Knight", actions: "attack(a)".into() },
+            MsgEvent::MenuPrompt,
+            MsgEvent::InvalidChoice { answer: "x".into(), options: "attack, defend".into() },
+            MsgEvent::AmbiguousChoice { answer: "d".into(), matches: "defend, dodge".into() },
+            MsgEvent::InvalidNumber { count: 2 },
+            MsgEvent::GuardBroken { name: "Knight" },
+            MsgEvent::BattleSummary { dealt: 30, taken: 12, defeated: 2, turns: 4 },
+            MsgEvent::RunSummary { dealt: 90, taken: 40, defeated: 6, turns: 15 },
+            MsgEvent::Records { runs: 3, best_dealt: 90, most_defeated: 6 },
+            MsgEvent::Formula { text: "(7 + 2 - 1) / 2 = 4".into() },
+            MsgEvent::Summons { name: "Necromancer" },
+            MsgEvent::Enraged { name: "Necromancer" },
+            MsgEvent::FleeFailed { name: "Knight" },
+            MsgEvent::Fled { name: "Ranger" },
+            MsgEvent::Recovers { name: "Necromancer", amount: 3, hp: 20, max: 22 },
+        ];
+        for msg in samples {
+            let text = english.format(&msg);
+            assert!(!text.contains('{'), "unfilled parameter in {:?}: {}", msg, text);
+            assert!(!text.starts_with(msg.key()), "missing key {}", msg.key());
+        }
+        assert_eq!(
+            english.format(&MsgEvent::Hit { attacker: "Orc", target: "Knight", dmg: 5, hp: 40, max: 45 }),
+            "Orc hits Knight for 5 damage! (HP: 40/45)"
+        );
+    }
+
+    #[test]
+    fn test_parse_and_fallback() {
+        let german = Localization::parse("# Deutsch\ncollapses = {name} bricht zusammen!\n").unwrap();
+
+        assert_eq!(
+            german.format(&MsgEvent::Collapses { name: "Ork" }),
+            "Ork bricht zusammen!"
+        );
+        assert_eq!(german.format(&MsgEvent::EnemyDefeated { name: "Ork" }), "enemy_defeated(name=Ork)");
+        assert!(Localization::parse("no separator").is_err());
+    }
+
+    #[test]
+    fn test_colored_log_lines() {
+        use rusty_ecs_core::{SystemExecutor, World};
+
+        let hit =
+            MsgEvent::PlayerHits { attacker: "Knight", target: "Orc", dmg: 7, hp: 5, max: 12 };
+        let mut executor = SystemExecutor::new();
+        executor.add_system(ColoredLogSystem);
+        let mut world = World::new();
+        world.push_event(hit.clone());
+        // Left alone without a localization
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<MsgEvent>(), vec![hit.clone()]);
+
+        world.insert_resource(Localization::english());
+        world.push_event(hit.clone());
+        executor.run(&mut world);
+        let plain = "Knight strikes Orc for 7 damage! (HP: 5/12)";
+        assert_eq!(world.take_events::<LogLine>(), vec![LogLine(plain.to_string())]);
+
+        world.insert_resource(ColorConfig { enabled: true });
+        world.push_event(hit);
+        executor.run(&mut world);
+        let colored = "Knight strikes \x1b[1mOrc\x1b[0m for \x1b[31m7\x1b[0m damage! (HP: 5/12)";
+        assert_eq!(world.take_events::<LogLine>(), vec![LogLine(colored.to_string())]);
+        assert!(world.take_events::<MsgEvent>().is_empty());
+    }
+
+    #[test]
+    fn test_filter_follows_verbosity() {
+        use rusty_ecs_core::{SystemExecutor, World};
+
+        let sent = [
+            MsgEvent::Collapses { name: "Orc" },
+            MsgEvent::Hit { attacker: "Orc", target: "Knight", dmg: 5, hp: 40, max: 45 },
+            MsgEvent::Formula { text: "5 = 5".into() },
+        ];
+        let mut executor = SystemExecutor::new();
+        executor.add_system(MessageFilterSystem);
+        let mut shown = Vec::new();
+        for verbosity in ["quiet", "normal", "verbose"] {
+            let mut world = World::new();
+            world.insert_resource(verbosity.parse::<Verbosity>().unwrap());
+            for msg in &sent {
+                world.push_event(msg.clone());
+            }
+            executor.run(&mut world);
+            shown.push(world.take_events::<MsgEvent>().len());
+        }
+        assert_eq!(shown, vec![1, 2, 3]);
+        assert!("loud".parse::<Verbosity>().is_err());
+    }
+}
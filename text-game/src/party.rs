@@ -0,0 +1,185 @@
+use crate::components::{Damage, Enemy, Health, Name, Player, Speed, Summoned};
+use rusty_ecs_core::{Entity, Persist, SnapshotError, With, World};
+
+// A player-controlled hero. Living members act in `slot` order each round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartyMember {
+    pub slot: u8,
+}
+
+impl Persist for PartyMember {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.slot.write(out);
+    }
+
+    fn read(input: &mut &[u8]) -> Result<Self, SnapshotError> {
+        Ok(PartyMember { slot: u8::read(input)? })
+    }
+}
+
+pub struct HeroSpec {
+    pub name: &'static str,
+    pub hp: i32,
+    pub damage: i32,
+    pub speed: i32,
+}
+
+// Heroes a party is drawn from, in slot order.
+pub const ROSTER: [HeroSpec; 3] = [
+    HeroSpec { name: "Knight", hp: 45, damage: 7, speed: 3 },
+    HeroSpec { name: "Ranger", hp: 35, damage: 9, speed: 7 },
+    HeroSpec { name: "Cleric", hp: 40, damage: 5, speed: 5 },
+];
+
+// Heroes get their Equipment through the Player rule in
+// `register_required_components`.
+pub fn spawn_party(world: &mut World, size: usize) -> Vec<Entity> {
+    ROSTER
+        .iter()
+        .take(size)
+        .enumerate()
+        .map(|(slot, hero)| {
+            let e = world.create_entity();
+            world.add_component(e, Name(hero.name));
+            world.add_component(e, Player);
+            world.add_component(e, PartyMember { slot: slot as u8 });
+            world.add_component(e, Health { hp: hero.hp, max: hero.hp });
+            world.add_component(e, Damage { value: hero.damage });
+            world.add_component(e, Speed(hero.speed));
+            e
+        })
+        .collect()
+}
+
+// Names are not persisted, so members restored from a checkpoint get theirs
+// back from the roster.
+pub fn restore_names(world: &mut World) {
+    let members: Vec<(Entity, u8)> = world
+        .query::<&PartyMember, ()>()
+        .map(|(e, m)| (e, m.slot))
+        .collect();
+    for (member, slot) in members {
+        if let Some(hero) = ROSTER.get(slot as usize) {
+            world.add_component(member, Name(hero.name));
+        }
+    }
+}
+
+fn is_alive(world: &World, entity: Entity) -> bool {
+    world.get_component::<Health>(entity).is_some_and(|h| h.hp > 0)
+}
+
+// Living party members in the order they take their turns.
+pub fn turn_order(world: &World) -> Vec<Entity> {
+    world
+        .query_sorted::<PartyMember, _>(|m| m.slot)
+        .into_iter()
+        .filter(|e| is_alive(world, *e))
+        .collect()
+}
+
+// Living enemies in spawn order, which is also the order targets are listed in.
+pub fn living_enemies(world: &World) -> Vec<Entity> {
+    let mut enemies: Vec<Entity> = world
+        .query::<&Health, With<Enemy>>()
+        .filter(|(_, h)| h.hp > 0)
+        .map(|(e, _)| e)
+        .collect();
+    enemies.sort();
+    enemies
+}
+
+// The order enemies act in this round: `enemies` as given, with any living
+// enemy summoned since then slotted in right after its summoner (or last if
+// the summoner is gone), so summons join the round they arrive in.
+pub fn enemy_turn_order(world: &World, enemies: &[Entity]) -> Vec<Entity> {
+    let mut order = enemies.to_vec();
+    for summon in living_enemies(world) {
+        if order.contains(&summon) {
+            continue;
+        }
+        let Some(summoned) = world.get_component::<Summoned>(summon) else {
+            continue;
+        };
+        // After the summoner and whatever it summoned before. A despawned
+        // summoner's id may belong to another enemy by now, hence the weak handle.
+        let summoner = summoned.by.get(world);
+        let at = match order.iter().position(|&e| Some(e) == summoner) {
+            Some(summoner) => {
+                let after = order[summoner + 1..].iter().take_while(|&&e| {
+                    world.get_component::<Summoned>(e).is_some_and(|s| s.by == summoned.by)
+                });
+                summoner + 1 + after.count()
+            }
+            None => order.len(),
+        };
+        order.insert(at, summon);
+    }
+    order
+}
+
+// True once every party member is down. A world without a party counts as
+// wiped, so a game never runs without anyone to control.
+pub fn party_wiped(world: &World) -> bool {
+    turn_order(world).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_order_skips_fallen_members() {
+        let mut world = World::new();
+        let party = spawn_party(&mut world, 3);
+        assert_eq!(turn_order(&world), party);
+
+        world.get_component_mut::<Health>(party[1]).unwrap().hp = 0;
+        assert_eq!(turn_order(&world), vec![party[0], party[2]]);
+        assert!(!party_wiped(&world));
+
+        world.get_component_mut::<Health>(party[0]).unwrap().hp = 0;
+        world.get_component_mut::<Health>(party[2]).unwrap().hp = 0;
+        assert!(party_wiped(&world));
+    }
+
+    #[test]
+    fn test_living_enemies_in_spawn_order() {
+        let mut world = World::new();
+        spawn_party(&mut world, 2);
+        let enemies: Vec<Entity> = (0..3)
+            .map(|hp| {
+                let e = world.create_entity();
+                world.add_component(e, Enemy);
+                world.add_component(e, Health { hp, max: 5 });
+                e
+            })
+            .collect();
+
+        assert_eq!(living_enemies(&world), vec![enemies[1], enemies[2]]);
+    }
+
+    #[test]
+    fn test_summons_act_after_their_summoner() {
+        let mut world = World::new();
+        let spawn = |world: &mut World, summoner: Option<Entity>| {
+            let e = world.create_entity();
+            world.add_component(e, Enemy);
+            world.add_component(e, Health { hp: 5, max: 5 });
+            if let Some(by) = summoner {
+                world.add_component(e, Summoned { by: by.into() });
+            }
+            e
+        };
+        let boss = spawn(&mut world, None);
+        let grunt = spawn(&mut world, None);
+        let first = spawn(&mut world, Some(boss));
+        let second = spawn(&mut world, Some(boss));
+        let orphan = spawn(&mut world, Some(Entity { id: 99, generation: 0 }));
+
+        let order = enemy_turn_order(&world, &[boss, grunt]);
+        assert_eq!(order, vec![boss, first, second, grunt, orphan]);
+        // Summons already in the round keep their place
+        assert_eq!(enemy_turn_order(&world, &[grunt, first]), vec![grunt, first, second, orphan]);
+    }
+}
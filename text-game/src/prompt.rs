@@ -0,0 +1,137 @@
+use crate::input::Input;
+
+// Why an answer was not accepted; the caller tells the player and the
+// question is asked again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    Unknown { answer: String },
+    Ambiguous { answer: String, matches: Vec<&'static str> },
+    OutOfRange { answer: String, count: usize },
+}
+
+// A list of commands matched by unambiguous prefix, so `a` or `att` both
+// mean `attack`. Hidden commands are not listed to the player and only
+// match when typed in full, so they never make a shortcut ambiguous.
+#[derive(Debug, Clone)]
+pub struct Menu {
+    options: Vec<&'static str>,
+    hidden: Vec<&'static str>,
+}
+
+impl Menu {
+    pub fn new(options: &[&'static str]) -> Self {
+        Menu {
+            options: options.to_vec(),
+            hidden: Vec::new(),
+        }
+    }
+
+    pub fn hidden(mut self, commands: &[&'static str]) -> Self {
+        self.hidden.extend_from_slice(commands);
+        self
+    }
+
+    pub fn options(&self) -> &[&'static str] {
+        &self.options
+    }
+
+    // An exact match wins over prefixes, so an option may be the prefix of
+    // another.
+    pub fn resolve(&self, answer: &str) -> Result<&'static str, Rejection> {
+        if let Some(&exact) = self.options.iter().chain(&self.hidden).find(|&&o| o == answer) {
+            return Ok(exact);
+        }
+        let matches: Vec<&'static str> = if answer.is_empty() {
+            Vec::new()
+        } else {
+            self.options.iter().copied().filter(|o| o.starts_with(answer)).collect()
+        };
+        match matches.as_slice() {
+            [only] => Ok(only),
+            [] => Err(Rejection::Unknown { answer: answer.to_string() }),
+            _ => Err(Rejection::Ambiguous { answer: answer.to_string(), matches }),
+        }
+    }
+
+    // Asks until an answer resolves, passing each rejection to `reject`.
+    // `None` when the input runs out.
+    pub fn ask(
+        &self,
+        input: &mut Input,
+        prompt: &str,
+        mut reject: impl FnMut(Rejection),
+    ) -> Option<&'static str> {
+        loop {
+            match self.resolve(&input.ask(prompt)?) {
+                Ok(choice) => return Some(choice),
+                Err(rejection) => reject(rejection),
+            }
+        }
+    }
+}
+
+// Reads a 1-based menu number as an index below `count`. An empty answer
+// picks the first entry.
+pub fn resolve_number(answer: &str, count: usize) -> Result<usize, Rejection> {
+    if answer.is_empty() && count > 0 {
+        return Ok(0);
+    }
+    match answer.parse::<usize>() {
+        Ok(n) if (1..=count).contains(&n) => Ok(n - 1),
+        _ => Err(Rejection::OutOfRange { answer: answer.to_string(), count }),
+    }
+}
+
+// Asks for a menu number until one is in range. `None` when the input runs out.
+pub fn ask_number(
+    input: &mut Input,
+    prompt: &str,
+    count: usize,
+    mut reject: impl FnMut(Rejection),
+) -> Option<usize> {
+    loop {
+        match resolve_number(&input.ask(prompt)?, count) {
+            Ok(index) => return Some(index),
+            Err(rejection) => reject(rejection),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ScriptedInput;
+
+    #[test]
+    fn test_menu_resolves_unambiguous_prefixes() {
+        let menu = Menu::new(&["attack", "defend", "dodge", "quit"]).hidden(&["debug", "step"]);
+        assert_eq!(menu.resolve("a"), Ok("attack"));
+        assert_eq!(menu.resolve("def"), Ok("defend"));
+        assert_eq!(menu.resolve("debug"), Ok("debug"));
+        assert_eq!(
+            menu.resolve("d"),
+            Err(Rejection::Ambiguous { answer: "d".into(), matches: vec!["defend", "dodge"] })
+        );
+        // Hidden commands need the full word
+        assert_eq!(menu.resolve("st"), Err(Rejection::Unknown { answer: "st".into() }));
+        assert_eq!(menu.resolve(""), Err(Rejection::Unknown { answer: "".into() }));
+
+        let nested = Menu::new(&["go", "gold"]);
+        assert_eq!(nested.resolve("go"), Ok("go"));
+        assert_eq!(nested.resolve("gol"), Ok("gold"));
+    }
+
+    #[test]
+    fn test_ask_repeats_until_valid() {
+        let mut input = Input::new(ScriptedInput::new(["x", "d", "De", "0", "4", "", "3"]));
+        let menu = Menu::new(&["attack", "defend", "dodge"]);
+        let mut rejected = Vec::new();
+        assert_eq!(menu.ask(&mut input, ">", |r| rejected.push(r)), Some("defend"));
+        assert_eq!(rejected.len(), 2);
+
+        assert_eq!(ask_number(&mut input, ">", 3, |r| rejected.push(r)), Some(0));
+        assert_eq!(rejected.len(), 4);
+        assert_eq!(ask_number(&mut input, ">", 3, |r| rejected.push(r)), Some(2));
+        assert_eq!(ask_number(&mut input, ">", 3, |_| ()), None);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::components::{Damage, Health, Name};
+use crate::events::SummonEvent;
+use crate::messages::MsgEvent;
+use crate::spawner::EnemyPrefab;
+use rusty_ecs_core::{ExclusiveSystem, World};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerAction {
+    // Spawns these enemies next to the scripted one.
+    Summon(Vec<EnemyPrefab>),
+    // Scales the scripted enemy's Damage by this percentage.
+    Enrage(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trigger {
+    // Fires once health drops to this percentage of max or below.
+    pub below_percent: i32,
+    pub action: TriggerAction,
+    pub fired: bool,
+}
+
+// Boss phases: actions an enemy takes once, when its health first crosses
+// each threshold.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EncounterScript(pub Vec<Trigger>);
+
+impl EncounterScript {
+    // Parses comma-separated triggers, `50% summon Skeleton x2` or
+    // `25% enrage 150%`. `lookup` finds the enemies to summon by name.
+    pub fn parse(
+        text: &str,
+        lookup: impl Fn(&str) -> Option<EnemyPrefab>,
+    ) -> Result<Self, String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| parse_trigger(entry, &lookup))
+            .collect::<Result<_, _>>()
+            .map(EncounterScript)
+    }
+}
+
+fn parse_trigger(
+    entry: &str,
+    lookup: &impl Fn(&str) -> Option<EnemyPrefab>,
+) -> Result<Trigger, String> {
+    let bad = || format!("trigger '{}' is not `N% summon Name [xN]` or `N% enrage N%`", entry);
+    let percent = |word: &str| word.strip_suffix('%').and_then(|p| p.parse::<i32>().ok());
+    let (threshold, rest) = entry.split_once(' ').ok_or_else(bad)?;
+    let below_percent = percent(threshold).filter(|p| (1..100).contains(p)).ok_or_else(bad)?;
+    let action = match rest.trim().split_once(' ').ok_or_else(bad)? {
+        ("summon", what) => {
+            let (name, count) = match what.rsplit_once(" x") {
+                Some((name, count)) => (name, count.parse::<usize>().map_err(|_| bad())?),
+                None => (what, 1),
+            };
+            let prefab = lookup(name.trim())
+                .ok_or_else(|| format!("cannot summon unknown enemy '{}'", name))?;
+            TriggerAction::Summon(vec![prefab; count])
+        }
+        ("enrage", scale) => TriggerAction::Enrage(percent(scale.trim()).ok_or_else(bad)?),
+        _ => return Err(bad()),
+    };
+    Ok(Trigger { below_percent, action, fired: false })
+}
+
+// Fires EncounterScript triggers. Only entities whose Health changed since
+// the last run are checked, through change detection, so the world needs
+// change tracking on (or a watch on Health, which tracks it anyway). Summons
+// are pushed as SummonEvents for SummonSystem.
+pub struct TriggerSystem;
+
+impl ExclusiveSystem for TriggerSystem {
+    fn run(&mut self, world: &mut World) {
+        for entity in world.changed::<Health>() {
+            let Some(&Health { hp, max }) = world.get_component::<Health>(entity) else {
+                continue;
+            };
+            if hp <= 0 || max <= 0 {
+                continue;
+            }
+            let Some(script) = world.get_component_mut::<EncounterScript>(entity) else {
+                continue;
+            };
+            let due: Vec<TriggerAction> = script
+                .0
+                .iter_mut()
+                .filter(|t| !t.fired && hp * 100 <= max * t.below_percent)
+                .map(|t| {
+                    t.fired = true;
+                    t.action.clone()
+                })
+                .collect();
+
+            let name = world.get_component::<Name>(entity).map_or("Unknown", |n| n.0);
+            for action in due {
+                match action {
+                    TriggerAction::Summon(prefabs) => {
+                        world.push_event(MsgEvent::Summons { name });
+                        for prefab in prefabs {
+                            world.push_event(SummonEvent { summoner: entity, prefab });
+                        }
+                    }
+                    TriggerAction::Enrage(percent) => {
+                        if let Some(damage) = world.get_component_mut::<Damage>(entity) {
+                            damage.value = damage.value * percent / 100;
+                            world.push_event(MsgEvent::Enraged { name });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attacks::AttackSet;
+    use crate::components::Enemy;
+    use crate::loot::LootTable;
+    use rusty_ecs_core::{SystemExecutor, WorldConfig};
+
+    const PHASES: &str = "50% summon Skeleton x2, 25% enrage 150%";
+
+    fn skeleton() -> EnemyPrefab {
+        EnemyPrefab {
+            name: "Skeleton",
+            hp: 16,
+            damage: 4,
+            attacks: AttackSet::parse("Rusty Blade").unwrap(),
+            loot: LootTable::default(),
+            script: EncounterScript::default(),
+        }
+    }
+
+    fn lookup(name: &str) -> Option<EnemyPrefab> {
+        (name == "Skeleton").then(skeleton)
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = EncounterScript::parse(PHASES, lookup).unwrap();
+        assert_eq!(script.0[0].below_percent, 50);
+        assert_eq!(script.0[0].action, TriggerAction::Summon(vec![skeleton(), skeleton()]));
+        assert_eq!(script.0[1].action, TriggerAction::Enrage(150));
+        assert!(EncounterScript::parse("50% summon Dragon", lookup).is_err());
+        assert!(EncounterScript::parse("150% enrage 200%", lookup).is_err());
+        assert!(EncounterScript::parse("50% dance", lookup).is_err());
+    }
+
+    #[test]
+    fn test_triggers_fire_once_when_health_crosses_thresholds() {
+        let mut world = World::with_config(WorldConfig::new().change_tracking(true));
+        let boss = world.create_entity();
+        world.add_component(boss, Name("Necromancer"));
+        world.add_component(boss, Enemy);
+        world.add_component(boss, Health { hp: 20, max: 20 });
+        world.add_component(boss, Damage { value: 6 });
+        world.add_component(boss, EncounterScript::parse(PHASES, lookup).unwrap());
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(TriggerSystem);
+        executor.run(&mut world);
+        assert!(world.take_events::<SummonEvent>().is_empty());
+
+        world.get_component_mut::<Health>(boss).unwrap().hp = 10;
+        executor.run(&mut world);
+        assert_eq!(world.take_events::<SummonEvent>().len(), 2);
+        let summons = MsgEvent::Summons { name: "Necromancer" };
+        assert_eq!(world.take_events::<MsgEvent>(), vec![summons]);
+
+        // Staying below the threshold does not summon again
+        world.get_component_mut::<Health>(boss).unwrap().hp = 8;
+        executor.run(&mut world);
+        assert!(world.take_events::<SummonEvent>().is_empty());
+
+        world.get_component_mut::<Health>(boss).unwrap().hp = 4;
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Damage>(boss).unwrap().value, 9);
+        let enraged = MsgEvent::Enraged { name: "Necromancer" };
+        assert_eq!(world.take_events::<MsgEvent>(), vec![enraged]);
+    }
+}
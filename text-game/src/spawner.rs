@@ -0,0 +1,262 @@
+use crate::ai::enemy_considerations;
+use crate::attacks::AttackSet;
+use crate::components::{BaseStats, Enemy, Health, Name, Summoned};
+use crate::events::SummonEvent;
+use crate::loot::LootTable;
+use crate::messages::MsgEvent;
+use crate::script::EncounterScript;
+use rusty_ecs_core::{Access, Entity, Event, ExclusiveSystem, System, SystemData, World};
+
+// Blueprint for one enemy. Spawned enemies only carry `BaseStats`, so
+// `StatScalingSystem` has to run after whatever spawns them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnemyPrefab {
+    pub name: &'static str,
+    pub hp: i32,
+    pub damage: i32,
+    pub attacks: AttackSet,
+    pub loot: LootTable,
+    pub script: EncounterScript,
+}
+
+impl EnemyPrefab {
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        let e = world.create_entity();
+        self.build(world, e);
+        e
+    }
+
+    // Gives `e` the prefab's components, for entities spawned through commands.
+    pub fn build(&self, world: &mut World, e: Entity) {
+        world.add_component(e, Name(self.name));
+        world.add_component(e, Enemy);
+        world.add_component(e, BaseStats { hp: self.hp, damage: self.damage });
+        world.add_component(e, self.attacks.clone());
+        world.add_component(e, enemy_considerations());
+        if !self.loot.0.is_empty() {
+            world.add_component(e, self.loot.clone());
+        }
+        if !self.script.0.is_empty() {
+            world.add_component(e, self.script.clone());
+        }
+    }
+}
+
+pub type Wave = Vec<EnemyPrefab>;
+
+// Sends waves of enemies, one every `cooldown` turns (executor runs), or
+// right away once the previous waves are all down. An endless spawner starts
+// over from its first wave after the last one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spawner {
+    pub waves: Vec<Wave>,
+    pub cooldown: u32,
+    pub endless: bool,
+    next: usize,
+    timer: u32,
+}
+
+impl Spawner {
+    pub fn new(waves: Vec<Wave>, cooldown: u32) -> Self {
+        Self {
+            waves,
+            cooldown,
+            endless: false,
+            next: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn endless(mut self) -> Self {
+        self.endless = true;
+        self
+    }
+
+    pub fn waves_spawned(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.waves.is_empty() || (!self.endless && self.next >= self.waves.len())
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnEvent {
+    pub entity: Entity,
+    pub spawner: Entity,
+    // Counts from 1 and keeps counting when an endless spawner starts over.
+    // Enemies summoned by an `EncounterScript` have wave 0 and their
+    // summoner as `spawner`.
+    pub wave: u32,
+}
+
+pub struct SpawnerSystem;
+
+impl ExclusiveSystem for SpawnerSystem {
+    fn run(&mut self, world: &mut World) {
+        // Enemies without Health have not been scaled yet and count as alive
+        let mut field_clear = world
+            .query_entities::<Enemy>()
+            .into_iter()
+            .all(|e| world.get_component::<Health>(e).is_some_and(|h| h.hp <= 0));
+
+        let mut spawners = world.query_entities::<Spawner>();
+        spawners.sort();
+        for spawner in spawners {
+            let s = world.get_component_mut::<Spawner>(spawner).unwrap();
+            if s.is_exhausted() {
+                continue;
+            }
+            if s.timer > 0 && !field_clear {
+                s.timer -= 1;
+                continue;
+            }
+            let wave = s.waves[s.next % s.waves.len()].clone();
+            let announce = s.endless || s.waves.len() > 1;
+            s.next += 1;
+            s.timer = s.cooldown;
+            let number = s.next as u32;
+
+            if announce {
+                world.push_event(MsgEvent::WaveIncoming { number });
+            }
+            for prefab in &wave {
+                let entity = prefab.spawn(world);
+                world.push_event(SpawnEvent { entity, spawner, wave: number });
+            }
+            field_clear &= wave.is_empty();
+        }
+    }
+}
+
+// Spawns the enemy of each SummonEvent through commands, so it appears at
+// the next flush point rather than in the middle of other systems' queries.
+// Summoned enemies are marked `Summoned` and announced with a SpawnEvent
+// (wave 0, the summoner as `spawner`). Has to run before StatScalingSystem.
+pub struct SummonSystem;
+
+impl System for SummonSystem {
+    fn access(&self) -> Access {
+        Access::new()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        for SummonEvent { summoner, prefab } in data.take_events::<SummonEvent>() {
+            data.commands().spawn(move |world, entity| {
+                prefab.build(world, entity);
+                world.add_component(entity, Summoned { by: summoner.into() });
+                world.push_event(SpawnEvent { entity, spawner: summoner, wave: 0 });
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_ecs_core::SystemExecutor;
+
+    fn rat() -> EnemyPrefab {
+        EnemyPrefab {
+            name: "Rat",
+            hp: 3,
+            damage: 1,
+            attacks: AttackSet::parse("Nibble").unwrap(),
+            loot: LootTable::default(),
+            script: EncounterScript::default(),
+        }
+    }
+
+    fn kill_all(world: &mut World) {
+        for e in world.query_entities::<Enemy>() {
+            world.add_component(e, Health { hp: 0, max: 3 });
+        }
+    }
+
+    #[test]
+    fn test_waves_follow_cooldown_or_a_clear_field() {
+        let mut world = World::new();
+        let spawner = world.create_entity();
+        world.add_component(spawner, Spawner::new(vec![vec![rat()], vec![rat(), rat()], vec![rat()]], 2));
+
+        let mut system = SpawnerSystem;
+        system.run(&mut world);
+        let spawned = world.take_events::<SpawnEvent>();
+        assert_eq!(spawned.len(), 1);
+        assert_eq!((spawned[0].spawner, spawned[0].wave), (spawner, 1));
+        assert_eq!(world.get_component::<BaseStats>(spawned[0].entity).unwrap().hp, 3);
+
+        // The first rat is still standing, so wave 2 waits out the cooldown
+        system.run(&mut world);
+        system.run(&mut world);
+        assert!(world.take_events::<SpawnEvent>().is_empty());
+        system.run(&mut world);
+        assert_eq!(world.take_events::<SpawnEvent>().len(), 2);
+
+        kill_all(&mut world);
+        system.run(&mut world);
+        assert_eq!(world.take_events::<SpawnEvent>()[0].wave, 3);
+        let s = world.get_component::<Spawner>(spawner).unwrap();
+        assert!(s.is_exhausted());
+
+        kill_all(&mut world);
+        system.run(&mut world);
+        assert!(world.take_events::<SpawnEvent>().is_empty());
+    }
+
+    #[test]
+    fn test_endless_spawner_starts_over() {
+        let mut world = World::new();
+        let spawner = world.create_entity();
+        world.add_component(spawner, Spawner::new(vec![vec![rat()]], 0).endless());
+
+        let mut system = SpawnerSystem;
+        for _ in 0..3 {
+            system.run(&mut world);
+            kill_all(&mut world);
+        }
+        let waves: Vec<u32> = world.take_events::<SpawnEvent>().iter().map(|s| s.wave).collect();
+        assert_eq!(waves, vec![1, 2, 3]);
+        assert!(!world.get_component::<Spawner>(spawner).unwrap().is_exhausted());
+        assert_eq!(world.take_events::<MsgEvent>().len(), 3);
+    }
+
+    // Enemy counts seen by each CountEnemies run.
+    struct Census(Vec<usize>);
+
+    struct CountEnemies;
+
+    impl System for CountEnemies {
+        fn access(&self) -> Access {
+            Access::new().read::<Enemy>().write::<Census>()
+        }
+
+        fn run(&mut self, mut data: SystemData<'_>) {
+            let seen = data.query::<&Enemy, ()>().count();
+            data.resource_mut::<Census>().unwrap().0.push(seen);
+        }
+    }
+
+    #[test]
+    fn test_summons_appear_at_the_next_flush() {
+        let mut world = World::new();
+        world.insert_resource(Census(Vec::new()));
+        let boss = rat().spawn(&mut world);
+        world.push_event(SummonEvent { summoner: boss, prefab: rat() });
+        let mut executor = SystemExecutor::new();
+        executor.add_system(SummonSystem);
+        executor.add_system(CountEnemies);
+        executor.add_barrier();
+        executor.add_system(CountEnemies);
+        executor.run(&mut world);
+
+        assert_eq!(world.resource::<Census>().unwrap().0, vec![1, 2]);
+        let spawns = world.take_events::<SpawnEvent>();
+        assert_eq!(spawns.len(), 1);
+        assert_eq!(spawns[0].spawner, boss);
+        assert_eq!(spawns[0].wave, 0);
+        let summon = world.get_component::<Summoned>(spawns[0].entity);
+        assert_eq!(summon, Some(&Summoned { by: boss.into() }));
+    }
+}
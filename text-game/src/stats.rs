@@ -0,0 +1,188 @@
+use crate::components::Enemy;
+use crate::events::EntityDied;
+use crate::messages::MsgEvent;
+use rusty_ecs_core::{ExclusiveSystem, World};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub turns: u32,
+    pub enemies_defeated: u32,
+}
+
+impl Tally {
+    fn add(&mut self, other: &Tally) {
+        self.damage_dealt += other.damage_dealt;
+        self.damage_taken += other.damage_taken;
+        self.turns += other.turns;
+        self.enemies_defeated += other.enemies_defeated;
+    }
+}
+
+// Resource counting what happened in the current battle and in the whole
+// run. DamageSystem records damage, StatsSystem defeated enemies and the
+// front-end turns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub battle: Tally,
+    pub run: Tally,
+}
+
+impl Stats {
+    pub fn record(&mut self, f: impl Fn(&mut Tally)) {
+        f(&mut self.battle);
+        f(&mut self.run);
+    }
+}
+
+// Counts enemy deaths into `Stats` and, once the field is clear, reports the
+// battle with a `BattleSummary` message and starts a new battle tally.
+pub struct StatsSystem;
+
+impl ExclusiveSystem for StatsSystem {
+    fn run(&mut self, world: &mut World) {
+        let died = world.take_events::<EntityDied>();
+        let defeated = died.iter().filter(|d| world.has::<Enemy>(d.entity)).count() as u32;
+        let field_clear = world.query_entities::<Enemy>().is_empty();
+        let Some(stats) = world.resource_mut::<Stats>() else {
+            return;
+        };
+        stats.record(|t| t.enemies_defeated += defeated);
+        if !field_clear || stats.battle == Tally::default() {
+            return;
+        }
+        let battle = std::mem::take(&mut stats.battle);
+        world.push_event(MsgEvent::BattleSummary {
+            dealt: battle.damage_dealt,
+            taken: battle.damage_taken,
+            defeated: battle.enemies_defeated,
+            turns: battle.turns,
+        });
+    }
+}
+
+// All-time totals and bests, kept in a `key = value` text file between runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Records {
+    pub runs: u32,
+    pub total: Tally,
+    // Best single run for each counter
+    pub best_damage_dealt: u32,
+    pub most_enemies_defeated: u32,
+}
+
+impl Records {
+    pub fn add_run(&mut self, run: &Tally) {
+        self.runs += 1;
+        self.total.add(run);
+        self.best_damage_dealt = self.best_damage_dealt.max(run.damage_dealt);
+        self.most_enemies_defeated = self.most_enemies_defeated.max(run.enemies_defeated);
+    }
+
+    // Unknown keys are ignored so older games can read newer files.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut records = Records::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", number + 1))?;
+            let value: u32 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("line {}: '{}' is not a count", number + 1, value.trim()))?;
+            match key.trim() {
+                "runs" => records.runs = value,
+                "damage_dealt" => records.total.damage_dealt = value,
+                "damage_taken" => records.total.damage_taken = value,
+                "turns" => records.total.turns = value,
+                "enemies_defeated" => records.total.enemies_defeated = value,
+                "best_damage_dealt" => records.best_damage_dealt = value,
+                "most_enemies_defeated" => records.most_enemies_defeated = value,
+                _ => {}
+            }
+        }
+        Ok(records)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Rusty Text Battle records\n");
+        let fields = [
+            ("runs", self.runs),
+            ("damage_dealt", self.total.damage_dealt),
+            ("damage_taken", self.total.damage_taken),
+            ("turns", self.total.turns),
+            ("enemies_defeated", self.total.enemies_defeated),
+            ("best_damage_dealt", self.best_damage_dealt),
+            ("most_enemies_defeated", self.most_enemies_defeated),
+        ];
+        for (key, value) in fields {
+            let _ = writeln!(text, "{} = {}", key, value);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaign::register_watches;
+    use crate::components::{Damage, Health, Name, Player};
+    use crate::events::AttackEvent;
+    use crate::systems::{DamageSystem, DeathSystem};
+    use rusty_ecs_core::SystemExecutor;
+
+    #[test]
+    fn test_battle_summary_once_the_field_is_clear() {
+        let mut world = World::new();
+        register_watches(&mut world);
+        world.insert_resource(Stats::default());
+        let hero = world.create_entity();
+        world.add_component(hero, Name("Knight"));
+        world.add_component(hero, Player);
+        world.add_component(hero, Health { hp: 45, max: 45 });
+        world.add_component(hero, Damage { value: 7 });
+        let rat = world.create_entity();
+        world.add_component(rat, Name("Rat"));
+        world.add_component(rat, Enemy);
+        world.add_component(rat, Health { hp: 10, max: 10 });
+        let mut executor = SystemExecutor::new();
+        executor.add_system(DamageSystem);
+        executor.add_exclusive_system(DeathSystem::new());
+        executor.add_exclusive_system(StatsSystem);
+
+        world.push_event(AttackEvent { attacker: rat, target: hero, damage: 3 });
+        world.push_event(AttackEvent { attacker: hero, target: rat, damage: 6 });
+        executor.run(&mut world);
+        world.take_events::<MsgEvent>();
+        world.push_event(AttackEvent { attacker: hero, target: rat, damage: 6 });
+        executor.run(&mut world);
+
+        let summary = MsgEvent::BattleSummary { dealt: 12, taken: 3, defeated: 1, turns: 0 };
+        assert_eq!(world.take_events::<MsgEvent>().last(), Some(&summary));
+        let stats = world.resource::<Stats>().unwrap();
+        assert_eq!(stats.battle, Tally::default());
+        assert_eq!(stats.run.enemies_defeated, 1);
+    }
+
+    #[test]
+    fn test_records_round_trip_and_keep_bests() {
+        let mut records = Records::default();
+        let first = Tally { damage_dealt: 40, damage_taken: 10, turns: 5, enemies_defeated: 2 };
+        let second = Tally { damage_dealt: 25, damage_taken: 30, turns: 7, enemies_defeated: 3 };
+        records.add_run(&first);
+        records.add_run(&second);
+        assert_eq!(records.total.damage_dealt, 65);
+        assert_eq!(records.best_damage_dealt, 40);
+        assert_eq!(records.most_enemies_defeated, 3);
+
+        assert_eq!(Records::parse(&records.to_text()), Ok(records));
+        assert_eq!(Records::parse("runs = 2\nfuture_key = 9\n").unwrap().runs, 2);
+        assert!(Records::parse("runs = many").is_err());
+    }
+}
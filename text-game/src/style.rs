@@ -0,0 +1,98 @@
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    fn code(self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+        }
+    }
+}
+
+// How a piece of text is drawn on an ANSI terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style { color: None, bold: false };
+
+    pub fn fg(color: Color) -> Self {
+        Style { color: Some(color), bold: false }
+    }
+
+    pub fn bold(self) -> Self {
+        Style { bold: true, ..self }
+    }
+
+    // `text` wrapped in this style's escape codes, reset at the end.
+    pub fn paint(&self, text: &str) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if let Some(color) = self.color {
+            codes.push(color.code().to_string());
+        }
+        if codes.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+// Resource: whether messages are colored. Off by default, so tests and
+// other front-ends get plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ColorConfig {
+    pub enabled: bool,
+}
+
+impl ColorConfig {
+    // On for terminals unless NO_COLOR is set to anything non-empty, as the
+    // no-color.org convention asks.
+    pub fn from_env() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        ColorConfig { enabled: !no_color && std::io::stdout().is_terminal() }
+    }
+
+    pub fn paint(&self, text: &str, style: Style) -> String {
+        match self.enabled {
+            true => style.paint(text),
+            false => text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(Style::fg(Color::Red).paint("7"), "\x1b[31m7\x1b[0m");
+        assert_eq!(Style::PLAIN.bold().paint("Orc"), "\x1b[1mOrc\x1b[0m");
+        assert_eq!(Style::fg(Color::Green).bold().paint("3"), "\x1b[1;32m3\x1b[0m");
+        assert_eq!(Style::PLAIN.paint("plain"), "plain");
+        let off = ColorConfig::default();
+        assert_eq!(off.paint("7", Style::fg(Color::Red)), "7");
+        let on = ColorConfig { enabled: true };
+        assert_eq!(on.paint("7", Style::fg(Color::Red)), "\x1b[31m7\x1b[0m");
+    }
+}
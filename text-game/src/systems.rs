@@ -0,0 +1,450 @@
+use crate::combat::{AttackContext, CombatRules, DamageModel, HitOutcome, StandardModel};
+use crate::components::{BaseStats, Damage, Dead, Defending, Enemy, Health, Name, Player, Speed};
+use crate::difficulty::Difficulty;
+use crate::events::{AttackEvent, EnemyDefeatedEvent, EntityDied, FleeAttemptEvent, HealthDepleted};
+use crate::loot::{Equipment, LootTable, StatModifiers};
+use crate::messages::{MsgEvent, Verbosity};
+use crate::stats::Stats;
+use rusty_ecs_core::{Access, Entity, ExclusiveSystem, RngResource, System, SystemData, World};
+
+// Resolves AttackEvents through the `CombatRules` damage model.
+pub struct DamageSystem;
+
+impl System for DamageSystem {
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<Name>()
+            .read::<Player>()
+            .read::<Defending>()
+            .read::<Enemy>()
+            .read::<StatModifiers>()
+            .read::<CombatRules>()
+            .read::<Verbosity>()
+            .write::<RngResource>()
+            .write::<Health>()
+            .write::<Stats>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        let attacks = data.resolve_events::<AttackEvent, &Name, _>(|_, name| name.0);
+        for (attack, target_name) in attacks {
+            let target_name = target_name.unwrap_or("Unknown");
+            // Without an RNG nothing misses or crits
+            let (hit_roll, crit_roll) = data
+                .resource_mut::<RngResource>()
+                .map(|rng| {
+                    let stream = rng.stream("combat");
+                    (stream.next_f32(), stream.next_f32())
+                })
+                .unwrap_or((1.0, 1.0));
+            let context = AttackContext {
+                damage: attack.damage,
+                attack_bonus: data.get::<StatModifiers>(attack.attacker).map_or(0, |m| m.attack),
+                armor: data.get::<StatModifiers>(attack.target).map_or(0, |m| m.defense),
+                defending: data.get::<Defending>(attack.target).is_some(),
+                hit_roll,
+                crit_roll,
+            };
+            let default_model = StandardModel::default();
+            let model: &dyn DamageModel = match data.resource::<CombatRules>() {
+                Some(rules) => rules.0.as_ref(),
+                None => &default_model,
+            };
+            let outcome = model.resolve(&context);
+            // Only worth building when it will be shown
+            let formula = (data.resource::<Verbosity>() == Some(&Verbosity::Verbose))
+                .then(|| model.explain(&context, outcome))
+                .flatten();
+
+            let attacker_name = data
+                .get::<Name>(attack.attacker)
+                .map(|n| n.0)
+                .unwrap_or("Unknown");
+            let attacker_is_player = data.get::<Player>(attack.attacker).is_some();
+            let target_is_enemy = data.get::<Enemy>(attack.target).is_some();
+
+            let damage = match outcome {
+                HitOutcome::Miss => {
+                    data.push_event(MsgEvent::Misses { attacker: attacker_name, target: target_name });
+                    continue;
+                }
+                HitOutcome::Critical(damage) => {
+                    data.push_event(MsgEvent::CriticalHit);
+                    damage
+                }
+                HitOutcome::Hit(damage) => damage,
+            };
+
+            if let Some(h) = data.get_mut::<Health>(attack.target) {
+                let was_alive = h.hp > 0;
+                h.hp = (h.hp - damage).max(0);
+                let (hp, max) = (h.hp, h.max);
+                let target_is_player = data.get::<Player>(attack.target).is_some();
+                if let Some(stats) = data.resource_mut::<Stats>() {
+                    let amount = damage.max(0) as u32;
+                    if attacker_is_player {
+                        stats.record(|t| t.damage_dealt += amount);
+                    }
+                    if target_is_player {
+                        stats.record(|t| t.damage_taken += amount);
+                    }
+                }
+
+                let msg = if attacker_is_player {
+                    MsgEvent::PlayerHits {
+                        attacker: attacker_name,
+                        target: target_name,
+                        dmg: damage,
+                        hp,
+                        max,
+                    }
+                } else {
+                    MsgEvent::Hit { attacker: attacker_name, target: target_name, dmg: damage, hp, max }
+                };
+                data.push_event(msg);
+                if let Some(text) = formula {
+                    data.push_event(MsgEvent::Formula { text });
+                }
+                if hp == 0 && was_alive && target_is_enemy {
+                    data.push_event(EnemyDefeatedEvent {
+                        enemy: attack.target,
+                        by: attack.attacker,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// Gives every entity with BaseStats the Health and Damage matching the current
+// Difficulty. Entities that already have Health are left alone, so scaling
+// happens once, on the first run after spawning.
+pub struct StatScalingSystem;
+
+impl System for StatScalingSystem {
+    fn access(&self) -> Access {
+        Access::new().read::<BaseStats>().read::<Health>().read::<Difficulty>()
+    }
+
+    fn run(&mut self, mut data: SystemData<'_>) {
+        let difficulty = data.resource::<Difficulty>().copied().unwrap_or_default();
+        let unscaled: Vec<_> = data
+            .query::<&BaseStats, ()>()
+            .filter(|(e, _)| data.get::<Health>(*e).is_none())
+            .map(|(e, base)| (e, *base))
+            .collect();
+
+        for (entity, base) in unscaled {
+            let hp = difficulty.scale_health(base.hp);
+            let commands = data.commands();
+            commands.add_component(entity, Health { hp, max: hp });
+            commands.add_component(entity, Damage { value: difficulty.scale_damage(base.damage) });
+        }
+    }
+}
+
+// Turns each HealthDepleted into a death: the entity gets the Dead marker, is
+// disabled so queries and turn order pass over it, and an EntityDied is
+// pushed along with the one message announcing it. With `despawn_after` the
+// body is also despawned that many runs later, unless something revived it in
+// the meantime.
+#[derive(Default)]
+pub struct DeathSystem {
+    despawn_after: Option<u32>,
+    // Bodies waiting to be despawned, with the runs they have left.
+    pending: Vec<(Entity, u32)>,
+}
+
+impl DeathSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn despawn_after(mut self, runs: u32) -> Self {
+        self.despawn_after = Some(runs);
+        self
+    }
+}
+
+impl ExclusiveSystem for DeathSystem {
+    fn run(&mut self, world: &mut World) {
+        self.pending.retain_mut(|(entity, runs)| {
+            *runs = runs.saturating_sub(1);
+            if !world.has::<Dead>(*entity) {
+                return false;
+            }
+            if *runs == 0 {
+                world.destroy_entity(*entity);
+            }
+            *runs > 0
+        });
+
+        for HealthDepleted { entity } in world.take_events::<HealthDepleted>() {
+            // Healed again, or already handled
+            if world.get_component::<Health>(entity).is_none_or(|h| h.hp > 0)
+                || world.has::<Dead>(entity)
+            {
+                continue;
+            }
+            if let Some(name) = world.get_component::<Name>(entity).map(|n| n.0) {
+                match world.has::<Enemy>(entity) {
+                    true => world.push_event(MsgEvent::EnemyDefeated { name }),
+                    false => world.push_event(MsgEvent::Collapses { name }),
+                }
+            }
+            world.add_component(entity, Dead);
+            world.set_enabled(entity, false);
+            if let Some(runs) = self.despawn_after {
+                self.pending.push((entity, runs));
+            }
+            world.push_event(EntityDied { entity });
+        }
+    }
+}
+
+// Share of their loot chances that enemies left behind still drop.
+pub const FLEE_LOOT_SHARE: f32 = 0.5;
+
+// Odds of getting away: even against equal speed, better for each point the
+// hero is faster than the quickest enemy, never certain either way.
+pub fn flee_chance(speed: Speed, fastest_enemy: Speed) -> f32 {
+    (0.5 + 0.1 * (speed.0 - fastest_enemy.0) as f32).clamp(0.1, 0.9)
+}
+
+// Rolls each FleeAttemptEvent from the "flee" stream. A failed attempt only
+// costs the hero's turn. A successful one ends the battle: the enemies still
+// standing are despawned, so ProgressionSystem (which has to run after this)
+// moves on, and their loot is rolled at `FLEE_LOOT_SHARE` of the usual odds
+// for the hero who fled.
+pub struct FleeSystem;
+
+impl ExclusiveSystem for FleeSystem {
+    fn run(&mut self, world: &mut World) {
+        for FleeAttemptEvent { entity } in world.take_events::<FleeAttemptEvent>() {
+            let enemies = world.query_entities::<Enemy>();
+            if !world.is_enabled(entity) || enemies.is_empty() {
+                continue;
+            }
+            let speed = |e| world.get_component::<Speed>(e).copied().unwrap_or(Speed::DEFAULT);
+            let fastest = enemies.iter().map(|&e| speed(e)).max_by_key(|s| s.0).unwrap();
+            let chance = flee_chance(speed(entity), fastest);
+            let name = world.get_component::<Name>(entity).map_or("Unknown", |n| n.0);
+            let Some(rng) = world.resource_mut::<RngResource>() else {
+                continue;
+            };
+            if !rng.stream("flee").chance(chance) {
+                world.push_event(MsgEvent::FleeFailed { name });
+                continue;
+            }
+
+            world.push_event(MsgEvent::Fled { name });
+            let carries = world.has::<Equipment>(entity);
+            for enemy in enemies {
+                let table = world.get_component::<LootTable>(enemy).cloned();
+                world.destroy_entity(enemy);
+                let (Some(table), true) = (table, carries) else {
+                    continue;
+                };
+                let Some(rng) = world.resource_mut::<RngResource>() else {
+                    continue;
+                };
+                for item in table.roll(rng.stream("loot"), FLEE_LOOT_SHARE) {
+                    world.push_event(MsgEvent::LootDropped { item: item.name.clone(), to: name });
+                    world.get_component_mut::<Equipment>(entity).unwrap().0.push(item);
+                }
+            }
+        }
+    }
+}
+
+pub fn set_defending(world: &mut World, entity: Entity, value: bool) {
+    world.toggle_marker::<Defending>(entity, value);
+}
+
+pub fn is_defending(world: &World, entity: Entity) -> bool {
+    world.has::<Defending>(entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaign::register_watches;
+    use rusty_ecs_core::SystemExecutor;
+
+    fn scaled_goblin(difficulty: Option<Difficulty>) -> (i32, i32) {
+        let mut world = World::new();
+        if let Some(difficulty) = difficulty {
+            world.insert_resource(difficulty);
+        }
+        let goblin = world.create_entity();
+        world.add_component(goblin, BaseStats { hp: 12, damage: 3 });
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(StatScalingSystem);
+        executor.run(&mut world);
+        // A second run must not rescale
+        world.get_component_mut::<Health>(goblin).unwrap().hp -= 1;
+        executor.run(&mut world);
+
+        let health = world.get_component::<Health>(goblin).unwrap();
+        assert_eq!(health.hp, health.max - 1);
+        (health.max, world.get_component::<Damage>(goblin).unwrap().value)
+    }
+
+    #[test]
+    fn test_stat_scaling_follows_difficulty() {
+        assert_eq!(scaled_goblin(None), (12, 3));
+        assert_eq!(scaled_goblin(Some(Difficulty::Easy)), (9, 2));
+        assert_eq!(scaled_goblin(Some(Difficulty::Hard)), (18, 3));
+    }
+
+    #[test]
+    fn test_damage_system_reports_hits_as_messages() {
+        let mut world = World::new();
+        let hero = world.create_entity();
+        world.add_component(hero, Name("Hero"));
+        world.add_component(hero, Player);
+        world.add_component(hero, Health { hp: 45, max: 45 });
+        world.add_component(hero, Defending);
+        let orc = world.create_entity();
+        world.add_component(orc, Name("Orc"));
+        world.add_component(orc, Health { hp: 18, max: 18 });
+
+        world.push_event(AttackEvent { attacker: hero, target: orc, damage: 7 });
+        world.push_event(AttackEvent { attacker: orc, target: hero, damage: 5 });
+        world.push_event(AttackEvent { attacker: hero, target: orc, damage: 11 });
+        let mut executor = SystemExecutor::new();
+        executor.add_system(DamageSystem);
+        executor.run(&mut world);
+
+        assert_eq!(
+            world.take_events::<MsgEvent>(),
+            vec![
+                MsgEvent::PlayerHits { attacker: "Hero", target: "Orc", dmg: 7, hp: 11, max: 18 },
+                MsgEvent::Hit { attacker: "Orc", target: "Hero", dmg: 2, hp: 43, max: 45 },
+                MsgEvent::PlayerHits { attacker: "Hero", target: "Orc", dmg: 11, hp: 0, max: 18 },
+            ]
+        );
+    }
+
+    struct Glancing;
+
+    impl DamageModel for Glancing {
+        fn resolve(&self, attack: &AttackContext) -> HitOutcome {
+            HitOutcome::Hit(if attack.hit_roll < 0.5 { 1 } else { 0 })
+        }
+    }
+
+    #[test]
+    fn test_damage_system_uses_combat_rules() {
+        let mut world = World::new();
+        world.insert_resource(CombatRules::new(Glancing));
+        let hero = world.create_entity();
+        world.add_component(hero, Player);
+        let orc = world.create_entity();
+        world.add_component(orc, Health { hp: 18, max: 18 });
+
+        // No RngResource, so the roll is 1.0
+        world.push_event(AttackEvent { attacker: hero, target: orc, damage: 7 });
+        let mut executor = SystemExecutor::new();
+        executor.add_system(DamageSystem);
+        executor.run(&mut world);
+        assert_eq!(world.get_component::<Health>(orc).unwrap().hp, 18);
+
+        world.insert_resource(RngResource::new(3));
+        world.insert_resource(CombatRules::new(StandardModel {
+            miss_chance: 1.0,
+            ..StandardModel::default()
+        }));
+        world.push_event(AttackEvent { attacker: hero, target: orc, damage: 7 });
+        executor.run(&mut world);
+        assert_eq!(
+            world.take_events::<MsgEvent>().last(),
+            Some(&MsgEvent::Misses { attacker: "Unknown", target: "Unknown" })
+        );
+    }
+
+    #[test]
+    fn test_death_system_disables_and_despawns_the_fallen() {
+        let mut world = World::new();
+        register_watches(&mut world);
+        let hero = world.create_entity();
+        world.add_component(hero, Name("Hero"));
+        world.add_component(hero, Health { hp: 5, max: 5 });
+        let orc = world.create_entity();
+        world.add_component(orc, Name("Orc"));
+        world.add_component(orc, Enemy);
+        world.add_component(orc, Health { hp: 4, max: 4 });
+        world.push_event(AttackEvent { attacker: hero, target: orc, damage: 9 });
+
+        let mut executor = SystemExecutor::new();
+        executor.add_system(DamageSystem);
+        executor.add_exclusive_system(DeathSystem::new().despawn_after(2));
+        executor.run(&mut world);
+        let died: Vec<Entity> = world.take_events::<EntityDied>().iter().map(|d| d.entity).collect();
+        assert_eq!(died, vec![orc]);
+        let deaths: Vec<MsgEvent> = world
+            .take_events::<MsgEvent>()
+            .into_iter()
+            .filter(|m| matches!(m, MsgEvent::EnemyDefeated { .. } | MsgEvent::Collapses { .. }))
+            .collect();
+        assert_eq!(deaths, [MsgEvent::EnemyDefeated { name: "Orc" }]);
+        assert!(world.has::<Dead>(orc));
+        assert!(!world.is_enabled(orc));
+        assert!(world.is_enabled(hero));
+
+        executor.run(&mut world);
+        assert!(world.is_alive(orc));
+        executor.run(&mut world);
+        assert!(!world.is_alive(orc));
+
+        // A fallen hero collapses instead
+        world.push_event(AttackEvent { attacker: orc, target: hero, damage: 9 });
+        executor.run(&mut world);
+        assert!(world.take_events::<MsgEvent>().contains(&MsgEvent::Collapses { name: "Hero" }));
+        assert!(!world.is_enabled(hero));
+    }
+
+    #[test]
+    fn test_flee_ends_the_battle_with_partial_loot() {
+        assert_eq!(flee_chance(Speed(5), Speed(5)), 0.5);
+        assert_eq!(flee_chance(Speed(7), Speed(5)), 0.7);
+        assert_eq!(flee_chance(Speed(0), Speed(9)), 0.1);
+
+        let mut world = World::new();
+        world.insert_resource(RngResource::new(3));
+        let hero = world.create_entity();
+        world.add_component(hero, Name("Ranger"));
+        world.add_component(hero, Player);
+        world.add_component(hero, Health { hp: 35, max: 35 });
+        world.add_component(hero, Speed(1));
+        world.add_component(hero, Equipment::default());
+        let wolf = world.create_entity();
+        world.add_component(wolf, Name("Wolf"));
+        world.add_component(wolf, Enemy);
+        world.add_component(wolf, Speed(5));
+        world.add_component(wolf, LootTable::parse("Pelt +0/+1 100%, Fang +1/+0 100%").unwrap());
+        let mut executor = SystemExecutor::new();
+        executor.add_exclusive_system(FleeSystem);
+
+        // One in ten attempts at this speed gets away
+        let mut attempts = 0;
+        while world.is_alive(wolf) {
+            attempts += 1;
+            assert!(attempts < 100, "never got away");
+            world.push_event(FleeAttemptEvent { entity: hero });
+            executor.run(&mut world);
+        }
+        let messages = world.take_events::<MsgEvent>();
+        let failed = messages.iter().filter(|m| matches!(m, MsgEvent::FleeFailed { .. }));
+        assert_eq!(failed.count(), attempts - 1);
+        assert!(messages.contains(&MsgEvent::Fled { name: "Ranger" }));
+        // Loot left behind goes to the hero, and there is nothing left to flee from
+        let looted = world.get_component::<Equipment>(hero).unwrap().0.len();
+        let drops = messages.iter().filter(|m| matches!(m, MsgEvent::LootDropped { .. }));
+        assert_eq!(drops.count(), looted);
+        world.push_event(FleeAttemptEvent { entity: hero });
+        executor.run(&mut world);
+        assert!(world.take_events::<MsgEvent>().is_empty());
+    }
+}